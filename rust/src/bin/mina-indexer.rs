@@ -1,13 +1,22 @@
-use clap::{Parser, Subcommand};
+use anyhow::bail;
+use clap::{Args, Parser, Subcommand};
 use log::{debug, error, info, warn, LevelFilter};
 use mina_indexer::{
-    block::precomputed::PcbVersion,
+    block::{archive::is_block_archive, precomputed::PcbVersion, verify::verify_store},
     chain::Network,
     client,
+    command::unsigned::{UnsignedCommand, UnsignedCommandBody, VALID_UNTIL_DEFAULT},
     constants::*,
     ledger::{
         self,
-        genesis::{GenesisConstants, GenesisLedger, GenesisRoot},
+        account::{Account, Nonce},
+        genesis::{GenesisConstants, GenesisLedger, GenesisRoot, HardforkGenesis},
+        public_key::{self, PublicKey},
+    },
+    proof_systems::signer::{
+        pubkey::{CompressedPubKey, PubKey},
+        seckey::SecKey,
+        signature::Signature,
     },
     server::{
         initialize_indexer_database, start_indexer, IndexerConfiguration, InitializationMode,
@@ -16,6 +25,7 @@ use mina_indexer::{
     unix_socket_server::remove_unix_socket,
     web::start_web_server,
 };
+use rand::rngs::OsRng;
 use std::{
     fs::{self, File},
     io::Write,
@@ -56,6 +66,18 @@ enum IndexerCommand {
         server_command: ServerCommand,
     },
 
+    /// Key management commands
+    Keys {
+        #[command(subcommand)]
+        keys_command: KeysCommand,
+    },
+
+    /// Offline transaction commands
+    Tx {
+        #[command(subcommand)]
+        tx_command: TxCommand,
+    },
+
     /// Client commands
     #[clap(flatten)]
     Client(#[command(subcommand)] client::ClientCli),
@@ -101,6 +123,14 @@ enum DatabaseCommand {
         restore_dir: PathBuf,
     },
 
+    /// Verify the parent linkage of every block in a mina indexer database.
+    /// State hashes are not recomputed
+    Verify {
+        /// Full path to a mina indexer database directory
+        #[arg(long)]
+        database_dir: PathBuf,
+    },
+
     /// Query mina indexer database version
     Version {
         /// Output JSON data
@@ -109,6 +139,88 @@ enum DatabaseCommand {
     },
 }
 
+#[derive(Subcommand, Debug)]
+enum KeysCommand {
+    /// Generate a new key pair
+    Generate,
+
+    /// Derive the public key (address) of a private key
+    DerivePublic {
+        /// Private key (base58 or hex)
+        #[arg(long)]
+        private_key: String,
+    },
+
+    /// Check a public key (address) is a valid curve point
+    ValidateAddress {
+        /// Public key (base58)
+        #[arg(long)]
+        address: String,
+    },
+
+    /// Show a public key in its base58, hex & compressed hex forms, or a
+    /// private key in its base58 & hex forms
+    Convert {
+        /// Public key (base58, hex or compressed hex) or private key (base58
+        /// or hex)
+        #[arg(long)]
+        key: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum TxCommand {
+    /// Build an unsigned payment or delegation & its random oracle input
+    Build(TxArgs),
+
+    /// Attach the sender's signature to a payment or delegation
+    Sign {
+        #[clap(flatten)]
+        tx: TxArgs,
+
+        /// Sender's signature (hex) of the random oracle input
+        #[arg(long)]
+        signature: String,
+
+        /// Block version whose encoding the transaction hash uses, v1 before
+        /// the hardfork & v2 after. Required since a hash of the wrong
+        /// version doesn't match the one the network reports
+        #[arg(long)]
+        hash_version: PcbVersion,
+    },
+}
+
+#[derive(Args, Debug)]
+struct TxArgs {
+    /// Public key of the sender & fee payer
+    #[arg(long)]
+    sender: String,
+
+    /// Public key of the payment receiver or new delegate
+    #[arg(long)]
+    receiver: String,
+
+    /// Payment amount (nanomina), omit for a delegation
+    #[arg(long)]
+    amount: Option<u64>,
+
+    /// Fee (nanomina)
+    #[arg(long)]
+    fee: u64,
+
+    /// Sender's nonce [default: best ledger nonce of the running indexer]
+    #[arg(long)]
+    nonce: Option<u32>,
+
+    /// Transaction memo
+    #[arg(long, default_value = "")]
+    memo: String,
+
+    /// Global slot the transaction is valid until
+    #[arg(long, default_value_t = VALID_UNTIL_DEFAULT)]
+    valid_until: u32,
+}
+
 #[derive(Parser, Debug, Clone, Default)]
 #[command(author, version, about, long_about = None)]
 pub struct ServerArgs {
@@ -123,6 +235,11 @@ pub struct ServerArgs {
     #[arg(long, default_value_t = DEFAULT_WEB_PORT)]
     web_port: u16,
 
+    /// Admin web server port, e.g. for the live witness tree. The admin web
+    /// server only listens on localhost & is disabled by default
+    #[arg(long)]
+    admin_web_port: Option<u16>,
+
     /// Start with data consistency checks
     #[arg(long, default_value_t = false)]
     self_check: bool,
@@ -147,6 +264,12 @@ pub struct ServerArgs {
     #[arg(long)]
     missing_block_recovery_batch: Option<bool>,
 
+    /// Block source for new & missing blocks: a blocks directory, a tar
+    /// archive or an http(s):// object store URL. Takes precedence over the
+    /// fetch executables
+    #[arg(long)]
+    block_source: Option<String>,
+
     /// Indexer process ID
     #[arg(last = true)]
     pid: Option<u32>,
@@ -166,13 +289,19 @@ pub struct DatabaseArgs {
     )]
     genesis_hash: String,
 
+    /// Path to the hardfork runtime config (JSON) with the fork block & the
+    /// post-fork genesis ledger
+    #[arg(long, value_name = "FILE")]
+    hardfork_config: Option<PathBuf>,
+
     /// Path to the genesis constants (JSON)
     genesis_constants: Option<PathBuf>,
 
     /// Override the constraint system digests
     constraint_system_digests: Option<Vec<String>>,
 
-    /// Directory of precomputed blocks
+    /// Directory of precomputed blocks, or a .tar, .tar.gz or .tar.zst
+    /// archive of them
     #[arg(long)]
     blocks_dir: Option<PathBuf>,
 
@@ -216,6 +345,17 @@ pub struct DatabaseArgs {
     /// Network name
     #[arg(long, default_value = Network::Mainnet)]
     network: Network,
+
+    /// Quarantine block files whose height labels or parent linkage fail
+    /// verification. This is not tamper detection, state hashes are not
+    /// recomputed
+    #[arg(long, default_value_t = false)]
+    verify_blocks: bool,
+
+    /// Number of threads parsing blocks ahead of ingestion [default: number
+    /// of CPUs]
+    #[arg(long)]
+    parse_threads: Option<usize>,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -245,6 +385,8 @@ pub async fn main() -> anyhow::Result<()> {
                 IndexerCommand::Server { server_command } => {
                     server_command.run(s, domain_socket_path).await
                 }
+                IndexerCommand::Keys { keys_command } => keys_command.run(),
+                IndexerCommand::Tx { tx_command } => tx_command.run(domain_socket_path).await,
                 IndexerCommand::Version => Ok(println!("{VERSION}")),
             }
         }));
@@ -356,6 +498,28 @@ impl DatabaseCommand {
                         .await;
                 }
             }
+            Self::Verify { database_dir } => {
+                if !database_dir.exists() {
+                    error!("Database dir {database_dir:#?} does not exist");
+                    process::exit(1);
+                }
+                info!("Verifying blocks in database dir {database_dir:#?}");
+                let tmp_dir = TempDir::new()?;
+                let db = IndexerStore::read_only(&database_dir, tmp_dir.as_ref())?;
+                let verification = verify_store(&db)?;
+                for (state_hash, reason) in verification.failures.iter() {
+                    error!("Block {state_hash} failed verification: {reason}");
+                }
+                println!(
+                    "Verified {} blocks: {} missing parents, {} failures",
+                    verification.num_blocks,
+                    verification.num_missing_parents,
+                    verification.failures.len(),
+                );
+                if !verification.failures.is_empty() {
+                    process::exit(1);
+                }
+            }
             Self::Restore {
                 snapshot_file,
                 restore_dir,
@@ -404,6 +568,183 @@ impl DatabaseCommand {
     }
 }
 
+impl KeysCommand {
+    fn run(self) -> anyhow::Result<()> {
+        match self {
+            Self::Generate => {
+                let sec_key = SecKey::rand(&mut OsRng);
+                let public_key = pub_key_from_sec_key(&sec_key)?;
+                let json = serde_json::json!({
+                    "private_key": sec_key.to_base58(),
+                    "public_key": public_key.into_address(),
+                });
+                println!("{}", serde_json::to_string_pretty(&json)?);
+            }
+            Self::DerivePublic { private_key } => {
+                let sec_key = parse_sec_key(&private_key)?;
+                println!("{}", pub_key_from_sec_key(&sec_key)?.into_address());
+            }
+            Self::ValidateAddress { address } => {
+                if let Err(e) = validate_address(&address) {
+                    eprintln!("Invalid public key {address}: {e}");
+                    process::exit(1);
+                }
+                println!("Valid public key {address}");
+            }
+            Self::Convert { key } => {
+                println!("{}", serde_json::to_string_pretty(&convert_key(&key)?)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks the address is a base58 public key of a curve point
+fn validate_address(address: &str) -> Result<(), String> {
+    if !public_key::is_valid_public_key(address) {
+        return Err(format!(
+            "expected {} characters starting with {}",
+            PublicKey::LEN,
+            PublicKey::PREFIX
+        ));
+    }
+    PubKey::from_address(address)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// All forms of a public or private key
+fn convert_key(key: &str) -> anyhow::Result<serde_json::Value> {
+    if let Some(pub_key) = parse_pub_key(key) {
+        Ok(serde_json::json!({
+            "base58": pub_key.into_address(),
+            "hex": pub_key.to_hex(),
+            "compressed": pub_key.into_compressed().to_hex(),
+        }))
+    } else if let Ok(sec_key) = parse_sec_key(key) {
+        Ok(serde_json::json!({
+            "base58": sec_key.to_base58(),
+            "hex": sec_key.to_hex(),
+        }))
+    } else {
+        bail!("Unrecognized key {key}")
+    }
+}
+
+/// Parses a base58 or hex private key
+fn parse_sec_key(key: &str) -> anyhow::Result<SecKey> {
+    SecKey::from_base58(key)
+        .or_else(|_| SecKey::from_hex(key))
+        .map_err(|e| anyhow::anyhow!("Invalid private key: {e}"))
+}
+
+/// Parses a base58, hex or compressed hex public key
+fn parse_pub_key(key: &str) -> Option<PubKey> {
+    PubKey::from_address(key)
+        .or_else(|_| PubKey::from_hex(key))
+        .ok()
+        .or_else(|| {
+            CompressedPubKey::from_hex(key)
+                .ok()
+                .and_then(|pk| PubKey::from_address(&pk.into_address()).ok())
+        })
+}
+
+fn pub_key_from_sec_key(sec_key: &SecKey) -> anyhow::Result<PubKey> {
+    PubKey::from_secret_key(sec_key.clone())
+        .map_err(|e| anyhow::anyhow!("Invalid private key: {e}"))
+}
+
+impl TxCommand {
+    async fn run(self, domain_socket_path: PathBuf) -> anyhow::Result<()> {
+        match self {
+            Self::Build(tx) => {
+                let unsigned = tx.into_unsigned_command(domain_socket_path).await?;
+                println!("{}", serde_json::to_string_pretty(&build_tx(&unsigned)?)?);
+            }
+            Self::Sign {
+                tx,
+                signature,
+                hash_version,
+            } => {
+                let unsigned = tx.into_unsigned_command(domain_socket_path).await?;
+                let signature = Signature::from_hex(&signature)?;
+                let (json, txn_hash) = sign_tx(unsigned, &signature, &hash_version)?;
+                println!("{}", serde_json::to_string_pretty(&json)?);
+                println!("{txn_hash}");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Unsigned command & its random oracle input
+fn build_tx(unsigned: &UnsignedCommand) -> anyhow::Result<serde_json::Value> {
+    Ok(serde_json::json!({
+        "unsigned_command": unsigned,
+        "random_oracle_input": unsigned.to_roinput()?.to_hex(),
+    }))
+}
+
+/// Daemon json of the signed command & its transaction hash
+fn sign_tx(
+    unsigned: UnsignedCommand,
+    signature: &Signature,
+    hash_version: &PcbVersion,
+) -> anyhow::Result<(serde_json::Value, String)> {
+    let signed = unsigned.into_signed_command(signature)?;
+    Ok((signed.to_daemon_json()?, signed.txn_hash(hash_version)?))
+}
+
+impl TxArgs {
+    async fn into_unsigned_command(
+        self,
+        domain_socket_path: PathBuf,
+    ) -> anyhow::Result<UnsignedCommand> {
+        for pk in [&self.sender, &self.receiver] {
+            if let Err(e) = PubKey::from_address(pk) {
+                bail!("Invalid public key {pk}: {e}")
+            }
+        }
+
+        let nonce = match self.nonce {
+            Some(nonce) => nonce,
+            None => best_ledger_nonce(&self.sender, domain_socket_path).await?,
+        };
+        let receiver = self.receiver.into();
+        Ok(UnsignedCommand {
+            sender: self.sender.into(),
+            fee: self.fee.into(),
+            nonce: Nonce(nonce),
+            valid_until: self.valid_until,
+            memo: self.memo,
+            body: match self.amount {
+                Some(amount) => UnsignedCommandBody::Payment {
+                    receiver,
+                    amount: amount.into(),
+                },
+                None => UnsignedCommandBody::Delegation {
+                    new_delegate: receiver,
+                },
+            },
+        })
+    }
+}
+
+/// Queries the running indexer for the account's best ledger nonce
+async fn best_ledger_nonce(pk: &str, domain_socket_path: PathBuf) -> anyhow::Result<u32> {
+    let msg = client::ClientCli::Accounts(client::Accounts::PublicKey {
+        public_key: pk.to_string(),
+    })
+    .query(domain_socket_path)
+    .await?;
+
+    match serde_json::from_str::<Account>(&msg) {
+        Ok(account) => Ok(account.nonce.0),
+        Err(_) => bail!("Unable to get the nonce of {pk}: {msg}"),
+    }
+}
+
 /// Creates directories, processes constants & parses genesis ledger.
 /// Returns indexer config.
 fn process_indexer_configuration(
@@ -424,6 +765,14 @@ fn process_indexer_configuration(
     let missing_block_recovery_exe = args.missing_block_recovery_exe;
     let missing_block_recovery_delay = args.missing_block_recovery_delay;
     let missing_block_recovery_batch = args.missing_block_recovery_batch.unwrap_or(false);
+    let block_source = args.block_source;
+    let admin_web_port = args.admin_web_port;
+    let verify_blocks = args.db.verify_blocks;
+    let parse_threads = args.db.parse_threads.unwrap_or_else(|| {
+        std::thread::available_parallelism()
+            .map(usize::from)
+            .unwrap_or(1)
+    });
 
     assert!(
         // bad things happen if this condition fails
@@ -432,7 +781,7 @@ fn process_indexer_configuration(
     );
 
     // Ensure blocks and staking ledgers dirs exist
-    if let Some(ref blocks_dir) = blocks_dir {
+    if let Some(blocks_dir) = blocks_dir.as_ref().filter(|dir| !is_block_archive(dir)) {
         debug!("Ensuring blocks directory exists: {blocks_dir:#?}");
         if let Err(e) = fs::create_dir_all(blocks_dir) {
             error!("Failed to create blocks directory: {e}");
@@ -458,9 +807,11 @@ fn process_indexer_configuration(
     );
 
     let genesis_ledger = parse_genesis_ledger(args.db.genesis_ledger)?;
+    let hardfork = parse_hardfork_config(args.db.hardfork_config)?;
     Ok(IndexerConfiguration {
         genesis_ledger,
         genesis_hash,
+        hardfork,
         genesis_constants,
         constraint_system_digests,
         version: PcbVersion::default(),
@@ -478,6 +829,10 @@ fn process_indexer_configuration(
         missing_block_recovery_exe,
         missing_block_recovery_delay,
         missing_block_recovery_batch,
+        block_source,
+        admin_web_port,
+        verify_blocks,
+        parse_threads,
     })
 }
 
@@ -507,6 +862,23 @@ fn parse_genesis_ledger(path: Option<PathBuf>) -> anyhow::Result<GenesisLedger>
     Ok(genesis_ledger)
 }
 
+fn parse_hardfork_config(path: Option<PathBuf>) -> anyhow::Result<Option<HardforkGenesis>> {
+    if let Some(path) = path {
+        assert!(
+            path.is_file(),
+            "Hardfork config does not exist at {path:#?}"
+        );
+        info!("Parsing hardfork config at {path:#?}");
+        let hardfork = ledger::genesis::parse_hardfork_file(&path)?;
+        info!(
+            "Hardfork genesis extends fork block (length {}): {}",
+            hardfork.fork.blockchain_length, hardfork.fork.state_hash,
+        );
+        return Ok(Some(hardfork));
+    }
+    Ok(None)
+}
+
 fn protocol_constants(path: Option<PathBuf>) -> anyhow::Result<GenesisConstants> {
     let mut constants = GenesisConstants::default();
     if let Some(path) = path {
@@ -593,6 +965,7 @@ fn check_or_write_pid_file<P: AsRef<Path>>(database_dir: P) {
 struct ServerArgsJson {
     genesis_ledger: Option<String>,
     genesis_hash: String,
+    hardfork_config: Option<String>,
     genesis_constants: Option<String>,
     constraint_system_digests: Option<Vec<String>>,
     blocks_dir: Option<String>,
@@ -606,13 +979,20 @@ struct ServerArgsJson {
     canonical_update_threshold: u32,
     web_hostname: String,
     web_port: u16,
+    #[serde(default)]
+    admin_web_port: Option<u16>,
     pid: Option<u32>,
     fetch_new_blocks_exe: Option<String>,
     fetch_new_blocks_delay: Option<u64>,
     missing_block_recovery_exe: Option<String>,
     missing_block_recovery_delay: Option<u64>,
     missing_block_recovery_batch: Option<bool>,
+    block_source: Option<String>,
     network: String,
+    #[serde(default)]
+    verify_blocks: bool,
+    #[serde(default)]
+    parse_threads: Option<usize>,
 }
 
 impl From<ServerArgs> for ServerArgsJson {
@@ -625,6 +1005,10 @@ impl From<ServerArgs> for ServerArgsJson {
                 .genesis_ledger
                 .map(|path| path.display().to_string()),
             genesis_hash: value.db.genesis_hash,
+            hardfork_config: value
+                .db
+                .hardfork_config
+                .map(|path| path.display().to_string()),
             genesis_constants: value.db.genesis_constants.map(|g| g.display().to_string()),
             constraint_system_digests: value.db.constraint_system_digests,
             blocks_dir: value.db.blocks_dir.map(|d| d.display().to_string()),
@@ -641,6 +1025,7 @@ impl From<ServerArgs> for ServerArgsJson {
             canonical_update_threshold: value.db.canonical_update_threshold,
             web_hostname: value.web_hostname,
             web_port: value.web_port,
+            admin_web_port: value.admin_web_port,
             pid: value.pid,
             fetch_new_blocks_delay: value.fetch_new_blocks_delay,
             fetch_new_blocks_exe: value.fetch_new_blocks_exe.map(|p| p.display().to_string()),
@@ -649,7 +1034,10 @@ impl From<ServerArgs> for ServerArgsJson {
                 .missing_block_recovery_exe
                 .map(|p| p.display().to_string()),
             missing_block_recovery_batch: value.missing_block_recovery_batch,
+            block_source: value.block_source,
             network: value.db.network.to_string(),
+            verify_blocks: value.db.verify_blocks,
+            parse_threads: value.db.parse_threads,
         }
     }
 }
@@ -659,6 +1047,7 @@ impl From<ServerArgsJson> for ServerArgs {
         let db = DatabaseArgs {
             genesis_ledger: value.genesis_ledger.and_then(|path| path.parse().ok()),
             genesis_hash: value.genesis_hash,
+            hardfork_config: value.hardfork_config.map(|path| path.into()),
             genesis_constants: value.genesis_constants.map(|g| g.into()),
             constraint_system_digests: value.constraint_system_digests,
             blocks_dir: value.blocks_dir.map(|d| d.into()),
@@ -672,11 +1061,14 @@ impl From<ServerArgsJson> for ServerArgs {
             canonical_update_threshold: value.canonical_update_threshold,
             config: None,
             network: (&value.network as &str).into(),
+            verify_blocks: value.verify_blocks,
+            parse_threads: value.parse_threads,
         };
         Self {
             db,
             web_hostname: value.web_hostname,
             web_port: value.web_port,
+            admin_web_port: value.admin_web_port,
             self_check: false,
             pid: value.pid,
             fetch_new_blocks_delay: value.fetch_new_blocks_delay,
@@ -684,6 +1076,7 @@ impl From<ServerArgsJson> for ServerArgs {
             missing_block_recovery_delay: value.missing_block_recovery_delay,
             missing_block_recovery_exe: value.missing_block_recovery_exe.map(|p| p.into()),
             missing_block_recovery_batch: value.missing_block_recovery_batch,
+            block_source: value.block_source,
         }
     }
 }
@@ -724,3 +1117,146 @@ impl FromStr for LogLevelFilter {
         LevelFilter::from_str(s).map(Self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mina_indexer::{block::precomputed::PrecomputedBlock, command::signed::SignedCommand};
+
+    fn block_commands(path: &str, version: PcbVersion) -> anyhow::Result<Vec<SignedCommand>> {
+        let block = PrecomputedBlock::parse_file(&PathBuf::from(path), version)?;
+        Ok(SignedCommand::from_precomputed(&block)
+            .into_iter()
+            .map(|cmd| cmd.signed_command)
+            .collect())
+    }
+
+    #[test]
+    fn public_key_forms() -> anyhow::Result<()> {
+        let address = "B62qre3erTHfzQckNuibViWQGyyKwZseztqrjPZBv6SQF384Rg6ESAy";
+        let json = convert_key(address)?;
+        assert_eq!(json["base58"], address);
+
+        // each form converts to the same key
+        for form in ["base58", "hex", "compressed"] {
+            let key = json[form].as_str().unwrap();
+            assert_eq!(convert_key(key)?, json, "{form}");
+            assert_eq!(
+                parse_pub_key(key).map(|pk| pk.into_address()),
+                Some(address.to_string())
+            );
+        }
+        assert_eq!(json["hex"].as_str().unwrap().len(), 128);
+        assert_eq!(json["compressed"].as_str().unwrap().len(), 66);
+        Ok(())
+    }
+
+    #[test]
+    fn private_key_forms() -> anyhow::Result<()> {
+        let sec_key = SecKey::rand(&mut OsRng);
+        let json = convert_key(&sec_key.to_base58())?;
+        assert_eq!(json["base58"], sec_key.to_base58());
+        assert_eq!(json["hex"], sec_key.to_hex());
+
+        // each form parses to the same key & public key
+        let address = pub_key_from_sec_key(&sec_key)?.into_address();
+        for key in [sec_key.to_base58(), sec_key.to_hex()] {
+            assert_eq!(convert_key(&key)?, json);
+            assert_eq!(
+                pub_key_from_sec_key(&parse_sec_key(&key)?)?.into_address(),
+                address
+            );
+        }
+        assert!(validate_address(&address).is_ok());
+        assert!(parse_sec_key(&address).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_addresses() {
+        let address = "B62qre3erTHfzQckNuibViWQGyyKwZseztqrjPZBv6SQF384Rg6ESAy";
+        assert_eq!(validate_address(address), Ok(()));
+
+        // wrong length & prefix
+        assert!(validate_address(&address[..54]).is_err());
+        assert!(validate_address(&address.replacen("B62", "B63", 1)).is_err());
+
+        // bad checksum
+        let bad_checksum = format!("{}z", &address[..54]);
+        assert!(validate_address(&bad_checksum).is_err());
+
+        assert!(convert_key("not a key").is_err());
+    }
+
+    #[test]
+    fn build_and_sign_tx() -> anyhow::Result<()> {
+        let v1_block = "./tests/data/sequential_blocks/mainnet-105489-3NK4huLvUDiL4XuCUcyrWCKynmvhqfKsx5h2MfBXVVUq2Qwzi5uT.json";
+        let v2_block = "./tests/data/berkeley/sequential_blocks/berkeley-10-3NL53c8uTVnoFjzh17VAeCR9r3zjmDowNpeFRRVEUqnvX5WdHtWE.json";
+
+        for (path, version) in [(v1_block, PcbVersion::V1), (v2_block, PcbVersion::V2)] {
+            for signed in block_commands(path, version.clone())? {
+                let unsigned = UnsignedCommand::from(&signed);
+                let built = build_tx(&unsigned)?;
+                assert_eq!(
+                    built["random_oracle_input"],
+                    unsigned.to_roinput()?.to_hex()
+                );
+                assert_eq!(
+                    serde_json::from_value::<UnsignedCommand>(built["unsigned_command"].clone())?,
+                    unsigned
+                );
+
+                // signing with the block's signature recovers the block's command
+                let signature: Signature = signed.0.t.t.signature.clone().into();
+                let (json, txn_hash) = sign_tx(unsigned, &signature, &version)?;
+                assert_eq!(json, signed.to_daemon_json()?);
+                assert_eq!(txn_hash, signed.txn_hash(&version)?);
+            }
+        }
+
+        // the known mainnet hashes
+        let hashes: Vec<String> = block_commands(v1_block, PcbVersion::V1)?
+            .into_iter()
+            .map(|signed| {
+                let signature: Signature = signed.0.t.t.signature.clone().into();
+                sign_tx(UnsignedCommand::from(&signed), &signature, &PcbVersion::V1).map(|tx| tx.1)
+            })
+            .collect::<anyhow::Result<_>>()?;
+        assert_eq!(
+            hashes,
+            vec![
+                "CkpZZsSm9hQpGkGzMi8rcsQEWPZwGJXktiqGYADNwLoBeeamhzqnX",
+                "CkpZDcqGWQVpckXjcg99hh4EzmCrnPzMM8VzHaLAYxPU5tMubuLaj"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn sign_tx_requires_hash_version() {
+        let args = [
+            "mina-indexer",
+            "tx",
+            "sign",
+            "--sender",
+            "B62qre3erTHfzQckNuibViWQGyyKwZseztqrjPZBv6SQF384Rg6ESAy",
+            "--receiver",
+            "B62qre3erTHfzQckNuibViWQGyyKwZseztqrjPZBv6SQF384Rg6ESAy",
+            "--fee",
+            "10000000",
+            "--nonce",
+            "0",
+            "--signature",
+            "00",
+        ];
+        assert!(Cli::try_parse_from(args).is_err());
+
+        let cli = Cli::try_parse_from(args.into_iter().chain(["--hash-version", "v1"])).unwrap();
+        match cli.command {
+            IndexerCommand::Tx {
+                tx_command: TxCommand::Sign { hash_version, .. },
+            } => assert_eq!(hash_version, PcbVersion::V1),
+            command => panic!("unexpected command {command:?}"),
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Precomputed block archives
+//!
+//! Block bundles are tar archives, optionally gzip (`.tar.gz`, `.tgz`) or
+//! zstd (`.tar.zst`, `.tzst`) compressed. Archives are read in a single
+//! streaming pass which indexes the block entries. Blocks of an uncompressed
+//! archive are read by offset from the archive file itself, while the blocks
+//! of a compressed archive are decompressed into an anonymous temp file &
+//! read by offset from it, instead of unpacking every block file.
+//!
+//! Archive entries are addressed by the _virtual path_
+//! `{archive}/{file name}`, so the usual file name helpers
+//! ([super::extract_block_height], [super::extract_state_hash], ...) apply.
+
+use super::{is_valid_block_file, previous_state_hash::PreviousStateHash, sort_block_paths};
+use anyhow::{anyhow, Context};
+use log::info;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read},
+    os::unix::fs::FileExt,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveCompression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl ArchiveCompression {
+    /// Compression of the archive at `path`, based on its extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        if name.ends_with(".tar") {
+            Some(Self::None)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::Gzip)
+        } else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            Some(Self::Zstd)
+        } else {
+            None
+        }
+    }
+
+    /// Decompressed stream of `reader`
+    pub fn decoder<'a, R: Read + 'a>(&self, reader: R) -> io::Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            Self::None => Box::new(reader),
+            Self::Gzip => Box::new(flate2::read::MultiGzDecoder::new(reader)),
+            Self::Zstd => Box::new(zstd::stream::read::Decoder::new(reader)?),
+        })
+    }
+}
+
+/// Checks if `path` is a (compressed) tar archive file
+pub fn is_block_archive(path: &Path) -> bool {
+    path.is_file() && ArchiveCompression::from_path(path).is_some()
+}
+
+/// Location of a block entry's contents in [BlockArchive::file]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ArchiveEntry {
+    offset: u64,
+    size: u64,
+}
+
+/// Index of the block entries of an archive
+pub struct BlockArchive {
+    path: PathBuf,
+
+    /// The archive file itself if uncompressed, otherwise the temp file of
+    /// the decompressed block entries
+    file: File,
+    entries: HashMap<PathBuf, ArchiveEntry>,
+}
+
+impl BlockArchive {
+    /// Indexes the block entries of the archive at `path`
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let compression = ArchiveCompression::from_path(path)
+            .ok_or_else(|| anyhow!("{} is not a tar archive", path.display()))?;
+        let file = File::open(path).with_context(|| format!("opening {}", path.display()))?;
+
+        let time = Instant::now();
+        let (file, entries) = match compression {
+            ArchiveCompression::None => {
+                let entries = Self::index(path, &file, |entry| {
+                    Ok(ArchiveEntry {
+                        offset: entry.raw_file_position(),
+                        size: entry.size(),
+                    })
+                });
+                (file, entries)
+            }
+            _ => {
+                info!("Decompressing block archive {}", path.display());
+                let blocks = tempfile::tempfile().context("creating block archive temp file")?;
+                let decoder = compression.decoder(BufReader::new(&file))?;
+                let mut writer = BufWriter::new(blocks);
+                let mut offset = 0;
+                let entries = Self::index(path, decoder, |entry| {
+                    let size = io::copy(entry, &mut writer)?;
+                    let indexed = ArchiveEntry { offset, size };
+                    offset += size;
+                    Ok(indexed)
+                });
+                (writer.into_inner().map_err(|e| e.into_error())?, entries)
+            }
+        };
+        let entries = entries.with_context(|| format!("reading {}", path.display()))?;
+
+        info!(
+            "Indexed {} blocks in {} in {:?}",
+            entries.len(),
+            path.display(),
+            time.elapsed()
+        );
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            entries,
+        })
+    }
+
+    /// Streams the tar entries of `reader`, indexing the block files by their
+    /// virtual paths
+    fn index<R: Read>(
+        path: &Path,
+        reader: R,
+        mut index_entry: impl FnMut(&mut tar::Entry<'_, R>) -> io::Result<ArchiveEntry>,
+    ) -> io::Result<HashMap<PathBuf, ArchiveEntry>> {
+        let mut entries = HashMap::new();
+        let mut archive = tar::Archive::new(reader);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+
+            let name = match entry.path()?.file_name() {
+                Some(name) if is_valid_block_file(Path::new(name)) => name.to_owned(),
+                _ => continue,
+            };
+            entries.insert(path.join(name), index_entry(&mut entry)?);
+        }
+
+        // tar stops at its end-of-archive marker, drain the rest of the stream
+        // so the trailing gzip/zstd checksums are verified
+        io::copy(&mut archive.into_inner(), &mut io::sink())?;
+        Ok(entries)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Virtual paths of the block entries, sorted by height & file name
+    pub fn paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<_> = self.entries.keys().cloned().collect();
+        sort_block_paths(&mut paths);
+        paths
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Size of the block entry at the virtual path
+    pub fn size(&self, path: &Path) -> Option<u64> {
+        self.entries.get(path).map(|entry| entry.size)
+    }
+
+    /// Contents of the block entry at the virtual path
+    pub fn read(&self, path: &Path) -> anyhow::Result<Vec<u8>> {
+        let entry = self
+            .entries
+            .get(path)
+            .ok_or_else(|| anyhow!("{} is not in {}", path.display(), self.path.display()))?;
+
+        let mut contents = vec![0; entry.size as usize];
+        self.file.read_exact_at(&mut contents, entry.offset)?;
+        Ok(contents)
+    }
+
+    /// Previous state hash of the block entry at the virtual path
+    pub fn previous_state_hash(&self, path: &Path) -> anyhow::Result<PreviousStateHash> {
+        PreviousStateHash::from_bytes(&self.read(path)?)
+            .with_context(|| format!("{}", path.display()))
+    }
+}
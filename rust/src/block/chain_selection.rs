@@ -0,0 +1,186 @@
+//! Mina consensus chain selection
+//!
+//! https://github.com/MinaProtocol/mina/tree/develop/docs/specs/consensus#62-select-chain
+
+use super::{precomputed::PrecomputedBlock, BlockHash};
+use crate::constants::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// Consensus state fields used by chain selection
+#[derive(Debug, Default, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct ConsensusInfo {
+    pub epoch_count: u32,
+
+    /// Global slot since the most recent genesis
+    pub curr_global_slot: u32,
+    pub min_window_density: u32,
+    pub sub_window_densities: Vec<u32>,
+    pub staking_lock_checkpoint: BlockHash,
+    pub next_lock_checkpoint: BlockHash,
+}
+
+/// Chain selection between blocks whose [Ord] follows `selectLongerChain`,
+/// i.e. `a < b` means `a` is the longer chain
+pub trait ChainSelection: Ord {
+    fn consensus_info(&self) -> &ConsensusInfo;
+
+    /// Follows `selectSecureChain`, whether `self` is selected over `tip`.
+    /// This is not a total order, use it for pairwise best tip decisions only
+    fn is_better_chain(&self, tip: &Self) -> bool {
+        let candidate_info = self.consensus_info();
+        let tip_info = tip.consensus_info();
+
+        // short-range fork rule
+        if candidate_info.is_short_range(tip_info) {
+            return self < tip;
+        }
+
+        // long-range fork rule
+        let tip_density = tip_info.relative_min_window_density(candidate_info);
+        let candidate_density = candidate_info.relative_min_window_density(tip_info);
+        match candidate_density.cmp(&tip_density) {
+            Ordering::Greater => true,
+            Ordering::Equal => self < tip,
+            Ordering::Less => false,
+        }
+    }
+}
+
+/// Selects the best chain among `candidates`, in order
+pub fn select_best<'a, T, I>(candidates: I) -> Option<&'a T>
+where
+    T: ChainSelection + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    candidates
+        .into_iter()
+        .fold(None, |best, candidate| match best {
+            Some(tip) if !candidate.is_better_chain(tip) => Some(tip),
+            _ => Some(candidate),
+        })
+}
+
+impl ConsensusInfo {
+    /// Follows `isShortRange`, both chains are in the same or adjacent epochs
+    /// and agree on the lock checkpoint of the epoch they share
+    pub fn is_short_range(&self, other: &Self) -> bool {
+        if self.epoch_count == other.epoch_count {
+            return self.staking_lock_checkpoint == other.staking_lock_checkpoint;
+        }
+
+        // one chain's next epoch is the other's staking epoch
+        let is_prev_epoch = |prev: &Self, curr: &Self| {
+            prev.epoch_count + 1 == curr.epoch_count
+                && prev.next_lock_checkpoint == curr.staking_lock_checkpoint
+        };
+        is_prev_epoch(self, other) || is_prev_epoch(other, self)
+    }
+
+    /// Follows `relativeMinWindowDensity`, the minimum window density of this
+    /// chain with its sliding window projected to the other chain's slot
+    pub fn relative_min_window_density(&self, other: &Self) -> u32 {
+        let max_slot = self.curr_global_slot.max(other.curr_global_slot);
+
+        // grace period rule
+        if max_slot < MAINNET_GRACE_PERIOD_END {
+            return self.min_window_density;
+        }
+
+        // sub windows which elapse before the max slot are empty
+        let shift_count = (sub_window(max_slot) - sub_window(self.curr_global_slot))
+            .min(MAINNET_SUB_WINDOWS_PER_WINDOW);
+        let mut projected_window = self.sub_window_densities.clone();
+        let mut index = relative_sub_window(self.curr_global_slot);
+
+        for _ in 0..shift_count {
+            index = (index + 1) % MAINNET_SUB_WINDOWS_PER_WINDOW;
+            if let Some(density) = projected_window.get_mut(index as usize) {
+                *density = 0;
+            }
+        }
+
+        let projected_window_density = projected_window.iter().sum();
+        self.min_window_density.min(projected_window_density)
+    }
+}
+
+fn sub_window(global_slot: u32) -> u32 {
+    global_slot / MAINNET_SLOTS_PER_SUB_WINDOW
+}
+
+fn relative_sub_window(global_slot: u32) -> u32 {
+    sub_window(global_slot) % MAINNET_SUB_WINDOWS_PER_WINDOW
+}
+
+impl From<&PrecomputedBlock> for ConsensusInfo {
+    fn from(value: &PrecomputedBlock) -> Self {
+        Self {
+            epoch_count: value.epoch_count(),
+            curr_global_slot: value.curr_global_slot(),
+            min_window_density: value.min_window_density(),
+            sub_window_densities: value.sub_window_densities(),
+            staking_lock_checkpoint: value.staking_epoch_data().lock_checkpoint,
+            next_lock_checkpoint: value.next_epoch_data().lock_checkpoint,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(epoch_count: u32, curr_global_slot: u32, densities: Vec<u32>) -> ConsensusInfo {
+        ConsensusInfo {
+            epoch_count,
+            curr_global_slot,
+            min_window_density: densities.iter().sum(),
+            sub_window_densities: densities,
+            staking_lock_checkpoint: "staking".into(),
+            next_lock_checkpoint: "next".into(),
+        }
+    }
+
+    #[test]
+    fn short_range() {
+        let a = info(1, 7140, vec![]);
+        let mut b = info(1, 7150, vec![]);
+        assert!(a.is_short_range(&b));
+
+        // different staking epoch lock checkpoint
+        b.staking_lock_checkpoint = "other".into();
+        assert!(!a.is_short_range(&b));
+
+        // adjacent epochs
+        b.epoch_count = 2;
+        b.staking_lock_checkpoint = a.next_lock_checkpoint.clone();
+        assert!(a.is_short_range(&b));
+        assert!(b.is_short_range(&a));
+
+        // distant epochs
+        b.epoch_count = 3;
+        assert!(!a.is_short_range(&b));
+    }
+
+    #[test]
+    fn relative_min_window_density() {
+        let densities = vec![7; MAINNET_SUB_WINDOWS_PER_WINDOW as usize];
+        let a = info(0, 100, densities.clone());
+
+        // grace period
+        let b = info(0, 1000, densities.clone());
+        assert_eq!(a.relative_min_window_density(&b), 77);
+
+        // slot 2100 is in sub window 300, relative sub window 3
+        let a = info(0, 2100, densities.clone());
+        assert_eq!(a.relative_min_window_density(&a), 77);
+
+        // projecting 2 sub windows ahead empties relative sub windows 4 & 5
+        let b = info(0, 2114, densities.clone());
+        assert_eq!(a.relative_min_window_density(&b), 63);
+
+        // projecting a full window ahead empties all sub windows
+        let b = info(0, 2100 + 7 * 11, densities);
+        assert_eq!(a.relative_min_window_density(&b), 0);
+    }
+}
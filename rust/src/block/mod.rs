@@ -1,14 +1,21 @@
 //! Indexer internal block representation used in the witness tree
 
+pub mod archive;
 pub mod blockchain_length;
+pub mod chain_selection;
 pub mod genesis;
 pub mod parser;
 pub mod precomputed;
 pub mod previous_state_hash;
+pub mod source;
 pub mod store;
+pub mod verify;
 pub mod vrf_output;
 
-use self::vrf_output::VrfOutput;
+use self::{
+    chain_selection::{ChainSelection, ConsensusInfo},
+    vrf_output::VrfOutput,
+};
 use crate::{
     block::precomputed::PrecomputedBlock,
     canonicity::Canonicity,
@@ -20,7 +27,10 @@ use crate::{
 };
 use anyhow::{anyhow, bail};
 use serde::{Deserialize, Serialize};
-use std::{ffi::OsStr, path::Path};
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
 
 #[derive(Hash, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct Block {
@@ -31,6 +41,7 @@ pub struct Block {
     pub genesis_state_hash: BlockHash,
     pub global_slot_since_genesis: u32,
     pub hash_last_vrf_output: VrfOutput,
+    pub consensus_info: ConsensusInfo,
 }
 
 #[derive(Hash, PartialEq, Eq, Clone, Serialize, Deserialize)]
@@ -83,6 +94,7 @@ impl Block {
             hash_last_vrf_output: precomputed_block.hash_last_vrf_output(),
             global_slot_since_genesis: precomputed_block.global_slot_since_genesis(),
             genesis_state_hash: precomputed_block.genesis_state_hash(),
+            consensus_info: precomputed_block.into(),
         }
     }
 
@@ -135,6 +147,7 @@ pub struct BlockComparison {
     pub state_hash: BlockHash,
     pub blockchain_length: u32,
     pub hash_last_vrf_output: VrfOutput,
+    pub consensus_info: ConsensusInfo,
 }
 
 impl From<PrecomputedBlock> for Block {
@@ -147,6 +160,7 @@ impl From<PrecomputedBlock> for Block {
             hash_last_vrf_output: value.hash_last_vrf_output(),
             genesis_state_hash: value.genesis_state_hash(),
             global_slot_since_genesis: value.global_slot_since_genesis(),
+            consensus_info: (&value).into(),
         }
     }
 }
@@ -161,6 +175,7 @@ impl From<&PrecomputedBlock> for Block {
             hash_last_vrf_output: value.hash_last_vrf_output(),
             genesis_state_hash: value.genesis_state_hash(),
             global_slot_since_genesis: value.global_slot_since_genesis(),
+            consensus_info: value.into(),
         }
     }
 }
@@ -183,6 +198,7 @@ impl From<&PrecomputedBlock> for BlockComparison {
             state_hash: value.state_hash(),
             blockchain_length: value.blockchain_length(),
             hash_last_vrf_output: value.hash_last_vrf_output(),
+            consensus_info: value.into(),
         }
     }
 }
@@ -260,6 +276,18 @@ impl std::cmp::Ord for Block {
     }
 }
 
+impl ChainSelection for Block {
+    fn consensus_info(&self) -> &ConsensusInfo {
+        &self.consensus_info
+    }
+}
+
+impl ChainSelection for BlockComparison {
+    fn consensus_info(&self) -> &ConsensusInfo {
+        &self.consensus_info
+    }
+}
+
 impl std::default::Default for BlockHash {
     fn default() -> Self {
         Self("3NLDEFAULTDEFAULTDEFAULTDEFAULTDEFAULTDEFAULTDEFAULT".into())
@@ -400,6 +428,17 @@ pub fn extract_block_height_or_max(path: &Path) -> u32 {
     extract_block_height(path).unwrap_or(u32::MAX)
 }
 
+/// Sorts block file paths by height, then file name, so the same block files
+/// are always traversed in the same order
+pub fn sort_block_paths(paths: &mut [PathBuf]) {
+    paths.sort_by_cached_key(|path| {
+        (
+            extract_block_height_or_max(path),
+            path.file_name().map(ToOwned::to_owned),
+        )
+    });
+}
+
 pub fn extract_state_hash(path: &Path) -> String {
     let name = path.file_stem().and_then(|x| x.to_str()).unwrap();
     let dash_pos = name.rfind('-').unwrap();
@@ -1,15 +1,26 @@
 use super::precomputed::PcbVersion;
 use crate::{
-    block::{extract_block_height, precomputed::PrecomputedBlock},
-    canonicity::canonical_chain_discovery::discovery,
+    block::{
+        archive::{is_block_archive, BlockArchive},
+        extract_block_height,
+        precomputed::PrecomputedBlock,
+        previous_state_hash::PreviousStateHash,
+        sort_block_paths,
+        verify::verify_block_file,
+    },
+    canonicity::canonical_chain_discovery::discovery_with,
+    ledger::genesis::ForkConfig,
 };
 use anyhow::{anyhow, bail};
 use glob::glob;
-use log::info;
+use log::{info, warn};
 use std::{
+    collections::VecDeque,
     path::{Path, PathBuf},
+    sync::Arc,
     vec::IntoIter,
 };
+use tokio::task::JoinHandle;
 
 /// Splits block paths into three collections:
 /// - _deep canonical_ (chain of canonical blocks with at least
@@ -20,6 +31,9 @@ use std::{
 ///
 /// Traverses deep canoncial, recent, then orphaned (orphaned paths bypass the
 /// witness tree)
+///
+/// `blocks_dir` is either a directory of block files or a (compressed) tar
+/// archive of them, see [BlockArchive]
 pub struct BlockParser {
     pub blocks_dir: PathBuf,
     pub blocks_processed: u32,
@@ -29,6 +43,33 @@ pub struct BlockParser {
     pub total_num_bytes: u64,
     pub deep_canonical_bytes: u64,
     pub version: PcbVersion,
+
+    /// Blocks above the fork block are parsed as V2
+    pub fork: Option<ForkConfig>,
+
+    /// Skip block files failing [verify_block_file]
+    pub verify: bool,
+
+    /// Block files skipped by verification
+    pub quarantined: Vec<PathBuf>,
+
+    /// Number of blocks parsed concurrently ahead of [Self::next_block], at
+    /// most 1 parses serially
+    pub parse_threads: usize,
+
+    /// Index of the block archive the paths point into
+    archive: Option<Arc<BlockArchive>>,
+
+    /// Last block path consumed by [Self::next_block] or [Self::skip]
+    last_path: Option<PathBuf>,
+
+    /// Blocks being parsed ahead, in traversal order
+    parse_ahead: VecDeque<(
+        PathBuf,
+        PathKind,
+        JoinHandle<anyhow::Result<PrecomputedBlock>>,
+    )>,
+
     canonical_paths: IntoIter<PathBuf>,
     recent_paths: IntoIter<PathBuf>,
     orphaned_paths: IntoIter<PathBuf>,
@@ -48,6 +89,14 @@ pub enum ParsedBlock {
     Orphaned(PrecomputedBlock),
 }
 
+/// Collection a block path is traversed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PathKind {
+    DeepCanonical,
+    Recent,
+    Orphaned,
+}
+
 impl BlockParser {
     pub fn paths(&self) -> BlockParserPaths {
         BlockParserPaths {
@@ -115,12 +164,8 @@ impl BlockParser {
     ) -> anyhow::Result<Self> {
         if blocks_dir.exists() {
             let blocks_dir = blocks_dir.to_owned();
-            let mut paths: Vec<PathBuf> = glob(&format!("{}/*-*-*.json", blocks_dir.display()))?
-                .filter_map(|x| x.ok())
-                .collect();
-            let total_num_bytes = paths
-                .iter()
-                .fold(0, |acc, p| acc + p.metadata().unwrap().len());
+            let (mut paths, archive) = block_paths(&blocks_dir)?;
+            let total_num_bytes = total_size(archive.as_ref(), &paths);
 
             if min_length.is_some() {
                 paths.retain(|p| extract_block_height(p) > min_length)
@@ -133,6 +178,13 @@ impl BlockParser {
             paths.sort_by_cached_key(|path| extract_block_height(path));
             Ok(Self {
                 version,
+                fork: None,
+                verify: false,
+                quarantined: vec![],
+                parse_threads: 1,
+                parse_ahead: VecDeque::new(),
+                archive: archive.map(Arc::new),
+                last_path: None,
                 blocks_dir,
                 total_num_bytes,
                 bytes_processed: 0,
@@ -145,7 +197,7 @@ impl BlockParser {
                 orphaned_paths: vec![].into_iter(),
             })
         } else {
-            Ok(Self::empty(blocks_dir, &[]))
+            Ok(Self::empty(blocks_dir, &[], None))
         }
     }
 
@@ -153,9 +205,7 @@ impl BlockParser {
     pub fn new_testing(blocks_dir: &Path) -> anyhow::Result<Self> {
         if blocks_dir.exists() {
             let blocks_dir = blocks_dir.to_owned();
-            let mut paths: Vec<PathBuf> = glob(&format!("{}/*-*-*.json", blocks_dir.display()))?
-                .filter_map(|x| x.ok())
-                .collect();
+            let (mut paths, archive) = block_paths(&blocks_dir)?;
             paths.sort_by_cached_key(|path| extract_block_height(path));
 
             println!("===== Testing block parser paths =====");
@@ -164,7 +214,7 @@ impl BlockParser {
             }
             println!("======================================");
 
-            Ok(Self::empty(&blocks_dir, &paths))
+            Ok(Self::empty(&blocks_dir, &paths, archive))
         } else {
             bail!("blocks_dir: {:?}, does not exist!", blocks_dir)
         }
@@ -184,25 +234,32 @@ impl BlockParser {
     ) -> anyhow::Result<Self> {
         info!("Block parser with canonical chain discovery");
         if blocks_dir.exists() {
-            let pattern = format!("{}/*-*-*.json", blocks_dir.display());
             let blocks_dir = blocks_dir.to_owned();
-            let paths: Vec<PathBuf> = glob(&pattern)?.filter_map(|x| x.ok()).collect();
-            let total_num_bytes = paths
-                .iter()
-                .fold(0, |acc, p| acc + p.metadata().unwrap().len());
-            if let Ok((canonical_paths, recent_paths, orphaned_paths)) = discovery(
+            let (paths, archive) = block_paths(&blocks_dir)?;
+            let total_num_bytes = total_size(archive.as_ref(), &paths);
+            let previous_state_hash = |path: &Path| match archive.as_ref() {
+                Some(archive) => archive.previous_state_hash(path),
+                None => PreviousStateHash::from_path(path),
+            };
+            if let Ok((canonical_paths, recent_paths, orphaned_paths)) = discovery_with(
                 min_len_filter,
                 max_len_filter,
                 canonical_threshold,
                 reporting_freq,
                 paths.iter().collect(),
+                &previous_state_hash,
             ) {
                 info!("Canonical chain discovery successful...");
-                let deep_canonical_bytes = canonical_paths
-                    .iter()
-                    .fold(0, |acc, p| acc + p.metadata().unwrap().len());
+                let deep_canonical_bytes = total_size(archive.as_ref(), &canonical_paths);
                 Ok(Self {
                     version,
+                    fork: None,
+                    verify: false,
+                    quarantined: vec![],
+                    parse_threads: 1,
+                    parse_ahead: VecDeque::new(),
+                    archive: archive.map(Arc::new),
+                    last_path: None,
                     blocks_dir,
                     total_num_bytes,
                     bytes_processed: 0,
@@ -215,7 +272,7 @@ impl BlockParser {
                     orphaned_paths: orphaned_paths.into_iter(),
                 })
             } else {
-                Ok(Self::empty(&blocks_dir, &paths))
+                Ok(Self::empty(&blocks_dir, &paths, archive))
             }
         } else {
             bail!("blocks_dir {} does not exist!", blocks_dir.display())
@@ -225,36 +282,100 @@ impl BlockParser {
     fn consume_block(
         &mut self,
         path: &Path,
-        designation: &dyn Fn(PrecomputedBlock) -> ParsedBlock,
+        kind: PathKind,
+        block: anyhow::Result<PrecomputedBlock>,
     ) -> anyhow::Result<Option<(ParsedBlock, u64)>> {
-        let block_bytes = path.metadata().unwrap().len();
-        match PrecomputedBlock::parse_file(path, self.version.clone()).map(designation) {
-            Ok(parsed_block) => {
+        let block_bytes = file_size(self.archive.as_deref(), path);
+        self.last_path = Some(path.to_path_buf());
+        match block {
+            Ok(block) => {
+                if self.verify {
+                    if let Err(e) = verify_block_file(&block, path) {
+                        warn!("Quarantining block file {}: {e}", path.display());
+                        self.quarantined.push(path.to_path_buf());
+                        return Ok(None);
+                    }
+                }
+
                 self.blocks_processed += 1;
                 self.bytes_processed += block_bytes;
-                Ok(Some((parsed_block, block_bytes)))
+                Ok(Some((kind.designate(block), block_bytes)))
             }
             Err(e) => bail!("Block parsing error: {}", e),
         }
     }
+
+    /// Next path in traversal order
+    fn next_path(&mut self) -> Option<(PathBuf, PathKind)> {
+        if let Some(path) = self.canonical_paths.next() {
+            return Some((path, PathKind::DeepCanonical));
+        }
+        if let Some(path) = self.recent_paths.next() {
+            return Some((path, PathKind::Recent));
+        }
+        self.orphaned_paths
+            .next()
+            .map(|path| (path, PathKind::Orphaned))
+    }
+
+    /// Keeps `parse_threads` blocks parsing on the blocking thread pool
+    fn fill_parse_ahead(&mut self) {
+        while self.parse_ahead.len() < self.parse_threads {
+            match self.next_path() {
+                Some((path, kind)) => {
+                    let archive = self.archive.clone();
+                    let version = self.block_version(&path);
+                    let parse_path = path.clone();
+                    let handle = tokio::task::spawn_blocking(move || {
+                        parse_block(archive.as_deref(), &parse_path, version)
+                    });
+                    self.parse_ahead.push_back((path, kind, handle));
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Precomputed block version of the block file at `path`
+    pub fn block_version(&self, path: &Path) -> PcbVersion {
+        match (self.fork.as_ref(), extract_block_height(path)) {
+            (Some(fork), Some(height)) if fork.is_post_fork(height) => PcbVersion::V2,
+            _ => self.version.clone(),
+        }
+    }
+
     /// Traverses `self`'s internal paths
     /// - deep canonical
     /// - recent
     /// - orphaned
+    ///
+    /// With `parse_threads > 1`, the following blocks are parsed concurrently
+    /// & returned in order. Quarantined block files are skipped
     pub async fn next_block(&mut self) -> anyhow::Result<Option<(ParsedBlock, u64)>> {
-        if let Some(next_path) = self.canonical_paths.next() {
-            return self.consume_block(&next_path, &ParsedBlock::DeepCanonical);
-        }
-
-        if let Some(next_path) = self.recent_paths.next() {
-            return self.consume_block(&next_path, &ParsedBlock::Recent);
-        }
+        loop {
+            let (path, kind, block) = if self.parse_threads > 1 {
+                self.fill_parse_ahead();
+                match self.parse_ahead.pop_front() {
+                    Some((path, kind, handle)) => (path, kind, handle.await?),
+                    None => return Ok(None),
+                }
+            } else {
+                match self.next_path() {
+                    Some((path, kind)) => {
+                        let block =
+                            parse_block(self.archive.as_deref(), &path, self.block_version(&path));
+                        (path, kind, block)
+                    }
+                    None => return Ok(None),
+                }
+            };
 
-        if let Some(next_path) = self.orphaned_paths.next() {
-            return self.consume_block(&next_path, &ParsedBlock::Orphaned);
+            match self.consume_block(&path, kind, block)? {
+                Some(parsed) => return Ok(Some(parsed)),
+                None if kind == PathKind::DeepCanonical => self.num_deep_canonical_blocks -= 1,
+                None => (),
+            }
         }
-
-        Ok(None)
     }
 
     /// Gets the precomputed block with supplied `state_hash`, it must exist
@@ -280,15 +401,61 @@ impl BlockParser {
         Ok(next_block)
     }
 
-    fn empty(blocks_dir: &Path, paths: &[PathBuf]) -> Self {
-        let total_num_bytes = paths
+    /// Number of block paths consumed by [Self::next_block], including
+    /// quarantined block files
+    pub fn position(&self) -> u32 {
+        self.blocks_processed + self.quarantined.len() as u32
+    }
+
+    /// Last block path consumed by [Self::next_block] or [Self::skip]
+    pub fn last_path(&self) -> Option<&Path> {
+        self.last_path.as_deref()
+    }
+
+    /// Skips the next `num_paths` block paths without parsing them, e.g. to
+    /// resume from an [crate::store::checkpoint::IngestionCheckpoint].
+    /// Returns the last skipped path
+    pub fn skip(&mut self, num_paths: u32) -> Option<PathBuf> {
+        assert!(self.parse_ahead.is_empty(), "cannot skip parsed blocks");
+
+        let mut last = None;
+        for _ in 0..num_paths {
+            match self.next_path() {
+                Some((path, _)) => {
+                    self.blocks_processed += 1;
+                    self.bytes_processed += file_size(self.archive.as_deref(), &path);
+                    self.last_path = Some(path.clone());
+                    last = Some(path);
+                }
+                None => break,
+            }
+        }
+        last
+    }
+
+    /// Number of orphaned blocks not yet returned by [Self::next_block]
+    pub fn num_pending_orphaned_blocks(&self) -> u32 {
+        let num_parsing = self
+            .parse_ahead
             .iter()
-            .fold(0, |acc, p| acc + p.metadata().unwrap().len());
+            .filter(|(_, kind, _)| *kind == PathKind::Orphaned)
+            .count();
+        (num_parsing + self.orphaned_paths.len()) as u32
+    }
+
+    fn empty(blocks_dir: &Path, paths: &[PathBuf], archive: Option<BlockArchive>) -> Self {
         Self {
-            total_num_bytes,
+            total_num_bytes: total_size(archive.as_ref(), paths),
+            archive: archive.map(Arc::new),
+            last_path: None,
+            parse_threads: 1,
+            parse_ahead: VecDeque::new(),
             bytes_processed: 0,
             blocks_processed: 0,
             version: PcbVersion::default(),
+            fork: None,
+            verify: false,
+            quarantined: vec![],
             deep_canonical_bytes: 0,
             num_deep_canonical_blocks: 0,
             total_num_blocks: paths.len() as u32,
@@ -300,6 +467,56 @@ impl BlockParser {
     }
 }
 
+impl PathKind {
+    fn designate(self, block: PrecomputedBlock) -> ParsedBlock {
+        match self {
+            Self::DeepCanonical => ParsedBlock::DeepCanonical(block),
+            Self::Recent => ParsedBlock::Recent(block),
+            Self::Orphaned => ParsedBlock::Orphaned(block),
+        }
+    }
+}
+
+/// Parses the block file at `path`, or the archive entry at the virtual path
+fn parse_block(
+    archive: Option<&BlockArchive>,
+    path: &Path,
+    version: PcbVersion,
+) -> anyhow::Result<PrecomputedBlock> {
+    match archive {
+        Some(archive) => archive
+            .read(path)
+            .and_then(|contents| PrecomputedBlock::parse_file_contents(path, contents, version)),
+        None => PrecomputedBlock::parse_file(path, version),
+    }
+}
+
+/// Block file paths of a blocks directory, or the virtual paths of a block
+/// archive's entries, sorted by height & file name
+fn block_paths(blocks_dir: &Path) -> anyhow::Result<(Vec<PathBuf>, Option<BlockArchive>)> {
+    if is_block_archive(blocks_dir) {
+        let archive = BlockArchive::open(blocks_dir)?;
+        Ok((archive.paths(), Some(archive)))
+    } else {
+        let mut paths: Vec<_> = glob(&format!("{}/*-*-*.json", blocks_dir.display()))?
+            .filter_map(|x| x.ok())
+            .collect();
+        sort_block_paths(&mut paths);
+        Ok((paths, None))
+    }
+}
+
+fn file_size(archive: Option<&BlockArchive>, path: &Path) -> u64 {
+    match archive {
+        Some(archive) => archive.size(path).unwrap_or_default(),
+        None => path.metadata().unwrap().len(),
+    }
+}
+
+fn total_size(archive: Option<&BlockArchive>, paths: &[PathBuf]) -> u64 {
+    paths.iter().fold(0, |acc, p| acc + file_size(archive, p))
+}
+
 impl From<ParsedBlock> for PrecomputedBlock {
     fn from(value: ParsedBlock) -> Self {
         match value {
@@ -7,15 +7,21 @@ use crate::{
     canonicity::Canonicity,
     chain::Network,
     command::{signed::SignedCommand, UserCommandWithStatus, UserCommandWithStatusT},
-    constants::{berkeley::*, *},
-    ledger::{coinbase::Coinbase, public_key::PublicKey, username::Username, LedgerHash},
+    constants::*,
+    ledger::{
+        coinbase::Coinbase, public_key::PublicKey, token::TokenId, username::Username, LedgerHash,
+    },
     mina_blocks::{common::from_str, v2},
-    protocol::serialization_types::{
-        blockchain_state::BlockchainState,
-        consensus_state as mina_consensus,
-        protocol_state::{ProtocolState, ProtocolStateJson},
-        snark_work as mina_snark, staged_ledger_diff as mina_rs,
+    protocol::{
+        self,
+        serialization_types::{
+            common::Base58EncodableVersionedType,
+            consensus_state as mina_consensus,
+            protocol_state::{ProtocolState, ProtocolStateJson},
+            staged_ledger_diff as mina_rs, version_bytes,
+        },
     },
+    snark_work::SnarkWorkSummary,
     store::username::UsernameUpdate,
 };
 use anyhow::bail;
@@ -46,20 +52,6 @@ fn mainnet_genesis_timestamp() -> u64 {
     MAINNET_GENESIS_TIMESTAMP
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct BlockFileV2 {
-    #[serde(default = "berkeley_genesis_timestamp")]
-    #[serde(deserialize_with = "from_str")]
-    scheduled_time: u64,
-
-    protocol_state: v2::protocol_state::ProtocolState,
-    staged_ledger_diff: v2::staged_ledger_diff::StagedLedgerDiff,
-}
-
-fn berkeley_genesis_timestamp() -> u64 {
-    BERKELEY_GENESIS_TIMESTAMP
-}
-
 #[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum PcbVersion {
     #[default]
@@ -92,6 +84,10 @@ pub struct PrecomputedBlockV2 {
     pub blockchain_length: u32,
     pub protocol_state: v2::protocol_state::ProtocolState,
     pub staged_ledger_diff: v2::staged_ledger_diff::StagedLedgerDiff,
+    pub accounts_created: Vec<v2::AccountCreated>,
+
+    #[serde(default)]
+    pub accounts_accessed: Vec<(u64, v2::AccountAccessed)>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -121,6 +117,10 @@ pub struct PrecomputedBlockWithCanonicityV2 {
     pub blockchain_length: u32,
     pub protocol_state: v2::protocol_state::ProtocolState,
     pub staged_ledger_diff: v2::staged_ledger_diff::StagedLedgerDiff,
+    pub accounts_created: Vec<v2::AccountCreated>,
+
+    #[serde(default)]
+    pub accounts_accessed: Vec<(u64, v2::AccountAccessed)>,
 }
 
 impl PrecomputedBlock {
@@ -148,18 +148,26 @@ impl PrecomputedBlock {
                 })))
             }
             PcbVersion::V2 => {
-                let BlockFileV2 {
+                let v2::precomputed_block::PrecomputedBlockData {
                     scheduled_time,
                     protocol_state,
                     staged_ledger_diff,
-                } = serde_json::from_slice(&block_file_contents.contents)?;
+                    accounts_created,
+                    accounts_accessed,
+                    ..
+                } = serde_json::from_slice::<v2::precomputed_block::PrecomputedBlock>(
+                    &block_file_contents.contents,
+                )?
+                .data;
                 Ok(Self::V2(PrecomputedBlockV2 {
                     state_hash,
                     scheduled_time,
                     blockchain_length,
                     network: block_file_contents.network,
-                    protocol_state: protocol_state.to_owned(),
-                    staged_ledger_diff: staged_ledger_diff.to_owned(),
+                    protocol_state,
+                    staged_ledger_diff,
+                    accounts_created,
+                    accounts_accessed,
                 }))
             }
         }
@@ -186,10 +194,19 @@ impl PrecomputedBlock {
 
     /// Parses the precomputed block if the path is a valid block file
     pub fn parse_file(path: &Path, version: PcbVersion) -> anyhow::Result<Self> {
+        Self::parse_file_contents(path, std::fs::read(path)?, version)
+    }
+
+    /// Parses the contents of the block file at `path`, e.g. a block archive
+    /// entry
+    pub fn parse_file_contents(
+        path: &Path,
+        contents: Vec<u8>,
+        version: PcbVersion,
+    ) -> anyhow::Result<Self> {
         let network = extract_network(path);
         let blockchain_length = extract_block_height(path).expect("length in filename");
         let state_hash = extract_state_hash(path);
-        let contents = std::fs::read(path)?;
         let precomputed_block = PrecomputedBlock::from_file_contents(
             BlockFileContents {
                 contents,
@@ -211,55 +228,121 @@ impl PrecomputedBlock {
     pub fn commands_pre_diff(&self) -> Vec<UserCommandWithStatus> {
         match self {
             Self::V1(v1) => v1
-                .staged_ledger_diff
-                .diff
-                .clone()
-                .inner()
-                .0
-                .inner()
-                .inner()
+                .staged_ledger_pre_diff()
                 .commands
                 .into_iter()
                 .map(UserCommandWithStatus)
                 .collect(),
-            Self::V2(_) => todo!("commands_pre_diff {}", self.summary()),
+            Self::V2(v2) => v2.commands(0),
         }
     }
 
     pub fn commands_post_diff(&self) -> Vec<UserCommandWithStatus> {
         match self {
-            Self::V1(v1) => v1
-                .staged_ledger_diff
-                .diff
-                .clone()
-                .inner()
-                .1
-                .map_or(vec![], |diff| {
-                    diff.inner()
-                        .inner()
-                        .commands
-                        .into_iter()
-                        .map(UserCommandWithStatus)
-                        .collect()
-                }),
-            Self::V2(_) => todo!("commands_post_diff {}", self.summary()),
+            Self::V1(v1) => v1.staged_ledger_post_diff().map_or(vec![], |diff| {
+                diff.commands
+                    .into_iter()
+                    .map(UserCommandWithStatus)
+                    .collect()
+            }),
+            Self::V2(v2) => v2.commands(1),
+        }
+    }
+
+    /// Applied & failed zkApp commands, in application order
+    pub fn zkapp_commands(&self) -> Vec<v2::staged_ledger_diff::command::ZkappCommand> {
+        self.zkapp_commands_with_accounts_created()
+            .into_iter()
+            .map(|(cmd, _)| cmd)
+            .collect()
+    }
+
+    /// zkApp commands, in application order, paired with the MINA accounts
+    /// they created
+    pub fn zkapp_commands_with_accounts_created(
+        &self,
+    ) -> Vec<(
+        v2::staged_ledger_diff::command::ZkappCommand,
+        Vec<PublicKey>,
+    )> {
+        match self {
+            Self::V1(_) => vec![],
+            Self::V2(v2) => {
+                let mut accounts_created = v2.accounts_created_by().zkapp_commands;
+                v2.diffs()
+                    .flat_map(|(n, diff)| {
+                        diff.commands
+                            .iter()
+                            .enumerate()
+                            .filter_map(move |(m, cmd)| {
+                                match v2::staged_ledger_diff::command::Command::from(cmd.clone()) {
+                                    v2::staged_ledger_diff::command::Command::ZkappCommand(
+                                        zkapp,
+                                    ) => Some(((n, m), zkapp)),
+                                    v2::staged_ledger_diff::command::Command::UserCommand(_) => {
+                                        None
+                                    }
+                                }
+                            })
+                    })
+                    .map(|(idx, zkapp)| (zkapp, accounts_created.remove(&idx).unwrap_or_default()))
+                    .collect()
+            }
+        }
+    }
+
+    /// Accounts accessed by the block's transactions, in their post-block
+    /// state. Pre-hardfork blocks don't record them.
+    pub fn accounts_accessed(&self) -> Vec<v2::AccountAccessed> {
+        match self {
+            Self::V1(_) => vec![],
+            Self::V2(v2) => v2
+                .accounts_accessed
+                .iter()
+                .map(|(_, account)| account.clone())
+                .collect(),
+        }
+    }
+
+    /// MINA accounts created by SNARK work & coinbase fee transfers
+    pub fn fee_transfer_accounts_created(&self) -> Vec<PublicKey> {
+        match self {
+            Self::V1(_) => vec![],
+            Self::V2(v2) => v2.accounts_created_by().fee_transfers,
+        }
+    }
+
+    /// Custom token accounts created by the block's zkApp commands
+    pub fn token_accounts_created(&self) -> Vec<(PublicKey, TokenId)> {
+        match self {
+            Self::V1(_) => vec![],
+            Self::V2(v2) => v2
+                .accounts_created
+                .iter()
+                .filter_map(|account| {
+                    let token = TokenId::new(account.token_id());
+                    (!token.is_mina()).then(|| (account.public_key().clone(), token))
+                })
+                .collect(),
         }
     }
 
     pub fn tx_fees(&self) -> u64 {
+        let zkapp_fees: u64 = self.zkapp_commands().iter().map(|cmd| cmd.fee()).sum();
         self.commands()
             .into_iter()
             .map(|cmd| {
                 let signed: SignedCommand = cmd.clone().into();
                 signed.fee()
             })
-            .sum()
+            .sum::<u64>()
+            + zkapp_fees
     }
 
     pub fn snark_fees(&self) -> u64 {
         self.completed_works()
             .into_iter()
-            .map(|work| work.fee.t.t)
+            .map(|work| work.fee)
             .sum()
     }
 
@@ -301,102 +384,269 @@ impl PrecomputedBlock {
                 );
             }
         });
+
+        // from zkApp commands, the net MINA balance change
+        for (cmd, created) in self.zkapp_commands_with_accounts_created() {
+            let updates = cmd.account_updates();
+            for pk in created {
+                let mut balance: i128 = 0;
+                let mut pays_creation_fee = false;
+                for update in updates
+                    .iter()
+                    .filter(|update| update.is_mina_token() && update.public_key == pk)
+                {
+                    balance += update.balance_change();
+                    pays_creation_fee |= update.implicit_account_creation_fee;
+                }
+                if pays_creation_fee {
+                    balance -= MAINNET_ACCOUNT_CREATION_FEE.0 as i128;
+                }
+                account_balances.insert(pk, balance.max(0) as u64);
+            }
+        }
+
+        // from fee transfers, balances are updated by the fee transfers
+        for pk in self.fee_transfer_accounts_created() {
+            account_balances.insert(pk, 0);
+        }
         (account_balances, new_coinbase_receiver)
     }
 
-    pub fn consensus_state(&self) -> mina_consensus::ConsensusState {
+    pub fn block_creator(&self) -> PublicKey {
         match self {
-            Self::V1(v1) => v1.protocol_state.body.t.t.consensus_state.t.t.to_owned(),
-            Self::V2(_) => todo!("consensus_state {}", self.summary()),
+            Self::V1(v1) => v1.consensus_state().block_creator.into(),
+            Self::V2(v2) => v2.protocol_state.body.consensus_state.block_creator.clone(),
         }
     }
 
-    pub fn blockchain_state(&self) -> BlockchainState {
+    pub fn block_stake_winner(&self) -> PublicKey {
         match self {
-            Self::V1(v1) => v1.protocol_state.body.t.t.blockchain_state.t.t.to_owned(),
-            Self::V2(_) => todo!("blockchain_state {}", self.summary()),
+            Self::V1(v1) => v1.consensus_state().block_stake_winner.into(),
+            Self::V2(v2) => v2
+                .protocol_state
+                .body
+                .consensus_state
+                .block_stake_winner
+                .clone(),
         }
     }
 
-    pub fn block_creator(&self) -> PublicKey {
-        self.consensus_state().block_creator.into()
+    pub fn coinbase_receiver(&self) -> PublicKey {
+        match self {
+            Self::V1(v1) => v1.consensus_state().coinbase_receiver.into(),
+            Self::V2(v2) => v2
+                .protocol_state
+                .body
+                .consensus_state
+                .coinbase_receiver
+                .clone(),
+        }
     }
 
-    pub fn block_stake_winner(&self) -> PublicKey {
-        self.consensus_state().block_stake_winner.into()
+    /// Post-hardfork coinbases are never supercharged
+    pub fn supercharge_coinbase(&self) -> bool {
+        match self {
+            Self::V1(v1) => v1.consensus_state().supercharge_coinbase,
+            Self::V2(_) => false,
+        }
     }
 
-    pub fn internal_command_balances(&self) -> Vec<mina_rs::InternalCommandBalanceData> {
-        self.staged_ledger_pre_diff()
-            .internal_command_balances
-            .iter()
-            .map(|x| x.t.to_owned())
-            .collect()
+    pub fn min_window_density(&self) -> u32 {
+        match self {
+            Self::V1(v1) => v1.consensus_state().min_window_density.t.t,
+            Self::V2(v2) => v2.protocol_state.body.consensus_state.min_window_density,
+        }
     }
 
-    pub fn staged_ledger_hash(&self) -> LedgerHash {
-        LedgerHash::from_hashv1(match self {
+    pub fn sub_window_densities(&self) -> Vec<u32> {
+        match self {
             Self::V1(v1) => v1
+                .consensus_state()
+                .sub_window_densities
+                .iter()
+                .map(|density| density.t.t)
+                .collect(),
+            Self::V2(v2) => v2
                 .protocol_state
                 .body
-                .t
-                .t
-                .blockchain_state
-                .t
-                .t
-                .staged_ledger_hash
-                .t
-                .t
-                .non_snark
-                .t
-                .ledger_hash
-                .to_owned(),
-            Self::V2(_) => todo!("staged_ledger_hash {}", self.summary()),
-        })
+                .consensus_state
+                .sub_window_densities
+                .iter()
+                .map(|density| density.parse().expect("sub window density"))
+                .collect(),
+        }
     }
 
-    pub fn staged_ledger_diff_tuple(&self) -> mina_rs::StagedLedgerDiffTuple {
+    /// Global slot since the most recent genesis (pre- or post-hardfork),
+    /// unlike [Self::global_slot_since_genesis]
+    pub fn curr_global_slot(&self) -> u32 {
         match self {
-            Self::V1(v1) => v1.staged_ledger_diff.diff.t.to_owned(),
-            Self::V2(_) => todo!("staged_ledger_diff_tuple {}", self.summary()),
+            Self::V1(v1) => v1.consensus_state().curr_global_slot.t.t.slot_number.t.t,
+            Self::V2(v2) => {
+                v2.protocol_state
+                    .body
+                    .consensus_state
+                    .curr_global_slot_since_hard_fork
+                    .slot_number
+            }
         }
     }
 
-    pub fn staged_ledger_pre_diff(&self) -> mina_rs::StagedLedgerPreDiff {
-        self.staged_ledger_diff_tuple().0.inner().inner()
+    pub fn total_currency(&self) -> u64 {
+        match self {
+            Self::V1(v1) => v1.consensus_state().total_currency.t.t,
+            Self::V2(v2) => v2.protocol_state.body.consensus_state.total_currency,
+        }
     }
 
-    pub fn staged_ledger_post_diff(&self) -> Option<mina_rs::StagedLedgerPreDiff> {
-        self.staged_ledger_diff_tuple().1.map(|x| x.inner().inner())
+    pub fn has_ancestor_in_same_checkpoint_window(&self) -> bool {
+        match self {
+            Self::V1(v1) => v1.consensus_state().has_ancestor_in_same_checkpoint_window,
+            Self::V2(v2) => {
+                v2.protocol_state
+                    .body
+                    .consensus_state
+                    .has_ancestor_in_same_checkpoint_window
+            }
+        }
     }
 
-    pub fn completed_works(&self) -> Vec<mina_snark::TransactionSnarkWork> {
+    pub fn next_epoch_data(&self) -> v2::protocol_state::EpochData {
+        match self {
+            Self::V1(v1) => epoch_data_v1(&v1.consensus_state().next_epoch_data.t.t),
+            Self::V2(v2) => v2
+                .protocol_state
+                .body
+                .consensus_state
+                .next_epoch_data
+                .clone(),
+        }
+    }
+
+    pub fn staking_epoch_data(&self) -> v2::protocol_state::EpochData {
+        match self {
+            Self::V1(v1) => epoch_data_v1(&v1.consensus_state().staking_epoch_data.t.t),
+            Self::V2(v2) => v2
+                .protocol_state
+                .body
+                .consensus_state
+                .staking_epoch_data
+                .clone(),
+        }
+    }
+
+    pub fn internal_command_balances(&self) -> Vec<mina_rs::InternalCommandBalanceData> {
+        match self {
+            Self::V1(v1) => v1
+                .staged_ledger_pre_diff()
+                .internal_command_balances
+                .iter()
+                .map(|x| x.t.to_owned())
+                .collect(),
+            // post-hardfork blocks don't report internal command balances
+            Self::V2(_) => vec![],
+        }
+    }
+
+    pub fn snarked_ledger_hash(&self) -> LedgerHash {
+        match self {
+            Self::V1(v1) => LedgerHash::from_hashv1(
+                v1.protocol_state
+                    .body
+                    .t
+                    .t
+                    .blockchain_state
+                    .t
+                    .t
+                    .snarked_ledger_hash
+                    .to_owned(),
+            ),
+            Self::V2(v2) => v2
+                .protocol_state
+                .body
+                .blockchain_state
+                .ledger_proof_statement
+                .target
+                .first_pass_ledger
+                .to_owned(),
+        }
+    }
+
+    pub fn staged_ledger_hash(&self) -> LedgerHash {
+        match self {
+            Self::V1(v1) => LedgerHash::from_hashv1(
+                v1.protocol_state
+                    .body
+                    .t
+                    .t
+                    .blockchain_state
+                    .t
+                    .t
+                    .staged_ledger_hash
+                    .t
+                    .t
+                    .non_snark
+                    .t
+                    .ledger_hash
+                    .to_owned(),
+            ),
+            Self::V2(v2) => v2
+                .protocol_state
+                .body
+                .blockchain_state
+                .staged_ledger_hash
+                .non_snark
+                .ledger_hash
+                .to_owned(),
+        }
+    }
+
+    pub fn completed_works(&self) -> Vec<SnarkWorkSummary> {
         let mut completed_works = self.completed_works_post_diff().unwrap_or_default();
         completed_works.append(&mut self.completed_works_pre_diff());
         completed_works
     }
 
-    pub fn completed_works_pre_diff(&self) -> Vec<mina_snark::TransactionSnarkWork> {
-        self.staged_ledger_pre_diff()
-            .completed_works
-            .iter()
-            .map(|x| x.t.clone())
-            .collect()
+    pub fn completed_works_pre_diff(&self) -> Vec<SnarkWorkSummary> {
+        match self {
+            Self::V1(v1) => v1
+                .staged_ledger_pre_diff()
+                .completed_works
+                .into_iter()
+                .map(|x| x.t.into())
+                .collect(),
+            Self::V2(v2) => v2.completed_works(0).unwrap_or_default(),
+        }
     }
 
-    pub fn completed_works_post_diff(&self) -> Option<Vec<mina_snark::TransactionSnarkWork>> {
-        self.staged_ledger_post_diff()
-            .map(|diff| diff.completed_works.iter().map(|x| x.t.clone()).collect())
+    pub fn completed_works_post_diff(&self) -> Option<Vec<SnarkWorkSummary>> {
+        match self {
+            Self::V1(v1) => v1.staged_ledger_post_diff().map(|diff| {
+                diff.completed_works
+                    .into_iter()
+                    .map(|x| x.t.into())
+                    .collect()
+            }),
+            Self::V2(v2) => v2.completed_works(1),
+        }
     }
 
     pub fn coinbase_receiver_balance(&self) -> Option<u64> {
-        for internal_balance in self.internal_command_balances() {
-            if let mina_rs::InternalCommandBalanceData::CoinBase(x) = internal_balance {
-                return Some(x.inner().coinbase_receiver_balance.inner().inner().inner());
+        match self {
+            Self::V1(_) => {
+                for internal_balance in self.internal_command_balances() {
+                    if let mina_rs::InternalCommandBalanceData::CoinBase(x) = internal_balance {
+                        return Some(x.inner().coinbase_receiver_balance.inner().inner().inner());
+                    }
+                }
+                None
             }
+            // only known for new coinbase receivers
+            Self::V2(v2) => v2
+                .accounts_created_by()
+                .coinbase_receiver
+                .map(|fee| MAINNET_COINBASE_REWARD - fee),
         }
-
-        None
     }
 
     pub fn fee_transfer_balances(&self) -> Vec<(u64, Option<u64>)> {
@@ -412,10 +662,6 @@ impl PrecomputedBlock {
         res
     }
 
-    pub fn coinbase_receiver(&self) -> PublicKey {
-        self.consensus_state().coinbase_receiver.into()
-    }
-
     pub fn consensus_public_keys(&self) -> HashSet<PublicKey> {
         HashSet::from([
             self.block_creator(),
@@ -439,6 +685,11 @@ impl PrecomputedBlock {
             add_keys(&mut pk_set, signed_command.all_command_public_keys());
         });
 
+        // add keys from all zkApp commands
+        self.zkapp_commands()
+            .iter()
+            .for_each(|zkapp| add_keys(&mut pk_set, zkapp.public_keys()));
+
         let mut pks: Vec<PublicKey> = pk_set.into_iter().collect();
         pks.sort();
         pks
@@ -450,8 +701,8 @@ impl PrecomputedBlock {
 
         // add prover keys from completed SNARK work
         let completed_works = self.completed_works();
-        completed_works.iter().for_each(|work| {
-            pk_set.insert(work.prover.clone().into());
+        completed_works.into_iter().for_each(|work| {
+            pk_set.insert(work.prover);
         });
 
         let mut pks: Vec<PublicKey> = pk_set.into_iter().collect();
@@ -484,6 +735,12 @@ impl PrecomputedBlock {
                 add_keys(&mut public_keys, signed_command.all_command_public_keys());
             });
 
+        // applied zkApp commands
+        self.zkapp_commands()
+            .iter()
+            .filter(|zkapp| zkapp.is_applied())
+            .for_each(|zkapp| add_keys(&mut public_keys, zkapp.public_keys()));
+
         let mut pks: Vec<PublicKey> = public_keys.into_iter().collect();
         pks.sort();
         pks
@@ -524,7 +781,7 @@ impl PrecomputedBlock {
                     .t
             }
             Self::V2(v2) => {
-                // TODO add mainnet end slot height?
+                // continues the pre-fork global slot numbering
                 v2.protocol_state
                     .body
                     .consensus_state
@@ -533,6 +790,15 @@ impl PrecomputedBlock {
         }
     }
 
+    /// Blockchain length in the protocol state, [Self::blockchain_length]
+    /// comes from the block file name
+    pub fn consensus_blockchain_length(&self) -> u32 {
+        match self {
+            Self::V1(v1) => v1.consensus_state().blockchain_length.t.t,
+            Self::V2(v2) => v2.protocol_state.body.consensus_state.blockchain_length,
+        }
+    }
+
     pub fn epoch_count(&self) -> u32 {
         match self {
             Self::V1(v1) => {
@@ -589,7 +855,7 @@ impl PrecomputedBlock {
     pub fn command_hashes(&self) -> Vec<String> {
         SignedCommand::from_precomputed(self)
             .iter()
-            .filter_map(|cmd| cmd.signed_command.hash_signed_command().ok())
+            .filter_map(|cmd| cmd.signed_command.txn_hash(&self.version()).ok())
             .collect()
     }
 
@@ -617,13 +883,27 @@ impl PrecomputedBlock {
 
     /// Base64 encoded string
     pub fn last_vrf_output(&self) -> String {
-        let last_vrf_output = VrfOutput::new(self.consensus_state().last_vrf_output.t.0.clone());
-        last_vrf_output.base64_encode()
+        match self {
+            Self::V1(v1) => {
+                VrfOutput::new(v1.consensus_state().last_vrf_output.t.0.clone()).base64_encode()
+            }
+            Self::V2(v2) => v2
+                .protocol_state
+                .body
+                .consensus_state
+                .last_vrf_output
+                .clone(),
+        }
     }
 
     /// Blake2b hex digest of last_vrf_output
     pub fn hash_last_vrf_output(&self) -> VrfOutput {
-        let last_vrf_output = VrfOutput::new(self.consensus_state().last_vrf_output.t.0.clone());
+        let last_vrf_output = match self {
+            Self::V1(v1) => VrfOutput::new(v1.consensus_state().last_vrf_output.t.0.clone()),
+            Self::V2(_) => {
+                VrfOutput::base64_decode(&self.last_vrf_output()).expect("base64 last vrf output")
+            }
+        };
         VrfOutput::new(last_vrf_output.hex_digest())
     }
 
@@ -649,6 +929,8 @@ impl PrecomputedBlock {
                     scheduled_time: pcb_v2.scheduled_time,
                     protocol_state: pcb_v2.protocol_state.to_owned(),
                     staged_ledger_diff: pcb_v2.staged_ledger_diff.to_owned(),
+                    accounts_created: pcb_v2.accounts_created.to_owned(),
+                    accounts_accessed: pcb_v2.accounts_accessed.to_owned(),
                 })
             }
         }
@@ -692,6 +974,144 @@ impl PrecomputedBlock {
     }
 }
 
+impl PrecomputedBlockV1 {
+    pub fn consensus_state(&self) -> mina_consensus::ConsensusState {
+        self.protocol_state.body.t.t.consensus_state.t.t.to_owned()
+    }
+
+    pub fn staged_ledger_pre_diff(&self) -> mina_rs::StagedLedgerPreDiff {
+        self.staged_ledger_diff.diff.t.0.clone().inner().inner()
+    }
+
+    pub fn staged_ledger_post_diff(&self) -> Option<mina_rs::StagedLedgerPreDiff> {
+        self.staged_ledger_diff
+            .diff
+            .t
+            .1
+            .clone()
+            .map(|x| x.inner().inner())
+    }
+}
+
+/// Attribution of a V2 block's new MINA accounts to the transactions which
+/// created them. Commands are indexed by (diff, command) position.
+#[derive(Debug, Default)]
+struct AccountsCreatedBy {
+    /// Payments creating their receiver, with the creation fee
+    commands: HashMap<(usize, usize), u64>,
+
+    /// zkApp commands creating accounts
+    zkapp_commands: HashMap<(usize, usize), Vec<PublicKey>>,
+
+    /// Creation fee of a new coinbase receiver
+    coinbase_receiver: Option<u64>,
+
+    /// Accounts created by SNARK work & coinbase fee transfers
+    fee_transfers: Vec<PublicKey>,
+}
+
+impl PrecomputedBlockV2 {
+    /// Non-empty staged ledger diffs & their index
+    fn diffs(&self) -> impl Iterator<Item = (usize, &v2::staged_ledger_diff::Diff)> {
+        self.staged_ledger_diff
+            .diff
+            .iter()
+            .enumerate()
+            .filter_map(|(n, diff)| diff.as_ref().map(|diff| (n, diff)))
+    }
+
+    /// The block only lists the accounts created, so we walk the diffs in
+    /// application order (commands, coinbase, fee transfers) and attribute
+    /// each new account to the first transaction crediting it
+    fn accounts_created_by(&self) -> AccountsCreatedBy {
+        use v2::staged_ledger_diff::{CommandData, UserCommandPayloadBody};
+
+        let mut remaining: HashMap<PublicKey, u64> = self
+            .accounts_created
+            .iter()
+            .filter(|account| account.token_id() == v2::staged_ledger_diff::command::MINA_TOKEN_ID)
+            .map(|account| (account.public_key().clone(), account.creation_fee()))
+            .collect();
+        let mut res = AccountsCreatedBy::default();
+
+        for (n, diff) in self.diffs() {
+            for (m, cmd) in diff.commands.iter().enumerate() {
+                if !cmd.status.is_applied() {
+                    continue;
+                }
+                match &cmd.data.1 {
+                    CommandData::UserCommandData(data) => {
+                        if let UserCommandPayloadBody::Payment(payment) = &data.payload.body.1 {
+                            if let Some(fee) = remaining.remove(&payment.receiver_pk) {
+                                res.commands.insert((n, m), fee);
+                            }
+                        }
+                    }
+                    CommandData::ZkappCommandData(data) => {
+                        let zkapp = v2::staged_ledger_diff::command::ZkappCommand {
+                            data: data.clone(),
+                            status: cmd.status.clone(),
+                        };
+                        let created: Vec<PublicKey> = zkapp
+                            .account_updates()
+                            .into_iter()
+                            .filter(|update| update.is_mina_token())
+                            .filter_map(|update| {
+                                remaining.remove_entry(&update.public_key).map(|(pk, _)| pk)
+                            })
+                            .collect();
+                        if !created.is_empty() {
+                            res.zkapp_commands.insert((n, m), created);
+                        }
+                    }
+                }
+            }
+
+            if !matches!(diff.coinbase, v2::staged_ledger_diff::Coinbase::Zero(_)) {
+                let receiver = &self.protocol_state.body.consensus_state.coinbase_receiver;
+                if let Some(fee) = remaining.remove(receiver) {
+                    res.coinbase_receiver = Some(fee);
+                }
+            }
+        }
+
+        res.fee_transfers = remaining.into_keys().collect();
+        res.fee_transfers.sort();
+        res
+    }
+
+    /// Signed commands of the `n`th staged ledger diff
+    fn commands(&self, n: usize) -> Vec<UserCommandWithStatus> {
+        let accounts_created = self.accounts_created_by().commands;
+        self.diffs()
+            .filter(|(m, _)| *m == n)
+            .flat_map(|(_, diff)| diff.commands.iter().enumerate())
+            .filter_map(|(m, cmd)| {
+                match v2::staged_ledger_diff::command::Command::from(cmd.clone()) {
+                    v2::staged_ledger_diff::command::Command::UserCommand(cmd) => {
+                        Some(UserCommandWithStatus::from_v2(
+                            &cmd,
+                            accounts_created.get(&(n, m)).copied(),
+                        ))
+                    }
+                    v2::staged_ledger_diff::command::Command::ZkappCommand(_) => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Completed SNARK work of the `n`th staged ledger diff
+    fn completed_works(&self, n: usize) -> Option<Vec<SnarkWorkSummary>> {
+        self.diffs().find(|(m, _)| *m == n).map(|(_, diff)| {
+            diff.completed_works
+                .iter()
+                .cloned()
+                .map(SnarkWorkSummary::from)
+                .collect()
+        })
+    }
+}
+
 impl PcbVersion {
     pub fn update(&mut self) -> anyhow::Result<()> {
         match self {
@@ -729,6 +1149,38 @@ impl std::fmt::Display for PcbVersion {
     }
 }
 
+impl std::str::FromStr for PcbVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "v1" => Ok(Self::V1),
+            "v2" => Ok(Self::V2),
+            _ => bail!("Invalid block version {s}, expected v1 or v2"),
+        }
+    }
+}
+
+/// Base58 encoded pre-hardfork epoch data
+fn epoch_data_v1(
+    data: &protocol::serialization_types::epoch_data::EpochData,
+) -> v2::protocol_state::EpochData {
+    let seed: Base58EncodableVersionedType<{ version_bytes::EPOCH_SEED }, _> =
+        data.seed.clone().into();
+    v2::protocol_state::EpochData {
+        ledger: v2::protocol_state::LedgerData {
+            hash: LedgerHash::from_hashv1(data.ledger.t.t.hash.clone()),
+            total_currency: data.ledger.t.t.total_currency.t.t,
+        },
+        seed: seed.to_base58_string().expect("bs58 encoded seed"),
+        start_checkpoint: BlockHash::from_hashv1(data.start_checkpoint.clone())
+            .expect("start checkpoint"),
+        lock_checkpoint: BlockHash::from_hashv1(data.lock_checkpoint.clone())
+            .expect("lock checkpoint"),
+        epoch_length: data.epoch_length.t.t,
+    }
+}
+
 fn add_keys(pks: &mut HashSet<PublicKey>, new_pks: Vec<PublicKey>) {
     for pk in new_pks {
         pks.insert(pk);
@@ -757,4 +1209,78 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn berkeley_blocks() -> anyhow::Result<()> {
+        use crate::ledger::diff::LedgerDiff;
+
+        for path in glob::glob("./tests/data/berkeley/*/berkeley-*-*.json")? {
+            let path = path?;
+            let block = PrecomputedBlock::parse_file(&path, PcbVersion::V2)?;
+            assert_eq!(block.version(), PcbVersion::V2);
+
+            // round trips through the store's json encoding
+            let bytes = serde_json::to_vec(&block)?;
+            assert_eq!(serde_json::from_slice::<PrecomputedBlock>(&bytes)?, block);
+
+            // doesn't panic
+            block.hash_last_vrf_output();
+            LedgerDiff::from_precomputed(&block);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn berkeley_accounts_created() -> anyhow::Result<()> {
+        let path: PathBuf = "./tests/data/berkeley/sequential_blocks/berkeley-42-3NLrRhsjHr4Bi5w9f7DrsBaK9cRu9xwH8GnsPWBwBSXJ8GsLD28Y.json".into();
+        let block = PrecomputedBlock::parse_file(&path, PcbVersion::V2)?;
+        let new_account: PublicKey =
+            "B62qjJQ8Up2zUVgax2PwoYyDJ4mTq8japh9vt3gBoBWY5ubN7m2yuTf".into();
+
+        // the applied payment pays the creation fee
+        let commands = block.commands();
+        assert_eq!(commands.len(), 25);
+        assert_eq!(commands.iter().filter(|cmd| cmd.is_applied()).count(), 1);
+        assert!(commands
+            .iter()
+            .find(|cmd| cmd.is_applied())
+            .unwrap()
+            .receiver_account_creation_fee_paid());
+
+        let (new_pk_balances, new_coinbase_receiver) = block.accounts_created();
+        assert_eq!(
+            new_pk_balances,
+            BTreeMap::from([(new_account, 49_999_000_000_000)])
+        );
+        assert_eq!(new_coinbase_receiver, None);
+        assert!(block.fee_transfer_accounts_created().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn berkeley_zkapp_command() -> anyhow::Result<()> {
+        let path: PathBuf = "./tests/data/berkeley/non_sequential_blocks/berkeley-4969-3NL8QoLQMtsBH8vUnccQw3vt8PgYuZRMApq1yZT1jwhZjbBLMRJU.json".into();
+        let block = PrecomputedBlock::parse_file(&path, PcbVersion::V2)?;
+        let zkapp_commands = block.zkapp_commands();
+
+        assert_eq!(zkapp_commands.len(), 1);
+        assert!(zkapp_commands[0].is_applied());
+        assert_eq!(
+            zkapp_commands[0].fee_payer().0,
+            "B62qnMq3eeU5VNxrWQwsqXAveATqS8zzEF4uWsRoBeA7mHN774Mp6ec"
+        );
+        assert_eq!(zkapp_commands[0].fee(), 10_100_000);
+        assert!(block.all_command_public_keys().contains(&PublicKey::from(
+            "B62qqpAFkz374qJpuFKYZPjT1KxSmnLoY4zEc878FaW4DSxgYNXZiny"
+        )));
+
+        // zkApp fees go to the coinbase receiver
+        let user_command_fees: u64 = block
+            .commands()
+            .into_iter()
+            .map(|cmd| SignedCommand::from(cmd).fee())
+            .sum();
+        assert_eq!(block.tx_fees(), user_command_fees + 10_100_000);
+        Ok(())
+    }
 }
@@ -1,4 +1,5 @@
 use crate::block::BlockHash;
+use anyhow::{anyhow, bail};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
@@ -12,18 +13,33 @@ pub struct ProtocolState {
     pub previous_state_hash: String,
 }
 
+/// V1 blocks have a top-level protocol state, V2 blocks nest it in `data`
+#[derive(Debug, Clone, Deserialize)]
+struct PreviousStateHashFile {
+    protocol_state: Option<ProtocolState>,
+    data: Option<PreviousStateHashBlock>,
+}
+
 #[derive(PartialEq, Eq)]
 pub struct PreviousStateHash(pub String);
 
 impl PreviousStateHash {
     pub fn from_path(path: &Path) -> anyhow::Result<Self> {
-        let bytes = &std::fs::read(path)?;
-        let PreviousStateHashBlock {
-            protocol_state: ProtocolState {
-                previous_state_hash,
-            },
+        Self::from_bytes(&std::fs::read(path)?).map_err(|e| anyhow!("{e} in {}", path.display()))
+    }
+
+    /// Parses the previous state hash from block file contents
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let PreviousStateHashFile {
+            protocol_state,
+            data,
         } = serde_json::from_slice(bytes)?;
-        Ok(Self(previous_state_hash))
+        match protocol_state.or(data.map(|block| block.protocol_state)) {
+            Some(ProtocolState {
+                previous_state_hash,
+            }) => Ok(Self(previous_state_hash)),
+            None => bail!("Missing protocol state"),
+        }
     }
 }
 
@@ -72,4 +88,18 @@ mod test {
         }
         Ok(())
     }
+
+    #[test]
+    fn previous_state_hash_v2() -> anyhow::Result<()> {
+        let paths: Vec<PathBuf> = glob::glob("./tests/data/berkeley/sequential_blocks/*.json")?
+            .filter_map(|x| x.ok())
+            .collect();
+
+        for path in paths {
+            let previous_state_hash = PreviousStateHash::from_path(&path)?.0;
+            let block = PrecomputedBlock::parse_file(&path, PcbVersion::V2)?;
+            assert_eq!(previous_state_hash, block.previous_state_hash().0);
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,660 @@
+//! Block sources
+//!
+//! A [BlockSource] fetches the precomputed blocks at a given height into the
+//! watched blocks directory, where the filesystem watcher picks them up.
+//! Block files are named `{network}-{height}-{state_hash}.json` in every
+//! source. Local directories & tar archives are indexed by height once, when
+//! the source is opened.
+
+use super::{archive::BlockArchive, extract_block_height, is_valid_block_file};
+use crate::chain::Network;
+use async_trait::async_trait;
+use log::{debug, trace, warn};
+use quick_xml::events::Event;
+use reqwest::Url;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use thiserror::Error;
+use tokio::process::Command;
+
+#[derive(Debug, Error)]
+pub enum BlockSourceError {
+    #[error("invalid block source {0}")]
+    InvalidSource(String),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("request to {url} failed: {source}")]
+    Request { url: String, source: reqwest::Error },
+
+    #[error("request to {url} failed with status {status}")]
+    Status { url: String, status: u16 },
+
+    #[error("malformed response from {url}: {reason}")]
+    MalformedResponse { url: String, reason: String },
+
+    #[error("{exe} exited with {status}: {stderr}")]
+    Executable {
+        exe: String,
+        status: std::process::ExitStatus,
+        stderr: String,
+    },
+}
+
+impl BlockSourceError {
+    /// Transient errors are worth retrying
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Request { source, .. } => {
+                source.is_timeout()
+                    || source.is_connect()
+                    || source.is_request()
+                    || source.is_body()
+            }
+            Self::Executable { .. } => true,
+            Self::Status { status, .. } => *status == 429 || *status >= 500,
+            Self::InvalidSource(_) | Self::Io(_) | Self::MalformedResponse { .. } => false,
+        }
+    }
+}
+
+/// Source of precomputed block files
+#[async_trait]
+pub trait BlockSource: Display + Send + Sync {
+    /// Fetches the blocks at `height` into `blocks_dir`
+    ///
+    /// Returns the paths of the block files which were not already present
+    async fn fetch(
+        &self,
+        network: &Network,
+        height: u32,
+        blocks_dir: &Path,
+    ) -> Result<Vec<PathBuf>, BlockSourceError>;
+}
+
+/// Parses a block source from
+/// - an `http://` or `https://` object store URL
+/// - a `.tar` archive path
+/// - a blocks directory path
+pub fn block_source_from_spec(spec: &str) -> Result<Box<dyn BlockSource>, BlockSourceError> {
+    if spec.starts_with("http://") || spec.starts_with("https://") {
+        return Ok(Box::new(HttpObjectStoreSource::new(spec)?));
+    }
+
+    let path = PathBuf::from(spec);
+    if path.extension().is_some_and(|ext| ext == "tar") && path.is_file() {
+        Ok(Box::new(TarArchiveSource::new(path)?))
+    } else if path.is_dir() {
+        Ok(Box::new(LocalDirSource::new(path)?))
+    } else {
+        Err(BlockSourceError::InvalidSource(format!(
+            "{spec}: not an http(s):// URL, tar archive or directory"
+        )))
+    }
+}
+
+/// Retry schedule of [fetch_with_retry], the backoff doubles after each
+/// failed attempt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Backoff after the given (zero-based) failed attempt
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+/// Fetches the blocks at `height`, retrying transient errors
+pub async fn fetch_with_retry(
+    source: &dyn BlockSource,
+    policy: &RetryPolicy,
+    network: &Network,
+    height: u32,
+    blocks_dir: &Path,
+) -> Result<Vec<PathBuf>, BlockSourceError> {
+    let mut attempt = 0;
+    loop {
+        match source.fetch(network, height, blocks_dir).await {
+            Ok(paths) => return Ok(paths),
+            Err(e) if e.is_transient() && attempt + 1 < policy.max_attempts => {
+                let backoff = policy.backoff(attempt);
+                warn!(
+                    "Fetching height {height} from {source} failed, retrying in {backoff:?}: {e}"
+                );
+                tokio::time::sleep(backoff).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Copies block files from another directory
+///
+/// Block files added to the directory after it's opened are not fetched
+pub struct LocalDirSource {
+    dir: PathBuf,
+    index: HeightIndex,
+}
+
+impl LocalDirSource {
+    /// Indexes the block files of `dir` by height
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, BlockSourceError> {
+        let dir = dir.into();
+        let mut paths = vec![];
+        for entry in fs::read_dir(&dir)? {
+            paths.push(entry?.path());
+        }
+        Ok(Self {
+            dir,
+            index: HeightIndex::new(paths),
+        })
+    }
+}
+
+impl Display for LocalDirSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "directory {}", self.dir.display())
+    }
+}
+
+#[async_trait]
+impl BlockSource for LocalDirSource {
+    async fn fetch(
+        &self,
+        network: &Network,
+        height: u32,
+        blocks_dir: &Path,
+    ) -> Result<Vec<PathBuf>, BlockSourceError> {
+        let sources = self.index.get(network, height);
+        let blocks_dir = blocks_dir.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            let mut paths = vec![];
+            for (name, source) in sources {
+                if blocks_dir.join(&name).exists() {
+                    continue;
+                }
+                if let Some(path) = write_block_file(&blocks_dir, &name, &fs::read(&source)?)? {
+                    paths.push(path);
+                }
+            }
+            Ok(paths)
+        })
+        .await
+        .map_err(io::Error::other)?
+    }
+}
+
+/// Extracts block files from a tar archive
+pub struct TarArchiveSource {
+    archive: Arc<BlockArchive>,
+    index: HeightIndex,
+}
+
+impl TarArchiveSource {
+    /// Indexes the block entries of the archive at `path` by height
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, BlockSourceError> {
+        let path = path.into();
+        let archive = BlockArchive::open(&path)
+            .map_err(|e| BlockSourceError::InvalidSource(format!("{}: {e}", path.display())))?;
+        Ok(Self {
+            index: HeightIndex::new(archive.paths()),
+            archive: Arc::new(archive),
+        })
+    }
+}
+
+impl Display for TarArchiveSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "tar archive {}", self.archive.path().display())
+    }
+}
+
+#[async_trait]
+impl BlockSource for TarArchiveSource {
+    async fn fetch(
+        &self,
+        network: &Network,
+        height: u32,
+        blocks_dir: &Path,
+    ) -> Result<Vec<PathBuf>, BlockSourceError> {
+        let archive = self.archive.clone();
+        let entries = self.index.get(network, height);
+        let blocks_dir = blocks_dir.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            let mut paths = vec![];
+            for (name, entry) in entries {
+                if blocks_dir.join(&name).exists() {
+                    continue;
+                }
+                let bytes = archive.read(&entry).map_err(io::Error::other)?;
+                if let Some(path) = write_block_file(&blocks_dir, &name, &bytes)? {
+                    paths.push(path);
+                }
+            }
+            Ok(paths)
+        })
+        .await
+        .map_err(io::Error::other)?
+    }
+}
+
+/// Downloads block files from an S3/GCS-compatible object store over HTTP(S)
+///
+/// The objects at a height are listed with the `?prefix=` XML listing API,
+/// following its `marker` pagination, and downloaded from `{url}/{key}`
+pub struct HttpObjectStoreSource {
+    url: Url,
+    client: reqwest::Client,
+    timeout: Duration,
+}
+
+impl HttpObjectStoreSource {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+    pub fn new(url: &str) -> Result<Self, BlockSourceError> {
+        let invalid = || BlockSourceError::InvalidSource(url.to_string());
+        let parsed = Url::parse(url.trim_end_matches('/')).map_err(|_| invalid())?;
+        if !matches!(parsed.scheme(), "http" | "https")
+            || parsed.cannot_be_a_base()
+            || parsed.host_str().map_or(true, str::is_empty)
+            || parsed.query().is_some()
+        {
+            return Err(invalid());
+        }
+
+        let client = reqwest::Client::builder()
+            .user_agent("mina-indexer")
+            .build()
+            .map_err(|e| BlockSourceError::InvalidSource(format!("{url}: {e}")))?;
+        Ok(Self {
+            url: parsed,
+            client,
+            timeout: Self::DEFAULT_TIMEOUT,
+        })
+    }
+
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// Keys of the objects with the given prefix, from every page of the
+    /// listing
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, BlockSourceError> {
+        let mut keys = vec![];
+        let mut marker = None;
+        loop {
+            let url = self.list_url(prefix, marker.as_deref());
+            let body = self.get(&url).await?;
+            let page =
+                ListBucketResult::parse(&String::from_utf8_lossy(&body)).map_err(|reason| {
+                    BlockSourceError::MalformedResponse {
+                        url: url.to_string(),
+                        reason,
+                    }
+                })?;
+
+            // the next page starts after `NextMarker`, or the last key
+            let next_marker = page.next_marker.or_else(|| page.keys.last().cloned());
+            keys.extend(page.keys);
+            if !page.is_truncated {
+                return Ok(keys);
+            }
+            if next_marker.is_none() || next_marker == marker {
+                return Err(BlockSourceError::MalformedResponse {
+                    url: url.to_string(),
+                    reason: "truncated listing without a new marker".to_string(),
+                });
+            }
+            marker = next_marker;
+        }
+    }
+
+    /// Listing URL of the objects with the given prefix, after `marker`
+    fn list_url(&self, prefix: &str, marker: Option<&str>) -> Url {
+        let mut url = self.url.clone();
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("prefix", prefix);
+            if let Some(marker) = marker {
+                query.append_pair("marker", marker);
+            }
+        }
+        url
+    }
+
+    /// URL of the object with the given key, each segment percent-encoded
+    fn object_url(&self, key: &str) -> Url {
+        let mut url = self.url.clone();
+        if let Ok(mut segments) = url.path_segments_mut() {
+            segments.pop_if_empty().extend(key.split('/'));
+        }
+        url
+    }
+
+    /// Body of a successful `GET` request
+    async fn get(&self, url: &Url) -> Result<Vec<u8>, BlockSourceError> {
+        trace!("GET {url}");
+        let request_error = |source| BlockSourceError::Request {
+            url: url.to_string(),
+            source,
+        };
+
+        let response = self
+            .client
+            .get(url.clone())
+            .timeout(self.timeout)
+            .send()
+            .await
+            .map_err(request_error)?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(BlockSourceError::Status {
+                url: url.to_string(),
+                status: status.as_u16(),
+            });
+        }
+        Ok(response.bytes().await.map_err(request_error)?.to_vec())
+    }
+}
+
+impl Display for HttpObjectStoreSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "object store {}", self.url)
+    }
+}
+
+#[async_trait]
+impl BlockSource for HttpObjectStoreSource {
+    async fn fetch(
+        &self,
+        network: &Network,
+        height: u32,
+        blocks_dir: &Path,
+    ) -> Result<Vec<PathBuf>, BlockSourceError> {
+        let prefix = block_file_prefix(network, height);
+        let mut paths = vec![];
+        for key in self.list(&prefix).await? {
+            if let Some(name) = matching_file_name(Path::new(&key), &prefix) {
+                if blocks_dir.join(name).exists() {
+                    continue;
+                }
+
+                let body = self.get(&self.object_url(&key)).await?;
+                if let Some(path) = write_block_file(blocks_dir, name, &body)? {
+                    paths.push(path);
+                }
+            }
+        }
+        Ok(paths)
+    }
+}
+
+/// Runs an external executable with the network, height & blocks directory
+/// as arguments
+pub struct ExecutableSource {
+    exe: PathBuf,
+}
+
+impl ExecutableSource {
+    pub fn new(exe: impl Into<PathBuf>) -> Self {
+        Self { exe: exe.into() }
+    }
+}
+
+impl Display for ExecutableSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "executable {}", self.exe.display())
+    }
+}
+
+#[async_trait]
+impl BlockSource for ExecutableSource {
+    async fn fetch(
+        &self,
+        network: &Network,
+        height: u32,
+        blocks_dir: &Path,
+    ) -> Result<Vec<PathBuf>, BlockSourceError> {
+        let prefix = block_file_prefix(network, height);
+        let before = matching_block_files(blocks_dir, &prefix)?;
+        let output = Command::new(&self.exe)
+            .arg(network.to_string())
+            .arg(height.to_string())
+            .arg(blocks_dir)
+            .kill_on_drop(true)
+            .output()
+            .await?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if !stdout.trim().is_empty() {
+            debug!("{self} stdout: {}", stdout.trim());
+        }
+        if !output.status.success() {
+            return Err(BlockSourceError::Executable {
+                exe: self.exe.display().to_string(),
+                status: output.status,
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+
+        let after = matching_block_files(blocks_dir, &prefix)?;
+        Ok(after.difference(&before).cloned().collect())
+    }
+}
+
+/// Block file paths by their `{network}-{height}-` file name prefix
+struct HeightIndex(HashMap<String, Vec<(String, PathBuf)>>);
+
+impl HeightIndex {
+    fn new(paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        let mut index: HashMap<_, Vec<_>> = HashMap::new();
+        for path in paths {
+            if let Some((prefix, name)) = block_file_name_prefix(&path) {
+                index.entry(prefix).or_default().push((name, path));
+            }
+        }
+        index.values_mut().for_each(|paths| paths.sort());
+        Self(index)
+    }
+
+    /// File names & paths of the blocks at `height`
+    fn get(&self, network: &Network, height: u32) -> Vec<(String, PathBuf)> {
+        self.0
+            .get(&block_file_prefix(network, height))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// `{network}-{height}-` prefix & file name of a valid block file
+fn block_file_name_prefix(path: &Path) -> Option<(String, String)> {
+    let name = path.file_name()?.to_str()?;
+    let network = name.split('-').next()?;
+    let prefix = format!("{network}-{}-", extract_block_height(path)?);
+    matching_file_name(path, &prefix).map(|name| (prefix, name.to_string()))
+}
+
+/// File name prefix of the blocks at `height`
+fn block_file_prefix(network: &Network, height: u32) -> String {
+    format!("{network}-{height}-")
+}
+
+/// File name of a valid block file with the given prefix
+fn matching_file_name<'a>(path: &'a Path, prefix: &str) -> Option<&'a str> {
+    let name = path.file_name()?.to_str()?;
+    (name.starts_with(prefix) && is_valid_block_file(Path::new(name))).then_some(name)
+}
+
+fn matching_block_files(dir: &Path, prefix: &str) -> io::Result<HashSet<PathBuf>> {
+    let mut paths = HashSet::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if matching_file_name(&path, prefix).is_some() {
+            paths.insert(path);
+        }
+    }
+    Ok(paths)
+}
+
+/// Writes a block file into `blocks_dir` via a rename, so the watcher never
+/// sees a partial file
+///
+/// Returns `None` if the file is already present
+fn write_block_file(blocks_dir: &Path, name: &str, bytes: &[u8]) -> io::Result<Option<PathBuf>> {
+    let path = blocks_dir.join(name);
+    if path.exists() {
+        return Ok(None);
+    }
+
+    let part = blocks_dir.join(format!(".{name}.part"));
+    fs::write(&part, bytes)?;
+    fs::rename(&part, &path)?;
+    Ok(Some(path))
+}
+
+/// Page of an S3/GCS `ListBucketResult`
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ListBucketResult {
+    keys: Vec<String>,
+    is_truncated: bool,
+    next_marker: Option<String>,
+}
+
+impl ListBucketResult {
+    fn parse(xml: &str) -> Result<Self, String> {
+        let mut reader = quick_xml::Reader::from_str(xml);
+        reader.trim_text(true);
+
+        let mut result = Self::default();
+        let mut elements = vec![];
+        loop {
+            match reader.read_event().map_err(|e| e.to_string())? {
+                Event::Start(start) => {
+                    elements.push(String::from_utf8_lossy(start.local_name().as_ref()).into_owned())
+                }
+                Event::End(_) => {
+                    elements.pop();
+                }
+                Event::Text(text) => {
+                    let text = text.unescape().map_err(|e| e.to_string())?.into_owned();
+                    let elements: Vec<_> = elements.iter().map(String::as_str).collect();
+                    match elements[..] {
+                        ["ListBucketResult", "Contents", "Key"] => result.keys.push(text),
+                        ["ListBucketResult", "IsTruncated"] => result.is_truncated = text == "true",
+                        ["ListBucketResult", "NextMarker"] => result.next_marker = Some(text),
+                        _ => (),
+                    }
+                }
+                Event::Eof => return Ok(result),
+                _ => (),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn object_store_urls() -> Result<(), BlockSourceError> {
+        let source = HttpObjectStoreSource::new("https://storage.example.com/mina blocks/")?;
+        assert_eq!(
+            source.object_url("mainnet-2-a.json").as_str(),
+            "https://storage.example.com/mina%20blocks/mainnet-2-a.json"
+        );
+        assert_eq!(
+            source.object_url("dir/a b#c?.json").as_str(),
+            "https://storage.example.com/mina%20blocks/dir/a%20b%23c%3F.json"
+        );
+        assert_eq!(
+            source.list_url("mainnet-2-", Some("a&b c")).as_str(),
+            "https://storage.example.com/mina%20blocks?prefix=mainnet-2-&marker=a%26b+c"
+        );
+
+        let root = HttpObjectStoreSource::new("http://localhost:9000")?;
+        assert_eq!(
+            root.object_url("mainnet-2-a.json").as_str(),
+            "http://localhost:9000/mainnet-2-a.json"
+        );
+
+        for invalid in ["http://", "ftp://host/bucket", "http://host/bucket?x=1"] {
+            assert!(HttpObjectStoreSource::new(invalid).is_err(), "{invalid}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn listing_pages() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <Name>blocks</Name>
+  <Prefix>mainnet-2-</Prefix>
+  <IsTruncated>true</IsTruncated>
+  <NextMarker>mainnet-2-b&amp;c.json</NextMarker>
+  <Contents><Key>mainnet-2-a.json</Key></Contents>
+  <Contents><Key>mainnet-2-b&amp;c&#46;json</Key></Contents>
+</ListBucketResult>"#;
+        assert_eq!(
+            ListBucketResult::parse(xml),
+            Ok(ListBucketResult {
+                keys: vec![
+                    "mainnet-2-a.json".to_string(),
+                    "mainnet-2-b&c.json".to_string()
+                ],
+                is_truncated: true,
+                next_marker: Some("mainnet-2-b&c.json".to_string()),
+            })
+        );
+
+        let last_page = "<ListBucketResult><IsTruncated>false</IsTruncated><Contents><Key>mainnet-2-&lt;d&gt;.json</Key></Contents></ListBucketResult>";
+        assert_eq!(
+            ListBucketResult::parse(last_page),
+            Ok(ListBucketResult {
+                keys: vec!["mainnet-2-<d>.json".to_string()],
+                is_truncated: false,
+                next_marker: None,
+            })
+        );
+
+        assert!(ListBucketResult::parse("<ListBucketResult><Key>&bogus;</Key>").is_err());
+    }
+
+    #[test]
+    fn retry_backoff() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+        };
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff(3), Duration::from_millis(500));
+        assert_eq!(policy.backoff(40), Duration::from_millis(500));
+    }
+}
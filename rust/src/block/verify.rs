@@ -0,0 +1,110 @@
+//! Block verification
+//!
+//! Checks block file height labels & `previous_state_hash` linkage. This is
+//! not tamper detection: the state hash is taken from the file name & is not
+//! recomputed from the protocol state, the indexer has no Poseidon hasher
+//! yet.
+
+use super::{extract_block_height, precomputed::PrecomputedBlock, store::BlockStore, BlockHash};
+use crate::{
+    constants::MAINNET_GENESIS_PREV_STATE_HASH,
+    store::{block_state_hash_from_key, IndexerStore},
+};
+use anyhow::bail;
+use speedb::IteratorMode;
+use std::path::Path;
+
+/// Summary of a [verify_store] pass
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StoreVerification {
+    /// Number of blocks checked
+    pub num_blocks: u32,
+
+    /// Number of blocks whose parent is not in the store
+    pub num_missing_parents: u32,
+
+    /// Blocks failing verification & the reason
+    pub failures: Vec<(BlockHash, String)>,
+}
+
+/// Checks the block file's height label against the block
+///
+/// The block's state hash comes from the file name, so it is not checked
+pub fn verify_block_file(block: &PrecomputedBlock, path: &Path) -> anyhow::Result<()> {
+    let state_hash = block.state_hash();
+    let blockchain_length = block.consensus_blockchain_length();
+    if let Some(height) = extract_block_height(path).filter(|height| *height != blockchain_length) {
+        bail!("Block {state_hash} is labeled height {height} but has blockchain length {blockchain_length}")
+    }
+
+    if block.previous_state_hash() == state_hash {
+        bail!("Block {state_hash} is its own parent")
+    }
+    Ok(())
+}
+
+/// Checks a stored block's `previous_state_hash` linkage, a stored parent
+/// must be exactly one block lower
+///
+/// Returns `false` if the parent is not in the store
+pub fn verify_parent_linkage(store: &IndexerStore, state_hash: &BlockHash) -> anyhow::Result<bool> {
+    let (height, parent_hash) = match (
+        store.get_block_height(state_hash)?,
+        store.get_block_parent_hash(state_hash)?,
+    ) {
+        (Some(height), Some(parent_hash)) => (height, parent_hash),
+        _ => bail!("Block {state_hash} missing from store"),
+    };
+
+    if parent_hash.0 == MAINNET_GENESIS_PREV_STATE_HASH {
+        return Ok(true);
+    }
+
+    match store.get_block_height(&parent_hash)? {
+        Some(parent_height) if parent_height + 1 == height => Ok(true),
+        Some(parent_height) => bail!(
+            "Block {state_hash} at height {height} has parent {parent_hash} at height {parent_height}"
+        ),
+        None => Ok(false),
+    }
+}
+
+/// Checks the `previous_state_hash` linkage of every stored block
+pub fn verify_store(store: &IndexerStore) -> anyhow::Result<StoreVerification> {
+    let mut verification = StoreVerification::default();
+    for (key, _) in store.blocks_height_iterator(IteratorMode::Start).flatten() {
+        let state_hash = block_state_hash_from_key(&key)?;
+        verification.num_blocks += 1;
+
+        match verify_parent_linkage(store, &state_hash) {
+            Ok(true) => (),
+            Ok(false) => verification.num_missing_parents += 1,
+            Err(e) => verification.failures.push((state_hash, e.to_string())),
+        }
+    }
+    Ok(verification)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::precomputed::PcbVersion;
+    use std::path::PathBuf;
+
+    #[test]
+    fn mislabeled_block_file() -> anyhow::Result<()> {
+        let path = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous/mainnet-2-3NLyWnjZqUECniE1q719CoLmes6WDQAod4vrTeLfN7XXJbHv6EHH.json");
+        let block = PrecomputedBlock::parse_file(&path, PcbVersion::V1)?;
+        verify_block_file(&block, &path)?;
+
+        // wrong height label
+        let mislabeled =
+            PathBuf::from("mainnet-3-3NLyWnjZqUECniE1q719CoLmes6WDQAod4vrTeLfN7XXJbHv6EHH.json");
+        let err = verify_block_file(&block, &mislabeled).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Block 3NLyWnjZqUECniE1q719CoLmes6WDQAod4vrTeLfN7XXJbHv6EHH is labeled height 3 but has blockchain length 2"
+        );
+        Ok(())
+    }
+}
@@ -13,11 +13,31 @@ use std::{
 /// - recent blocks (following the witness tree root)
 /// - orphaned blocks (at or below witness tree root)
 pub fn discovery(
+    min_len_filter: Option<u32>,
+    max_len_filter: Option<u32>,
+    canonical_threshold: u32,
+    reporting_freq: u32,
+    paths: Vec<&PathBuf>,
+) -> anyhow::Result<(Vec<PathBuf>, Vec<PathBuf>, Vec<PathBuf>)> {
+    discovery_with(
+        min_len_filter,
+        max_len_filter,
+        canonical_threshold,
+        reporting_freq,
+        paths,
+        &PreviousStateHash::from_path,
+    )
+}
+
+/// [discovery] reading previous state hashes with `previous_state_hash`,
+/// e.g. from the entries of a block archive
+pub fn discovery_with(
     min_len_filter: Option<u32>,
     max_len_filter: Option<u32>,
     canonical_threshold: u32,
     reporting_freq: u32,
     mut paths: Vec<&PathBuf>,
+    previous_state_hash: &dyn Fn(&Path) -> anyhow::Result<PreviousStateHash>,
 ) -> anyhow::Result<(Vec<PathBuf>, Vec<PathBuf>, Vec<PathBuf>)> {
     let mut deep_canonical_state_hashes = HashSet::new();
     let mut deep_canonical_paths = vec![];
@@ -85,6 +105,7 @@ pub fn discovery(
                 .unwrap_or(0),
             last_contiguous_idx,
             canonical_threshold,
+            previous_state_hash,
         );
 
         if witness_tree_root_opt.is_none()
@@ -152,7 +173,7 @@ pub fn discovery(
             // search for parent in previous segment's blocks
             let mut parent_found = false;
             let prev_length_idx = length_start_indices_and_diffs[curr_start_idx - 1].0;
-            let parent_hash = PreviousStateHash::from_path(curr_path)?.0;
+            let parent_hash = previous_state_hash(curr_path)?.0;
 
             for path in paths[prev_length_idx..curr_length_idx].iter() {
                 if parent_hash == extract_state_hash(path) {
@@ -179,7 +200,7 @@ pub fn discovery(
 
         // push the lowest canonical block
         for path in paths[..curr_length_idx].iter() {
-            let prev_hash = PreviousStateHash::from_path(curr_path)?.0;
+            let prev_hash = previous_state_hash(curr_path)?.0;
             if prev_hash == extract_state_hash(path) {
                 debug!("Lowest canonical block found");
                 deep_canonical_paths.push(path.to_path_buf());
@@ -222,8 +243,12 @@ pub fn discovery(
 }
 
 /// Checks if the block at `curr_path` is the _parent_ of the block at `path`.
-fn is_parent(path: &Path, curr_path: &Path) -> bool {
-    if let Ok(prev_hash) = PreviousStateHash::from_path(curr_path) {
+fn is_parent(
+    path: &Path,
+    curr_path: &Path,
+    previous_state_hash: &dyn Fn(&Path) -> anyhow::Result<PreviousStateHash>,
+) -> bool {
+    if let Ok(prev_hash) = previous_state_hash(curr_path) {
         let prev_hash: String = prev_hash.into();
         return prev_hash == extract_state_hash(path);
     }
@@ -255,6 +280,7 @@ fn find_witness_tree_root(
     mut curr_start_idx: usize,
     mut curr_length_idx: usize,
     canonical_threshold: u32,
+    previous_state_hash: &dyn Fn(&Path) -> anyhow::Result<PreviousStateHash>,
 ) -> Option<(usize, usize)> {
     if length_start_indices_and_diffs.len() <= canonical_threshold as usize {
         None
@@ -275,7 +301,7 @@ fn find_witness_tree_root(
                 .enumerate()
             {
                 // if the parent is found, check that it has a parent, etc
-                if is_parent(path, curr_path) {
+                if is_parent(path, curr_path, previous_state_hash) {
                     debug!(
                         "{} is the parent of {}",
                         curr_path
@@ -307,6 +333,7 @@ fn find_witness_tree_root(
                         curr_start_idx.saturating_sub(1),
                         prev_length_start_idx,
                         canonical_threshold,
+                        previous_state_hash,
                     );
                 } else {
                     // root cannot be found
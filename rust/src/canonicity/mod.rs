@@ -13,6 +13,20 @@ pub struct CanonicityDiff {
 
 pub type CanonicityUpdate = DBUpdate<CanonicityDiff>;
 
+/// Best tip change which orphans previously canonical blocks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reorg {
+    pub old_best_tip: BlockHash,
+    pub old_blockchain_length: u32,
+    pub new_best_tip: BlockHash,
+    pub new_blockchain_length: u32,
+    pub common_ancestor: BlockHash,
+    pub common_ancestor_length: u32,
+
+    /// Number of previously canonical blocks orphaned by the reorg
+    pub depth: u32,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Hash)]
 pub enum Canonicity {
     Canonical,
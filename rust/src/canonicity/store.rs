@@ -1,6 +1,6 @@
 use crate::{
     block::BlockHash,
-    canonicity::{Canonicity, CanonicityUpdate},
+    canonicity::{Canonicity, CanonicityUpdate, Reorg},
 };
 
 pub trait CanonicityStore {
@@ -24,6 +24,18 @@ pub trait CanonicityStore {
         new_best_tip: &BlockHash,
     ) -> anyhow::Result<CanonicityUpdate>;
 
+    /// Record a reorg in the reorg history
+    fn add_reorg(&self, reorg: &Reorg) -> anyhow::Result<()>;
+
+    /// Get the reorgs with new best tip length in `[from, to]` and depth at
+    /// least `min_depth`, in order of new best tip length
+    fn get_reorgs(
+        &self,
+        from: Option<u32>,
+        to: Option<u32>,
+        min_depth: u32,
+    ) -> anyhow::Result<Vec<Reorg>>;
+
     /// Get the state hash of the canonical block at the given height
     fn get_canonical_hash_at_height(&self, height: u32) -> anyhow::Result<Option<BlockHash>>;
 
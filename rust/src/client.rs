@@ -63,6 +63,17 @@ pub enum ClientCli {
         json: bool,
     },
 
+    /// Export the witness tree's root & dangling branches
+    WitnessTree {
+        /// Path to write the witness tree [default: stdout]
+        #[arg(long)]
+        path: Option<PathBuf>,
+
+        /// Output JSON data instead of Graphviz DOT
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+
     /// Query transactions (user commands)
     #[clap(subcommand)]
     Transactions(Transactions),
@@ -84,6 +95,25 @@ pub enum Accounts {
         #[arg(long)]
         public_key: String,
     },
+
+    /// Query an account's balance, nonce & delegate history
+    History {
+        /// Path to write the account history [default: stdout]
+        #[arg(long)]
+        path: Option<PathBuf>,
+
+        /// Retrieve public key's account history
+        #[arg(long)]
+        public_key: String,
+
+        /// Lowest block height of the history
+        #[arg(long, default_value_t = 0)]
+        from: u32,
+
+        /// Highest block height of the history [default: best tip]
+        #[arg(long)]
+        to: Option<u32>,
+    },
 }
 
 #[derive(Subcommand, Debug, Encode, Decode)]
@@ -201,6 +231,48 @@ pub enum Chain {
         #[arg(long, default_value_t = false)]
         verbose: bool,
     },
+
+    /// Query the reorg history
+    Reorgs {
+        /// Path to write the reorgs [default: stdout]
+        #[arg(long)]
+        path: Option<PathBuf>,
+
+        /// Minimum new best tip blockchain length
+        #[arg(long)]
+        from: Option<u32>,
+
+        /// Maximum new best tip blockchain length
+        #[arg(long)]
+        to: Option<u32>,
+
+        /// Minimum number of orphaned canonical blocks
+        #[arg(long, default_value_t = 1)]
+        min_depth: u32,
+    },
+
+    /// Query orphan rates & fork stats
+    Forks {
+        /// Path to write the fork stats [default: stdout]
+        #[arg(long)]
+        path: Option<PathBuf>,
+
+        /// Constrain orphan rates to the epoch
+        #[arg(long)]
+        epoch: Option<u32>,
+
+        /// Constrain orphan rates to the block producer
+        #[arg(long)]
+        public_key: Option<String>,
+
+        /// Minimum fork blockchain length
+        #[arg(long)]
+        from: Option<u32>,
+
+        /// Maximum fork blockchain length
+        #[arg(long)]
+        to: Option<u32>,
+    },
 }
 
 #[derive(Subcommand, Debug, Encode, Decode)]
@@ -410,6 +482,13 @@ pub enum InternalCommands {
 
 impl ClientCli {
     pub async fn run(&self, domain_socket_path: PathBuf) -> anyhow::Result<()> {
+        let msg = self.query(domain_socket_path).await?;
+        println!("{msg}");
+        Ok(())
+    }
+
+    /// Sends the command to the running indexer & returns its response
+    pub async fn query(&self, domain_socket_path: PathBuf) -> anyhow::Result<String> {
         let conn = UnixStream::connect(domain_socket_path)
             .await
             .unwrap_or_else(|e| {
@@ -425,8 +504,6 @@ impl ClientCli {
         reader.read_to_end(&mut buffer).await?;
 
         let msg = String::from_utf8(buffer)?;
-        let msg = msg.trim_end();
-        println!("{msg}");
-        Ok(())
+        Ok(msg.trim_end().to_string())
     }
 }
@@ -1,6 +1,8 @@
 pub mod internal;
 pub mod signed;
 pub mod store;
+pub mod unsigned;
+pub mod zkapp;
 
 use crate::{
     block::{precomputed::PrecomputedBlock, BlockHash},
@@ -9,6 +11,7 @@ use crate::{
         account::{Amount, Nonce},
         public_key::PublicKey,
     },
+    mina_blocks::v2::staged_ledger_diff as v2,
     protocol::serialization_types::staged_ledger_diff as mina_rs,
 };
 use log::trace;
@@ -134,6 +137,96 @@ impl CommandStatusData {
 #[derive(PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct UserCommandWithStatus(pub mina_rs::UserCommandWithStatusV1);
 
+impl UserCommandWithStatus {
+    /// Convert a post-hardfork signed command.
+    ///
+    /// V2 statuses don't carry auxiliary or balance data, the block only
+    /// tells us which accounts were created. `account_creation_fee` is the
+    /// fee paid if the command created the receiver account.
+    pub fn from_v2(cmd: &v2::command::UserCommand, account_creation_fee: Option<u64>) -> Self {
+        let signed = SignedCommand::from(cmd.data.clone());
+        let status = if cmd.status.is_applied() {
+            // the new receiver's balance is the amount sent less the fee
+            let receiver_balance = match &cmd.data.payload.body.1 {
+                v2::UserCommandPayloadBody::Payment(payment) => {
+                    account_creation_fee.and_then(|fee| payment.amount.checked_sub(fee))
+                }
+                v2::UserCommandPayloadBody::StakeDelegation(_) => None,
+            };
+            mina_rs::TransactionStatus::Applied(
+                mina_rs::TransactionStatusAuxiliaryData {
+                    fee_payer_account_creation_fee_paid: None,
+                    receiver_account_creation_fee_paid: account_creation_fee.map(Into::into),
+                    created_token: None,
+                }
+                .into(),
+                mina_rs::TransactionStatusBalanceData {
+                    fee_payer_balance: None,
+                    source_balance: None,
+                    receiver_balance: receiver_balance.map(Into::into),
+                }
+                .into(),
+            )
+        } else {
+            mina_rs::TransactionStatus::Failed(
+                cmd.status
+                    .failures()
+                    .into_iter()
+                    .map(|reason| failure_reason_v1(reason).into())
+                    .collect(),
+                mina_rs::TransactionStatusBalanceData {
+                    fee_payer_balance: None,
+                    source_balance: None,
+                    receiver_balance: None,
+                }
+                .into(),
+            )
+        };
+
+        Self(
+            mina_rs::UserCommandWithStatus {
+                data: mina_rs::UserCommand::SignedCommand(signed.0).into(),
+                status: status.into(),
+            }
+            .into(),
+        )
+    }
+}
+
+/// Signed commands can only fail for the reasons shared by both versions
+fn failure_reason_v1(reason: v2::FailureReason) -> mina_rs::TransactionStatusFailedType {
+    use mina_rs::TransactionStatusFailedType as V1;
+    use v2::FailureReason as V2;
+
+    match reason {
+        V2::SourceNotPresent => V1::SourceNotPresent,
+        V2::ReceiverNotPresent => V1::ReceiverNotPresent,
+        V2::AmountInsufficientToCreateAccount => V1::AmountInsufficientToCreateAccount,
+        V2::CannotPayCreationFeeInToken => V1::CannotPayCreationFeeInToken,
+        V2::SourceInsufficientBalance => V1::SourceInsufficientBalance,
+        V2::SourceMinimumBalanceViolation => V1::SourceMinimumBalanceViolation,
+        V2::ReceiverAlreadyExists => V1::ReceiverAlreadyExists,
+        V2::TokenOwnerNotCaller => V1::NotTokenOwner,
+        V2::Overflow => V1::Overflow,
+        V2::SignedCommandOnZkappAccount => V1::SignedCommandOnSnappAccount,
+        V2::ZkappAccountNotPresent => V1::SnappAccountNotPresent,
+        V2::IncorrectNonce => V1::IncorrectNonce,
+        V2::UpdateNotPermittedBalance
+        | V2::UpdateNotPermittedAccess
+        | V2::UpdateNotPermittedTiming
+        | V2::UpdateNotPermittedDelegate
+        | V2::UpdateNotPermittedAppState
+        | V2::UpdateNotPermittedVerificationKey
+        | V2::UpdateNotPermittedactionState
+        | V2::UpdateNotPermittedZkappUri
+        | V2::UpdateNotPermittedTokenSymbol
+        | V2::UpdateNotPermittedpermissions
+        | V2::UpdateNotPermittedNonce
+        | V2::UpdateNotPermittedVotingFor => V1::UpdateNotPermitted,
+        _ => V1::Predicate,
+    }
+}
+
 pub trait UserCommandWithStatusT {
     fn is_applied(&self) -> bool;
 
@@ -280,6 +373,9 @@ impl UserCommandWithStatusT for UserCommandWithStatus {
 
 pub const MEMO_LEN: usize = 32;
 
+/// Tag of user-provided (non-digest) memos
+pub const MEMO_BYTES_TAG: u8 = 1;
+
 /// Decode memo
 ///
 /// 0th byte - tag to distinguish digests from other data
@@ -295,6 +391,22 @@ pub fn decode_memo(encoded: &[u8]) -> String {
     }
 }
 
+/// Encode memo, inverse of [decode_memo]
+///
+/// Memos are at most [MEMO_LEN] bytes long
+
+pub fn encode_memo(memo: &str) -> anyhow::Result<Vec<u8>> {
+    let bytes = memo.as_bytes();
+    if bytes.len() > MEMO_LEN {
+        anyhow::bail!("memo exceeds {MEMO_LEN} bytes: {memo}");
+    }
+
+    let mut encoded = vec![MEMO_BYTES_TAG, bytes.len() as u8];
+    encoded.extend_from_slice(bytes);
+    encoded.resize(MEMO_LEN + 2, 0);
+    Ok(encoded)
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct PaymentPayload(pub mina_rs::PaymentPayloadV1);
 
@@ -595,7 +707,7 @@ mod test {
     use super::{Command, Delegation, Payment};
     use crate::{
         block::{parser::BlockParser, precomputed::PcbVersion},
-        command::decode_memo,
+        command::{decode_memo, encode_memo, MEMO_LEN},
         constants::*,
         ledger::account::nanomina_to_mina,
     };
@@ -613,6 +725,18 @@ mod test {
         assert_eq!(&expected, &actual);
     }
 
+    #[test]
+    fn encode_memo_test() -> anyhow::Result<()> {
+        let encoded = encode_memo("MIP4")?;
+        assert_eq!(encoded.len(), MEMO_LEN + 2);
+        assert_eq!(encoded[..6], [1, 4, 77, 73, 80, 52]);
+        assert_eq!(decode_memo(&encoded), "MIP4");
+
+        // too long
+        assert!(encode_memo(&"x".repeat(MEMO_LEN + 1)).is_err());
+        Ok(())
+    }
+
     #[tokio::test]
     async fn mainnet_from_precomputed() {
         // mainnet-220897-3NL4HLb7MQrxmAqVw8D4vEXCj2tdT8zgP9DFWGRoDxP72b4wxyUw
@@ -1,8 +1,17 @@
 use crate::{
-    command::*,
+    block::precomputed::PcbVersion,
+    command::{unsigned::MINA_TOKEN_ID, *},
+    mina_blocks::v2::staged_ledger_diff as v2,
+    proof_systems::signer::pubkey::CompressedPubKey,
     protocol::{
         bin_prot,
-        serialization_types::{staged_ledger_diff as mina_rs, version_bytes::V1_TXN_HASH},
+        serialization_types::{
+            common::{AmountV1, ExtendedU32, ExtendedU64_3},
+            field_and_curve_elements::{FieldElement, InnerCurveScalar},
+            signatures::{CompressedCurvePoint, PublicKeyV1},
+            staged_ledger_diff as mina_rs,
+            version_bytes::{V1_TXN_HASH, V2_TXN_HASH},
+        },
     },
 };
 use blake2::digest::VariableOutput;
@@ -125,9 +134,27 @@ impl SignedCommand {
         self.payload().common.clone().inner().inner().inner()
     }
 
+    pub fn memo(&self) -> String {
+        decode_memo(&self.payload_common().memo.t.0)
+    }
+
+    /// Serialize to the daemon's JSON format, i.e. as the command appears in
+    /// precomputed blocks
+    pub fn to_daemon_json(&self) -> anyhow::Result<serde_json::Value> {
+        let json: mina_rs::SignedCommandJson = self.0.clone().inner().inner().into();
+        Ok(serde_json::to_value(json)?)
+    }
+
+    /// Deserialize from the daemon's JSON format
+    pub fn from_daemon_json(json: &str) -> anyhow::Result<Self> {
+        let json: mina_rs::SignedCommandJson = serde_json::from_str(json)?;
+        let cmd: mina_rs::SignedCommand = json.into();
+        Ok(Self(cmd.into()))
+    }
+
     /// This returns a user command (transaction) hash that starts with
     /// [TXN_HASH_PREFIX]
-    pub fn hash_signed_command(&self) -> anyhow::Result<String> {
+    fn hash_signed_command(&self) -> anyhow::Result<String> {
         let mut binprot_bytes = Vec::new();
         bin_prot::to_writer(&mut binprot_bytes, &self.0).map_err(anyhow::Error::from)?;
 
@@ -147,6 +174,39 @@ impl SignedCommand {
             .into_string())
     }
 
+    /// This returns a post-hardfork user command (transaction) hash that
+    /// starts with [TXN_HASH_V2_PREFIX]
+    ///
+    /// Blake2b digest of the unversioned bin_prot serialization of the
+    /// command with a dummy signature. Not yet checked against hashes
+    /// reported by a post-hardfork node, there are no known-answer fixtures
+    fn hash_signed_command_v2(&self) -> anyhow::Result<String> {
+        let mut binprot_bytes = Vec::new();
+        bin_prot::to_writer(&mut binprot_bytes, &SignedCommandV2Bin::from(self))
+            .map_err(anyhow::Error::from)?;
+
+        let mut hasher = blake2::Blake2bVar::new(32).unwrap();
+        hasher.write_all(&binprot_bytes).unwrap();
+
+        let mut hash = hasher.finalize_boxed().to_vec();
+        hash.insert(0, hash.len() as u8);
+
+        Ok(bs58::encode(hash)
+            .with_check_version(V2_TXN_HASH)
+            .into_string())
+    }
+
+    /// Transaction hash of the command in a block of the given version
+    ///
+    /// zkApp commands are not signed commands & have no hash here, they are
+    /// keyed by block state hash & index instead
+    pub fn txn_hash(&self, version: &PcbVersion) -> anyhow::Result<String> {
+        match version {
+            PcbVersion::V1 => self.hash_signed_command(),
+            PcbVersion::V2 => self.hash_signed_command_v2(),
+        }
+    }
+
     pub fn from_precomputed(block: &PrecomputedBlock) -> Vec<SignedCommandWithCreationData> {
         block
             .commands()
@@ -180,6 +240,7 @@ impl SignedCommandWithData {
         blockchain_length: u32,
         date_time: u64,
         global_slot_since_genesis: u32,
+        version: &PcbVersion,
     ) -> Self {
         let command = SignedCommand::from(user_cmd.clone());
         Self {
@@ -189,9 +250,7 @@ impl SignedCommandWithData {
             nonce: command.source_nonce(),
             state_hash: state_hash.into(),
             status: user_cmd.status_data(),
-            tx_hash: command
-                .hash_signed_command()
-                .expect("valid transaction hash"),
+            tx_hash: command.txn_hash(version).expect("valid transaction hash"),
             command,
         }
     }
@@ -207,6 +266,7 @@ impl SignedCommandWithData {
                     block.blockchain_length(),
                     block.timestamp(),
                     block.global_slot_since_genesis(),
+                    &block.version(),
                 )
             })
             .collect()
@@ -301,6 +361,61 @@ impl From<Versioned2<mina_rs::SignedCommand, 1, 1>> for SignedCommand {
     }
 }
 
+/// Post-hardfork signed commands only transfer MINA & the source is always
+/// the fee payer
+impl From<v2::UserCommandData> for SignedCommand {
+    fn from(value: v2::UserCommandData) -> Self {
+        let v2::UserCommandPayloadCommon {
+            fee,
+            fee_payer_pk,
+            nonce,
+            valid_until,
+            memo,
+        } = value.payload.common;
+        let fee_payer: PublicKeyV1 = fee_payer_pk.into();
+        let common = mina_rs::SignedCommandPayloadCommon {
+            fee: AmountV1::from(fee),
+            fee_token: mina_rs::SignedCommandFeeTokenV1::from(MINA_TOKEN_ID),
+            fee_payer_pk: fee_payer.clone(),
+            nonce: ExtendedU32::from(nonce as i32),
+            valid_until: ExtendedU32::from(valid_until as u32 as i32),
+            memo: mina_rs::SignedCommandMemoV1::from(mina_rs::SignedCommandMemo::from(memo)),
+        };
+        let body = match value.payload.body.1 {
+            v2::UserCommandPayloadBody::Payment(v2::PaymentPayload {
+                receiver_pk,
+                amount,
+            }) => mina_rs::SignedCommandPayloadBody::PaymentPayload(
+                mina_rs::PaymentPayloadV1::from(mina_rs::PaymentPayload {
+                    source_pk: fee_payer,
+                    receiver_pk: receiver_pk.into(),
+                    token_id: ExtendedU64_3::from(MINA_TOKEN_ID),
+                    amount: AmountV1::from(amount),
+                }),
+            ),
+            v2::UserCommandPayloadBody::StakeDelegation((
+                _,
+                v2::StakeDelegationPayload { new_delegate },
+            )) => mina_rs::SignedCommandPayloadBody::StakeDelegation(
+                mina_rs::StakeDelegationV1::from(mina_rs::StakeDelegation::SetDelegate {
+                    delegator: fee_payer,
+                    new_delegate: new_delegate.into(),
+                }),
+            ),
+        };
+        let signer = CompressedPubKey::from_address(&value.signer.0).expect("signer public key");
+
+        Self(mina_rs::SignedCommandV1::from(mina_rs::SignedCommand {
+            payload: mina_rs::SignedCommandPayloadV1::from(mina_rs::SignedCommandPayload {
+                common: mina_rs::SignedCommandPayloadCommonV1::from(common),
+                body: mina_rs::SignedCommandPayloadBodyV1::from(body),
+            }),
+            signer: signer.into(),
+            signature: value.signature.into(),
+        }))
+    }
+}
+
 impl From<SignedCommand> for serde_json::Value {
     fn from(value: SignedCommand) -> Self {
         use serde_json::*;
@@ -512,16 +627,116 @@ fn payload_json(value: mina_rs::SignedCommandV1) -> serde_json::Value {
     Value::Object(payload_obj)
 }
 
+/// Unversioned bin_prot layout of a post-hardfork signed command
+#[derive(Serialize)]
+struct SignedCommandV2Bin {
+    payload: SignedCommandPayloadV2Bin,
+    signer: CompressedCurvePoint,
+    signature: (FieldElement, InnerCurveScalar),
+}
+
+#[derive(Serialize)]
+struct SignedCommandPayloadV2Bin {
+    common: SignedCommandPayloadCommonV2Bin,
+    body: SignedCommandPayloadBodyV2Bin,
+}
+
+#[derive(Serialize)]
+struct SignedCommandPayloadCommonV2Bin {
+    fee: u64,
+    fee_payer_pk: CompressedCurvePoint,
+    nonce: u32,
+    valid_until: GlobalSlotSinceGenesisV2Bin,
+    memo: mina_rs::SignedCommandMemo,
+}
+
+#[derive(Serialize)]
+enum GlobalSlotSinceGenesisV2Bin {
+    SinceGenesis(u32),
+}
+
+#[derive(Serialize)]
+enum SignedCommandPayloadBodyV2Bin {
+    Payment {
+        receiver_pk: CompressedCurvePoint,
+        amount: u64,
+    },
+    StakeDelegation(StakeDelegationV2Bin),
+}
+
+#[derive(Serialize)]
+enum StakeDelegationV2Bin {
+    SetDelegate { new_delegate: CompressedCurvePoint },
+}
+
+impl From<&SignedCommand> for SignedCommandV2Bin {
+    fn from(value: &SignedCommand) -> Self {
+        let common = value.payload_common();
+        let body = match value.payload_body() {
+            mina_rs::SignedCommandPayloadBody::PaymentPayload(payment) => {
+                let payment = payment.inner().inner();
+                SignedCommandPayloadBodyV2Bin::Payment {
+                    receiver_pk: payment.receiver_pk.0.inner().inner(),
+                    amount: payment.amount.inner().inner(),
+                }
+            }
+            mina_rs::SignedCommandPayloadBody::StakeDelegation(delegation) => {
+                let mina_rs::StakeDelegation::SetDelegate { new_delegate, .. } = delegation.inner();
+                SignedCommandPayloadBodyV2Bin::StakeDelegation(StakeDelegationV2Bin::SetDelegate {
+                    new_delegate: new_delegate.0.inner().inner(),
+                })
+            }
+        };
+
+        // Signature.dummy = (Field.one, Scalar.one)
+        let mut one = FieldElement::default();
+        one[0] = 1;
+
+        Self {
+            payload: SignedCommandPayloadV2Bin {
+                common: SignedCommandPayloadCommonV2Bin {
+                    fee: common.fee.inner().inner(),
+                    fee_payer_pk: common.fee_payer_pk.0.inner().inner(),
+                    nonce: common.nonce.inner().inner() as u32,
+                    valid_until: GlobalSlotSinceGenesisV2Bin::SinceGenesis(
+                        common.valid_until.inner().inner() as u32,
+                    ),
+                    memo: common.memo.inner(),
+                },
+                body,
+            },
+            signer: value
+                .0
+                .clone()
+                .inner()
+                .inner()
+                .signer
+                .0
+                .inner()
+                .0
+                .inner()
+                .inner(),
+            signature: (one, one),
+        }
+    }
+}
+
 pub const TXN_HASH_LEN: usize = 53;
 pub const TXN_HASH_PREFIX: &str = "Ckp";
 
+/// Post-hardfork transaction hashes are one byte shorter
+pub const TXN_HASH_V2_LEN: usize = 52;
+pub const TXN_HASH_V2_PREFIX: &str = "5J";
+
 pub fn is_valid_tx_hash(input: &str) -> bool {
-    input.starts_with(TXN_HASH_PREFIX) && input.len() == TXN_HASH_LEN
+    (input.starts_with(TXN_HASH_PREFIX) && input.len() == TXN_HASH_LEN)
+        || (input.starts_with(TXN_HASH_V2_PREFIX) && input.len() == TXN_HASH_V2_LEN)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::block::precomputed::{PcbVersion, PrecomputedBlock};
+    use super::*;
+    use crate::store::{txn_hash_from_key_bytes, txn_hash_key_bytes};
     use std::path::PathBuf;
 
     #[test]
@@ -540,4 +755,30 @@ mod tests {
 
         assert_eq!(hashes, expect);
     }
+
+    #[test]
+    fn transaction_hash_v2() -> anyhow::Result<()> {
+        let block_file = PathBuf::from("./tests/data/berkeley/sequential_blocks/berkeley-10-3NL53c8uTVnoFjzh17VAeCR9r3zjmDowNpeFRRVEUqnvX5WdHtWE.json");
+        let block = PrecomputedBlock::parse_file(&block_file, PcbVersion::V2)?;
+        let hashes = block.command_hashes();
+        assert_eq!(hashes.len(), 12);
+
+        for hash in hashes.iter() {
+            assert!(hash.starts_with(TXN_HASH_V2_PREFIX), "{hash}");
+            assert!(is_valid_tx_hash(hash));
+
+            // fixed width store keys
+            let key = txn_hash_key_bytes(hash);
+            assert_eq!(key.len(), TXN_HASH_LEN);
+            assert_eq!(txn_hash_from_key_bytes(&key)?, *hash);
+        }
+
+        // only the shape is checked, there are no known V2 hashes to compare
+        // against; the pre-hardfork encoding hashes differently
+        for cmd in SignedCommand::from_precomputed(&block) {
+            let cmd = cmd.signed_command;
+            assert_ne!(cmd.hash_signed_command()?, cmd.hash_signed_command_v2()?);
+        }
+        Ok(())
+    }
 }
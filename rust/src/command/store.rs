@@ -3,7 +3,7 @@ use crate::{
     block::{precomputed::PrecomputedBlock, BlockHash},
     command::{signed::SignedCommandWithData, UserCommandWithStatus},
     ledger::public_key::PublicKey,
-    store::from_be_bytes,
+    store::{from_be_bytes, txn_hash_from_key_bytes},
 };
 use anyhow::anyhow;
 use speedb::{DBIterator, IteratorMode};
@@ -163,7 +163,7 @@ pub fn user_commands_iterator_u32_prefix(key: &[u8]) -> u32 {
 /// - discard 4 bytes, keep [TXN_HASH_LEN] bytes
 /// - [user_commands_slot_iterator] & [user_commands_height_iterator]
 pub fn user_commands_iterator_txn_hash(key: &[u8]) -> anyhow::Result<String> {
-    String::from_utf8(key[4..(4 + TXN_HASH_LEN)].to_vec())
+    txn_hash_from_key_bytes(&key[4..(4 + TXN_HASH_LEN)])
         .map_err(|e| anyhow!("Error reading txn hash: {e}"))
 }
 
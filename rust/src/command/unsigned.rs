@@ -0,0 +1,229 @@
+use crate::{
+    command::{encode_memo, signed::SignedCommand},
+    ledger::{
+        account::{Amount, Nonce},
+        public_key::PublicKey,
+    },
+    proof_systems::{
+        hasher::roinput::ROInput,
+        signer::{pubkey::CompressedPubKey, signature::Signature},
+    },
+    protocol::serialization_types::{
+        common::{AmountV1, ExtendedU32, ExtendedU64_3},
+        signatures::{PublicKey2V1, PublicKeyV1},
+        staged_ledger_diff as mina_rs,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+/// Token id of MINA
+pub const MINA_TOKEN_ID: u64 = 1;
+
+/// Commands are valid until this global slot by default
+pub const VALID_UNTIL_DEFAULT: u32 = u32::MAX;
+
+/// Transaction tag bits used in the legacy random oracle input
+const PAYMENT_TX_TAG: [bool; 3] = [false, false, false];
+const DELEGATION_TX_TAG: [bool; 3] = [false, false, true];
+
+/// A payment or stake delegation which has not been signed yet.
+/// The sender pays the fee.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnsignedCommand {
+    pub sender: PublicKey,
+    pub fee: Amount,
+    pub nonce: Nonce,
+    pub valid_until: u32,
+    pub memo: String,
+    pub body: UnsignedCommandBody,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnsignedCommandBody {
+    Payment { receiver: PublicKey, amount: Amount },
+    Delegation { new_delegate: PublicKey },
+}
+
+impl UnsignedCommand {
+    /// Receiver of a payment or new delegate of a delegation
+    pub fn receiver(&self) -> &PublicKey {
+        match &self.body {
+            UnsignedCommandBody::Payment { receiver, .. } => receiver,
+            UnsignedCommandBody::Delegation { new_delegate } => new_delegate,
+        }
+    }
+
+    pub fn amount(&self) -> Option<Amount> {
+        match &self.body {
+            UnsignedCommandBody::Payment { amount, .. } => Some(*amount),
+            UnsignedCommandBody::Delegation { .. } => None,
+        }
+    }
+
+    /// The signed command payload
+    pub fn payload(&self) -> anyhow::Result<mina_rs::SignedCommandPayload> {
+        let sender: PublicKeyV1 = compressed(&self.sender)?.into();
+        let receiver: PublicKeyV1 = compressed(self.receiver())?.into();
+        let common = mina_rs::SignedCommandPayloadCommon {
+            fee: AmountV1::from(self.fee.0),
+            fee_token: mina_rs::SignedCommandFeeTokenV1::from(MINA_TOKEN_ID),
+            fee_payer_pk: sender.clone(),
+            nonce: ExtendedU32::from(self.nonce.0 as i32),
+            valid_until: ExtendedU32::from(self.valid_until as i32),
+            memo: mina_rs::SignedCommandMemoV1::from(mina_rs::SignedCommandMemo(encode_memo(
+                &self.memo,
+            )?)),
+        };
+        let body = match &self.body {
+            UnsignedCommandBody::Payment { amount, .. } => {
+                mina_rs::SignedCommandPayloadBody::PaymentPayload(mina_rs::PaymentPayloadV1::from(
+                    mina_rs::PaymentPayload {
+                        source_pk: sender,
+                        receiver_pk: receiver,
+                        token_id: ExtendedU64_3::from(MINA_TOKEN_ID),
+                        amount: AmountV1::from(amount.0),
+                    },
+                ))
+            }
+            UnsignedCommandBody::Delegation { .. } => {
+                mina_rs::SignedCommandPayloadBody::StakeDelegation(
+                    mina_rs::StakeDelegationV1::from(mina_rs::StakeDelegation::SetDelegate {
+                        delegator: sender,
+                        new_delegate: receiver,
+                    }),
+                )
+            }
+        };
+
+        Ok(mina_rs::SignedCommandPayload {
+            common: mina_rs::SignedCommandPayloadCommonV1::from(common),
+            body: mina_rs::SignedCommandPayloadBodyV1::from(body),
+        })
+    }
+
+    /// Legacy random oracle input of the payload, i.e. the signed message
+    pub fn to_roinput(&self) -> anyhow::Result<ROInput> {
+        let sender = compressed(&self.sender)?;
+        let receiver = compressed(self.receiver())?;
+        let (tag, amount) = match &self.body {
+            UnsignedCommandBody::Payment { amount, .. } => (PAYMENT_TX_TAG, amount.0),
+            UnsignedCommandBody::Delegation { .. } => (DELEGATION_TX_TAG, 0),
+        };
+
+        let roi = ROInput::new()
+            .append_field(sender.x)
+            .append_field(sender.x)
+            .append_field(receiver.x)
+            .append_u64(self.fee.0)
+            .append_u64(MINA_TOKEN_ID)
+            .append_bool(sender.is_odd)
+            .append_u32(self.nonce.0)
+            .append_u32(self.valid_until)
+            .append_bytes(&encode_memo(&self.memo)?);
+        let roi = tag.iter().fold(roi, |roi, bit| roi.append_bool(*bit));
+
+        Ok(roi
+            .append_bool(sender.is_odd)
+            .append_bool(receiver.is_odd)
+            .append_u64(MINA_TOKEN_ID)
+            .append_u64(amount)
+            .append_bool(false))
+    }
+
+    /// Attach the sender's signature
+    pub fn into_signed_command(self, signature: &Signature) -> anyhow::Result<SignedCommand> {
+        let signer: PublicKey2V1 = compressed(&self.sender)?.into();
+        Ok(SignedCommand(mina_rs::SignedCommandV1::from(
+            mina_rs::SignedCommand {
+                payload: mina_rs::SignedCommandPayloadV1::from(self.payload()?),
+                signer,
+                signature: signature.into(),
+            },
+        )))
+    }
+}
+
+fn compressed(pk: &PublicKey) -> anyhow::Result<CompressedPubKey> {
+    CompressedPubKey::from_address(&pk.0)
+        .map_err(|e| anyhow::anyhow!("invalid public key {pk}: {e}"))
+}
+
+impl From<&SignedCommand> for UnsignedCommand {
+    fn from(value: &SignedCommand) -> Self {
+        let common = value.payload_common();
+        let body = match value.payload_body() {
+            mina_rs::SignedCommandPayloadBody::PaymentPayload(payload) => {
+                let payload = payload.inner().inner();
+                UnsignedCommandBody::Payment {
+                    receiver: payload.receiver_pk.into(),
+                    amount: payload.amount.inner().inner().into(),
+                }
+            }
+            mina_rs::SignedCommandPayloadBody::StakeDelegation(_) => {
+                UnsignedCommandBody::Delegation {
+                    new_delegate: value.receiver_pk(),
+                }
+            }
+        };
+
+        Self {
+            body,
+            sender: value.fee_payer_pk(),
+            fee: value.fee().into(),
+            nonce: value.source_nonce(),
+            valid_until: common.valid_until.inner().inner() as u32,
+            memo: value.memo(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::precomputed::{PcbVersion, PrecomputedBlock};
+    use std::path::PathBuf;
+
+    #[test]
+    fn roundtrip_signed_commands() -> anyhow::Result<()> {
+        let path = PathBuf::from("./tests/data/non_sequential_blocks/mainnet-220897-3NL4HLb7MQrxmAqVw8D4vEXCj2tdT8zgP9DFWGRoDxP72b4wxyUw.json");
+        let block = PrecomputedBlock::parse_file(&path, PcbVersion::V1)?;
+
+        for cmd in SignedCommand::from_precomputed(&block) {
+            let signed = cmd.signed_command;
+            let signature: Signature = signed.0.t.t.signature.clone().into();
+            let unsigned = UnsignedCommand::from(&signed);
+            let resigned = unsigned.into_signed_command(&signature)?;
+
+            // rebuilding the command preserves its hash
+            assert_eq!(resigned, signed);
+            assert_eq!(
+                resigned.txn_hash(&PcbVersion::V1)?,
+                signed.txn_hash(&PcbVersion::V1)?
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn roinput_size() -> anyhow::Result<()> {
+        let unsigned = UnsignedCommand {
+            sender: "B62qre3erTHfzQckNuibViWQGyyKwZseztqrjPZBv6SQF384Rg6ESAy".into(),
+            fee: Amount(10_000_000),
+            nonce: Nonce(5),
+            valid_until: VALID_UNTIL_DEFAULT,
+            memo: "hello".into(),
+            body: UnsignedCommandBody::Payment {
+                receiver: "B62qs2YyNuo1LbNo5sbhPByDDAB7NZiejFM6H1ctND5ui7wH4PWa7qm".into(),
+                amount: Amount(1_000_000_000),
+            },
+        };
+        let roi = unsigned.to_roinput()?;
+
+        assert_eq!(roi.fields().len(), 3);
+        assert_eq!(
+            roi.bits().len(),
+            64 + 64 + 1 + 32 + 32 + 34 * 8 + 3 + 1 + 1 + 64 + 64 + 1
+        );
+        Ok(())
+    }
+}
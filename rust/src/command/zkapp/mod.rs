@@ -0,0 +1,257 @@
+pub mod store;
+
+use crate::{
+    block::{precomputed::PrecomputedBlock, BlockHash},
+    command::decode_memo,
+    ledger::public_key::PublicKey,
+    mina_blocks::v2::staged_ledger_diff::{
+        command::ZkappCommand, Authorization, AuthorizationKind, Elt,
+    },
+    protocol::serialization_types::version_bytes::USER_COMMAND_MEMO,
+};
+use serde::{Deserialize, Serialize};
+
+/// zkApp command with the data of the block it was included in
+///
+/// zkApp commands are identified by their block's state hash & their index
+/// in the block since the indexer doesn't compute zkApp transaction hashes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ZkappCommandWithData {
+    pub state_hash: BlockHash,
+    pub blockchain_length: u32,
+    pub global_slot_since_genesis: u32,
+    pub date_time: u64,
+
+    /// Index among the block's zkApp commands, in application order
+    pub index: u32,
+
+    pub fee_payer: PublicKey,
+    pub fee: u64,
+    pub nonce: u32,
+    pub memo: String,
+    pub applied: bool,
+    pub failure_reasons: Vec<String>,
+    pub account_updates: Vec<ZkappAccountUpdate>,
+}
+
+/// Account update of a zkApp command, depth-first in call order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ZkappAccountUpdate {
+    pub public_key: PublicKey,
+    pub token_id: String,
+    pub balance_change: i64,
+    pub increment_nonce: bool,
+
+    /// Depth in the call forest, top-level updates have depth 0
+    pub call_depth: u32,
+
+    pub authorization_kind: String,
+    pub events: Vec<Vec<String>>,
+    pub actions: Vec<Vec<String>>,
+}
+
+/// Event or action emitted by a zkApp account update
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ZkappEventWithData {
+    pub zkapp: PublicKey,
+    pub state_hash: BlockHash,
+
+    /// Index of the emitting zkApp command in the block
+    pub command_index: u32,
+
+    pub blockchain_length: u32,
+    pub date_time: u64,
+    pub fields: Vec<String>,
+}
+
+/// zkApp actions carry the same data as events
+pub type ZkappActionWithData = ZkappEventWithData;
+
+impl ZkappCommandWithData {
+    pub fn from_precomputed(block: &PrecomputedBlock) -> Vec<Self> {
+        block
+            .zkapp_commands()
+            .into_iter()
+            .enumerate()
+            .map(|(index, cmd)| Self::new(&cmd, block, index as u32))
+            .collect()
+    }
+
+    pub fn new(cmd: &ZkappCommand, block: &PrecomputedBlock, index: u32) -> Self {
+        Self {
+            state_hash: block.state_hash(),
+            blockchain_length: block.blockchain_length(),
+            global_slot_since_genesis: block.global_slot_since_genesis(),
+            date_time: block.timestamp(),
+            index,
+            fee_payer: cmd.fee_payer(),
+            fee: cmd.fee(),
+            nonce: cmd.nonce(),
+            memo: zkapp_memo(&cmd.data.memo),
+            applied: cmd.is_applied(),
+            failure_reasons: cmd
+                .status
+                .failures()
+                .iter()
+                .filter_map(|reason| {
+                    serde_json::to_value(reason)
+                        .ok()
+                        .and_then(|v| v.as_str().map(ToOwned::to_owned))
+                })
+                .collect(),
+            account_updates: ZkappAccountUpdate::from_zkapp_command(cmd),
+        }
+    }
+
+    /// Fee payer & account update public keys
+    pub fn public_keys(&self) -> Vec<PublicKey> {
+        let mut pks = vec![self.fee_payer.clone()];
+        for update in &self.account_updates {
+            if !pks.contains(&update.public_key) {
+                pks.push(update.public_key.clone());
+            }
+        }
+        pks
+    }
+
+    pub fn contains_pk(&self, pk: &PublicKey) -> bool {
+        self.fee_payer == *pk || self.account_updates.iter().any(|u| u.public_key == *pk)
+    }
+
+    /// Events emitted by the command's account updates, in call order
+    pub fn events(&self) -> Vec<ZkappEventWithData> {
+        self.emitted(|update| &update.events)
+    }
+
+    /// Actions dispatched by the command's account updates, in call order
+    pub fn actions(&self) -> Vec<ZkappActionWithData> {
+        self.emitted(|update| &update.actions)
+    }
+
+    fn emitted<F>(&self, f: F) -> Vec<ZkappEventWithData>
+    where
+        F: Fn(&ZkappAccountUpdate) -> &Vec<Vec<String>>,
+    {
+        self.account_updates
+            .iter()
+            .flat_map(|update| {
+                f(update).iter().map(|fields| ZkappEventWithData {
+                    zkapp: update.public_key.clone(),
+                    state_hash: self.state_hash.clone(),
+                    command_index: self.index,
+                    blockchain_length: self.blockchain_length,
+                    date_time: self.date_time,
+                    fields: fields.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl ZkappAccountUpdate {
+    pub fn from_zkapp_command(cmd: &ZkappCommand) -> Vec<Self> {
+        fn collect(elt: &Elt, depth: u32, updates: &mut Vec<ZkappAccountUpdate>) {
+            let body = &elt.account_update.body;
+            let kind = match &body.authorization_kind {
+                Authorization::Kind((kind,)) | Authorization::KindWithData((kind, _)) => kind,
+            };
+            updates.push(ZkappAccountUpdate {
+                public_key: body.public_key.clone(),
+                token_id: body.token_id.clone(),
+                balance_change: body.balance_change() as i64,
+                increment_nonce: body.increment_nonce,
+                call_depth: depth,
+                authorization_kind: authorization_kind_str(kind).to_string(),
+                events: body.events.iter().map(|e| e.0.clone()).collect(),
+                actions: body.actions.iter().map(|a| a.0.clone()).collect(),
+            });
+            for call in &elt.calls {
+                collect(&call.elt, depth + 1, updates);
+            }
+        }
+
+        let mut updates = vec![];
+        for account_update in &cmd.data.account_updates {
+            collect(&account_update.elt, 0, &mut updates);
+        }
+        updates
+    }
+}
+
+fn authorization_kind_str(kind: &AuthorizationKind) -> &'static str {
+    match kind {
+        AuthorizationKind::NoneGiven => "None_given",
+        AuthorizationKind::Either => "Either",
+        AuthorizationKind::Proof => "Proof",
+        AuthorizationKind::Signature => "Signature",
+        AuthorizationKind::Impossible => "Impossible",
+    }
+}
+
+/// Decode the base58 encoded memo of a zkApp command
+fn zkapp_memo(memo: &str) -> String {
+    bs58::decode(memo)
+        .with_check(Some(USER_COMMAND_MEMO))
+        .into_vec()
+        .ok()
+        .filter(|bytes| bytes.len() > 2 && bytes[2] as usize + 3 <= bytes.len())
+        .map(|bytes| decode_memo(&bytes[1..]))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        block::precomputed::PcbVersion, mina_blocks::v2::staged_ledger_diff::command::MINA_TOKEN_ID,
+    };
+    use std::path::PathBuf;
+
+    #[test]
+    fn zkapp_commands_from_precomputed() -> anyhow::Result<()> {
+        let path: PathBuf = "./tests/data/berkeley/non_sequential_blocks/berkeley-4969-3NL8QoLQMtsBH8vUnccQw3vt8PgYuZRMApq1yZT1jwhZjbBLMRJU.json".into();
+        let block = PrecomputedBlock::parse_file(&path, PcbVersion::V2)?;
+        let zkapp_commands = ZkappCommandWithData::from_precomputed(&block);
+        assert_eq!(zkapp_commands.len(), 1);
+
+        let cmd = &zkapp_commands[0];
+        assert!(cmd.applied);
+        assert!(cmd.failure_reasons.is_empty());
+        assert_eq!(cmd.index, 0);
+        assert_eq!(cmd.fee, 10_100_000);
+        assert_eq!(cmd.state_hash, block.state_hash());
+
+        // single proof authorized account update emitting one event
+        let zkapp: PublicKey = "B62qqpAFkz374qJpuFKYZPjT1KxSmnLoY4zEc878FaW4DSxgYNXZiny".into();
+        assert_eq!(
+            cmd.account_updates,
+            vec![ZkappAccountUpdate {
+                public_key: zkapp.clone(),
+                token_id: MINA_TOKEN_ID.to_string(),
+                balance_change: 0,
+                increment_nonce: false,
+                call_depth: 0,
+                authorization_kind: "Proof".to_string(),
+                events: vec![vec![
+                    "0x000000000000000000000000000000000000000000000000000000000000000D"
+                        .to_string(),
+                    "0x000000000000000000000000000000000000000000000000000000000134D75C"
+                        .to_string(),
+                ]],
+                actions: vec![],
+            }]
+        );
+        assert_eq!(
+            cmd.public_keys(),
+            vec![cmd.fee_payer.clone(), zkapp.clone()]
+        );
+
+        let events = cmd.events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].zkapp, zkapp);
+        assert_eq!(events[0].state_hash, cmd.state_hash);
+        assert_eq!(events[0].command_index, cmd.index);
+        assert!(cmd.actions().is_empty());
+        Ok(())
+    }
+}
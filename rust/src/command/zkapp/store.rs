@@ -0,0 +1,78 @@
+use crate::{
+    block::{precomputed::PrecomputedBlock, BlockHash},
+    command::zkapp::{ZkappActionWithData, ZkappCommandWithData, ZkappEventWithData},
+    ledger::public_key::PublicKey,
+};
+use speedb::{DBIterator, IteratorMode};
+
+/// Store for zkApp commands, events & actions
+///
+/// zkApp commands have no transaction hash lookup, the indexer doesn't
+/// compute zkApp command hashes yet
+pub trait ZkappCommandStore {
+    /// Index zkApp commands from the given block on:
+    /// state hash & index, fee payer, touched accounts, and the zkApp
+    /// addresses of emitted events & actions
+    fn add_zkapp_commands(&self, block: &PrecomputedBlock) -> anyhow::Result<()>;
+
+    /// Get the zkApp command at the given index of the given block
+    fn get_zkapp_command(
+        &self,
+        state_hash: &BlockHash,
+        index: u32,
+    ) -> anyhow::Result<Option<ZkappCommandWithData>>;
+
+    /// Get the zkApp commands of the given block, in application order
+    fn get_block_zkapp_commands(
+        &self,
+        state_hash: &BlockHash,
+    ) -> anyhow::Result<Option<Vec<ZkappCommandWithData>>>;
+
+    /// Get the most recent zkApp commands paid for by the given account
+    fn get_zkapp_commands_fee_payer(
+        &self,
+        pk: &PublicKey,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ZkappCommandWithData>>;
+
+    /// Get the most recent zkApp commands touching the given account,
+    /// i.e. as fee payer or in any account update
+    fn get_zkapp_commands_public_key(
+        &self,
+        pk: &PublicKey,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ZkappCommandWithData>>;
+
+    /// Get the most recent events emitted by the given zkApp
+    fn get_zkapp_events(
+        &self,
+        zkapp: &PublicKey,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ZkappEventWithData>>;
+
+    /// Get the most recent actions dispatched by the given zkApp
+    fn get_zkapp_actions(
+        &self,
+        zkapp: &PublicKey,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ZkappActionWithData>>;
+
+    ///////////////
+    // Iterators //
+    ///////////////
+
+    /// Iterator over zkApp commands by block height
+    /// - key: `{block height}{state hash}{index}`
+    /// - value: empty byte
+    fn zkapp_commands_height_iterator(&self, mode: IteratorMode) -> DBIterator<'_>;
+
+    /// Iterator over zkApp commands by fee payer & block height
+    /// - key: `{fee payer}{block height}{nonce}{state hash}{index}`
+    /// - value: empty byte
+    fn zkapp_commands_fee_payer_iterator(&self, mode: IteratorMode) -> DBIterator<'_>;
+
+    /// Iterator over zkApp commands by touched account & block height
+    /// - key: `{pk}{block height}{nonce}{state hash}{index}`
+    /// - value: empty byte
+    fn zkapp_commands_pk_iterator(&self, mode: IteratorMode) -> DBIterator<'_>;
+}
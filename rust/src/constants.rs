@@ -14,6 +14,7 @@ pub const LEDGER_CADENCE: u32 = 100;
 pub const CANONICAL_UPDATE_THRESHOLD: u32 = PRUNE_INTERVAL_DEFAULT / 5;
 pub const MAINNET_CANONICAL_THRESHOLD: u32 = 10;
 pub const PRUNE_INTERVAL_DEFAULT: u32 = 10;
+pub const WITNESS_TREE_PERSIST_FREQ_SEC: u64 = 300;
 
 // mina constants
 
@@ -28,6 +29,13 @@ pub const MAINNET_TRANSITION_FRONTIER_K: u32 = 290;
 pub const MAINNET_ACCOUNT_CREATION_FEE: Amount = Amount(1e9 as u64);
 pub const MAINNET_COINBASE_REWARD: u64 = 720000000000;
 
+// hardfork constants, the mainnet fork block the hardfork genesis extends
+
+pub const HARDFORK_GENESIS_PREV_STATE_HASH: &str =
+    "3NLRTfY4kZyJtvaP4dFenDcxfoMfT3uEpkWS913KkeXLtziyVd15";
+pub const HARDFORK_GENESIS_BLOCKCHAIN_LENGTH: u32 = 359605;
+pub const HARDFORK_GENESIS_GLOBAL_SLOT: u32 = 564480;
+
 // protocol constants
 
 pub const MAINNET_PROTOCOL_CONSTANTS: &[u32] = &[
@@ -39,6 +47,8 @@ pub const MAINNET_PROTOCOL_CONSTANTS: &[u32] = &[
 ];
 pub const MAINNET_EPOCH_SLOT_COUNT: u32 = 7140;
 pub const MAINNET_SLOTS_PER_SUB_WINDOW: u32 = 7;
+pub const MAINNET_SUB_WINDOWS_PER_WINDOW: u32 = 11;
+pub const MAINNET_GRACE_PERIOD_END: u32 = 1440;
 pub const MAINNET_DELTA: u32 = 0;
 pub const MAINNET_TXPOOL_MAX_SIZE: u32 = 3000;
 
@@ -1,4 +1,4 @@
-use crate::{block::BlockHash, ledger::LedgerHash};
+use crate::{block::BlockHash, canonicity::Reorg, ledger::LedgerHash};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
@@ -49,6 +49,7 @@ pub enum DbCanonicityEvent {
         state_hash: BlockHash,
         blockchain_length: u32,
     },
+    Reorg(Reorg),
 }
 
 impl DbEvent {
@@ -104,6 +105,17 @@ impl std::fmt::Debug for DbCanonicityEvent {
                 "db new canonical block (length {}): {}",
                 blockchain_length, state_hash
             ),
+            Self::Reorg(Reorg {
+                new_best_tip,
+                new_blockchain_length,
+                common_ancestor_length,
+                depth,
+                ..
+            }) => write!(
+                f,
+                "db reorg depth {} from length {} to (length {}): {}",
+                depth, common_ancestor_length, new_blockchain_length, new_best_tip
+            ),
         }
     }
 }
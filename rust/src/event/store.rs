@@ -1,10 +1,12 @@
 use crate::event::IndexerEvent;
 use speedb::{DBIterator, IteratorMode};
+use tokio::sync::broadcast;
 
 pub trait EventStore {
     /// Add event to db and return the next sequence number
     ///
-    /// `WitnessTree` events are not recorded
+    /// `WitnessTree` events are not recorded. Recorded events are also sent
+    /// to all subscribers.
     fn add_event(&self, event: &IndexerEvent) -> anyhow::Result<u32>;
 
     /// Get the event from the log
@@ -18,4 +20,7 @@ pub trait EventStore {
 
     /// Returns the event log iterator
     fn event_log_iterator(&self, mode: IteratorMode) -> DBIterator<'_>;
+
+    /// Subscribe to events recorded after this call
+    fn subscribe_events(&self) -> broadcast::Receiver<IndexerEvent>;
 }
@@ -1,7 +1,7 @@
 use super::username::Username;
 use crate::{
     block::{genesis::GenesisBlock, BlockHash},
-    ledger::{diff::account::PaymentDiff, public_key::PublicKey},
+    ledger::{diff::account::PaymentDiff, public_key::PublicKey, token::TokenId},
     mina_blocks::v2::ZkappAccount,
 };
 use rust_decimal::Decimal;
@@ -58,6 +58,14 @@ impl From<String> for Nonce {
     }
 }
 
+impl std::str::FromStr for Nonce {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Nonce(s.parse()?))
+    }
+}
+
 impl From<Nonce> for serde_json::value::Number {
     fn from(n: Nonce) -> Self {
         Number::from(n.0)
@@ -78,8 +86,10 @@ pub struct Account {
     pub delegate: PublicKey,
     pub genesis_account: bool,
 
+    // MINA unless created by a zkApp
+    pub token: TokenId,
+
     // optional
-    pub token: Option<u64>,
     pub token_permissions: Option<TokenPermissions>,
     pub receipt_chain_hash: Option<ReceiptChainHash>,
     pub voting_for: Option<BlockHash>,
@@ -126,6 +136,30 @@ pub struct TokenPermissions {}
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ReceiptChainHash(pub String);
 
+/// An account's balance, nonce & delegate after a block is applied
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountHistory {
+    pub state_hash: BlockHash,
+    pub blockchain_length: u32,
+    pub token: TokenId,
+    pub balance: Amount,
+    pub nonce: Nonce,
+    pub delegate: PublicKey,
+}
+
+impl AccountHistory {
+    pub fn new(state_hash: &BlockHash, blockchain_length: u32, account: &Account) -> Self {
+        Self {
+            state_hash: state_hash.clone(),
+            blockchain_length,
+            token: account.token.clone(),
+            balance: account.balance,
+            nonce: account.nonce,
+            delegate: account.delegate.clone(),
+        }
+    }
+}
+
 impl Account {
     /// Time-locked balance (subtracted from circulating supply)
     /// as per https://docs.minaprotocol.com/mina-protocol/time-locked-accounts
@@ -150,6 +184,19 @@ impl Account {
         }
     }
 
+    /// Empty account of the given token
+    pub fn empty_token(public_key: PublicKey, token: TokenId) -> Self {
+        Account {
+            token,
+            ..Self::empty(public_key)
+        }
+    }
+
+    /// Ledger identifier of the account
+    pub fn id(&self) -> (PublicKey, TokenId) {
+        (self.public_key.clone(), self.token.clone())
+    }
+
     pub fn set_username(&mut self, username: Username) -> anyhow::Result<()> {
         self.username = Some(username);
         Ok(())
@@ -223,12 +270,10 @@ impl PartialOrd for Account {
 
 impl Ord for Account {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        let balance_cmp = self.balance.cmp(&other.balance);
-        if balance_cmp == std::cmp::Ordering::Equal {
-            self.public_key.cmp(&other.public_key)
-        } else {
-            balance_cmp
-        }
+        self.balance
+            .cmp(&other.balance)
+            .then_with(|| self.public_key.cmp(&other.public_key))
+            .then_with(|| self.token.cmp(&other.token))
     }
 }
 
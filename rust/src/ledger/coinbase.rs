@@ -4,8 +4,10 @@ use crate::{
     constants::*,
     ledger::{
         diff::account::{AccountDiff, PaymentDiff, UpdateType},
+        token::TokenId,
         PublicKey,
     },
+    mina_blocks::v2::staged_ledger_diff as v2,
     protocol::serialization_types::staged_ledger_diff,
 };
 
@@ -40,49 +42,56 @@ impl From<staged_ledger_diff::CoinBaseFeeTransfer> for CoinbaseFeeTransfer {
     }
 }
 
-impl CoinbaseKind {
-    pub fn from_precomputed(precomputed_block: &PrecomputedBlock) -> Vec<Self> {
-        let mut res = vec![];
-        let pre_diff_coinbase = match precomputed_block.staged_ledger_pre_diff().coinbase.inner() {
+impl From<v2::CoinbasePayload> for CoinbaseFeeTransfer {
+    fn from(value: v2::CoinbasePayload) -> Self {
+        Self {
+            receiver_pk: value.receiver_pk,
+            fee: value.fee,
+        }
+    }
+}
+
+impl From<staged_ledger_diff::CoinBase> for CoinbaseKind {
+    fn from(value: staged_ledger_diff::CoinBase) -> Self {
+        match value {
             staged_ledger_diff::CoinBase::Zero => Self::Zero,
-            staged_ledger_diff::CoinBase::One(x) => Self::One(x.map(|cb| {
-                let staged_ledger_diff::CoinBaseFeeTransfer { receiver_pk, fee } =
-                    cb.inner().inner();
-                CoinbaseFeeTransfer {
-                    receiver_pk: PublicKey::from(receiver_pk),
-                    fee: fee.inner().inner(),
-                }
-            })),
+            staged_ledger_diff::CoinBase::One(x) => Self::One(x.map(|c| c.inner().inner().into())),
             staged_ledger_diff::CoinBase::Two(x, y) => Self::Two(
                 x.map(|c| c.inner().inner().into()),
                 y.map(|c| c.inner().inner().into()),
             ),
-        };
-        let post_diff_coinbase = match precomputed_block
-            .staged_ledger_post_diff()
-            .map(|diff| diff.coinbase.inner())
-        {
-            None => None,
-            Some(staged_ledger_diff::CoinBase::Zero) => Some(Self::Zero),
-            Some(staged_ledger_diff::CoinBase::One(x)) => Some(Self::One(x.map(|cb| {
-                let staged_ledger_diff::CoinBaseFeeTransfer { receiver_pk, fee } =
-                    cb.inner().inner();
-                CoinbaseFeeTransfer {
-                    receiver_pk: PublicKey::from(receiver_pk),
-                    fee: fee.inner().inner(),
-                }
-            }))),
-            Some(staged_ledger_diff::CoinBase::Two(x, y)) => Some(Self::Two(
-                x.map(|c| c.inner().inner().into()),
-                y.map(|c| c.inner().inner().into()),
-            )),
-        };
+        }
+    }
+}
 
-        res.push(pre_diff_coinbase);
-        if let Some(post_diff_coinbase) = post_diff_coinbase {
-            res.push(post_diff_coinbase);
+impl From<v2::Coinbase> for CoinbaseKind {
+    fn from(value: v2::Coinbase) -> Self {
+        match value {
+            v2::Coinbase::Zero(_) => Self::Zero,
+            v2::Coinbase::One(_, x) => Self::One(x.map(Into::into)),
+            v2::Coinbase::Two(_, x, y) => Self::Two(x.map(Into::into), y.map(Into::into)),
+        }
+    }
+}
+
+impl CoinbaseKind {
+    pub fn from_precomputed(precomputed_block: &PrecomputedBlock) -> Vec<Self> {
+        match precomputed_block {
+            PrecomputedBlock::V1(v1) => {
+                let mut res = vec![v1.staged_ledger_pre_diff().coinbase.inner().into()];
+                if let Some(diff) = v1.staged_ledger_post_diff() {
+                    res.push(diff.coinbase.inner().into());
+                }
+                res
+            }
+            PrecomputedBlock::V2(v2) => v2
+                .staged_ledger_diff
+                .diff
+                .iter()
+                .flatten()
+                .map(|diff| diff.coinbase.clone().into())
+                .collect(),
         }
-        res
     }
 }
 
@@ -109,7 +118,7 @@ impl Coinbase {
             receiver: block.coinbase_receiver(),
             receiver_balance: block.coinbase_receiver_balance(),
             is_new_account: block.accounts_created().1.is_some(),
-            supercharge: block.consensus_state().supercharge_coinbase,
+            supercharge: block.supercharge_coinbase(),
         }
     }
 
@@ -125,11 +134,13 @@ impl Coinbase {
                             public_key: fee_transfer.receiver_pk.clone(),
                             amount: fee_transfer.fee.into(),
                             update_type: UpdateType::Credit,
+                            token: TokenId::default(),
                         },
                         PaymentDiff {
                             public_key: self.receiver.clone(),
                             amount: fee_transfer.fee.into(),
                             update_type: UpdateType::Debit(None),
+                            token: TokenId::default(),
                         },
                     ]
                 } else {
@@ -144,11 +155,13 @@ impl Coinbase {
                             public_key: t0.receiver_pk.clone(),
                             amount: t0.fee.into(),
                             update_type: UpdateType::Credit,
+                            token: TokenId::default(),
                         },
                         PaymentDiff {
                             public_key: self.receiver.clone(),
                             amount: t0.fee.into(),
                             update_type: UpdateType::Debit(None),
+                            token: TokenId::default(),
                         },
                     ]);
                 }
@@ -158,11 +171,13 @@ impl Coinbase {
                             public_key: t1.receiver_pk.clone(),
                             amount: t1.fee.into(),
                             update_type: UpdateType::Credit,
+                            token: TokenId::default(),
                         },
                         PaymentDiff {
                             public_key: self.receiver.clone(),
                             amount: t1.fee.into(),
                             update_type: UpdateType::Debit(None),
+                            token: TokenId::default(),
                         },
                     ]);
                 }
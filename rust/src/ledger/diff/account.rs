@@ -5,8 +5,10 @@ use crate::{
     ledger::{
         account::Nonce,
         coinbase::{Coinbase, CoinbaseKind},
+        token::TokenId,
         Amount, PublicKey,
     },
+    mina_blocks::v2::{staged_ledger_diff::command::ZkappCommand, AccountAccessed, ZkappAccount},
     protocol::serialization_types::staged_ledger_diff,
     snark_work::SnarkWorkSummary,
 };
@@ -25,6 +27,10 @@ pub struct PaymentDiff {
     pub update_type: UpdateType,
     pub public_key: PublicKey,
     pub amount: Amount,
+
+    /// Token of the debited/credited account, MINA unless the payment comes
+    /// from a zkApp account update
+    pub token: TokenId,
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Hash, Serialize, Deserialize)]
@@ -46,6 +52,19 @@ pub struct FailedTransactionNonceDiff {
     pub nonce: Nonce,
 }
 
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Hash, Serialize, Deserialize)]
+pub struct ZkappFeePayerNonceDiff {
+    pub public_key: PublicKey,
+    pub nonce: Nonce,
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Hash, Serialize, Deserialize)]
+pub struct ZkappStateDiff {
+    pub public_key: PublicKey,
+    pub token: TokenId,
+    pub zkapp: ZkappAccount,
+}
+
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Hash, Serialize, Deserialize)]
 pub enum AccountDiff {
     Payment(PaymentDiff),
@@ -57,6 +76,10 @@ pub enum AccountDiff {
     FeeTransferViaCoinbase(PaymentDiff),
     /// Updates the nonce for a failed txn
     FailedTransactionNonce(FailedTransactionNonceDiff),
+    /// Updates the fee payer nonce for an applied or failed zkApp txn
+    ZkappFeePayerNonce(ZkappFeePayerNonceDiff),
+    /// Sets the zkApp state of an account accessed by the block
+    ZkappState(Box<ZkappStateDiff>),
 }
 
 #[derive(PartialEq, Eq, Clone, Debug)]
@@ -78,11 +101,13 @@ impl AccountDiff {
                         public_key: payment.receiver.clone(),
                         amount: payment.amount,
                         update_type: UpdateType::Credit,
+                        token: TokenId::default(),
                     }),
                     Self::Payment(PaymentDiff {
                         public_key: payment.source,
                         amount: payment.amount,
                         update_type: UpdateType::Debit(Some(payment.nonce)),
+                        token: TokenId::default(),
                     }),
                 ];
                 if payment.is_new_receiver_account {
@@ -100,6 +125,57 @@ impl AccountDiff {
         }
     }
 
+    /// Fee payer nonce & balance changes of the zkApp command's account
+    /// updates, in any token. New MINA accounts pay the creation fee out of
+    /// their balance change if the update says so.
+    pub fn from_zkapp_command(command: &ZkappCommand, accounts_created: &[PublicKey]) -> Vec<Self> {
+        let mut res = vec![Self::ZkappFeePayerNonce(ZkappFeePayerNonceDiff {
+            public_key: command.fee_payer(),
+            nonce: Nonce(command.nonce()),
+        })];
+        if !command.is_applied() {
+            return res;
+        }
+
+        let mut creation_fees = vec![];
+        for update in command.account_updates() {
+            let token = TokenId::new(update.token_id.clone());
+            let balance_change = update.balance_change();
+            if balance_change != 0 {
+                res.push(Self::Payment(PaymentDiff {
+                    public_key: update.public_key.clone(),
+                    amount: (balance_change.unsigned_abs() as u64).into(),
+                    update_type: if balance_change > 0 {
+                        UpdateType::Credit
+                    } else {
+                        UpdateType::Debit(None)
+                    },
+                    token: token.clone(),
+                }));
+            }
+            if token.is_mina()
+                && update.implicit_account_creation_fee
+                && accounts_created.contains(&update.public_key)
+                && !creation_fees.contains(&update.public_key)
+            {
+                creation_fees.push(update.public_key);
+            }
+        }
+        res.extend(creation_fees.into_iter().map(Self::AccountCreationFee));
+        res
+    }
+
+    /// zkApp state of an account accessed by a block
+    pub fn from_account_accessed(account: AccountAccessed) -> Option<Self> {
+        account.zkapp.map(|zkapp| {
+            Self::ZkappState(Box::new(ZkappStateDiff {
+                public_key: account.public_key,
+                token: account.token_id.into(),
+                zkapp,
+            }))
+        })
+    }
+
     pub fn from_coinbase(coinbase: Coinbase) -> Vec<Self> {
         let mut res = vec![Self::Coinbase(CoinbaseDiff {
             public_key: coinbase.receiver.clone(),
@@ -125,25 +201,40 @@ impl AccountDiff {
             Self::FeeTransfer(fee_transfer_diff) => fee_transfer_diff.public_key.clone(),
             Self::FeeTransferViaCoinbase(fee_transfer_diff) => fee_transfer_diff.public_key.clone(),
             Self::FailedTransactionNonce(failed_diff) => failed_diff.public_key.clone(),
+            Self::ZkappFeePayerNonce(nonce_diff) => nonce_diff.public_key.clone(),
+            Self::ZkappState(state_diff) => state_diff.public_key.clone(),
         }
     }
 
-    fn transaction_fees(
-        coinbase_receiver: &PublicKey,
-        user_cmds: Vec<UserCommandWithStatus>,
-    ) -> Vec<Self> {
+    /// Token of the account, only payments & zkApp state can be non-MINA
+    pub fn token(&self) -> TokenId {
+        match self {
+            Self::Payment(payment_diff) => payment_diff.token.clone(),
+            Self::ZkappState(state_diff) => state_diff.token.clone(),
+            Self::AccountCreationFee(_)
+            | Self::Delegation(_)
+            | Self::Coinbase(_)
+            | Self::FeeTransfer(_)
+            | Self::FeeTransferViaCoinbase(_)
+            | Self::FailedTransactionNonce(_)
+            | Self::ZkappFeePayerNonce(_) => TokenId::default(),
+        }
+    }
+
+    /// Ledger identifier of the account
+    pub fn account_id(&self) -> (PublicKey, TokenId) {
+        (self.public_key(), self.token())
+    }
+
+    fn transaction_fees(coinbase_receiver: &PublicKey, fees: Vec<(PublicKey, u64)>) -> Vec<Self> {
         let mut fee_map = HashMap::new();
-        user_cmds.iter().for_each(|user_cmd| {
-            let signed_cmd = SignedCommand::from_user_command(user_cmd.clone());
-            let fee_payer = signed_cmd.fee_payer_pk();
-            let fee = signed_cmd.fee();
-            match fee_map.get_mut(&fee_payer) {
+        fees.into_iter()
+            .for_each(|(fee_payer, fee)| match fee_map.get_mut(&fee_payer) {
                 None => {
-                    fee_map.insert(fee_payer.clone(), fee);
+                    fee_map.insert(fee_payer, fee);
                 }
                 Some(acc) => *acc += fee,
-            }
-        });
+            });
         fee_map
             .iter()
             .flat_map(|(pk, fee)| {
@@ -153,11 +244,13 @@ impl AccountDiff {
                         public_key: coinbase_receiver.clone(),
                         amount: (*fee).into(),
                         update_type: UpdateType::Credit,
+                        token: TokenId::default(),
                     }));
                     res.push(Self::FeeTransfer(PaymentDiff {
                         public_key: pk.clone(),
                         amount: (*fee).into(),
                         update_type: UpdateType::Debit(None),
+                        token: TokenId::default(),
                     }));
                 }
                 res
@@ -165,14 +258,35 @@ impl AccountDiff {
             .collect()
     }
 
-    /// Fees for user commands, applied or failed, aggregated per public key
+    fn user_command_fees(user_cmds: Vec<UserCommandWithStatus>) -> Vec<(PublicKey, u64)> {
+        user_cmds
+            .into_iter()
+            .map(|user_cmd| {
+                let signed_cmd = SignedCommand::from_user_command(user_cmd);
+                (signed_cmd.fee_payer_pk(), signed_cmd.fee())
+            })
+            .collect()
+    }
+
+    /// Fees for user & zkApp commands, applied or failed, aggregated per
+    /// public key
     pub fn from_transaction_fees(precomputed_block: &PrecomputedBlock) -> Vec<Self> {
         let coinbase_receiver = &precomputed_block.coinbase_receiver();
-        let mut fees =
-            Self::transaction_fees(coinbase_receiver, precomputed_block.commands_pre_diff());
+        let mut fees = Self::transaction_fees(
+            coinbase_receiver,
+            Self::user_command_fees(precomputed_block.commands_pre_diff()),
+        );
         fees.append(&mut Self::transaction_fees(
             coinbase_receiver,
-            precomputed_block.commands_post_diff(),
+            Self::user_command_fees(precomputed_block.commands_post_diff()),
+        ));
+        fees.append(&mut Self::transaction_fees(
+            coinbase_receiver,
+            precomputed_block
+                .zkapp_commands()
+                .iter()
+                .map(|zkapp| (zkapp.fee_payer(), zkapp.fee()))
+                .collect(),
         ));
         fees
     }
@@ -198,11 +312,13 @@ impl AccountDiff {
                         public_key: prover.clone(),
                         amount: (*total_fee).into(),
                         update_type: UpdateType::Credit,
+                        token: TokenId::default(),
                     }));
                     res.push(AccountDiff::FeeTransfer(PaymentDiff {
                         public_key: precomputed_block.coinbase_receiver(),
                         amount: (*total_fee).into(),
                         update_type: UpdateType::Debit(None),
+                        token: TokenId::default(),
                     }));
                     let coinbase = Coinbase::from_precomputed(precomputed_block);
                     if let CoinbaseKind::One(Some(coinbase_fee_transfer)) = coinbase.kind {
@@ -224,6 +340,7 @@ impl AccountDiff {
                                                     public_key: prover.clone(),
                                                     amount: MAINNET_ACCOUNT_CREATION_FEE,
                                                     update_type: UpdateType::Debit(None),
+                                                    token: TokenId::default(),
                                                 }));
                                             }
                                         }
@@ -260,11 +377,13 @@ impl AccountDiff {
                     public_key: receiver.into(),
                     amount: amount.into(),
                     update_type: UpdateType::Credit,
+                    token: TokenId::default(),
                 }),
                 Self::Payment(PaymentDiff {
                     public_key: sender.into(),
                     amount: amount.into(),
                     update_type: UpdateType::Debit(Some(nonce)),
+                    token: TokenId::default(),
                 }),
             ],
             AccountDiffType::AccountCreationFee => vec![Self::AccountCreationFee(receiver.into())],
@@ -282,11 +401,13 @@ impl AccountDiff {
                     public_key: receiver.into(),
                     amount: amount.into(),
                     update_type: UpdateType::Credit,
+                    token: TokenId::default(),
                 }),
                 Self::FeeTransfer(PaymentDiff {
                     public_key: sender.into(),
                     amount: amount.into(),
                     update_type: UpdateType::Debit(None),
+                    token: TokenId::default(),
                 }),
             ],
             AccountDiffType::FeeTransferViaCoinbase => vec![
@@ -294,11 +415,13 @@ impl AccountDiff {
                     public_key: receiver.into(),
                     amount: amount.into(),
                     update_type: UpdateType::Credit,
+                    token: TokenId::default(),
                 }),
                 Self::FeeTransferViaCoinbase(PaymentDiff {
                     public_key: sender.into(),
                     amount: amount.into(),
                     update_type: UpdateType::Debit(None),
+                    token: TokenId::default(),
                 }),
             ],
         }
@@ -315,13 +438,18 @@ impl PaymentDiff {
                 update_type: UpdateType::Credit,
                 public_key: cb_diff.public_key,
                 amount: cb_diff.amount,
+                token: TokenId::default(),
             }),
             AccountDiff::AccountCreationFee(pk) => Some(Self {
                 update_type: UpdateType::Debit(None),
                 public_key: pk.clone(),
                 amount: MAINNET_ACCOUNT_CREATION_FEE,
+                token: TokenId::default(),
             }),
-            AccountDiff::Delegation(_) | AccountDiff::FailedTransactionNonce(_) => None,
+            AccountDiff::Delegation(_)
+            | AccountDiff::FailedTransactionNonce(_)
+            | AccountDiff::ZkappFeePayerNonce(_)
+            | AccountDiff::ZkappState(_) => None,
         }
     }
 }
@@ -332,7 +460,11 @@ impl std::fmt::Debug for PaymentDiff {
             f,
             "{} | {:?} | {}",
             self.public_key, self.update_type, self.amount.0
-        )
+        )?;
+        if !self.token.is_mina() {
+            write!(f, " | {}", self.token)?;
+        }
+        Ok(())
     }
 }
 
@@ -358,6 +490,22 @@ impl std::fmt::Debug for FailedTransactionNonceDiff {
     }
 }
 
+impl std::fmt::Debug for ZkappFeePayerNonceDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} | Nonce {}", self.public_key, self.nonce)
+    }
+}
+
+impl std::fmt::Debug for ZkappStateDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} | App state {:?} | Proved {}",
+            self.public_key, self.zkapp.app_state, self.zkapp.proved_state
+        )
+    }
+}
+
 impl std::fmt::Debug for AccountDiff {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -372,6 +520,10 @@ impl std::fmt::Debug for AccountDiff {
             AccountDiff::FailedTransactionNonce(failed_diff) => {
                 write!(f, "Failed transaction: {failed_diff:?}")
             }
+            AccountDiff::ZkappFeePayerNonce(nonce_diff) => {
+                write!(f, "zkApp fee payer: {nonce_diff:?}")
+            }
+            AccountDiff::ZkappState(state_diff) => write!(f, "zkApp state:  {state_diff:?}"),
         }
     }
 }
@@ -400,6 +552,7 @@ mod tests {
             account::{Amount, Nonce},
             coinbase::{Coinbase, CoinbaseFeeTransfer, CoinbaseKind},
             diff::LedgerDiff,
+            token::TokenId,
             PublicKey,
         },
     };
@@ -429,11 +582,13 @@ mod tests {
                 public_key: snarker,
                 amount: fee.into(),
                 update_type: UpdateType::Credit,
+                token: TokenId::default(),
             }),
             AccountDiff::FeeTransferViaCoinbase(PaymentDiff {
                 public_key: receiver,
                 amount: fee.into(),
                 update_type: UpdateType::Debit(None),
+                token: TokenId::default(),
             }),
         ];
 
@@ -461,11 +616,13 @@ mod tests {
                 public_key: receiver_public_key.clone(),
                 amount: 536900000000.into(),
                 update_type: UpdateType::Credit,
+                token: TokenId::default(),
             }),
             AccountDiff::Payment(PaymentDiff {
                 public_key: source_public_key,
                 amount: 536900000000.into(),
                 update_type: UpdateType::Debit(Some(nonce)),
+                token: TokenId::default(),
             }),
             AccountDiff::AccountCreationFee(receiver_public_key),
         ];
@@ -519,6 +676,7 @@ mod tests {
             public_key: PublicKey::new("B62qqmveaSLtpcfNeaF9KsEvLyjsoKvnfaHy4LHyApihPVzR3qDNNEG"),
             amount: 536900000000.into(),
             update_type: UpdateType::Debit(Some(nonce)),
+            token: TokenId::default(),
         };
         let account_diff = AccountDiff::Payment(payment_diff);
         let result = account_diff.public_key();
@@ -584,6 +742,7 @@ mod tests {
             update_type: UpdateType::Debit(None),
             public_key: "B62qqsMmiJPjodmXxZuvXpEYRv4sBQLFDz1aHYesVmybTqyfZzWnd2n".into(),
             amount: MAINNET_ACCOUNT_CREATION_FEE,
+            token: TokenId::default(),
         }));
 
         for (i, ac) in actual_diffs.iter().enumerate() {
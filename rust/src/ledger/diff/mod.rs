@@ -68,6 +68,14 @@ impl LedgerDiff {
                 .collect(),
         );
 
+        // zkApp commands
+        for (zkapp, accounts_created) in precomputed_block.zkapp_commands_with_accounts_created() {
+            account_diff_txns.append(&mut AccountDiff::from_zkapp_command(
+                &zkapp,
+                &accounts_created,
+            ));
+        }
+
         // replace fee_transfer with fee_transfer_via_coinbase, if any
         let coinbase = Coinbase::from_precomputed(precomputed_block);
         if coinbase.has_fee_transfer() {
@@ -100,6 +108,24 @@ impl LedgerDiff {
         }
         account_diffs.append(&mut account_diff_fees);
 
+        // accounts created by fee transfers pay the creation fee
+        account_diffs.append(
+            &mut precomputed_block
+                .fee_transfer_accounts_created()
+                .into_iter()
+                .map(AccountDiff::AccountCreationFee)
+                .collect(),
+        );
+
+        // post-block zkApp state of the accessed accounts
+        account_diffs.append(
+            &mut precomputed_block
+                .accounts_accessed()
+                .into_iter()
+                .filter_map(AccountDiff::from_account_accessed)
+                .collect(),
+        );
+
         let accounts_created = precomputed_block.accounts_created();
         LedgerDiff {
             account_diffs,
@@ -1143,4 +1169,66 @@ mod tests {
         assert_eq!(ledger_diff.account_diffs, expected);
         Ok(())
     }
+
+    #[test]
+    fn zkapp_state_from_precomputed() -> anyhow::Result<()> {
+        use crate::ledger::{
+            account::Account, diff::account::AccountDiff, public_key::PublicKey, Ledger,
+        };
+
+        let path = PathBuf::from("./tests/data/berkeley/non_sequential_blocks/berkeley-4969-3NL8QoLQMtsBH8vUnccQw3vt8PgYuZRMApq1yZT1jwhZjbBLMRJU.json");
+        let block = PrecomputedBlock::parse_file(&path, PcbVersion::V2)?;
+        let ledger_diff = LedgerDiff::from_precomputed(&block);
+
+        // only the zkApp account has zkApp state
+        let zkapp_pk: PublicKey = "B62qqpAFkz374qJpuFKYZPjT1KxSmnLoY4zEc878FaW4DSxgYNXZiny".into();
+        let zkapp_diffs: Vec<&AccountDiff> = ledger_diff
+            .account_diffs
+            .iter()
+            .filter(|diff| matches!(diff, AccountDiff::ZkappState(_)))
+            .collect();
+        assert_eq!(zkapp_diffs.len(), 1);
+        assert_eq!(zkapp_diffs[0].public_key(), zkapp_pk);
+
+        // applying the diff sets the account's zkApp state
+        let mut ledger = Ledger::new();
+        for pk in ledger_diff
+            .account_diffs
+            .iter()
+            .map(|diff| diff.public_key())
+        {
+            ledger.insert_account(Account {
+                balance: 1_000_000_000_000.into(),
+                ..Account::empty(pk)
+            });
+        }
+        let ledger = ledger.apply_diff(&ledger_diff)?;
+        let zkapp = ledger
+            .get_mina_account(&zkapp_pk)
+            .and_then(|account| account.zkapp.clone())
+            .expect("zkApp account");
+
+        assert_eq!(zkapp.zkapp_uri, "https://idmask.xyz");
+        assert!(!zkapp.proved_state);
+        assert_eq!(
+            zkapp.app_state,
+            [
+                "0x0000000000000000000000000000000000000000000000000000000000000000".to_string(),
+                "0x0000000000000000000000000000000000000000000000000000000000000000".to_string(),
+                "0x0000000000000000000000000000000000000000000000000000000000000000".to_string(),
+                "0x0000000000000000000000000000000000000000000000000000000000000000".to_string(),
+                "0x0000000000000000000000000000000000000000000000000000000000000000".to_string(),
+                "0x0000000000000000000000000000000000000000000000000000000000000000".to_string(),
+                "0x0000000000000000000000000000000000000000000000000000000000000000".to_string(),
+                "0x0000000000000000000000000000000000000000000000000000000000000000".to_string(),
+            ]
+        );
+        assert!(zkapp.verification_key.is_some());
+        assert!(ledger
+            .accounts
+            .values()
+            .filter(|account| account.public_key != zkapp_pk)
+            .all(|account| account.zkapp.is_none()));
+        Ok(())
+    }
 }
@@ -1,13 +1,21 @@
 use super::{
     account::{Account, Amount, Nonce, Permissions, ReceiptChainHash, Timing, TokenPermissions},
     public_key::PublicKey,
-    Ledger,
+    token::TokenId,
+    Ledger, LedgerHash,
 };
-use crate::{block::genesis::GenesisBlock, constants::*, mina_blocks::v2::ZkappAccount};
-use anyhow::anyhow;
+use crate::{
+    block::{genesis::GenesisBlock, precomputed::PrecomputedBlock, BlockHash},
+    constants::*,
+    mina_blocks::{
+        common::{from_str, from_str_opt},
+        v2::ZkappAccount,
+    },
+};
+use anyhow::{anyhow, bail, Context};
 use rust_decimal::{prelude::ToPrimitive, Decimal};
 use rust_decimal_macros::dec;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
 use std::{collections::HashMap, path::Path};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,20 +27,56 @@ pub struct GenesisTimestamp {
 pub struct GenesisRoot {
     pub genesis: GenesisTimestamp,
     pub ledger: GenesisAccounts,
+
+    /// Present in hardfork runtime configs
+    #[serde(default)]
+    pub proof: Option<GenesisProof>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenesisProof {
+    pub fork: Option<ForkConfig>,
+}
+
+/// The pre-fork block the hardfork genesis block extends
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForkConfig {
+    pub state_hash: BlockHash,
+
+    #[serde(deserialize_with = "from_str")]
+    pub blockchain_length: u32,
+
+    #[serde(deserialize_with = "from_str")]
+    pub global_slot_since_genesis: u32,
 }
 
+/// V1 & V2 genesis ledger accounts, V2-only fields are ignored when they do
+/// not fit the V1 account representation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GenesisAccount {
     pub pk: String,
     pub balance: String,
+
+    #[serde(default, deserialize_with = "from_str_opt")]
     pub nonce: Option<Nonce>,
+
     pub delegate: Option<String>,
-    pub token: Option<u64>,
+
+    #[serde(default, deserialize_with = "from_str_opt")]
+    pub token: Option<TokenId>,
+
+    #[serde(default, deserialize_with = "ok_or_none")]
     pub token_permissions: Option<TokenPermissions>,
+
     pub receipt_chain_hash: Option<ReceiptChainHash>,
     pub voting_for: Option<String>,
+
+    #[serde(default, deserialize_with = "ok_or_none")]
     pub permissions: Option<Permissions>,
+
     pub timing: Option<GenesisAccountTiming>,
+
+    #[serde(default, deserialize_with = "ok_or_none")]
     pub zkapp: Option<ZkappAccount>,
 }
 
@@ -47,7 +91,15 @@ pub struct GenesisAccountTiming {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GenesisAccounts {
+    #[serde(default)]
     pub name: String,
+
+    /// Ledger hash, hardfork configs only
+    pub hash: Option<LedgerHash>,
+
+    /// Whether the genesis block winner is added to the ledger (default true)
+    pub add_genesis_winner: Option<bool>,
+
     pub accounts: Vec<GenesisAccount>,
 }
 
@@ -56,6 +108,15 @@ pub struct GenesisLedger {
     ledger: Ledger,
 }
 
+/// Hardfork genesis ledger & the pre-fork block it continues from
+#[derive(Debug, Clone)]
+pub struct HardforkGenesis {
+    pub fork: ForkConfig,
+    pub genesis_timestamp: String,
+    pub ledger_hash: Option<LedgerHash>,
+    pub ledger: GenesisLedger,
+}
+
 impl std::str::FromStr for GenesisRoot {
     type Err = anyhow::Error;
 
@@ -119,6 +180,48 @@ impl From<GenesisRoot> for GenesisLedger {
     }
 }
 
+impl TryFrom<GenesisRoot> for HardforkGenesis {
+    type Error = anyhow::Error;
+
+    fn try_from(value: GenesisRoot) -> anyhow::Result<Self> {
+        let Some(fork) = value.proof.and_then(|proof| proof.fork) else {
+            bail!("Missing fork config (proof.fork) in hardfork genesis")
+        };
+        Ok(Self {
+            fork,
+            genesis_timestamp: value.genesis.genesis_state_timestamp,
+            ledger_hash: value.ledger.hash.clone(),
+            ledger: GenesisLedger::new(value.ledger),
+        })
+    }
+}
+
+impl HardforkGenesis {
+    /// Whether `block` is the hardfork genesis block, i.e. the first post-fork
+    /// block which extends the fork block
+    pub fn is_fork_genesis(&self, block: &PrecomputedBlock) -> bool {
+        block.previous_state_hash() == self.fork.state_hash
+            && block.blockchain_length() == self.fork.blockchain_length + 1
+    }
+}
+
+impl ForkConfig {
+    /// Mainnet fork block, used to pick the block parser version when no
+    /// hardfork config is supplied
+    pub fn mainnet() -> Self {
+        Self {
+            state_hash: HARDFORK_GENESIS_PREV_STATE_HASH.into(),
+            blockchain_length: HARDFORK_GENESIS_BLOCKCHAIN_LENGTH - 1,
+            global_slot_since_genesis: HARDFORK_GENESIS_GLOBAL_SLOT,
+        }
+    }
+
+    /// Blocks above the fork block are post-hardfork
+    pub fn is_post_fork(&self, blockchain_length: u32) -> bool {
+        blockchain_length > self.blockchain_length
+    }
+}
+
 impl From<GenesisLedger> for Ledger {
     fn from(value: GenesisLedger) -> Self {
         value.ledger
@@ -133,9 +236,10 @@ impl GenesisLedger {
     pub fn new(genesis: GenesisAccounts) -> GenesisLedger {
         let mut accounts = HashMap::new();
         // Add genesis block winner
-        let block_creator = Account::from(GenesisBlock::new().unwrap());
-        let pk = block_creator.public_key.clone();
-        accounts.insert(pk, block_creator);
+        if genesis.add_genesis_winner.unwrap_or(true) {
+            let block_creator = Account::from(GenesisBlock::new().unwrap());
+            accounts.insert(block_creator.id(), block_creator);
+        }
         for genesis_account in genesis.accounts {
             let balance = Amount(match str::parse::<Decimal>(&genesis_account.balance) {
                 Ok(amt) => (amt * dec!(1_000_000_000))
@@ -144,19 +248,20 @@ impl GenesisLedger {
                 Err(_) => panic!("Unable to parse Genesis Balance"),
             });
             let public_key = PublicKey::from(genesis_account.pk);
+            let token = genesis_account.token.unwrap_or_default();
             accounts.insert(
-                public_key.clone(),
+                (public_key.clone(), token.clone()),
                 Account {
                     balance,
+                    token,
                     username: None,
-                    nonce: Nonce::default(),
+                    nonce: genesis_account.nonce.unwrap_or_default(),
                     public_key: public_key.clone(),
                     // If delegate is None, delegate to yourself
                     delegate: genesis_account
                         .delegate
                         .map(PublicKey)
                         .unwrap_or(public_key),
-                    token: genesis_account.token,
                     token_permissions: genesis_account.token_permissions,
                     receipt_chain_hash: genesis_account.receipt_chain_hash,
                     voting_for: genesis_account.voting_for.map(|v| v.into()),
@@ -178,6 +283,24 @@ pub fn parse_file<P: AsRef<Path>>(path: P) -> anyhow::Result<GenesisRoot> {
     Ok(serde_json::from_slice(&data)?)
 }
 
+/// Parses a hardfork runtime config, i.e. a genesis ledger with a fork config
+pub fn parse_hardfork_file<P: AsRef<Path>>(path: P) -> anyhow::Result<HardforkGenesis> {
+    let path = path.as_ref();
+    parse_file(path)
+        .and_then(HardforkGenesis::try_from)
+        .with_context(|| format!("Error parsing hardfork config {}", path.display()))
+}
+
+/// Deserializes to `None` when the value doesn't match `T`
+fn ok_or_none<'de, D, T>(de: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    Ok(<Option<serde_json::Value>>::deserialize(de)?
+        .and_then(|value| serde_json::from_value(value).ok()))
+}
+
 impl From<GenesisAccountTiming> for Timing {
     fn from(value: GenesisAccountTiming) -> Self {
         Self {
@@ -207,8 +330,8 @@ impl From<GenesisAccountTiming> for Timing {
 
 #[cfg(test)]
 mod tests {
-    use super::{GenesisConstants, GenesisLedger, GenesisRoot};
-    use crate::ledger::public_key::PublicKey;
+    use super::{parse_hardfork_file, GenesisConstants, GenesisLedger, GenesisRoot};
+    use crate::ledger::{account::Nonce, public_key::PublicKey, token::TokenId, Ledger};
     use std::path::PathBuf;
 
     #[test]
@@ -234,9 +357,9 @@ mod tests {
         assert_eq!(None, root.ledger.accounts.first().unwrap().delegate);
 
         let ledger = GenesisLedger::new(root.ledger);
-        let map = ledger.ledger.accounts;
-        let value = map
-            .get(&PublicKey::from(
+        let value = ledger
+            .ledger
+            .get_mina_account(&PublicKey::from(
                 "B62qqdcf6K9HyBSaxqH5JVFJkc1SUEe1VzDc5kYZFQZXWSQyGHoino1",
             ))
             .unwrap();
@@ -254,6 +377,46 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn parse_hardfork_config() -> anyhow::Result<()> {
+        let hardfork = parse_hardfork_file("./tests/data/hardfork/config.json")?;
+        assert_eq!(
+            hardfork.fork.state_hash.0,
+            "3NKZ6DTHiMtuaeP3tJq2xe4uujVRnGT9FX1rBiZY521uNToSppUZ"
+        );
+        assert_eq!(hardfork.fork.blockchain_length, 21);
+        assert_eq!(hardfork.fork.global_slot_since_genesis, 24);
+        assert!(hardfork.fork.is_post_fork(22));
+        assert!(!hardfork.fork.is_post_fork(21));
+
+        // no genesis block winner, V2 account fields
+        let ledger: Ledger = hardfork.ledger.into();
+        assert_eq!(ledger.accounts.len(), 3);
+
+        let pk = PublicKey::from("B62qmqMrgPshhHKLJ7DqWn1KeizEgga5MuGmWb2bXajUnyivfeMW6JE");
+        let account = ledger.get_mina_account(&pk).unwrap();
+        assert_eq!(account.token, TokenId::default());
+        assert_eq!(account.nonce, Nonce(3));
+        assert_eq!(account.balance.0, 372000 * 1_000_000_000);
+        assert_eq!(account.permissions, None);
+
+        // V1 genesis ledgers are not hardfork configs
+        let root: GenesisRoot = GenesisLedger::MAINNET_V1_GENESIS_LEDGER_CONTENTS.parse()?;
+        assert!(super::HardforkGenesis::try_from(root).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn mainnet_fork_config() {
+        let fork = super::ForkConfig::mainnet();
+        assert_eq!(
+            fork.state_hash.0,
+            "3NLRTfY4kZyJtvaP4dFenDcxfoMfT3uEpkWS913KkeXLtziyVd15"
+        );
+        assert!(!fork.is_post_fork(359604));
+        assert!(fork.is_post_fork(359605));
+    }
+
     #[test]
     fn override_genesis_constants() -> anyhow::Result<()> {
         let mut none_constants = GenesisConstants::default();
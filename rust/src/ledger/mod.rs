@@ -5,6 +5,7 @@ pub mod genesis;
 pub mod public_key;
 pub mod staking;
 pub mod store;
+pub mod token;
 pub mod username;
 
 use crate::{
@@ -17,6 +18,7 @@ use crate::{
             LedgerDiff,
         },
         public_key::PublicKey,
+        token::TokenId,
     },
     protocol::serialization_types::{
         common::{Base58EncodableVersionedType, HashV1},
@@ -25,12 +27,28 @@ use crate::{
 };
 use anyhow::bail;
 use log::debug;
-use serde::{Deserialize, Serialize};
+use serde::{
+    de::{DeserializeSeed, IgnoredAny, MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
 use std::{collections::HashMap, str::FromStr};
 
 #[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(from = "LedgerJson", into = "LedgerJson")]
 pub struct Ledger {
-    pub accounts: HashMap<PublicKey, Account>,
+    /// Accounts identified by public key & token
+    pub accounts: HashMap<(PublicKey, TokenId), Account>,
+}
+
+/// Serialized ledger: MINA accounts keyed by address, custom token accounts
+/// keyed by token & address
+#[derive(Default, Serialize, Deserialize)]
+struct LedgerJson {
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    tokens: HashMap<TokenId, HashMap<String, Account>>,
+
+    #[serde(flatten)]
+    accounts: HashMap<String, Account>,
 }
 
 #[allow(clippy::len_without_is_empty)]
@@ -102,6 +120,32 @@ impl Ledger {
         }
     }
 
+    /// Get the account of the given public key & token
+    pub fn get_account(&self, pk: &PublicKey, token: &TokenId) -> Option<&Account> {
+        self.accounts.get(&(pk.clone(), token.clone()))
+    }
+
+    /// Get the MINA account of the given public key
+    pub fn get_mina_account(&self, pk: &PublicKey) -> Option<&Account> {
+        self.get_account(pk, &TokenId::default())
+    }
+
+    pub fn get_mut_account(&mut self, pk: &PublicKey, token: &TokenId) -> Option<&mut Account> {
+        self.accounts.get_mut(&(pk.clone(), token.clone()))
+    }
+
+    /// Insert the account under its `(public key, token)` identifier
+    pub fn insert_account(&mut self, account: Account) -> Option<Account> {
+        self.accounts.insert(account.id(), account)
+    }
+
+    /// Accounts of the given token
+    pub fn token_accounts<'a>(&'a self, token: &'a TokenId) -> impl Iterator<Item = &'a Account> {
+        self.accounts
+            .iter()
+            .filter_map(move |((_, t), acct)| (t == token).then_some(acct))
+    }
+
     pub fn apply_diff_from_precomputed(self, block: &PrecomputedBlock) -> anyhow::Result<Self> {
         let diff = LedgerDiff::from_precomputed(block);
         self.apply_diff(&diff)
@@ -123,24 +167,23 @@ impl Ledger {
     /// Apply a ledger diff to a mutable ledger
     pub fn _apply_diff(&mut self, diff: &LedgerDiff) -> anyhow::Result<()> {
         let ledger_diff = diff.clone();
-        let keys: Vec<PublicKey> = ledger_diff
+        let keys: Vec<(PublicKey, TokenId)> = ledger_diff
             .account_diffs
             .iter()
-            .map(|diff| diff.public_key())
+            .map(|diff| diff.account_id())
             .collect();
 
-        keys.into_iter().for_each(|public_key| {
-            if self.accounts.get(&public_key).is_none() {
-                self.accounts
-                    .insert(public_key.clone(), Account::empty(public_key));
+        keys.into_iter().for_each(|(public_key, token)| {
+            if self.get_account(&public_key, &token).is_none() {
+                self.insert_account(Account::empty_token(public_key, token));
             }
         });
 
         for diff in ledger_diff.account_diffs {
-            match self.accounts.remove(&diff.public_key()) {
+            match self.accounts.remove(&diff.account_id()) {
                 Some(account_before) => {
                     self.accounts.insert(
-                        diff.public_key(),
+                        diff.account_id(),
                         match &diff {
                             AccountDiff::Payment(payment_diff) => {
                                 Account::from_payment(account_before, payment_diff)
@@ -168,6 +211,14 @@ impl Ledger {
                             AccountDiff::FailedTransactionNonce(failed_diff) => {
                                 Account::from_failed_transaction(account_before, failed_diff.nonce)
                             }
+                            AccountDiff::ZkappFeePayerNonce(nonce_diff) => Account {
+                                nonce: nonce_diff.nonce,
+                                ..account_before
+                            },
+                            AccountDiff::ZkappState(state_diff) => Account {
+                                zkapp: Some(state_diff.zkapp.clone()),
+                                ..account_before
+                            },
                         },
                     );
                 }
@@ -179,9 +230,9 @@ impl Ledger {
                         | AccountDiff::AccountCreationFee(_)
                         | AccountDiff::FeeTransfer(_)
                         | AccountDiff::FeeTransferViaCoinbase(_)
-                        | AccountDiff::FailedTransactionNonce(_) => {
-                            Err(LedgerError::AccountNotFound.into())
-                        }
+                        | AccountDiff::FailedTransactionNonce(_)
+                        | AccountDiff::ZkappFeePayerNonce(_)
+                        | AccountDiff::ZkappState(_) => Err(LedgerError::AccountNotFound.into()),
                     };
                 }
             }
@@ -192,8 +243,7 @@ impl Ledger {
 
     pub fn time_locked_amount(&self, curr_global_slot: u32) -> Amount {
         Amount(
-            self.accounts
-                .values()
+            self.token_accounts(&TokenId::default())
                 .filter_map(|acct| {
                     acct.timing
                         .as_ref()
@@ -208,41 +258,146 @@ impl Ledger {
         for (pubkey, balance, nonce, delgation) in value {
             let pk = PublicKey::new(pubkey);
             let delegate = delgation.map(PublicKey::new).unwrap_or(pk.clone());
-            ledger.accounts.insert(
-                pk.clone(),
-                Account {
-                    delegate,
-                    public_key: pk,
-                    balance: balance.into(),
-                    nonce: Nonce(nonce.unwrap_or_default()),
-                    ..Default::default()
-                },
-            );
+            ledger.insert_account(Account {
+                delegate,
+                public_key: pk,
+                balance: balance.into(),
+                nonce: Nonce(nonce.unwrap_or_default()),
+                ..Default::default()
+            });
         }
         Ok(ledger)
     }
 
     pub fn to_string_pretty(&self) -> String {
-        let mut accounts = HashMap::new();
-        for (pk, acct) in &self.accounts {
-            accounts.insert(pk.to_address(), acct.clone());
-        }
-
-        serde_json::to_string_pretty(&accounts).unwrap()
+        serde_json::to_string_pretty(self).unwrap()
     }
 
     pub fn from_bytes(bytes: Vec<u8>) -> anyhow::Result<Self> {
         Self::from_str(&String::from_utf8(bytes.to_vec())?)
     }
+
+    /// Deserialize a single account from a serialized ledger, all other
+    /// accounts are skipped
+    pub fn account_from_bytes(
+        bytes: &[u8],
+        pk: &PublicKey,
+        token: &TokenId,
+    ) -> anyhow::Result<Option<Account>> {
+        let mut de = serde_json::Deserializer::from_slice(bytes);
+        Ok(LedgerAccountSeed { pk, token }.deserialize(&mut de)?)
+    }
+}
+
+/// Picks the `(pk, token)` account out of a serialized [LedgerJson]
+struct LedgerAccountSeed<'a> {
+    pk: &'a PublicKey,
+    token: &'a TokenId,
+}
+
+/// Picks the `pk` account out of a map of accounts keyed by address
+struct AccountSeed<'a> {
+    pk: &'a PublicKey,
+}
+
+/// Picks the `(pk, token)` account out of the token accounts map
+struct TokenAccountSeed<'a> {
+    pk: &'a PublicKey,
+    token: &'a TokenId,
+}
+
+impl<'de> DeserializeSeed<'de> for LedgerAccountSeed<'_> {
+    type Value = Option<Account>;
+
+    fn deserialize<D: Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        de.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for LedgerAccountSeed<'_> {
+    type Value = Option<Account>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "a ledger")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut account = None;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "tokens" && !self.token.is_mina() {
+                account = map.next_value_seed(TokenAccountSeed {
+                    pk: self.pk,
+                    token: self.token,
+                })?;
+            } else if key == self.pk.0 && self.token.is_mina() {
+                account = Some(map.next_value()?);
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(account)
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for AccountSeed<'_> {
+    type Value = Option<Account>;
+
+    fn deserialize<D: Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        de.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for AccountSeed<'_> {
+    type Value = Option<Account>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "a map of accounts")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut account = None;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == self.pk.0 {
+                account = Some(map.next_value()?);
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(account)
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for TokenAccountSeed<'_> {
+    type Value = Option<Account>;
+
+    fn deserialize<D: Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        de.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for TokenAccountSeed<'_> {
+    type Value = Option<Account>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "a map of token accounts")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut account = None;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == self.token.0 {
+                account = map.next_value_seed(AccountSeed { pk: self.pk })?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(account)
+    }
 }
 
 impl ToString for Ledger {
     fn to_string(&self) -> String {
-        let mut accounts = HashMap::new();
-        for (pk, acct) in &self.accounts {
-            accounts.insert(pk.to_address(), acct.clone());
-        }
-        serde_json::to_string(&accounts).unwrap()
+        serde_json::to_string(self).unwrap()
     }
 }
 
@@ -250,12 +405,39 @@ impl FromStr for Ledger {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let deser: HashMap<String, Account> = serde_json::from_str(s)?;
+        Ok(serde_json::from_str(s)?)
+    }
+}
+
+impl From<LedgerJson> for Ledger {
+    fn from(value: LedgerJson) -> Self {
         let mut accounts = HashMap::new();
-        for (pk, acct) in deser {
-            accounts.insert(PublicKey(pk), acct);
+        for (pk, acct) in value.accounts {
+            accounts.insert((PublicKey(pk), TokenId::default()), acct);
+        }
+        for (token, token_accounts) in value.tokens {
+            for (pk, acct) in token_accounts {
+                accounts.insert((PublicKey(pk), token.clone()), acct);
+            }
         }
-        Ok(Ledger { accounts })
+        Ledger { accounts }
+    }
+}
+
+impl From<Ledger> for LedgerJson {
+    fn from(value: Ledger) -> Self {
+        let mut json = LedgerJson::default();
+        for ((pk, token), acct) in value.accounts {
+            if token.is_mina() {
+                json.accounts.insert(pk.to_address(), acct);
+            } else {
+                json.tokens
+                    .entry(token)
+                    .or_default()
+                    .insert(pk.to_address(), acct);
+            }
+        }
+        json
     }
 }
 
@@ -289,8 +471,12 @@ impl Eq for Ledger {}
 
 impl std::fmt::Debug for Ledger {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for (pk, acct) in &self.accounts {
-            writeln!(f, "{} -> {}", pk.to_address(), acct.balance.0)?;
+        for ((pk, token), acct) in &self.accounts {
+            if token.is_mina() {
+                writeln!(f, "{} -> {}", pk.to_address(), acct.balance.0)?;
+            } else {
+                writeln!(f, "{} ({token}) -> {}", pk.to_address(), acct.balance.0)?;
+            }
         }
         writeln!(f)?;
         Ok(())
@@ -345,6 +531,7 @@ mod tests {
         },
         is_valid_ledger_hash,
         public_key::PublicKey,
+        token::TokenId,
         Ledger, LedgerHash,
     };
     use crate::{block::BlockHash, ledger::account::Nonce};
@@ -361,7 +548,7 @@ mod tests {
         let public_key = PublicKey::new("B62qre3erTHfzQckNuibViWQGyyKwZseztqrjPZBv6SQF384Rg6ESAy");
         let account = Account::empty(public_key.clone());
         let mut accounts = HashMap::new();
-        accounts.insert(public_key.clone(), account);
+        accounts.insert(account.id(), account);
 
         let ledger_diff = LedgerDiff {
             state_hash: BlockHash::default(),
@@ -373,13 +560,14 @@ mod tests {
                 public_key: public_key.clone(),
                 amount: diff_amount,
                 update_type: UpdateType::Credit,
+                token: TokenId::default(),
             })],
         };
         let ledger = Ledger { accounts }
             .apply_diff(&ledger_diff)
             .expect("ledger diff application");
 
-        let account_after = ledger.accounts.get(&public_key).expect("account get");
+        let account_after = ledger.get_mina_account(&public_key).expect("account get");
         assert_eq!(account_after.balance, diff_amount);
     }
 
@@ -391,7 +579,7 @@ mod tests {
             PublicKey::new("B62qmMypEDCchUgPD6RU99gVKXJcY46urKdjbFmG5cYtaVpfKysXTz6");
         let account = Account::empty(public_key.clone());
         let mut accounts = HashMap::new();
-        accounts.insert(public_key.clone(), account);
+        accounts.insert(account.id(), account);
 
         let ledger_diff = LedgerDiff {
             state_hash: BlockHash::default(),
@@ -409,7 +597,95 @@ mod tests {
             .apply_diff(&ledger_diff)
             .expect("ledger diff application");
 
-        let account_after = ledger.accounts.get(&public_key).expect("account get");
+        let account_after = ledger.get_mina_account(&public_key).expect("account get");
         assert_eq!(account_after.delegate, delegate_key);
     }
+
+    #[test]
+    fn apply_diff_token_payment() {
+        let amount = 42.into();
+        let token = TokenId::new("wSMg68vWG5sUbsHsnctN8sDBSshoLDseY6E8HPSWzuqdhfpDFL");
+        let public_key = PublicKey::new("B62qre3erTHfzQckNuibViWQGyyKwZseztqrjPZBv6SQF384Rg6ESAy");
+        let account = Account {
+            balance: 1000.into(),
+            ..Account::empty(public_key.clone())
+        };
+        let mut accounts = HashMap::new();
+        accounts.insert(account.id(), account.clone());
+
+        let ledger_diff = LedgerDiff {
+            account_diffs: vec![AccountDiff::Payment(PaymentDiff {
+                public_key: public_key.clone(),
+                amount,
+                update_type: UpdateType::Credit,
+                token: token.clone(),
+            })],
+            ..Default::default()
+        };
+        let ledger = Ledger { accounts }
+            .apply_diff(&ledger_diff)
+            .expect("ledger diff application");
+
+        // the token account is created, the MINA account is untouched
+        assert_eq!(ledger.len(), 2);
+        assert_eq!(ledger.get_mina_account(&public_key), Some(&account));
+
+        let token_account = ledger
+            .get_account(&public_key, &token)
+            .expect("token account");
+        assert_eq!(token_account.balance, amount);
+        assert_eq!(token_account.token, token);
+        assert_eq!(ledger.token_accounts(&token).count(), 1);
+    }
+
+    #[test]
+    fn ledger_string_round_trip() -> anyhow::Result<()> {
+        let token = TokenId::new("wSMg68vWG5sUbsHsnctN8sDBSshoLDseY6E8HPSWzuqdhfpDFL");
+        let public_key = PublicKey::new("B62qre3erTHfzQckNuibViWQGyyKwZseztqrjPZBv6SQF384Rg6ESAy");
+        let mut ledger = Ledger::new();
+        ledger.insert_account(Account {
+            balance: 1000.into(),
+            ..Account::empty(public_key.clone())
+        });
+        ledger.insert_account(Account {
+            balance: 42.into(),
+            ..Account::empty_token(public_key.clone(), token.clone())
+        });
+
+        // MINA accounts stay keyed by address
+        let json: serde_json::Value = serde_json::from_str(&ledger.to_string())?;
+        assert_eq!(json[&public_key.0]["balance"], 1000);
+        assert_eq!(json["tokens"][&token.0][&public_key.0]["balance"], 42);
+
+        assert_eq!(ledger.to_string().parse::<Ledger>()?, ledger);
+        Ok(())
+    }
+
+    #[test]
+    fn account_from_bytes() -> anyhow::Result<()> {
+        let mina = TokenId::default();
+        let token = TokenId::new("wSMg68vWG5sUbsHsnctN8sDBSshoLDseY6E8HPSWzuqdhfpDFL");
+        let pk0 = PublicKey::new("B62qre3erTHfzQckNuibViWQGyyKwZseztqrjPZBv6SQF384Rg6ESAy");
+        let pk1 = PublicKey::new("B62qmMypEDCchUgPD6RU99gVKXJcY46urKdjbFmG5cYtaVpfKysXTz6");
+        let mut ledger = Ledger::new();
+        ledger.insert_account(Account {
+            balance: 1000.into(),
+            ..Account::empty(pk0.clone())
+        });
+        ledger.insert_account(Account {
+            balance: 42.into(),
+            ..Account::empty_token(pk0.clone(), token.clone())
+        });
+        ledger.insert_account(Account::empty(pk1.clone()));
+
+        // each account matches the fully deserialized ledger's
+        let bytes = ledger.to_string().into_bytes();
+        for (pk, token) in [(&pk0, &mina), (&pk0, &token), (&pk1, &mina), (&pk1, &token)] {
+            assert_eq!(
+                Ledger::account_from_bytes(&bytes, pk, token)?.as_ref(),
+                ledger.get_account(pk, token)
+            );
+        }
+        Ok(())
+    }
 }
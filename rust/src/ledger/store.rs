@@ -3,8 +3,11 @@
 use crate::{
     block::BlockHash,
     ledger::{
+        account::{Account, AccountHistory},
         diff::LedgerDiff,
+        public_key::PublicKey,
         staking::{AggregatedEpochStakeDelegations, StakingLedger},
+        token::TokenId,
         Ledger, LedgerHash,
     },
 };
@@ -45,6 +48,42 @@ pub trait LedgerStore {
     /// Get a ledger at a specified `blockchain_length`
     fn get_ledger_at_height(&self, height: u32, memoize: bool) -> anyhow::Result<Option<Ledger>>;
 
+    /// Get an account as of an arbitrary block, without materializing the
+    /// whole ledger
+    fn get_account_state_hash(
+        &self,
+        pk: &PublicKey,
+        token: &TokenId,
+        state_hash: &BlockHash,
+    ) -> anyhow::Result<Option<Account>>;
+
+    /// Get an account as of the canonical block at `height`
+    fn get_account_at_height(
+        &self,
+        pk: &PublicKey,
+        token: &TokenId,
+        height: u32,
+    ) -> anyhow::Result<Option<Account>>;
+
+    /// Record each account's balance, nonce & delegate after the block is
+    /// applied
+    fn add_account_history(
+        &self,
+        state_hash: &BlockHash,
+        blockchain_length: u32,
+        accounts: &[&Account],
+    ) -> anyhow::Result<()>;
+
+    /// Get the history of pk's token account between heights `from` & `to`
+    /// (inclusive), lowest first
+    fn get_account_history(
+        &self,
+        pk: &PublicKey,
+        token: &TokenId,
+        from: u32,
+        to: u32,
+    ) -> anyhow::Result<Vec<AccountHistory>>;
+
     /// Index the block's ledger diff
     fn set_block_ledger_diff(
         &self,
@@ -156,4 +195,7 @@ pub trait LedgerStore {
 
     /// Per epoch staking ledger iterator via epoch
     fn staking_ledger_epoch_iterator(&self, mode: IteratorMode) -> DBIterator<'_>;
+
+    /// Account history iterator via `{pk}{height}{state_hash}`
+    fn account_history_iterator(&self, mode: IteratorMode) -> DBIterator<'_>;
 }
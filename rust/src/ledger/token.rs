@@ -0,0 +1,100 @@
+use crate::{
+    mina_blocks::v2::staged_ledger_diff::command::MINA_TOKEN_ID,
+    protocol::serialization_types::version_bytes::TOKEN_ID_KEY,
+};
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+
+/// Base58 encoded token id, accounts are identified by `(PublicKey, TokenId)`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TokenId(pub String);
+
+impl TokenId {
+    pub fn new<S: Into<String>>(token: S) -> Self {
+        Self(token.into())
+    }
+
+    pub fn is_mina(&self) -> bool {
+        self.0 == MINA_TOKEN_ID
+    }
+
+    /// Convert to bytes (variable length)
+    pub fn to_bytes(self) -> Vec<u8> {
+        self.0.as_bytes().to_vec()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let res = String::from_utf8(bytes.to_vec())?;
+        if is_valid_token_id(&res) {
+            return Ok(Self(res));
+        }
+        bail!("Invalid token id from bytes")
+    }
+}
+
+/// The default token is MINA
+impl std::default::Default for TokenId {
+    fn default() -> Self {
+        Self(MINA_TOKEN_ID.to_string())
+    }
+}
+
+impl std::str::FromStr for TokenId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if is_valid_token_id(s) {
+            Ok(Self(s.to_string()))
+        } else {
+            bail!("Invalid token id: {s}")
+        }
+    }
+}
+
+impl From<&str> for TokenId {
+    fn from(value: &str) -> Self {
+        Self(value.to_owned())
+    }
+}
+
+impl From<String> for TokenId {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<TokenId> for String {
+    fn from(value: TokenId) -> Self {
+        value.0
+    }
+}
+
+impl std::fmt::Display for TokenId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+pub fn is_valid_token_id(input: &str) -> bool {
+    bs58::decode(input)
+        .with_check(Some(TOKEN_ID_KEY))
+        .into_vec()
+        .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mina_token_id() {
+        let mina = TokenId::default();
+        assert!(mina.is_mina());
+        assert!(is_valid_token_id(&mina.0));
+
+        let token: TokenId = "wSMg68vWG5sUbsHsnctN8sDBSshoLDseY6E8HPSWzuqdhfpDFL".into();
+        assert!(!token.is_mina());
+        assert!(is_valid_token_id(&token.0));
+        assert!(!is_valid_token_id("B62qkNoTaTokenId"));
+    }
+}
@@ -2,13 +2,32 @@ use rust_decimal::{prelude::ToPrimitive, Decimal};
 use serde::{Deserialize, Deserializer};
 use std::str::FromStr;
 
+/// Precomputed blocks encode numbers as strings, the indexer store encodes
+/// them as json numbers. We accept either.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringOrNumber {
+    String(String),
+    Number(serde_json::Number),
+}
+
+impl StringOrNumber {
+    fn into_string(self) -> String {
+        match self {
+            Self::String(s) => s,
+            Self::Number(n) => n.to_string(),
+        }
+    }
+}
+
 pub(crate) fn from_str<'de, T, D>(de: D) -> Result<T, D::Error>
 where
     D: Deserializer<'de>,
     T: FromStr,
     <T as FromStr>::Err: std::fmt::Display,
 {
-    String::deserialize(de)?
+    StringOrNumber::deserialize(de)?
+        .into_string()
         .parse()
         .map_err(serde::de::Error::custom)
 }
@@ -19,7 +38,7 @@ where
     T: FromStr,
     <T as FromStr>::Err: std::fmt::Display,
 {
-    Ok(<Option<String>>::deserialize(de)?.and_then(|x| x.parse().ok()))
+    Ok(<Option<StringOrNumber>>::deserialize(de)?.and_then(|x| x.into_string().parse().ok()))
 }
 
 pub(crate) fn vec_from_str<'de, T, D>(de: D) -> Result<Vec<T>, D::Error>
@@ -28,18 +47,26 @@ where
     T: FromStr,
     <T as FromStr>::Err: std::fmt::Debug,
 {
-    Ok(<Vec<String>>::deserialize(de)?
-        .iter()
-        .map(|x| x.parse().unwrap())
+    Ok(<Vec<StringOrNumber>>::deserialize(de)?
+        .into_iter()
+        .map(|x| x.into_string().parse().unwrap())
         .collect())
 }
 
+/// Decimal strings are MINA amounts, json numbers are nanomina amounts
 pub(crate) fn from_decimal_str<'de, D>(de: D) -> Result<u64, D::Error>
 where
     D: Deserializer<'de>,
 {
-    match String::deserialize(de)?.parse::<Decimal>() {
-        Ok(res) => Ok(res.to_u64().unwrap()),
-        Err(e) => Err(serde::de::Error::custom(e)),
+    match StringOrNumber::deserialize(de)? {
+        StringOrNumber::String(s) => match s.parse::<Decimal>() {
+            Ok(res) => (res * Decimal::from(1_000_000_000))
+                .to_u64()
+                .ok_or_else(|| serde::de::Error::custom(format!("invalid amount {s}"))),
+            Err(e) => Err(serde::de::Error::custom(e)),
+        },
+        StringOrNumber::Number(n) => n
+            .as_u64()
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid amount {n}"))),
     }
 }
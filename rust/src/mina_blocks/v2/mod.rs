@@ -30,7 +30,7 @@ pub struct PrecomputedBlockDataV2 {
     pub proposed_protocol_version: Option<ProtocolVersion>,
     pub protocol_state: ProtocolState,
     pub staged_ledger_diff: StagedLedgerDiff,
-    pub accounts_created: Vec<(u64, PublicKey)>,
+    pub accounts_created: Vec<AccountCreated>,
     pub accounts_accessed: Vec<(u64, AccountAccessed)>,
 
     #[serde(skip_deserializing)]
@@ -40,7 +40,30 @@ pub struct PrecomputedBlockDataV2 {
     pub protocol_state_proof: serde_json::Value,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Account created in the block & its creation fee,
+/// e.g. `[[<public key>, <token id>], "1"]`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountCreated(
+    pub (PublicKey, String),
+    #[serde(deserialize_with = "from_decimal_str")] pub u64,
+);
+
+impl AccountCreated {
+    pub fn public_key(&self) -> &PublicKey {
+        &self.0 .0
+    }
+
+    pub fn token_id(&self) -> &str {
+        &self.0 .1
+    }
+
+    pub fn creation_fee(&self) -> u64 {
+        self.1
+    }
+}
+
+/// Account accessed by the block's transactions, in its post-block state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AccountAccessed {
     #[serde(deserialize_with = "from_str")]
     pub public_key: PublicKey,
@@ -67,44 +90,65 @@ pub struct AccountAccessed {
     pub zkapp: Option<ZkappAccount>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// `["Untimed"]` or `["Timed", <timing>]`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
 pub enum AccountAccessedTiming {
     Untimed((TimingKind,)),
-    Timed((TimingKind, Timing)),
+    Timed((TimingKind, AccountTiming)),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TimingKind {
     Timed,
     Untimed,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountTiming {
+    #[serde(deserialize_with = "from_str")]
+    pub initial_minimum_balance: u64,
+
+    #[serde(deserialize_with = "from_str")]
+    pub cliff_time: u32,
+
+    #[serde(deserialize_with = "from_str")]
+    pub cliff_amount: u64,
+
+    #[serde(deserialize_with = "from_str")]
+    pub vesting_period: u32,
+
+    #[serde(deserialize_with = "from_str")]
+    pub vesting_increment: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Permissions {
-    pub edit_state: (Permission,),
-    pub access: (Permission,),
-    pub send: (Permission,),
-    pub receive: (Permission,),
-    pub set_delegate: (Permission,),
-    pub set_permissions: (Permission,),
-    pub set_verification_key: ((Permission, SetVerificationKey),),
-    pub set_zkapp_uri: (Permission,),
-    pub edit_action_state: (Permission,),
-    pub set_token_symbol: (Permission,),
-    pub increment_nonce: (Permission,),
-    pub set_voting_for: (Permission,),
-    pub set_timing: (Permission,),
+    pub edit_state: Permission,
+    pub access: Permission,
+    pub send: Permission,
+    pub receive: Permission,
+    pub set_delegate: Permission,
+    pub set_permissions: Permission,
+    pub set_verification_key: (Permission, SetVerificationKey),
+    pub set_zkapp_uri: Permission,
+    pub edit_action_state: Permission,
+    pub set_token_symbol: Permission,
+    pub increment_nonce: Permission,
+    pub set_voting_for: Permission,
+    pub set_timing: Permission,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SetVerificationKey(pub u32);
+/// Transaction version of the verification key permission
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SetVerificationKey(#[serde(deserialize_with = "from_str")] pub u32);
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Permission(pub (PermissionKind,));
 
 /// See https://github.com/MinaProtocol/mina/blob/berkeley/src/lib/mina_base/permissions.mli
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PermissionKind {
     None,
     Either,
@@ -113,11 +157,11 @@ pub enum PermissionKind {
     Impossible,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct ZkappAccount {
     pub app_state: [String; 8],    // 64 hex digits
     pub action_state: [String; 5], // 64 hex digits
-    pub verification_key: VerificationKey,
+    pub verification_key: Option<VerificationKey>,
     pub proved_state: bool,
     pub zkapp_uri: String,
 
@@ -128,12 +172,30 @@ pub struct ZkappAccount {
     pub last_action_slot: u32,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct VerificationKey {
     pub data: String,
     pub hash: String,
 }
 
+impl AccountAccessed {
+    pub fn is_mina_token(&self) -> bool {
+        self.token_id == staged_ledger_diff::command::MINA_TOKEN_ID
+    }
+}
+
+impl From<AccountTiming> for Timing {
+    fn from(value: AccountTiming) -> Self {
+        Self {
+            initial_minimum_balance: value.initial_minimum_balance,
+            cliff_time: value.cliff_time,
+            cliff_amount: value.cliff_amount,
+            vesting_period: value.vesting_period,
+            vesting_increment: value.vesting_increment,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProtocolVersion {
     pub transaction: u32,
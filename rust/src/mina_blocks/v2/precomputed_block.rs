@@ -1,4 +1,7 @@
-use super::{protocol_state::ProtocolState, staged_ledger_diff::StagedLedgerDiff};
+use super::{
+    protocol_state::ProtocolState, staged_ledger_diff::StagedLedgerDiff, AccountAccessed,
+    AccountCreated,
+};
 use crate::mina_blocks::common::from_str;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
@@ -21,6 +24,15 @@ pub struct PrecomputedBlockData {
     /// Collection of ledger updates
     pub staged_ledger_diff: StagedLedgerDiff,
 
+    /// Accounts created by the block's transactions
+    #[serde(default)]
+    pub accounts_created: Vec<AccountCreated>,
+
+    /// Accounts accessed by the block's transactions & their ledger index,
+    /// in their post-block state
+    #[serde(default)]
+    pub accounts_accessed: Vec<(u64, AccountAccessed)>,
+
     /// Protocol state proof
     #[serde(skip_deserializing)]
     pub protocol_state_proof: serde_json::Value,
@@ -1,13 +1,125 @@
+use super::{
+    AccountUpdateBody, CommandData, Elt, Failure, FailureReason, Status, StatusKind,
+    UserCommandData, ZkappCommandData,
+};
+use crate::{ledger::public_key::PublicKey, mina_blocks::v2::protocol_state::SupplyAdjustmentSign};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Base58 encoded MINA token id
+pub const MINA_TOKEN_ID: &str = "wSHV2S4qX9jFsLjQo8r1BsMLH2ZRKsZx6EJd1sbozGPieEC4Jf";
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Command {
     UserCommand(UserCommand),
     ZkappCommand(ZkappCommand),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct UserCommand {}
+/// Signed command, i.e. payment or stake delegation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserCommand {
+    pub data: UserCommandData,
+    pub status: Status,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ZkappCommand {
+    pub data: ZkappCommandData,
+    pub status: Status,
+}
+
+impl From<super::Command> for Command {
+    fn from(value: super::Command) -> Self {
+        let status = value.status;
+        match value.data.1 {
+            CommandData::UserCommandData(data) => Self::UserCommand(UserCommand { data, status }),
+            CommandData::ZkappCommandData(data) => {
+                Self::ZkappCommand(ZkappCommand { data, status })
+            }
+        }
+    }
+}
+
+impl Status {
+    pub fn is_applied(&self) -> bool {
+        match self {
+            Self::Status((kind,)) | Self::StatusAndFailure(kind, _) => *kind == StatusKind::Applied,
+        }
+    }
+
+    /// All failure reasons, in account update order
+    pub fn failures(&self) -> Vec<FailureReason> {
+        match self {
+            Self::Status(_) => vec![],
+            Self::StatusAndFailure(_, failures) => failures
+                .iter()
+                .flatten()
+                .map(|failure| match failure {
+                    Failure::Reason((reason,)) | Failure::IndexedReason((reason, _)) => {
+                        reason.clone()
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl ZkappCommand {
+    pub fn is_applied(&self) -> bool {
+        self.status.is_applied()
+    }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ZkappCommand {}
+    pub fn fee_payer(&self) -> PublicKey {
+        self.data.fee_payer.body.public_key.clone()
+    }
+
+    pub fn fee(&self) -> u64 {
+        self.data.fee_payer.body.fee
+    }
+
+    pub fn nonce(&self) -> u32 {
+        self.data.fee_payer.body.nonce
+    }
+
+    /// All account updates, depth-first in call order
+    pub fn account_updates(&self) -> Vec<AccountUpdateBody> {
+        fn collect(elt: &Elt, updates: &mut Vec<AccountUpdateBody>) {
+            updates.push(elt.account_update.body.clone());
+            for call in &elt.calls {
+                collect(&call.elt, updates);
+            }
+        }
+
+        let mut updates = vec![];
+        for account_update in &self.data.account_updates {
+            collect(&account_update.elt, &mut updates);
+        }
+        updates
+    }
+
+    /// Fee payer & account update public keys
+    pub fn public_keys(&self) -> Vec<PublicKey> {
+        let mut pks = vec![self.fee_payer()];
+        for update in self.account_updates() {
+            if !pks.contains(&update.public_key) {
+                pks.push(update.public_key);
+            }
+        }
+        pks
+    }
+}
+
+impl AccountUpdateBody {
+    pub fn is_mina_token(&self) -> bool {
+        self.token_id == MINA_TOKEN_ID
+    }
+
+    /// Signed balance change of the account
+    pub fn balance_change(&self) -> i128 {
+        let magnitude = self.balance_change.magnitude as i128;
+        match self.balance_change.sgn.0 {
+            SupplyAdjustmentSign::Pos => magnitude,
+            SupplyAdjustmentSign::Neg => -magnitude,
+        }
+    }
+}
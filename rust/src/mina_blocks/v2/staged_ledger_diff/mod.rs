@@ -2,7 +2,13 @@ pub mod command;
 pub mod completed_work;
 
 use super::protocol_state::SupplyAdjustment;
-use crate::{ledger::public_key::PublicKey, mina_blocks::common::*};
+use crate::{
+    ledger::public_key::PublicKey,
+    mina_blocks::common::*,
+    protocol::serialization_types::{
+        signatures::SignatureJson, staged_ledger_diff::SignedCommandMemoJson,
+    },
+};
 use completed_work::CompletedWork;
 use serde::{Deserialize, Serialize};
 
@@ -60,7 +66,18 @@ pub struct Command {
 #[serde(untagged)]
 pub enum Status {
     Status((StatusKind,)),
-    StatusAndFailure(StatusKind, (((FailureReason,),),)),
+
+    /// Failures are grouped per account update, the fee payer's first
+    StatusAndFailure(StatusKind, Vec<Vec<Failure>>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Failure {
+    Reason((FailureReason,)),
+
+    /// Failure reason with the index of the offending app state field
+    IndexedReason((FailureReason, i64)),
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -72,6 +89,7 @@ pub enum CommandKind {
     ZkappCommand,
 }
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum CommandData {
@@ -87,7 +105,7 @@ pub struct UserCommandData {
     pub signer: PublicKey,
 
     pub payload: UserCommandPayload,
-    pub signature: String,
+    pub signature: SignatureJson,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -108,7 +126,15 @@ pub enum UserCommandPayloadKind {
 #[serde(untagged)]
 pub enum UserCommandPayloadBody {
     Payment(PaymentPayload),
-    StakeDelegation(StakeDelegationPayload),
+
+    /// `["Set_delegate", { new_delegate }]`
+    StakeDelegation((StakeDelegationKind, StakeDelegationPayload)),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum StakeDelegationKind {
+    #[serde(rename = "Set_delegate")]
+    SetDelegate,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -177,23 +203,23 @@ pub struct AccountUpdate {
     pub authorization: Authorization,
 }
 
+/// Account update authorization, or authorization kind.
+/// Proofs & signatures carry their data, e.g. `["Proof", <proof>]`
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub enum ProofOrSignature {
-    Proof,
-    Signature,
+#[serde(untagged)]
+pub enum Authorization {
+    Kind((AuthorizationKind,)),
+    KindWithData((AuthorizationKind, String)),
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(untagged)]
-pub enum Authorization {
+pub enum AuthorizationKind {
     #[serde(rename = "None_given")]
     NoneGiven,
 
     Either,
     Proof,
-    Proof_((ProofOrSignature, String)),
     Signature,
-    Signature_((ProofOrSignature, String)),
     Impossible,
 }
 
@@ -219,7 +245,12 @@ pub struct AccountUpdateBody {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum MayUseToken {
     No,
-    Yes,
+
+    #[serde(rename = "Parents_own_token")]
+    ParentsOwnToken,
+
+    #[serde(rename = "Inherit_from_parent")]
+    InheritFromParent,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -231,38 +262,49 @@ pub struct ZkappEvents(pub Vec<String>);
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Update {
     // one for each app state field element
-    pub app_state: [(UpdateKind,); 8],
+    pub app_state: [UpdateKind; 8],
 
-    pub delegate: (UpdateKind,),
-    pub verification_key: (UpdateKind,),
-    pub permissions: (UpdateKind,),
-    pub zkapp_uri: (UpdateKind,),
-    pub token_symbol: (UpdateKind,),
-    pub timing: (UpdateKind,),
-    pub voting_for: (UpdateKind,),
+    pub delegate: UpdateKind,
+    pub verification_key: UpdateKind,
+    pub permissions: UpdateKind,
+    pub zkapp_uri: UpdateKind,
+    pub token_symbol: UpdateKind,
+    pub timing: UpdateKind,
+    pub voting_for: UpdateKind,
 }
 
+/// `["Keep"]` or `["Set", <value>]`
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum UpdateKind {
+    Keep((KeepTag,)),
+    Set((SetTag, serde_json::Value)),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum KeepTag {
     Keep,
-    Set(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SetTag {
+    Set,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Preconditions {
     pub network: NetworkPreconditions,
     pub account: AccountPreconditions,
-    pub valid_while: (PreconditionKind,),
+    pub valid_while: PreconditionKind,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct NetworkPreconditions {
-    pub snarked_ledger_hash: (PreconditionKind,),
-    pub blockchain_length: (PreconditionKind,),
-    pub min_window_density: (PreconditionKind,),
-    pub total_currency: (PreconditionKind,),
-    pub global_slot_since_genesis: (PreconditionKind,),
+    pub snarked_ledger_hash: PreconditionKind,
+    pub blockchain_length: PreconditionKind,
+    pub min_window_density: PreconditionKind,
+    pub total_currency: PreconditionKind,
+    pub global_slot_since_genesis: PreconditionKind,
     pub staking_epoch_data: StakingEpochDataPreconditions,
     pub next_epoch_data: StakingEpochDataPreconditions,
 }
@@ -270,35 +312,46 @@ pub struct NetworkPreconditions {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StakingEpochDataPreconditions {
     pub ledger: LedgerPreconditions,
-    pub seed: (PreconditionKind,),
-    pub start_checkpoint: (PreconditionKind,),
-    pub lock_checkpoint: (PreconditionKind,),
-    pub epoch_length: (PreconditionKind,),
+    pub seed: PreconditionKind,
+    pub start_checkpoint: PreconditionKind,
+    pub lock_checkpoint: PreconditionKind,
+    pub epoch_length: PreconditionKind,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LedgerPreconditions {
-    pub hash: (PreconditionKind,),
-    pub total_currency: (PreconditionKind,),
+    pub hash: PreconditionKind,
+    pub total_currency: PreconditionKind,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AccountPreconditions {
-    pub balance: (PreconditionKind,),
-    pub nonce: (PreconditionKind,),
-    pub receipt_chain_hash: (PreconditionKind,),
-    pub delegate: (PreconditionKind,),
-    pub state: Vec<(PreconditionKind,)>,
-    pub action_state: (PreconditionKind,),
-    pub proved_state: (PreconditionKind,),
-    pub is_new: (PreconditionKind,),
+    pub balance: PreconditionKind,
+    pub nonce: PreconditionKind,
+    pub receipt_chain_hash: PreconditionKind,
+    pub delegate: PreconditionKind,
+    pub state: Vec<PreconditionKind>,
+    pub action_state: PreconditionKind,
+    pub proved_state: PreconditionKind,
+    pub is_new: PreconditionKind,
 }
 
+/// `["Ignore"]` or `["Check", <value>]`
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum PreconditionKind {
+    Ignore((IgnoreTag,)),
+    Check((CheckTag, serde_json::Value)),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum IgnoreTag {
     Ignore,
-    Check(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CheckTag {
+    Check,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -321,8 +374,7 @@ pub struct UserCommandPayloadCommon {
     #[serde(deserialize_with = "from_str")]
     pub valid_until: u64,
 
-    // Base58 encoded string
-    pub memo: String,
+    pub memo: SignedCommandMemoJson,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -441,7 +493,7 @@ pub enum FailureReason {
     AccountActionStatePreconditionUnsatisfied,
 
     #[serde(rename = "Account_app_state_precondition_unsatisfied")]
-    AccountAppStatePreconditionUnsatisfied(i64),
+    AccountAppStatePreconditionUnsatisfied,
 
     #[serde(rename = "Account_proved_state_precondition_unsatisfied")]
     AccountProvedStatePreconditionUnsatisfied,
@@ -0,0 +1,3 @@
+//! Hashing helpers
+
+pub mod roinput;
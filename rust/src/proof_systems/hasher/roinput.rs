@@ -0,0 +1,122 @@
+//! Random oracle input structures and algorithms
+//!
+//! Definition of the (legacy) random oracle input structure and helpers. The
+//! input consists of a list of field elements followed by a list of bits.
+
+use crate::proof_systems::{signer::signature::BaseField, FieldHelpers};
+use ark_ff::PrimeField;
+
+/// Random oracle input
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ROInput {
+    fields: Vec<BaseField>,
+    bits: Vec<bool>,
+}
+
+impl ROInput {
+    /// Create a new empty random oracle input
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a base field element
+    pub fn append_field(mut self, f: BaseField) -> Self {
+        self.fields.push(f);
+        self
+    }
+
+    /// Append a single bit
+    pub fn append_bool(mut self, b: bool) -> Self {
+        self.bits.push(b);
+        self
+    }
+
+    /// Append bytes, least significant bit of each byte first
+    pub fn append_bytes(mut self, bytes: &[u8]) -> Self {
+        for byte in bytes {
+            for i in 0..8 {
+                self.bits.push((byte >> i) & 1 == 1);
+            }
+        }
+        self
+    }
+
+    /// Append a 32-bit unsigned integer (little-endian)
+    pub fn append_u32(self, x: u32) -> Self {
+        self.append_bytes(&x.to_le_bytes())
+    }
+
+    /// Append a 64-bit unsigned integer (little-endian)
+    pub fn append_u64(self, x: u64) -> Self {
+        self.append_bytes(&x.to_le_bytes())
+    }
+
+    /// Borrow the field elements
+    pub fn fields(&self) -> &[BaseField] {
+        &self.fields
+    }
+
+    /// Borrow the bits
+    pub fn bits(&self) -> &[bool] {
+        &self.bits
+    }
+
+    /// Serialize to bytes: the significant bits of each field element,
+    /// followed by the bits, packed least significant bit first
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let field_bits = BaseField::size_in_bits();
+        let bits: Vec<bool> = self
+            .fields
+            .iter()
+            .flat_map(|f| f.to_bits().into_iter().take(field_bits))
+            .chain(self.bits.iter().copied())
+            .collect();
+
+        bits.chunks(8)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0u8, |byte, (i, bit)| byte | ((*bit as u8) << i))
+            })
+            .collect()
+    }
+
+    /// Serialize to hex
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::{One, Zero};
+
+    #[test]
+    fn append_bits() {
+        let roi = ROInput::new()
+            .append_bool(true)
+            .append_u32(2)
+            .append_u64(u64::MAX);
+
+        assert_eq!(roi.bits().len(), 1 + 32 + 64);
+        assert_eq!(roi.bits()[..3], [true, false, true]);
+        assert!(roi.bits()[33..].iter().all(|b| *b));
+        assert_eq!(roi.to_bytes()[0], 0b0000_0101);
+    }
+
+    #[test]
+    fn field_bytes() {
+        let roi = ROInput::new()
+            .append_field(BaseField::one())
+            .append_field(BaseField::zero())
+            .append_bool(true);
+
+        // 2 * 255 field bits + 1 bit
+        let bytes = roi.to_bytes();
+        assert_eq!(bytes.len(), 64);
+        assert_eq!(bytes[0], 1);
+        assert_eq!(bytes[63], 0b0100_0000);
+    }
+}
@@ -87,7 +87,8 @@ impl PubKey {
             .map_err(|_| PubKeyError::XCoordinateBytes)?;
         let y = BaseField::from_bytes(&bytes[BaseField::size_in_bytes()..])
             .map_err(|_| PubKeyError::YCoordinateBytes)?;
-        let pt = CurvePoint::get_point_from_x(x, y.0.is_odd()).ok_or(PubKeyError::XCoordinate)?;
+        let pt = CurvePoint::get_point_from_x(x, y.into_repr().is_odd())
+            .ok_or(PubKeyError::XCoordinate)?;
         if pt.y != y {
             return Err(PubKeyError::NonCurvePoint);
         }
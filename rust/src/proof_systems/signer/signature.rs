@@ -23,6 +23,27 @@ impl Signature {
     pub fn new(rx: BaseField, s: ScalarField) -> Self {
         Self { rx, s }
     }
+
+    /// Deserialize a signature from its (big-endian) hex representation,
+    /// i.e. the inverse of [Display](fmt::Display)
+    pub fn from_hex(sig_hex: &str) -> anyhow::Result<Self> {
+        let bytes = hex::decode(sig_hex)?;
+        if bytes.len() != 2 * BaseField::size_in_bytes() {
+            anyhow::bail!("invalid signature length {}", bytes.len());
+        }
+
+        let (mut rx_bytes, mut s_bytes) = (
+            bytes[..BaseField::size_in_bytes()].to_vec(),
+            bytes[BaseField::size_in_bytes()..].to_vec(),
+        );
+        rx_bytes.reverse();
+        s_bytes.reverse();
+
+        Ok(Self {
+            rx: BaseField::from_bytes(&rx_bytes)?,
+            s: ScalarField::from_bytes(&s_bytes)?,
+        })
+    }
 }
 
 impl fmt::Display for Signature {
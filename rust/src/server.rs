@@ -1,26 +1,33 @@
 use crate::{
     block::{
         self,
+        archive::is_block_archive,
         parser::BlockParser,
         precomputed::{PcbVersion, PrecomputedBlock},
+        source::{
+            block_source_from_spec, fetch_with_retry, BlockSource, ExecutableSource, RetryPolicy,
+        },
+        verify::verify_block_file,
         BlockHash,
     },
     chain::{chain_id, ChainId, Network},
     constants::*,
     ledger::{
-        genesis::{GenesisConstants, GenesisLedger},
+        genesis::{GenesisConstants, GenesisLedger, HardforkGenesis},
         staking::{self, StakingLedger},
         store::LedgerStore,
     },
     state::{IndexerState, IndexerStateConfig},
-    store::IndexerStore,
+    store::{checkpoint::IngestionCheckpointStore, IndexerStore},
     unix_socket_server::{create_socket_listener, handle_connection},
+    web::start_admin_web_server,
 };
-use log::{debug, error, info, trace};
+use anyhow::bail;
+use log::{debug, error, info, trace, warn};
 use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use speedb::checkpoint::Checkpoint;
 use std::{
-    collections::HashSet,
+    collections::BTreeSet,
     fs,
     os::unix::fs::MetadataExt,
     path::{Path, PathBuf},
@@ -45,6 +52,7 @@ pub struct IndexerVersion {
 pub struct IndexerConfiguration {
     pub genesis_ledger: GenesisLedger,
     pub genesis_hash: BlockHash,
+    pub hardfork: Option<HardforkGenesis>,
     pub genesis_constants: GenesisConstants,
     pub constraint_system_digests: Vec<String>,
     pub version: PcbVersion,
@@ -62,6 +70,10 @@ pub struct IndexerConfiguration {
     pub missing_block_recovery_exe: Option<PathBuf>,
     pub missing_block_recovery_delay: Option<u64>,
     pub missing_block_recovery_batch: bool,
+    pub block_source: Option<String>,
+    pub admin_web_port: Option<u16>,
+    pub verify_blocks: bool,
+    pub parse_threads: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -104,7 +116,13 @@ pub async fn start_indexer(
     let missing_block_recovery_delay = config.missing_block_recovery_delay;
     let missing_block_recovery_exe = config.missing_block_recovery_exe.clone();
     let missing_block_recovery_batch = config.missing_block_recovery_batch;
+    let block_source: Option<Arc<dyn BlockSource>> = match config.block_source {
+        Some(ref spec) => Some(block_source_from_spec(spec)?.into()),
+        None => None,
+    };
+    let verify_blocks = config.verify_blocks;
     let domain_socket_path = config.domain_socket_path.clone();
+    let admin_web_port = config.admin_web_port;
 
     // initialize witness tree & connect database
     let state = Arc::new(RwLock::new(
@@ -116,24 +134,39 @@ pub async fn start_indexer(
 
     // read-only state
     start_uds_server(&subsys, state.clone(), &domain_socket_path).await?;
+    if let Some(port) = admin_web_port {
+        info!("Starting the admin web server listening on localhost:{port}");
+        let state = state.clone();
+        subsys.start(SubsystemBuilder::new("Admin Web Server", move |s| {
+            start_admin_web_server(s, state, ("localhost", port))
+        }));
+    }
 
     // modifies the state
-    let missing_block_recovery =
-        missing_block_recovery_exe.map(|exe| MissingBlockRecoveryOptions {
-            exe,
+    // the block source takes precedence over the fetch executables
+    let executable_source =
+        |exe: PathBuf| -> Arc<dyn BlockSource> { Arc::new(ExecutableSource::new(exe)) };
+    let missing_block_recovery = block_source
+        .clone()
+        .or(missing_block_recovery_exe.map(executable_source))
+        .map(|source| MissingBlockRecoveryOptions {
+            source,
             batch: missing_block_recovery_batch,
             delay: missing_block_recovery_delay.unwrap_or(180),
         });
-    let fetch_new_blocks = fetch_new_blocks_exe.map(|exe| FetchNewBlocksOptions {
-        exe,
-        delay: fetch_new_blocks_delay.unwrap_or(180),
-    });
+    let fetch_new_blocks = block_source
+        .or(fetch_new_blocks_exe.map(executable_source))
+        .map(|source| FetchNewBlocksOptions {
+            source,
+            delay: fetch_new_blocks_delay.unwrap_or(180),
+        });
     run_indexer(
         &subsys,
         blocks_dir,
         staking_ledgers_dir,
         missing_block_recovery,
         fetch_new_blocks,
+        verify_blocks,
         state.clone(),
     )
     .await?;
@@ -163,6 +196,7 @@ async fn initialize(
     let IndexerConfiguration {
         genesis_ledger,
         genesis_hash,
+        hardfork,
         blocks_dir,
         staking_ledgers_dir,
         prune_interval,
@@ -174,10 +208,12 @@ async fn initialize(
         genesis_constants,
         constraint_system_digests,
         version,
+        verify_blocks,
+        parse_threads,
         ..
     } = config;
 
-    if let Some(ref blocks_dir) = blocks_dir {
+    if let Some(blocks_dir) = blocks_dir.as_ref().filter(|dir| !is_block_archive(dir)) {
         if let Err(e) = fs::create_dir_all(blocks_dir) {
             error!("Failed to create blocks directory in {blocks_dir:#?}: {e}");
             process::exit(1);
@@ -217,6 +253,7 @@ async fn initialize(
         canonical_update_threshold,
         ledger_cadence,
         reporting_freq,
+        hardfork: hardfork.clone(),
     };
 
     let mut state = match initialization_mode {
@@ -256,6 +293,9 @@ async fn initialize(
                 )
                 .await
                 .unwrap_or_else(|e| panic!("Obtaining block parser failed: {e}"));
+                block_parser.fork = state.fork_config();
+                block_parser.verify = verify_blocks;
+                block_parser.parse_threads = parse_threads;
                 state
                     .initialize_with_canonical_chain_discovery(&mut block_parser)
                     .await?;
@@ -274,15 +314,19 @@ async fn initialize(
                     canonical_update_threshold,
                     ledger_cadence,
                     reporting_freq,
+                    hardfork: hardfork.clone(),
                 })
             {
                 let min_length_filter = state.replay_events(replay_state)?;
                 if let Some(ref blocks_dir) = blocks_dir {
                     let mut block_parser = BlockParser::new_length_sorted_min_filtered(
                         blocks_dir,
-                        version,
+                        state.version.version.clone(),
                         min_length_filter,
                     )?;
+                    block_parser.fork = state.fork_config();
+                    block_parser.verify = verify_blocks;
+                    block_parser.parse_threads = parse_threads;
 
                     if block_parser.total_num_blocks > 0 {
                         info!("Adding new blocks from {blocks_dir:#?}");
@@ -292,13 +336,44 @@ async fn initialize(
             }
         }
         InitializationMode::Sync => {
+            // resume an interrupted database creation
+            if let (Some(checkpoint), Some(blocks_dir)) =
+                (store.get_ingestion_checkpoint()?, blocks_dir.as_ref())
+            {
+                if checkpoint.blocks_dir != *blocks_dir {
+                    bail!(
+                        "Database creation was interrupted while ingesting blocks from {:#?}, not {blocks_dir:#?}",
+                        checkpoint.blocks_dir
+                    );
+                }
+
+                info!("Resuming database creation from ingestion checkpoint at {checkpoint}");
+                let mut block_parser = BlockParser::new_with_canonical_chain_discovery(
+                    blocks_dir,
+                    version,
+                    canonical_threshold,
+                    reporting_freq,
+                )
+                .await?;
+                block_parser.fork = state.fork_config();
+                block_parser.verify = verify_blocks;
+                block_parser.parse_threads = parse_threads;
+                state
+                    .resume_from_checkpoint(&mut block_parser, &checkpoint)
+                    .await?;
+                return flush_store(state);
+            }
+
             let min_length_filter = state.sync_from_db()?;
             if let Some(ref blocks_dir) = blocks_dir {
                 let mut block_parser = BlockParser::new_length_sorted_min_filtered(
                     blocks_dir,
-                    version,
+                    state.version.version.clone(),
                     min_length_filter,
                 )?;
+                block_parser.fork = state.fork_config();
+                block_parser.verify = verify_blocks;
+                block_parser.parse_threads = parse_threads;
 
                 if block_parser.total_num_blocks > 0 {
                     info!("Adding new blocks from {blocks_dir:#?}");
@@ -308,7 +383,11 @@ async fn initialize(
         }
     }
 
-    // flush/compress database
+    flush_store(state)
+}
+
+/// Flushes/compresses the database
+fn flush_store(state: IndexerState) -> anyhow::Result<IndexerState> {
     let store = state.indexer_store.as_ref().unwrap();
     let temp_checkpoint_dir = store.db_path.join("tmp-checkpoint");
     Checkpoint::new(&store.database)?.create_checkpoint(&temp_checkpoint_dir)?;
@@ -346,13 +425,13 @@ fn is_hard_link<P: AsRef<Path>>(path: P) -> bool {
 
 struct MissingBlockRecoveryOptions {
     pub delay: u64,
-    pub exe: PathBuf,
+    pub source: Arc<dyn BlockSource>,
     pub batch: bool,
 }
 
 struct FetchNewBlocksOptions {
     pub delay: u64,
-    pub exe: PathBuf,
+    pub source: Arc<dyn BlockSource>,
 }
 
 /// Starts filesystem watchers & runs the mina indexer
@@ -362,6 +441,7 @@ async fn run_indexer<P: AsRef<Path>>(
     staking_ledgers_dir: Option<P>,
     missing_block_recovery: Option<MissingBlockRecoveryOptions>,
     fetch_new_blocks_opts: Option<FetchNewBlocksOptions>,
+    verify_blocks: bool,
     state: Arc<RwLock<IndexerState>>,
 ) -> anyhow::Result<()> {
     // setup fs-based precomputed block & staking ledger watchers
@@ -380,6 +460,8 @@ async fn run_indexer<P: AsRef<Path>>(
         Config::default(),
     )?;
 
+    // block archives are only ingested on initialization
+    let blocks_dir = blocks_dir.filter(|dir| !is_block_archive(dir.as_ref()));
     if let Some(ref blocks_dir) = blocks_dir {
         watcher.watch(blocks_dir.as_ref(), RecursiveMode::NonRecursive)?;
         info!(
@@ -396,11 +478,43 @@ async fn run_indexer<P: AsRef<Path>>(
         );
     }
 
-    let fetch_new_blocks_delay = fetch_new_blocks_opts.as_ref().map(|f| f.delay);
-    let fetch_new_blocks_exe = fetch_new_blocks_opts.as_ref().map(|f| f.exe.clone());
-    let missing_block_recovery_delay = missing_block_recovery.as_ref().map(|m| m.delay);
-    let missing_block_recovery_exe = missing_block_recovery.as_ref().map(|m| m.exe.clone());
-    let missing_block_recovery_batch = missing_block_recovery.map_or(false, |m| m.batch);
+    // poll the block sources
+    if let Some(blocks_dir) = blocks_dir.as_ref().map(|dir| dir.as_ref().to_path_buf()) {
+        if let Some(opts) = fetch_new_blocks_opts {
+            subsys.start(SubsystemBuilder::new("Fetch New Blocks", {
+                let state = state.clone();
+                let blocks_dir = blocks_dir.clone();
+                move |subsys| {
+                    poll_block_source(subsys, opts.delay, move || {
+                        fetch_new_blocks(state.clone(), blocks_dir.clone(), opts.source.clone())
+                    })
+                }
+            }));
+        }
+
+        if let Some(opts) = missing_block_recovery {
+            subsys.start(SubsystemBuilder::new("Missing Block Recovery", {
+                let state = state.clone();
+                move |subsys| {
+                    poll_block_source(subsys, opts.delay, move || {
+                        recover_missing_blocks(
+                            state.clone(),
+                            blocks_dir.clone(),
+                            opts.source.clone(),
+                            opts.batch,
+                        )
+                    })
+                }
+            }));
+        }
+    }
+
+    // periodically persist the witness tree in case of an ungraceful shutdown
+    let mut persist_interval = tokio::time::interval(std::time::Duration::from_secs(
+        WITNESS_TREE_PERSIST_FREQ_SEC,
+    ));
+    persist_interval.tick().await;
+
     loop {
         tokio::select! {
             // watch for shutdown signals
@@ -408,38 +522,30 @@ async fn run_indexer<P: AsRef<Path>>(
                 break;
             }
 
+            _ = persist_interval.tick() => {
+                if let Err(e) = state.read().await.persist_witness_tree() {
+                    error!("Failed to persist the witness tree: {e}");
+                }
+            }
+
             // watch for precomputed blocks & staking ledgers
             Some(res) = rx.recv() => {
                 match res {
-                    Ok(event) => process_event(event, &state).await?,
+                    Ok(event) => process_event(event, verify_blocks, &state).await?,
                     Err(e) => {
                         error!("Filesystem watcher error: {e}");
                         break;
                     }
                 }
             }
-
-            // fetch new blocks
-            _ = tokio::time::sleep(std::time::Duration::from_secs(fetch_new_blocks_delay.unwrap_or(180))) => {
-                if let Some(ref blocks_dir) = blocks_dir {
-                    if let Some(ref fetch_new_blocks_exe) = fetch_new_blocks_exe {
-                        fetch_new_blocks(&state, &blocks_dir, fetch_new_blocks_exe).await
-                    }
-                }
-            }
-
-            // recover missing blocks
-            _ = tokio::time::sleep(std::time::Duration::from_secs(missing_block_recovery_delay.unwrap_or(180))) => {
-                if let Some(ref blocks_dir) = blocks_dir {
-                    if let Some(ref missing_block_recovery_exe) = missing_block_recovery_exe {
-                        recover_missing_blocks(&state, &blocks_dir, missing_block_recovery_exe, missing_block_recovery_batch).await
-                    }
-                }
-            }
         }
     }
 
     let state = state.write().await;
+    info!("Persisting the witness tree");
+    if let Err(e) = state.persist_witness_tree() {
+        error!("Failed to persist the witness tree: {e}");
+    }
     if let Some(store) = state.indexer_store.as_ref() {
         info!("Canceling db background work");
         store.database.cancel_all_background_work(true)
@@ -449,7 +555,11 @@ async fn run_indexer<P: AsRef<Path>>(
 }
 
 /// Precomputed block & staking ledger event handler
-async fn process_event(event: Event, state: &Arc<RwLock<IndexerState>>) -> anyhow::Result<()> {
+async fn process_event(
+    event: Event,
+    verify_blocks: bool,
+    state: &Arc<RwLock<IndexerState>>,
+) -> anyhow::Result<()> {
     trace!("Event: {event:?}");
     if matches_event_kind(event.kind) {
         for path in event.paths {
@@ -468,9 +578,16 @@ async fn process_event(event: Event, state: &Arc<RwLock<IndexerState>>) -> anyho
             }
             if block::is_valid_block_file(&path) {
                 debug!("Valid precomputed block file: {}", path.display());
-                let mut version = state.read().await.version.clone();
-                match PrecomputedBlock::parse_file(&path, version.version.clone()) {
+                let version = state.read().await.block_version(&path);
+                match parse_block_file(&path, version) {
                     Ok(block) => {
+                        if verify_blocks {
+                            if let Err(e) = verify_block_file(&block, &path) {
+                                warn!("Quarantining block file {}: {e}", path.display());
+                                continue;
+                            }
+                        }
+
                         // Acquire write lock
                         let mut state = state.write().await;
 
@@ -491,15 +608,6 @@ async fn process_event(event: Event, state: &Arc<RwLock<IndexerState>>) -> anyho
                             }
                             Err(e) => error!("Error adding block: {e}"),
                         }
-
-                        // check for block parser version update
-                        if state.version.genesis_state_hash.0 != *MAINNET_GENESIS_HASH {
-                            trace!("Changing block parser from {}", version.version);
-                            version.version.update()?;
-
-                            trace!("Block parser changed to {}", version.version);
-                            version.genesis_state_hash = state.version.genesis_state_hash.clone();
-                        }
                     }
                     Err(e) => error!("Error parsing precomputed block: {e}"),
                 }
@@ -540,93 +648,108 @@ async fn process_event(event: Event, state: &Arc<RwLock<IndexerState>>) -> anyho
     Ok(())
 }
 
-/// Fetch new blocks
-async fn fetch_new_blocks(
-    state: &Arc<RwLock<IndexerState>>,
-    blocks_dir: impl AsRef<Path>,
-    fetch_new_blocks_exe: impl AsRef<Path>,
-) {
-    let state = state.read().await;
-    let network = state.version.network.clone();
-    let new_block_length = state.best_tip_block().blockchain_length + 1;
-    let mut c = std::process::Command::new(fetch_new_blocks_exe.as_ref().display().to_string());
-    let cmd = c.args([
-        &network.to_string(),
-        &new_block_length.to_string(),
-        &blocks_dir.as_ref().display().to_string(),
-    ]);
-    match cmd.output() {
-        Ok(output) => {
-            use std::io::*;
-            stdout().write_all(&output.stdout).ok();
-            stderr().write_all(&output.stderr).ok();
+/// Parses the block file with `version`, falling back to the other parser
+/// version, e.g. post-fork blocks without a hardfork config
+fn parse_block_file(path: &Path, version: PcbVersion) -> anyhow::Result<PrecomputedBlock> {
+    PrecomputedBlock::parse_file(path, version.clone()).or_else(|e| {
+        let fallback = match version {
+            PcbVersion::V1 => PcbVersion::V2,
+            PcbVersion::V2 => PcbVersion::V1,
+        };
+        let block = PrecomputedBlock::parse_file(path, fallback.clone()).map_err(|_| e)?;
+
+        warn!(
+            "Parsed {} block file {} as {fallback}, check the hardfork config",
+            version,
+            path.display()
+        );
+        Ok(block)
+    })
+}
+
+/// Runs `poll` every `delay` seconds until shutdown
+async fn poll_block_source<F, Fut>(
+    subsys: SubsystemHandle,
+    delay: u64,
+    poll: F,
+) -> anyhow::Result<()>
+where
+    F: Fn() -> Fut,
+    Fut: std::future::Future<Output = ()>,
+{
+    loop {
+        tokio::select! {
+            _ = subsys.on_shutdown_requested() => break,
+            _ = async {
+                tokio::time::sleep(std::time::Duration::from_secs(delay)).await;
+                poll().await
+            } => (),
         }
-        Err(e) => error!(
-            "Error fetching new blocks: {e}, pgm: {}, args: {:?}",
-            cmd.get_program().to_str().unwrap(),
-            cmd.get_args()
-                .map(|arg| arg.to_str().unwrap())
-                .collect::<Vec<&str>>()
-        ),
     }
+    Ok(())
+}
+
+/// Fetches the blocks at the height after the best tip
+async fn fetch_new_blocks(
+    state: Arc<RwLock<IndexerState>>,
+    blocks_dir: PathBuf,
+    source: Arc<dyn BlockSource>,
+) {
+    let (network, new_block_length) = {
+        let state = state.read().await;
+        (
+            state.version.network.clone(),
+            state.best_tip_block().blockchain_length + 1,
+        )
+    };
+
+    fetch_blocks(&*source, &network, new_block_length, &blocks_dir).await
 }
 
-/// Recovers missing blocks
+/// Recovers the missing parents of dangling branch roots
 async fn recover_missing_blocks(
-    state: &Arc<RwLock<IndexerState>>,
-    blocks_dir: impl AsRef<Path>,
-    missing_block_recovery_exe: impl AsRef<Path>,
+    state: Arc<RwLock<IndexerState>>,
+    blocks_dir: PathBuf,
+    source: Arc<dyn BlockSource>,
     batch_recovery: bool,
 ) {
-    let state = state.read().await;
-    let network = state.version.network.clone();
-    let missing_parent_lengths: HashSet<u32> = state
-        .dangling_branches
-        .iter()
-        .map(|b| b.root_block().blockchain_length.saturating_sub(1))
-        .collect();
-    if missing_parent_lengths.is_empty() {
-        return;
-    }
+    let (network, mut missing_lengths) = {
+        let state = state.read().await;
+        let missing_parent_lengths: BTreeSet<u32> = state
+            .dangling_branches
+            .iter()
+            .map(|b| b.root_block().blockchain_length.saturating_sub(1))
+            .collect();
+        if missing_parent_lengths.is_empty() {
+            return;
+        }
 
-    let run_missing_blocks_recovery = |blockchain_length: u32| {
-        let mut c =
-            std::process::Command::new(missing_block_recovery_exe.as_ref().display().to_string());
-        let cmd = c.args([
-            &network.to_string(),
-            &blockchain_length.to_string(),
-            &blocks_dir.as_ref().display().to_string(),
-        ]);
-        match cmd.output() {
-            Ok(output) => {
-                use std::io::*;
-                stdout().write_all(&output.stdout).unwrap();
-                stderr().write_all(&output.stderr).unwrap();
+        let mut missing_lengths = missing_parent_lengths.clone();
+        if batch_recovery {
+            let best_tip_length = state.best_tip_block().blockchain_length;
+            if let (Some(min), Some(max)) = (
+                missing_parent_lengths.first(),
+                missing_parent_lengths.last(),
+            ) {
+                missing_lengths.extend(best_tip_length.min(*min)..best_tip_length.max(*max));
             }
-            Err(e) => error!(
-                "Error recovery missing block: {e}, pgm: {}, args: {:?}",
-                cmd.get_program().to_str().unwrap(),
-                cmd.get_args()
-                    .map(|arg| arg.to_str().unwrap())
-                    .collect::<Vec<&str>>()
-            ),
         }
+        (state.version.network.clone(), missing_lengths)
     };
 
     debug!("Getting missing parent blocks of dangling roots");
-    let min_missing_length = missing_parent_lengths.iter().min().cloned();
-    let max_missing_length = missing_parent_lengths.iter().max().cloned();
-    missing_parent_lengths
-        .into_iter()
-        .for_each(run_missing_blocks_recovery);
-
-    if batch_recovery {
-        let best_tip_length = state.best_tip_block().blockchain_length;
-        if let (Some(min), Some(max)) = (min_missing_length, max_missing_length) {
-            let min_length = best_tip_length.min(min);
-            let max_length = best_tip_length.max(max);
-            (min_length..max_length).for_each(run_missing_blocks_recovery)
-        }
+    while let Some(length) = missing_lengths.pop_first() {
+        fetch_blocks(&*source, &network, length, &blocks_dir).await
+    }
+}
+
+/// Fetches the blocks at the given height, logging failures
+async fn fetch_blocks(source: &dyn BlockSource, network: &Network, height: u32, blocks_dir: &Path) {
+    match fetch_with_retry(source, &RetryPolicy::default(), network, height, blocks_dir).await {
+        Ok(paths) => paths
+            .iter()
+            .for_each(|path| debug!("Fetched block file {} from {source}", path.display())),
+        Err(e) => error!("Error fetching blocks at height {height} from {source}: {e}"),
     }
 }
 
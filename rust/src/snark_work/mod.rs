@@ -3,6 +3,7 @@ pub mod store;
 use crate::{
     block::{precomputed::PrecomputedBlock, BlockHash},
     ledger::public_key::PublicKey,
+    mina_blocks::v2::staged_ledger_diff::completed_work::CompletedWork,
     protocol::serialization_types::snark_work as mina_rs,
 };
 use serde::{Deserialize, Serialize};
@@ -56,8 +57,7 @@ impl SnarkWorkSummary {
 }
 
 impl SnarkWorkSummaryWithStateHash {
-    pub fn from(snark: mina_rs::TransactionSnarkWork, state_hash: &BlockHash) -> Self {
-        let snark: SnarkWorkSummary = snark.into();
+    pub fn from(snark: SnarkWorkSummary, state_hash: &BlockHash) -> Self {
         Self {
             fee: snark.fee,
             prover: snark.prover,
@@ -89,6 +89,17 @@ impl From<mina_rs::TransactionSnarkWork> for SnarkWorkSummary {
     }
 }
 
+// post-hardfork conversions
+
+impl From<CompletedWork> for SnarkWorkSummary {
+    fn from(value: CompletedWork) -> Self {
+        Self {
+            fee: value.fee,
+            prover: value.prover,
+        }
+    }
+}
+
 impl From<SnarkWorkSummaryWithStateHash> for SnarkWorkSummary {
     fn from(value: SnarkWorkSummaryWithStateHash) -> Self {
         Self {
@@ -1,7 +1,13 @@
 use crate::{
-    block::{precomputed::PrecomputedBlock, vrf_output::VrfOutput, Block, BlockHash},
+    block::{
+        chain_selection::{select_best, ConsensusInfo},
+        precomputed::PrecomputedBlock,
+        vrf_output::VrfOutput,
+        Block, BlockHash,
+    },
     constants::*,
 };
+use anyhow::anyhow;
 use id_tree::{
     InsertBehavior::{AsRoot, UnderNode},
     LevelOrderTraversalIds,
@@ -40,6 +46,8 @@ impl Branch {
             hash_last_vrf_output: VrfOutput::new(
                 VrfOutput::base64_decode(MAINNET_GENESIS_LAST_VRF_OUTPUT)?.hex_digest(),
             ),
+            // genesis blocks are never contested
+            consensus_info: ConsensusInfo::default(),
         };
         let mut branches = Tree::new();
         let root = branches.insert(Node::new(genesis_block), AsRoot)?;
@@ -69,30 +77,9 @@ impl Branch {
 
     /// Returns the node id of the best tip
     pub fn best_tip_id(&self) -> NodeId {
-        let mut best_tip_id = self
-            .branches
-            .root_node_id()
+        self.best_tip_with_id()
+            .map(|(node_id, _)| node_id)
             .expect("branch always has root node")
-            .clone();
-
-        for node_id in self.traverse_level_order_ids() {
-            let best_tip = self
-                .branches
-                .get(&best_tip_id)
-                .expect("branch always has root node")
-                .data()
-                .clone();
-            let node = self
-                .branches
-                .get(&node_id)
-                .expect("node_id from iterator")
-                .data()
-                .clone();
-            if best_tip.height < node.height {
-                best_tip_id = node_id.clone();
-            }
-        }
-        best_tip_id
     }
 
     /// Returns the node id of the canonical root, if it exists
@@ -339,13 +326,12 @@ impl Branch {
             .collect()
     }
 
-    pub fn leaves(&self) -> Vec<Block> {
-        self.branches
-            .traverse_level_order(self.branches.root_node_id().expect("root node id"))
-            .expect("traverse level order")
-            .filter_map(|x| {
-                if x.children().is_empty() {
-                    Some(x.data().clone())
+    pub fn leaves_with_id(&self) -> Vec<(NodeId, Block)> {
+        self.traverse_level_order_ids()
+            .filter_map(|node_id| {
+                let node = self.branches.get(&node_id).unwrap();
+                if node.children().is_empty() {
+                    Some((node_id, node.data().clone()))
                 } else {
                     None
                 }
@@ -353,20 +339,28 @@ impl Branch {
             .collect()
     }
 
+    pub fn leaves(&self) -> Vec<Block> {
+        self.leaves_with_id()
+            .into_iter()
+            .map(|(_, block)| block)
+            .collect()
+    }
+
     // Always returns some for a non-empty tree
     pub fn best_tip(&self) -> Option<Block> {
         self.best_tip_with_id().map(|(_, x)| x)
     }
 
-    // Always returns some for a non-empty tree
+    // Always returns some for a non-empty tree, selected from all leaves since
+    // a shorter, denser fork can win the long-range fork rule
     pub fn best_tip_with_id(&self) -> Option<(NodeId, Block)> {
-        let mut leaves = self.top_leaves_with_id();
-        leaves.sort_by(|(_, x), (_, y)| x.cmp(y).reverse());
-        leaves.first().cloned()
+        let leaves = self.leaves_with_id();
+        let best_tip = select_best(leaves.iter().map(|(_, block)| block))?;
+        leaves.iter().find(|(_, block)| block == best_tip).cloned()
     }
 
-    /// Returns the `BlockHash`es of the longest chain in the branch,
-    /// sorted from highest to lowest
+    /// Returns the `BlockHash`es of the best chain in the branch, i.e. the
+    /// best tip & its ancestors, sorted from highest to lowest
     pub fn longest_chain(&self) -> Vec<BlockHash> {
         let mut longest_chain = Vec::new();
         if let Some((node_id, _)) = self.best_tip_with_id() {
@@ -397,6 +391,41 @@ impl Branch {
         self.branches.height() as u32
     }
 
+    /// Returns the blocks of the branch in level order, starting with the
+    /// root block
+    pub fn blocks(&self) -> Vec<Block> {
+        self.traverse_level_order_ids()
+            .map(|node_id| self.branches.get(&node_id).unwrap().data().clone())
+            .collect()
+    }
+
+    /// Rebuilds a branch from its [Branch::blocks]
+    pub fn from_blocks(blocks: Vec<Block>) -> anyhow::Result<Self> {
+        let mut blocks = blocks.into_iter();
+        let root_block = blocks
+            .next()
+            .ok_or_else(|| anyhow!("branch without blocks"))?;
+
+        let mut branches = Tree::new();
+        let root = branches.insert(Node::new(root_block.clone()), AsRoot)?;
+        let mut node_ids = HashMap::from([(root_block.state_hash, root.clone())]);
+        for block in blocks {
+            let parent_id = node_ids
+                .get(&block.parent_hash)
+                .ok_or_else(|| anyhow!("parent missing from branch: {}", block.parent_hash))?
+                .clone();
+            let node_id = branches.insert(Node::new(block.clone()), UnderNode(&parent_id))?;
+            node_ids.insert(block.state_hash, node_id);
+        }
+        Ok(Self { root, branches })
+    }
+
+    /// Returns the node id of the block with `state_hash`
+    pub fn node_id(&self, state_hash: &BlockHash) -> Option<NodeId> {
+        self.traverse_level_order_ids()
+            .find(|node_id| &self.branches.get(node_id).unwrap().data().state_hash == state_hash)
+    }
+
     pub fn mem(&self, state_hash: &BlockHash) -> bool {
         for node in self
             .branches
@@ -0,0 +1,195 @@
+//! Witness tree export for diagnosing stuck or forked ingestion
+
+use super::store::PersistedWitnessTree;
+use crate::block::{Block, BlockHash};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// Root & dangling branches of the witness tree
+///
+/// Branches are exported as their blocks in level order, starting with the
+/// branch root
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WitnessTreeExport {
+    pub best_tip: BlockHash,
+    pub canonical_root: BlockHash,
+    pub root_branch: Vec<BlockExport>,
+    pub dangling_branches: Vec<Vec<BlockExport>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockExport {
+    pub state_hash: BlockHash,
+    pub parent_hash: BlockHash,
+    pub blockchain_length: u32,
+    pub global_slot_since_genesis: u32,
+    pub is_best_tip: bool,
+    pub is_canonical_root: bool,
+}
+
+impl WitnessTreeExport {
+    pub fn new(
+        root_branch: &[Block],
+        dangling_branches: &[Vec<Block>],
+        best_tip: &BlockHash,
+        canonical_root: &BlockHash,
+    ) -> Self {
+        let export_branch = |blocks: &[Block]| {
+            blocks
+                .iter()
+                .map(|block| BlockExport {
+                    state_hash: block.state_hash.clone(),
+                    parent_hash: block.parent_hash.clone(),
+                    blockchain_length: block.blockchain_length,
+                    global_slot_since_genesis: block.global_slot_since_genesis,
+                    is_best_tip: &block.state_hash == best_tip,
+                    is_canonical_root: &block.state_hash == canonical_root,
+                })
+                .collect()
+        };
+
+        Self {
+            best_tip: best_tip.clone(),
+            canonical_root: canonical_root.clone(),
+            root_branch: export_branch(root_branch),
+            dangling_branches: dangling_branches
+                .iter()
+                .map(|blocks| export_branch(blocks))
+                .collect(),
+        }
+    }
+
+    /// Graphviz DOT digraph with an edge from each parent to its child
+    ///
+    /// Each branch is a cluster, the missing parents of dangling branch roots
+    /// are dashed
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        self.write_dot(&mut dot).expect("write to string");
+        dot
+    }
+
+    fn write_dot(&self, dot: &mut String) -> std::fmt::Result {
+        writeln!(dot, "digraph witness_tree {{\n    node [shape=box];")?;
+        write_branch(dot, "root", "root branch", &self.root_branch, false)?;
+
+        for (n, blocks) in self.dangling_branches.iter().enumerate() {
+            let label = format!("dangling branch {n}");
+            write_branch(dot, &format!("dangling_{n}"), &label, blocks, true)?;
+        }
+        writeln!(dot, "}}")
+    }
+}
+
+fn write_branch(
+    dot: &mut String,
+    name: &str,
+    label: &str,
+    blocks: &[BlockExport],
+    is_dangling: bool,
+) -> std::fmt::Result {
+    writeln!(
+        dot,
+        "    subgraph cluster_{name} {{\n        label=\"{label}\";"
+    )?;
+    for block in blocks {
+        let style = if block.is_best_tip {
+            ", style=filled, fillcolor=palegreen"
+        } else if block.is_canonical_root {
+            ", style=filled, fillcolor=lightblue"
+        } else {
+            ""
+        };
+        writeln!(
+            dot,
+            "        \"{}\" [label=\"{} (slot {})\\n{}\"{style}];",
+            block.state_hash,
+            block.blockchain_length,
+            block.global_slot_since_genesis,
+            block.state_hash,
+        )?;
+    }
+    writeln!(dot, "    }}")?;
+
+    // the root branch root's parent has been pruned
+    let skip = if is_dangling { 0 } else { 1 };
+    if let Some(root) = blocks.first().filter(|_| is_dangling) {
+        writeln!(
+            dot,
+            "    \"{}\" [label=\"missing\\n{}\", style=dashed];",
+            root.parent_hash, root.parent_hash,
+        )?;
+    }
+    for block in blocks.iter().skip(skip) {
+        writeln!(
+            dot,
+            "    \"{}\" -> \"{}\";",
+            block.parent_hash, block.state_hash
+        )?;
+    }
+    Ok(())
+}
+
+impl From<&PersistedWitnessTree> for WitnessTreeExport {
+    fn from(value: &PersistedWitnessTree) -> Self {
+        Self::new(
+            &value.root_branch,
+            &value.dangling_branches,
+            &value.best_tip,
+            &value.canonical_root,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{chain_selection::ConsensusInfo, vrf_output::VrfOutput};
+
+    fn block(state_hash: &str, parent_hash: &str, blockchain_length: u32) -> Block {
+        Block {
+            state_hash: state_hash.into(),
+            parent_hash: parent_hash.into(),
+            height: 0,
+            blockchain_length,
+            genesis_state_hash: "genesis".into(),
+            global_slot_since_genesis: blockchain_length * 2,
+            hash_last_vrf_output: VrfOutput::new(vec![]),
+            consensus_info: ConsensusInfo::default(),
+        }
+    }
+
+    #[test]
+    fn to_dot() {
+        let export = WitnessTreeExport::new(
+            &[block("a", "z", 1), block("b", "a", 2), block("c", "a", 2)],
+            &[vec![block("e", "d", 4)]],
+            &"b".into(),
+            &"a".into(),
+        );
+
+        assert!(export.root_branch[1].is_best_tip);
+        assert!(export.root_branch[0].is_canonical_root);
+        assert_eq!(
+            export.to_dot(),
+            r#"digraph witness_tree {
+    node [shape=box];
+    subgraph cluster_root {
+        label="root branch";
+        "a" [label="1 (slot 2)\na", style=filled, fillcolor=lightblue];
+        "b" [label="2 (slot 4)\nb", style=filled, fillcolor=palegreen];
+        "c" [label="2 (slot 4)\nc"];
+    }
+    "a" -> "b";
+    "a" -> "c";
+    subgraph cluster_dangling_0 {
+        label="dangling branch 0";
+        "e" [label="4 (slot 8)\ne"];
+    }
+    "d" [label="missing\nd", style=dashed];
+    "d" -> "e";
+}
+"#
+        );
+    }
+}
@@ -1,11 +1,15 @@
 pub mod branch;
+pub mod export;
+pub mod store;
 pub mod summary;
 
 use crate::{
     block::{
+        chain_selection::ChainSelection,
+        extract_block_height, extract_state_hash,
         genesis::GenesisBlock,
         parser::{BlockParser, ParsedBlock},
-        precomputed::PrecomputedBlock,
+        precomputed::{PcbVersion, PrecomputedBlock},
         store::BlockStore,
         Block, BlockHash, BlockWithoutHeight,
     },
@@ -14,13 +18,21 @@ use crate::{
     constants::*,
     event::{db::*, store::*, witness_tree::*, IndexerEvent},
     ledger::{
-        diff::LedgerDiff, genesis::GenesisLedger, public_key::PublicKey,
-        staking::parser::StakingLedgerParser, store::LedgerStore, username::Username, Ledger,
-        LedgerHash,
+        account::Account,
+        diff::LedgerDiff,
+        genesis::{ForkConfig, GenesisLedger, HardforkGenesis},
+        public_key::PublicKey,
+        staking::parser::StakingLedgerParser,
+        store::LedgerStore,
+        token::TokenId,
+        username::Username,
+        Ledger, LedgerHash,
     },
     server::IndexerVersion,
     state::{
         branch::Branch,
+        export::WitnessTreeExport,
+        store::{PersistedWitnessTree, WitnessTreeStore},
         summary::{
             DbStats, SummaryShort, SummaryVerbose, WitnessTreeSummaryShort,
             WitnessTreeSummaryVerbose,
@@ -28,6 +40,7 @@ use crate::{
     },
     store::{
         block_state_hash_from_key, block_u32_prefix_from_key,
+        checkpoint::{IngestionCheckpoint, IngestionCheckpointStore},
         fixed_keys::FixedKeys,
         from_be_bytes, from_u64_be_bytes,
         ledger_store_impl::{
@@ -39,9 +52,9 @@ use crate::{
         IndexerStore,
     },
 };
-use anyhow::{bail, Context};
+use anyhow::{anyhow, bail, Context};
 use id_tree::NodeId;
-use log::{debug, error, info, trace};
+use log::{debug, error, info, trace, warn};
 use std::{
     collections::HashMap,
     str::FromStr,
@@ -112,6 +125,9 @@ pub struct IndexerState {
 
     /// Network blocks and staking ledgers to be processed
     pub version: IndexerVersion,
+
+    /// Pending hardfork, taken once its genesis block is added
+    pub hardfork: Option<HardforkGenesis>,
 }
 
 #[derive(Debug, Clone)]
@@ -156,6 +172,7 @@ pub struct IndexerStateConfig {
     pub canonical_update_threshold: u32,
     pub ledger_cadence: u32,
     pub reporting_freq: u32,
+    pub hardfork: Option<HardforkGenesis>,
 }
 
 impl IndexerStateConfig {
@@ -177,6 +194,7 @@ impl IndexerStateConfig {
             canonical_update_threshold: CANONICAL_UPDATE_THRESHOLD,
             ledger_cadence: LEDGER_CADENCE,
             reporting_freq: BLOCK_REPORTING_FREQ_NUM,
+            hardfork: None,
         }
     }
 }
@@ -244,7 +262,7 @@ impl IndexerState {
             state_hash: root_branch.root_block().state_hash.clone(),
             node_id: root_branch.root.clone(),
         };
-        Ok(Self {
+        let state = Self {
             ledger: <GenesisLedger as Into<Ledger>>::into(config.genesis_ledger)
                 .apply_diff_from_precomputed(&genesis_block)?,
             diffs_map: HashMap::from([(
@@ -268,7 +286,15 @@ impl IndexerState {
             ledger_cadence: config.ledger_cadence,
             reporting_freq: config.reporting_freq,
             staking_ledgers: HashMap::new(),
-        })
+            hardfork: config.hardfork,
+        };
+
+        // genesis accounts start their history at the genesis block
+        state.add_ledger_account_history(
+            &genesis_block.state_hash(),
+            genesis_block.blockchain_length(),
+        )?;
+        Ok(state)
     }
 
     /// Creates a new indexer state without genesis events
@@ -300,6 +326,7 @@ impl IndexerState {
             ledger_cadence: config.ledger_cadence,
             reporting_freq: config.reporting_freq,
             staking_ledgers: HashMap::new(),
+            hardfork: config.hardfork,
         })
     }
 
@@ -359,6 +386,7 @@ impl IndexerState {
             reporting_freq: reporting_freq.unwrap_or(BLOCK_REPORTING_FREQ_NUM),
             staking_ledgers: HashMap::new(),
             version: IndexerVersion::new_testing(),
+            hardfork: None,
         })
     }
 
@@ -372,9 +400,7 @@ impl IndexerState {
     ) -> anyhow::Result<()> {
         info!("Initializing indexer with canonical chain discovery");
         let total_time = Instant::now();
-        if let Some(indexer_store) = self.indexer_store.as_ref() {
-            let mut ledger_diff = LedgerDiff::default();
-
+        if let Some(indexer_store) = self.indexer_store.clone() {
             if block_parser.num_deep_canonical_blocks > self.reporting_freq {
                 info!(
                     "Adding blocks to the witness tree, reporting every {}...",
@@ -395,31 +421,53 @@ impl IndexerState {
                     let state_hash = block.state_hash();
                     self.bytes_processed += block_bytes;
 
-                    // aggregate diffs, apply, and add to db
-                    let diff = LedgerDiff::from_precomputed(&block);
-                    ledger_diff.append(diff);
-
-                    indexer_store.add_block(&block, block_bytes)?;
-                    indexer_store.set_best_block(&block.state_hash())?;
-                    indexer_store.add_canonical_block(
-                        block.blockchain_length(),
-                        block.global_slot_since_genesis(),
-                        &block.state_hash(),
-                        &block.genesis_state_hash(),
-                        None,
-                    )?;
+                    if let Some(hardfork) = self.take_hardfork(&block) {
+                        // store the pre-fork ledger at the fork block & root
+                        // the ledger at the hardfork genesis block
+                        indexer_store.add_ledger_state_hash(
+                            &block.previous_state_hash(),
+                            self.ledger.clone(),
+                        )?;
+
+                        indexer_store.add_block(&block, block_bytes)?;
+                        self.add_hardfork_genesis(&block, hardfork)?;
+                    } else {
+                        // apply diff & add to db
+                        let diff = LedgerDiff::from_precomputed(&block);
+                        self.apply_canonical_diff(&diff, block.blockchain_length())?;
+
+                        // blocks applied again after resuming from an ingestion
+                        // checkpoint are already canonical
+                        if indexer_store.add_block(&block, block_bytes)?.is_some() {
+                            indexer_store.set_best_block(&block.state_hash())?;
+                            indexer_store.add_canonical_block(
+                                block.blockchain_length(),
+                                block.global_slot_since_genesis(),
+                                &block.state_hash(),
+                                &block.genesis_state_hash(),
+                                None,
+                            )?;
+                        }
+                    }
 
                     // compute and store ledger at specified cadence
                     if self.blocks_processed % self.ledger_cadence == 0 {
-                        self.ledger._apply_diff(&ledger_diff)?;
-                        ledger_diff = LedgerDiff::default();
                         indexer_store.add_ledger_state_hash(&state_hash, self.ledger.clone())?;
+
+                        // the ledger is stored, ingestion can resume from here
+                        if self.blocks_processed <= block_parser.num_deep_canonical_blocks {
+                            self.set_ingestion_checkpoint(
+                                block_parser,
+                                block.blockchain_length(),
+                                &state_hash,
+                                None,
+                            )?;
+                        }
                     }
 
                     if self.blocks_processed == block_parser.num_deep_canonical_blocks + 1 {
                         // update root branch on last deep canonical block
                         self.root_branch = Branch::new(&block)?;
-                        self.ledger._apply_diff(&ledger_diff)?;
                         self.best_tip = Tip {
                             state_hash: self.root_branch.root_block().state_hash.clone(),
                             node_id: self.root_branch.root.clone(),
@@ -435,6 +483,16 @@ impl IndexerState {
                 self.blocks_processed,
                 block_parser.num_deep_canonical_blocks + 1
             ); // +1 genesis
+
+            // recent blocks are recovered by syncing from the db, orphaned blocks
+            // are not
+            let canonical_root = self.canonical_root_block().clone();
+            self.set_ingestion_checkpoint(
+                block_parser,
+                canonical_root.blockchain_length,
+                &canonical_root.state_hash,
+                Some(block_parser.num_pending_orphaned_blocks()),
+            )?;
         }
 
         self.report_from_block_count(block_parser, total_time);
@@ -442,24 +500,100 @@ impl IndexerState {
         info!("Adding recent blocks to the witness tree and orphaned blocks to the block store");
 
         // deep canonical & recent blocks added, now add orphaned blocks
-        self.add_blocks_with_time(block_parser, Some(total_time.elapsed()))
+        self.add_blocks_with_time(block_parser, Some(total_time.elapsed()), true)
+            .await
+    }
+
+    /// Resumes [Self::initialize_with_canonical_chain_discovery] from the
+    /// ingestion `checkpoint` of an interrupted run
+    ///
+    /// `block_parser` (with canonical chain discovery) skips the paths
+    /// consumed before the checkpoint
+    /// - while applying deep canonical blocks, the ledger is restored from the
+    ///   store
+    /// - afterwards, the state is synced from the db & `block_parser`
+    ///   traverses the remaining recent, then pending orphaned blocks
+    pub async fn resume_from_checkpoint(
+        &mut self,
+        block_parser: &mut BlockParser,
+        checkpoint: &IngestionCheckpoint,
+    ) -> anyhow::Result<()> {
+        let indexer_store = self.indexer_store_or_panic().clone();
+        let last_path = block_parser.skip(checkpoint.parser_position);
+        let blocks_changed = || {
+            anyhow!(
+                "Blocks in {} changed since the ingestion checkpoint at {}, remove the database to start over",
+                block_parser.blocks_dir.display(),
+                checkpoint,
+            )
+        };
+
+        // the parser must have skipped to the same block path
+        let last_state_hash: Option<BlockHash> = last_path
+            .as_deref()
+            .map(|path| extract_state_hash(path).into());
+        if last_state_hash.is_none() || last_state_hash != checkpoint.parser_state_hash {
+            return Err(blocks_changed());
+        }
+
+        if let Some(num_pending) = checkpoint.num_pending_orphaned_blocks {
+            if block_parser.num_pending_orphaned_blocks() != num_pending {
+                return Err(blocks_changed());
+            }
+
+            self.sync_from_db()?;
+            let root_state_hash = self.canonical_root_block().state_hash.clone();
+            if let Some(ledger) = indexer_store.get_ledger_state_hash(&root_state_hash, false)? {
+                self.ledger = ledger;
+            }
+            return self.add_blocks_with_time(block_parser, None, true).await;
+        }
+
+        // the checkpoint block must be the last skipped block
+        if last_state_hash.as_ref() != Some(&checkpoint.canonical_state_hash) {
+            return Err(blocks_changed());
+        }
+        block_parser.num_deep_canonical_blocks = checkpoint.num_deep_canonical_blocks;
+
+        self.ledger = indexer_store
+            .get_ledger_state_hash(&checkpoint.canonical_state_hash, false)?
+            .with_context(|| {
+                format!(
+                    "Ledger missing from store for checkpoint block {}",
+                    checkpoint.canonical_state_hash
+                )
+            })?;
+        self.blocks_processed = checkpoint.blocks_processed;
+        self.bytes_processed = checkpoint.bytes_processed;
+
+        // the checkpoint block may already be past the hardfork
+        if let Some((block, _)) = indexer_store.get_block(&checkpoint.canonical_state_hash)? {
+            self.skip_past_hardfork(block.blockchain_length(), block.genesis_state_hash());
+        }
+
+        info!("Resuming ingestion at {checkpoint}");
+        self.initialize_with_canonical_chain_discovery(block_parser)
             .await
     }
 
     /// Adds blocks to the state according to `block_parser` then changes phase
     /// to Watching
     pub async fn add_blocks(&mut self, block_parser: &mut BlockParser) -> anyhow::Result<()> {
-        self.add_blocks_with_time(block_parser, None).await
+        self.add_blocks_with_time(block_parser, None, false).await
     }
 
+    /// With `checkpoint`, records ingestion checkpoints of the pending orphaned
+    /// blocks & removes the checkpoint after the last block
     async fn add_blocks_with_time(
         &mut self,
         block_parser: &mut BlockParser,
         elapsed: Option<Duration>,
+        checkpoint: bool,
     ) -> anyhow::Result<()> {
         let total_time = Instant::now();
         let offset = elapsed.unwrap_or(Duration::new(0, 0));
         let mut step_time = total_time;
+        let mut num_orphaned_blocks = 0;
         if block_parser.total_num_blocks > self.reporting_freq {
             info!(
                 "Reporting every {BLOCK_REPORTING_FREQ_SEC}s or {} blocks",
@@ -490,10 +624,26 @@ impl IndexerState {
                                 ParsedBlock::Orphaned(block) => {
                                     trace!("Adding orphaned block to store {}", block.summary());
                                     self.add_block_to_store(&block, block_bytes, true)?;
+
+                                    num_orphaned_blocks += 1;
+                                    if checkpoint && num_orphaned_blocks % self.reporting_freq == 0 {
+                                        let canonical_root = self.canonical_root_block().clone();
+                                        self.set_ingestion_checkpoint(
+                                            block_parser,
+                                            canonical_root.blockchain_length,
+                                            &canonical_root.state_hash,
+                                            Some(block_parser.num_pending_orphaned_blocks()),
+                                        )?;
+                                    }
                                 }
                             }
                         }
                         Ok(None) => {
+                            if checkpoint {
+                                if let Some(indexer_store) = self.indexer_store.as_ref() {
+                                    indexer_store.remove_ingestion_checkpoint()?;
+                                }
+                            }
                             info!(
                                 "Ingested and applied {} blocks ({}) to the witness tree in {:?}",
                                 self.blocks_processed,
@@ -525,6 +675,10 @@ impl IndexerState {
         block: &PrecomputedBlock,
         block_bytes: u64,
     ) -> anyhow::Result<bool> {
+        if let Some(hardfork) = self.take_hardfork(block) {
+            return self.hardfork_pipeline(block, block_bytes, hardfork);
+        }
+
         if let Some(db_event) = self.add_block_to_store(block, block_bytes, false)? {
             self.bytes_processed += block_bytes;
             let (best_tip, new_canonical_blocks) = if db_event.is_new_block_event() {
@@ -545,7 +699,7 @@ impl IndexerState {
 
             if let Some(username_updates) = self.update_best_block_in_store(&best_tip.state_hash)? {
                 for (pk, username) in username_updates.iter() {
-                    if let Some(account) = self.ledger.accounts.get_mut(pk) {
+                    if let Some(account) = self.ledger.get_mut_account(pk, &TokenId::default()) {
                         account.username = Some(username.clone());
                     }
                 }
@@ -559,6 +713,153 @@ impl IndexerState {
         Ok(true)
     }
 
+    /// **Hardfork pipeline**
+    /// - add the hardfork genesis block to the block store
+    /// - the pre-fork chain becomes canonical up to the fork block
+    /// - root the witness tree & ledger at the hardfork genesis block
+    fn hardfork_pipeline(
+        &mut self,
+        block: &PrecomputedBlock,
+        block_bytes: u64,
+        hardfork: HardforkGenesis,
+    ) -> anyhow::Result<bool> {
+        if let Some(db_event) = self.add_block_to_store(block, block_bytes, false)? {
+            if !db_event.is_new_block_event() {
+                debug!("Block not added: {db_event:?}");
+                self.hardfork = Some(hardfork);
+                return Ok(false);
+            }
+        }
+        self.bytes_processed += block_bytes;
+        self.blocks_processed += 1;
+
+        // canonical blocks between the canonical root & the fork block
+        let fork_block = &hardfork.fork.state_hash;
+        let mut canonical_blocks = vec![];
+        if let Some(fork_block_id) = self
+            .root_branch
+            .branches
+            .traverse_pre_order_ids(&self.root_branch.root)?
+            .find(|id| self.get_block_from_id(id).state_hash == *fork_block)
+        {
+            for id in std::iter::once(&fork_block_id)
+                .chain(self.root_branch.branches.ancestor_ids(&fork_block_id)?)
+            {
+                if *id == self.canonical_root.node_id {
+                    break;
+                }
+                canonical_blocks.push(self.get_block_from_id(id).clone());
+            }
+        } else {
+            warn!("Fork block missing from the witness tree {fork_block}");
+        }
+        canonical_blocks.reverse();
+
+        // the pre-fork ledger is kept at the fork block
+        self.update_ledger(&canonical_blocks)?;
+        for block in canonical_blocks.iter() {
+            self.add_canonical_block_to_store(block, &block.genesis_state_hash, None)?;
+        }
+        if let Some(indexer_store) = self.indexer_store.as_ref() {
+            indexer_store.add_ledger_state_hash(fork_block, self.ledger.clone())?;
+        }
+
+        self.add_hardfork_genesis(block, hardfork)?;
+        self.root_branch = Branch::new(block)?;
+        self.best_tip = Tip {
+            state_hash: self.root_branch.root_block().state_hash.clone(),
+            node_id: self.root_branch.root.clone(),
+        };
+        self.canonical_root = self.best_tip.clone();
+        self.dangling_branches
+            .retain(|branch| branch.root_block().blockchain_length > block.blockchain_length());
+        Ok(true)
+    }
+
+    /// Takes the pending hardfork if `block` is its genesis block
+    fn take_hardfork(&mut self, block: &PrecomputedBlock) -> Option<HardforkGenesis> {
+        if self
+            .hardfork
+            .as_ref()
+            .is_some_and(|hardfork| hardfork.is_fork_genesis(block))
+        {
+            return self.hardfork.take();
+        }
+        None
+    }
+
+    /// Replaces the ledger with the hardfork genesis ledger, marks the hardfork
+    /// genesis block canonical & switches to post-fork blocks
+    ///
+    /// The hardfork genesis block must already be in the block store
+    fn add_hardfork_genesis(
+        &mut self,
+        block: &PrecomputedBlock,
+        hardfork: HardforkGenesis,
+    ) -> anyhow::Result<()> {
+        let genesis_state_hash = block.state_hash();
+        info!("Adding hardfork genesis block {}", block.summary());
+
+        // global slots continue across the fork
+        if block.global_slot_since_genesis() < hardfork.fork.global_slot_since_genesis {
+            bail!(
+                "Hardfork genesis block global slot {} is below the fork block global slot {}",
+                block.global_slot_since_genesis(),
+                hardfork.fork.global_slot_since_genesis,
+            );
+        }
+
+        self.ledger = <GenesisLedger as Into<Ledger>>::into(hardfork.ledger)
+            .apply_diff_from_precomputed(block)?;
+        self.diffs_map = HashMap::from([(
+            genesis_state_hash.clone(),
+            LedgerDiff::from_precomputed(block),
+        )]);
+
+        if let Some(indexer_store) = self.indexer_store.as_ref() {
+            indexer_store.set_best_block(&genesis_state_hash)?;
+            indexer_store.add_canonical_block(
+                block.blockchain_length(),
+                block.global_slot_since_genesis(),
+                &genesis_state_hash,
+                &genesis_state_hash,
+                Some(&hardfork.fork.state_hash),
+            )?;
+
+            // overwrites the pre-fork account balances
+            indexer_store.add_genesis_ledger(&genesis_state_hash, self.ledger.clone())?;
+        }
+        self.add_ledger_account_history(&genesis_state_hash, block.blockchain_length())?;
+
+        self.version.genesis_state_hash = genesis_state_hash;
+        self.version.version = PcbVersion::V2;
+        Ok(())
+    }
+
+    /// Fork block of the pending hardfork
+    ///
+    /// Without a hardfork config, pre-fork mainnet indexers use the mainnet
+    /// fork block
+    pub fn fork_config(&self) -> Option<ForkConfig> {
+        match self.hardfork.as_ref() {
+            Some(hardfork) => Some(hardfork.fork.clone()),
+            None if self.version.version == PcbVersion::V1
+                && self.version.genesis_state_hash.0 == MAINNET_GENESIS_HASH =>
+            {
+                Some(ForkConfig::mainnet())
+            }
+            None => None,
+        }
+    }
+
+    /// Precomputed block version of the block file at `path`
+    pub fn block_version(&self, path: &std::path::Path) -> PcbVersion {
+        match (self.fork_config(), extract_block_height(path)) {
+            (Some(fork), Some(height)) if fork.is_post_fork(height) => PcbVersion::V2,
+            _ => self.version.version.clone(),
+        }
+    }
+
     /// Adds the block to the witness tree & skips store operations
     pub fn add_block_to_witness_tree(
         &mut self,
@@ -644,12 +945,7 @@ impl IndexerState {
                 }
             }
 
-            let best_tip_id = merged_tip_ids.iter().min_by(|a, b| {
-                let a_best_block = self.root_branch.branches.get(a).unwrap().data().clone();
-                let b_best_block = self.root_branch.branches.get(b).unwrap().data().clone();
-                a_best_block.cmp(&b_best_block)
-            });
-            if let Some(merged_tip_id) = best_tip_id {
+            for merged_tip_id in merged_tip_ids.iter() {
                 let merged_tip_block = self
                     .root_branch
                     .branches
@@ -770,15 +1066,17 @@ impl IndexerState {
         Ok(ExtensionType::DanglingNew)
     }
 
-    /// Checks if it's even possible to add block to the root branch
+    /// Checks if it's even possible to add block to the root branch, i.e. it
+    /// is at most one above the tallest leaf, which may not be the best tip
     fn is_length_within_root_bounds(&self, precomputed_block: &PrecomputedBlock) -> bool {
-        self.best_tip_block().blockchain_length + 1 >= precomputed_block.blockchain_length()
+        self.root_branch.root_block().blockchain_length + self.root_branch.height()
+            >= precomputed_block.blockchain_length()
     }
 
     /// Update the best tip of the root branch if the incoming block is better
     fn update_best_tip(&mut self, incoming_block: &Block, node_id: &NodeId) {
         let old_best_tip = self.best_tip_block();
-        if incoming_block < old_best_tip {
+        if incoming_block.is_better_chain(old_best_tip) {
             info!(
                 "Update best tip\n    old: {}\n    new: {}",
                 old_best_tip.summary(),
@@ -1008,16 +1306,63 @@ impl IndexerState {
         Ok(None)
     }
 
+    /// Records the progress of `block_parser` as the ingestion checkpoint
+    fn set_ingestion_checkpoint(
+        &self,
+        block_parser: &BlockParser,
+        canonical_height: u32,
+        canonical_state_hash: &BlockHash,
+        num_pending_orphaned_blocks: Option<u32>,
+    ) -> anyhow::Result<()> {
+        if let Some(indexer_store) = self.indexer_store.as_ref() {
+            let checkpoint = IngestionCheckpoint {
+                blocks_dir: block_parser.blocks_dir.clone(),
+                canonical_height,
+                canonical_state_hash: canonical_state_hash.clone(),
+                parser_position: block_parser.position(),
+                parser_state_hash: block_parser
+                    .last_path()
+                    .map(|path| extract_state_hash(path).into()),
+                num_deep_canonical_blocks: block_parser.num_deep_canonical_blocks,
+                blocks_processed: self.blocks_processed,
+                bytes_processed: self.bytes_processed,
+                num_pending_orphaned_blocks,
+            };
+            debug!("Ingestion checkpoint at {checkpoint}");
+            indexer_store.set_ingestion_checkpoint(&checkpoint)?;
+        }
+        Ok(())
+    }
+
     /// Sync from an existing db
     ///
-    /// Short-circuits adding blocks to the witness tree by rooting the
-    /// witness tree at the most recent deep canonical block and only adding
-    /// the successive blocks
+    /// Restores the persisted witness tree if it's consistent with the event
+    /// log (see [Self::persist_witness_tree]). Otherwise, short-circuits
+    /// adding blocks to the witness tree by rooting the witness tree at the
+    /// most recent deep canonical block and only adding the successive blocks
     pub fn sync_from_db(&mut self) -> anyhow::Result<Option<u32>> {
+        if let Some(indexer_store) = self.indexer_store.clone() {
+            if self.restore_witness_tree(&indexer_store)? {
+                let root_block = self.root_branch.root_block().clone();
+                info!(
+                    "Restored persisted witness tree rooted at block (length {}): {}",
+                    root_block.blockchain_length, root_block.state_hash
+                );
+
+                self.skip_past_hardfork(
+                    root_block.blockchain_length,
+                    root_block.genesis_state_hash,
+                );
+                self.staking_ledgers = self.stored_staking_ledgers(&indexer_store);
+                self.sync_processed_counts(&indexer_store)?;
+                return Ok(Some(root_block.blockchain_length));
+            }
+        }
+
         let mut min_length_filter = None;
         let mut witness_tree_blocks = vec![];
         let mut staking_ledgers = HashMap::new();
-        if let Some(indexer_store) = self.indexer_store.as_ref() {
+        if let Some(indexer_store) = self.indexer_store.clone() {
             debug!("Looking for witness tree root block");
             let next_seq_num = indexer_store.get_next_seq_num()?;
             let best_block_height = indexer_store.get_best_block_height()?.unwrap_or_default();
@@ -1061,6 +1406,9 @@ impl IndexerState {
                     self.best_tip = tip;
                     debug!("Witness tree root block (length {root_block_height}): {state_hash}");
 
+                    // the db may already be past the hardfork
+                    self.skip_past_hardfork(*root_block_height, root_block.genesis_state_hash());
+
                     // collect witness tree blocks
                     indexer_store
                         .blocks_height_iterator(speedb::IteratorMode::From(
@@ -1083,18 +1431,7 @@ impl IndexerState {
                         });
 
                     // collect staking ledger data
-                    for (key, _) in indexer_store
-                        .staking_ledger_epoch_iterator(speedb::IteratorMode::End)
-                        .flatten()
-                    {
-                        if staking_ledger_epoch_key_genesis_state_hash(&key).0
-                            == MAINNET_GENESIS_HASH
-                        {
-                            let epoch = staking_ledger_epoch_key_epoch(&key);
-                            let ledger_hash = staking_ledger_epoch_key_ledger_hash(&key);
-                            staking_ledgers.insert(epoch, ledger_hash);
-                        }
-                    }
+                    staking_ledgers = self.stored_staking_ledgers(&indexer_store);
                 } else {
                     panic!("Fatal sync error: block missing from db {state_hash}")
                 }
@@ -1118,11 +1455,7 @@ impl IndexerState {
                         }
                     });
             }
-            self.blocks_processed = indexer_store.get_block_production_total_count()?;
-            self.bytes_processed = indexer_store
-                .database
-                .get(IndexerStore::NUM_BLOCK_BYTES_PROCESSED)?
-                .map_or(self.genesis_bytes, from_u64_be_bytes);
+            self.sync_processed_counts(&indexer_store)?;
         } else {
             panic!("Fatal sync error: no indexer store");
         };
@@ -1136,6 +1469,146 @@ impl IndexerState {
         Ok(min_length_filter)
     }
 
+    /// Persists the witness tree to the db, restored by [Self::sync_from_db]
+    pub fn persist_witness_tree(&self) -> anyhow::Result<()> {
+        if let Some(indexer_store) = self.indexer_store.as_ref() {
+            let witness_tree = PersistedWitnessTree {
+                next_seq_num: indexer_store.get_next_seq_num()?,
+                root_branch: self.root_branch.blocks(),
+                dangling_branches: self.dangling_branches.iter().map(Branch::blocks).collect(),
+                diffs_map: self
+                    .diffs_map
+                    .iter()
+                    .map(|(state_hash, diff)| (state_hash.clone(), diff.clone()))
+                    .collect(),
+                best_tip: self.best_tip.state_hash.clone(),
+                canonical_root: self.canonical_root.state_hash.clone(),
+            };
+            debug!(
+                "Persisting witness tree ({} blocks, {} dangling branches)",
+                witness_tree.root_branch.len(),
+                witness_tree.dangling_branches.len()
+            );
+            indexer_store.set_witness_tree(&witness_tree)?;
+        }
+        Ok(())
+    }
+
+    /// Exports the root & dangling branches for debugging
+    pub fn export_witness_tree(&self) -> WitnessTreeExport {
+        WitnessTreeExport::new(
+            &self.root_branch.blocks(),
+            &self
+                .dangling_branches
+                .iter()
+                .map(Branch::blocks)
+                .collect::<Vec<_>>(),
+            &self.best_tip.state_hash,
+            &self.canonical_root.state_hash,
+        )
+    }
+
+    /// Restores the persisted witness tree, unless events were recorded after
+    /// it was persisted. Returns whether the witness tree was restored
+    fn restore_witness_tree(&mut self, indexer_store: &IndexerStore) -> anyhow::Result<bool> {
+        let witness_tree = match indexer_store.get_witness_tree()? {
+            Some(witness_tree) => witness_tree,
+            None => return Ok(false),
+        };
+
+        // the event log must not have moved on
+        let next_seq_num = indexer_store.get_next_seq_num()?;
+        if witness_tree.next_seq_num != next_seq_num {
+            warn!(
+                "Persisted witness tree is stale (event {} of {next_seq_num}), rebuilding it",
+                witness_tree.next_seq_num
+            );
+            return Ok(false);
+        }
+
+        let root_branch = Branch::from_blocks(witness_tree.root_branch)?;
+        let tip = |state_hash: BlockHash| {
+            root_branch.node_id(&state_hash).map(|node_id| Tip {
+                state_hash,
+                node_id,
+            })
+        };
+        let (best_tip, canonical_root) = match (
+            tip(witness_tree.best_tip.clone()),
+            tip(witness_tree.canonical_root.clone()),
+        ) {
+            (Some(best_tip), Some(canonical_root)) => (best_tip, canonical_root),
+            _ => {
+                warn!("Persisted witness tree is missing its tips, rebuilding it");
+                return Ok(false);
+            }
+        };
+        let ledger = match indexer_store.get_ledger_state_hash(&canonical_root.state_hash, false)? {
+            Some(ledger) => ledger,
+            None => {
+                warn!(
+                    "Ledger missing for the persisted canonical root {}, rebuilding the witness tree",
+                    canonical_root.state_hash
+                );
+                return Ok(false);
+            }
+        };
+
+        self.dangling_branches = witness_tree
+            .dangling_branches
+            .into_iter()
+            .map(Branch::from_blocks)
+            .collect::<anyhow::Result<_>>()?;
+        self.root_branch = root_branch;
+        self.diffs_map = witness_tree.diffs_map.into_iter().collect();
+        self.best_tip = best_tip;
+        self.canonical_root = canonical_root;
+        self.ledger = ledger;
+        Ok(true)
+    }
+
+    /// Drops the pending hardfork if the block at `blockchain_length` is
+    /// already past it
+    fn skip_past_hardfork(&mut self, blockchain_length: u32, genesis_state_hash: BlockHash) {
+        if self
+            .hardfork
+            .as_ref()
+            .is_some_and(|hardfork| hardfork.fork.is_post_fork(blockchain_length))
+        {
+            self.hardfork = None;
+            self.version.genesis_state_hash = genesis_state_hash;
+            self.version.version = PcbVersion::V2;
+        }
+    }
+
+    /// Staking ledger epochs & hashes of the current chain in the db
+    fn stored_staking_ledgers(&self, indexer_store: &IndexerStore) -> HashMap<u32, LedgerHash> {
+        let mut staking_ledgers = HashMap::new();
+        for (key, _) in indexer_store
+            .staking_ledger_epoch_iterator(speedb::IteratorMode::End)
+            .flatten()
+        {
+            let genesis_state_hash = staking_ledger_epoch_key_genesis_state_hash(&key);
+            if genesis_state_hash.0 == MAINNET_GENESIS_HASH
+                || genesis_state_hash == self.version.genesis_state_hash
+            {
+                let epoch = staking_ledger_epoch_key_epoch(&key);
+                let ledger_hash = staking_ledger_epoch_key_ledger_hash(&key);
+                staking_ledgers.insert(epoch, ledger_hash);
+            }
+        }
+        staking_ledgers
+    }
+
+    fn sync_processed_counts(&mut self, indexer_store: &IndexerStore) -> anyhow::Result<()> {
+        self.blocks_processed = indexer_store.get_block_production_total_count()?;
+        self.bytes_processed = indexer_store
+            .database
+            .get(IndexerStore::NUM_BLOCK_BYTES_PROCESSED)?
+            .map_or(self.genesis_bytes, from_u64_be_bytes);
+        Ok(())
+    }
+
     /// Replay events on a mutable state
     pub fn replay_events(&mut self, state: &Self) -> anyhow::Result<Option<u32>> {
         let mut min_length_filter = None;
@@ -1288,6 +1761,25 @@ impl IndexerState {
                     }
                     panic!("Fatal: canonical block not in store {block_summary}");
                 }
+                DbEvent::Canonicity(DbCanonicityEvent::Reorg(reorg)) => {
+                    info!(
+                        "Replaying reorg depth {} (length {}): {}",
+                        reorg.depth, reorg.new_blockchain_length, reorg.new_best_tip
+                    );
+
+                    // check reorg history
+                    let indexer_store = self.indexer_store_or_panic();
+                    let reorgs = indexer_store.get_reorgs(
+                        Some(reorg.new_blockchain_length),
+                        Some(reorg.new_blockchain_length),
+                        reorg.depth,
+                    )?;
+                    assert!(
+                        reorgs.contains(reorg),
+                        "Fatal: reorg not in store {reorg:?}"
+                    );
+                    Ok(())
+                }
             },
             IndexerEvent::WitnessTree(_) => unreachable!("Replay witness tree event"),
         }
@@ -1368,11 +1860,10 @@ impl IndexerState {
 
     /// Add new canonical diffs to the ledger
     fn update_ledger(&mut self, canonical_blocks: &Vec<Block>) -> anyhow::Result<()> {
-        // apply the new canonical diffs and store each nth resulting ledger
-        let mut ledger_diff = LedgerDiff::default();
+        // apply the new canonical diffs in order
         for canonical_block in canonical_blocks {
-            if let Some(diff) = self.diffs_map.get(&canonical_block.state_hash) {
-                ledger_diff.append(diff.clone());
+            if let Some(diff) = self.diffs_map.get(&canonical_block.state_hash).cloned() {
+                self.apply_canonical_diff(&diff, canonical_block.blockchain_length)?;
             } else {
                 error!(
                     "Block not in diffs map (length {}): {}",
@@ -1380,7 +1871,48 @@ impl IndexerState {
                 );
             }
         }
-        self.ledger._apply_diff(&ledger_diff)
+        Ok(())
+    }
+
+    /// Applies a canonical block's ledger diff & records the resulting
+    /// history of each account it touches
+    fn apply_canonical_diff(
+        &mut self,
+        diff: &LedgerDiff,
+        blockchain_length: u32,
+    ) -> anyhow::Result<()> {
+        self.ledger._apply_diff(diff)?;
+
+        if let Some(indexer_store) = self.indexer_store.as_ref() {
+            let mut account_ids: Vec<(PublicKey, TokenId)> = diff
+                .account_diffs
+                .iter()
+                .map(|diff| diff.account_id())
+                .collect();
+            account_ids.sort();
+            account_ids.dedup();
+
+            let accounts: Vec<&Account> = account_ids
+                .iter()
+                .filter_map(|(pk, token)| self.ledger.get_account(pk, token))
+                .collect();
+            indexer_store.add_account_history(&diff.state_hash, blockchain_length, &accounts)?;
+        }
+        Ok(())
+    }
+
+    /// Records the history of every account in the ledger, e.g. at a genesis
+    /// block
+    fn add_ledger_account_history(
+        &self,
+        state_hash: &BlockHash,
+        blockchain_length: u32,
+    ) -> anyhow::Result<()> {
+        if let Some(indexer_store) = self.indexer_store.as_ref() {
+            let accounts: Vec<&Account> = self.ledger.accounts.values().collect();
+            indexer_store.add_account_history(state_hash, blockchain_length, &accounts)?;
+        }
+        Ok(())
     }
 
     /// Add new canonical ledgers to the ledger store
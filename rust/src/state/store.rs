@@ -0,0 +1,31 @@
+//! Store of the witness tree persisted across restarts
+
+use crate::{
+    block::{Block, BlockHash},
+    ledger::diff::LedgerDiff,
+};
+use serde::{Deserialize, Serialize};
+
+pub trait WitnessTreeStore {
+    /// Persist the witness tree, replacing the previous one
+    fn set_witness_tree(&self, witness_tree: &PersistedWitnessTree) -> anyhow::Result<()>;
+
+    /// Get the last persisted witness tree
+    fn get_witness_tree(&self) -> anyhow::Result<Option<PersistedWitnessTree>>;
+}
+
+/// Witness tree of the [super::IndexerState]
+///
+/// Branches are stored as their blocks in level order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PersistedWitnessTree {
+    /// Next event sequence number at the time the witness tree was persisted,
+    /// later events aren't reflected in the witness tree
+    pub next_seq_num: u32,
+
+    pub root_branch: Vec<Block>,
+    pub dangling_branches: Vec<Vec<Block>>,
+    pub diffs_map: Vec<(BlockHash, LedgerDiff)>,
+    pub best_tip: BlockHash,
+    pub canonical_root: BlockHash,
+}
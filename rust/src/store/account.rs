@@ -5,8 +5,9 @@ use crate::{
     constants::{MAINNET_ACCOUNT_CREATION_FEE, MAINNET_GENESIS_HASH},
     ledger::{
         coinbase::Coinbase,
-        diff::account::{PaymentDiff, UpdateType},
+        diff::account::{AccountDiff, PaymentDiff, UpdateType},
         public_key::PublicKey,
+        token::TokenId,
     },
 };
 use serde::{Deserialize, Serialize};
@@ -14,8 +15,13 @@ use speedb::{DBIterator, IteratorMode};
 use std::collections::HashMap;
 
 pub trait AccountStore {
-    /// Update pk's balance-sorted account balance
-    fn update_account_balance(&self, pk: &PublicKey, balance: Option<u64>) -> anyhow::Result<()>;
+    /// Update the balance-sorted balance of pk's token account
+    fn update_account_balance(
+        &self,
+        pk: &PublicKey,
+        token: &TokenId,
+        balance: Option<u64>,
+    ) -> anyhow::Result<()>;
 
     /// Generate account balance updates when the best tip changes.
     /// Return with set of coinbase receivers.
@@ -45,8 +51,8 @@ pub trait AccountStore {
         updates: &DBAccountBalanceUpdate,
     ) -> anyhow::Result<()>;
 
-    /// Get pk's account balance
-    fn get_account_balance(&self, pk: &PublicKey) -> anyhow::Result<Option<u64>>;
+    /// Get the balance of pk's token account
+    fn get_account_balance(&self, pk: &PublicKey, token: &TokenId) -> anyhow::Result<Option<u64>>;
 
     /// Update best ledger accounts count
     fn update_num_accounts(&self, adjust: i32) -> anyhow::Result<()>;
@@ -58,19 +64,21 @@ pub trait AccountStore {
     // Iterators //
     ///////////////
 
-    /// Iterator for balance-sorted accounts
-    /// `{balance}{pk} -> _`
-    /// ```
+    /// Iterator for balance-sorted accounts, grouped by token
+    /// `{token}{balance}{pk} -> _`
+    /// ```text
+    /// - token:   [TokenId] bytes
     /// - balance: 8 BE bytes
     /// - pk:      [PublicKey::LEN] bytes
+    /// ```
     fn account_balance_iterator<'a>(&'a self, mode: IteratorMode) -> DBIterator<'a>;
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AccountBalanceUpdate {
     Payment(PaymentDiff),
-    CreateAccount(PublicKey),
-    RemoveAccount(PublicKey),
+    CreateAccount(PublicKey, TokenId),
+    RemoveAccount(PublicKey, TokenId),
 }
 
 pub type DBAccountBalanceUpdate = DBUpdate<AccountBalanceUpdate>;
@@ -83,6 +91,7 @@ impl AccountBalanceUpdate {
                     update_type,
                     public_key,
                     amount,
+                    token,
                 } = diff;
 
                 // change update type: debit <-> credit
@@ -94,10 +103,11 @@ impl AccountBalanceUpdate {
                     update_type,
                     public_key,
                     amount,
+                    token,
                 })
             }
-            Self::CreateAccount(pk) => Self::RemoveAccount(pk),
-            Self::RemoveAccount(pk) => Self::CreateAccount(pk),
+            Self::CreateAccount(pk, token) => Self::RemoveAccount(pk, token),
+            Self::RemoveAccount(pk, token) => Self::CreateAccount(pk, token),
         }
     }
 }
@@ -107,29 +117,29 @@ impl DBAccountBalanceUpdate {
         Self { apply, unapply }
     }
 
-    /// Update balances for pk -> Some(bal),
-    /// remove accounts for pk -> None
-    pub fn balance_updates(&self) -> HashMap<PublicKey, Option<i64>> {
+    /// Update balances for (pk, token) -> Some(bal),
+    /// remove accounts for (pk, token) -> None
+    pub fn balance_updates(&self) -> HashMap<(PublicKey, TokenId), Option<i64>> {
         use AccountBalanceUpdate::*;
-        let mut res = <HashMap<PublicKey, Option<i64>>>::new();
+        let mut res = <HashMap<(PublicKey, TokenId), Option<i64>>>::new();
         for account_balance_update in self.to_balance_update_vec() {
             match account_balance_update {
                 Payment(diff) => {
-                    let pk = diff.public_key.clone();
-                    let acc = res.remove(&pk).unwrap_or_default().unwrap_or_default();
+                    let id = (diff.public_key.clone(), diff.token.clone());
+                    let acc = res.remove(&id).unwrap_or_default().unwrap_or_default();
                     res.insert(
-                        pk,
+                        id,
                         match diff.update_type {
                             UpdateType::Credit => Some(acc + diff.amount.0 as i64),
                             UpdateType::Debit(_) => Some(acc - diff.amount.0 as i64),
                         },
                     );
                 }
-                CreateAccount(pk) => {
-                    res.insert(pk.clone(), Some(0));
+                CreateAccount(pk, token) => {
+                    res.insert((pk, token), Some(0));
                 }
-                RemoveAccount(pk) => {
-                    res.insert(pk.clone(), None);
+                RemoveAccount(pk, token) => {
+                    res.insert((pk, token), None);
                 }
             }
         }
@@ -156,6 +166,7 @@ impl DBAccountBalanceUpdate {
                 update_type: UpdateType::Credit,
                 public_key: block.block_creator(),
                 amount: 1000_u64.into(),
+                token: TokenId::default(),
             })];
         }
 
@@ -180,11 +191,13 @@ impl DBAccountBalanceUpdate {
                             } else {
                                 amount
                             },
+                            token: TokenId::default(),
                         }),
                         AccountBalanceUpdate::Payment(PaymentDiff {
                             update_type: UpdateType::Debit(None),
                             public_key: source.clone(),
                             amount,
+                            token: TokenId::default(),
                         }),
                     ],
                     Command::Delegation(_) => vec![],
@@ -194,6 +207,7 @@ impl DBAccountBalanceUpdate {
                 update_type: UpdateType::Credit,
                 public_key: coinbase.receiver.clone(),
                 amount: coinbase.amount().into(),
+                token: TokenId::default(),
             })],
             InternalCommand::from_precomputed(block)
                 .iter()
@@ -213,11 +227,13 @@ impl DBAccountBalanceUpdate {
                             update_type: UpdateType::Credit,
                             public_key: receiver.clone(),
                             amount: (*amount).into(),
+                            token: TokenId::default(),
                         }),
                         AccountBalanceUpdate::Payment(PaymentDiff {
                             update_type: UpdateType::Debit(None),
                             public_key: sender.clone(),
                             amount: (*amount).into(),
+                            token: TokenId::default(),
                         }),
                     ],
                 })
@@ -225,13 +241,30 @@ impl DBAccountBalanceUpdate {
         ]
         .concat();
 
+        // zkApp account updates, in any token
+        for (zkapp, accounts_created) in block.zkapp_commands_with_accounts_created() {
+            res.append(
+                &mut AccountDiff::from_zkapp_command(&zkapp, &accounts_created)
+                    .into_iter()
+                    .filter_map(PaymentDiff::from_account_diff)
+                    .map(AccountBalanceUpdate::Payment)
+                    .collect(),
+            );
+        }
+
         res.append(
             &mut block
                 .accounts_created()
                 .0
-                .keys()
-                .cloned()
-                .map(AccountBalanceUpdate::CreateAccount)
+                .into_keys()
+                .map(|pk| AccountBalanceUpdate::CreateAccount(pk, TokenId::default()))
+                .collect(),
+        );
+        res.append(
+            &mut block
+                .token_accounts_created()
+                .into_iter()
+                .map(|(pk, token)| AccountBalanceUpdate::CreateAccount(pk, token))
                 .collect(),
         );
         res
@@ -1,14 +1,15 @@
 use super::{account::AccountBalanceUpdate, column_families::ColumnFamilyHelpers, from_be_bytes};
 use crate::{
-    block::{store::BlockStore, BlockHash},
+    block::BlockHash,
     constants::MAINNET_GENESIS_HASH,
-    ledger::public_key::PublicKey,
+    ledger::{public_key::PublicKey, token::TokenId},
     store::{
         account::{AccountStore, DBAccountBalanceUpdate},
         fixed_keys::FixedKeys,
-        u64_prefix_key, IndexerStore,
+        pk_token_key, token_balance_sort_key, IndexerStore,
     },
 };
+use anyhow::Context;
 use log::trace;
 use speedb::{DBIterator, IteratorMode};
 
@@ -24,46 +25,22 @@ impl AccountStore for IndexerStore {
             new_best_tip
         );
 
-        // follows the old best tip back to the common ancestor
-        let mut a = old_best_tip.clone();
-        let mut unapply = vec![];
-
-        // follows the new best tip back to the common ancestor
-        let mut b = new_best_tip.clone();
-        let mut apply = vec![];
-
-        let a_length = self.get_block_height(&a)?.expect("a has a length");
-        let b_length = self.get_block_height(&b)?.expect("b has a length");
-
-        // bring b back to the same height as a
-        let genesis_state_hashes: Vec<BlockHash> = vec![MAINNET_GENESIS_HASH.into()];
-        for _ in 0..b_length.saturating_sub(a_length) {
-            // check if there's a previous block
-            if genesis_state_hashes.contains(&b) {
-                break;
+        let (old_branch, new_branch) =
+            self.reorg_branches(old_best_tip, new_best_tip, &[MAINNET_GENESIS_HASH.into()])?;
+        let balance_updates = |branch: Vec<(BlockHash, u32)>| -> anyhow::Result<Vec<_>> {
+            let mut updates = vec![];
+            for (state_hash, _) in branch {
+                updates.append(
+                    &mut self
+                        .get_block_balance_updates(&state_hash)?
+                        .with_context(|| format!("balance updates of {state_hash}"))?,
+                );
             }
+            Ok(updates)
+        };
 
-            apply.append(&mut self.get_block_balance_updates(&b)?.unwrap());
-            b = self.get_block_parent_hash(&b)?.expect("b has a parent");
-        }
-
-        // find the common ancestor
-        let mut a_prev = self.get_block_parent_hash(&a)?.expect("a has a parent");
-        let mut b_prev = self.get_block_parent_hash(&b)?.expect("b has a parent");
-
-        while a != b && !genesis_state_hashes.contains(&a) {
-            // add blocks to appropriate collection
-            unapply.append(&mut self.get_block_balance_updates(&a)?.unwrap());
-            apply.append(&mut self.get_block_balance_updates(&b)?.unwrap());
-
-            // descend
-            a = a_prev;
-            b = b_prev;
-
-            a_prev = self.get_block_parent_hash(&a)?.expect("a has a parent");
-            b_prev = self.get_block_parent_hash(&b)?.expect("b has a parent");
-        }
-
+        let unapply = balance_updates(old_branch)?;
+        let mut apply = balance_updates(new_branch)?;
         apply.reverse();
         Ok(<DBAccountBalanceUpdate>::new(apply, unapply))
     }
@@ -89,17 +66,17 @@ impl AccountStore for IndexerStore {
         use AccountBalanceUpdate::*;
         fn count(updates: &[AccountBalanceUpdate]) -> i32 {
             updates.iter().fold(0, |acc, update| match update {
-                CreateAccount(_) => acc + 1,
-                RemoveAccount(_) => acc - 1,
+                CreateAccount(..) => acc + 1,
+                RemoveAccount(..) => acc - 1,
                 Payment(_) => acc,
             })
         }
         self.update_num_accounts(count(&updates.apply) - count(&updates.unapply))?;
 
         // update balances
-        for (pk, amount) in <DBAccountBalanceUpdate>::balance_updates(updates) {
+        for ((pk, token), amount) in <DBAccountBalanceUpdate>::balance_updates(updates) {
             if let Some(amount) = amount {
-                let balance = self.get_account_balance(&pk)?.unwrap_or_default();
+                let balance = self.get_account_balance(&pk, &token)?.unwrap_or_default();
                 let balance = if amount > 0 {
                     balance + amount.unsigned_abs()
                 } else {
@@ -107,10 +84,10 @@ impl AccountStore for IndexerStore {
                 };
 
                 // update balance
-                self.update_account_balance(&pk, Some(balance))?;
+                self.update_account_balance(&pk, &token, Some(balance))?;
             } else {
                 // remove account
-                self.update_account_balance(&pk, None)?;
+                self.update_account_balance(&pk, &token, None)?;
             }
         }
         Ok(())
@@ -152,35 +129,44 @@ impl AccountStore for IndexerStore {
             .map(from_be_bytes))
     }
 
-    fn update_account_balance(&self, pk: &PublicKey, balance: Option<u64>) -> anyhow::Result<()> {
+    fn update_account_balance(
+        &self,
+        pk: &PublicKey,
+        token: &TokenId,
+        balance: Option<u64>,
+    ) -> anyhow::Result<()> {
         // delete account when balance is none
         if balance.is_none() {
             // delete stale data
-            let b = self.get_account_balance(pk)?.unwrap_or_default();
-            self.database
-                .delete_cf(self.account_balance_cf(), pk.0.as_bytes())?;
+            let b = self.get_account_balance(pk, token)?.unwrap_or_default();
             self.database
-                .delete_cf(self.account_balance_sort_cf(), u64_prefix_key(b, &pk.0))?;
+                .delete_cf(self.account_balance_cf(), pk_token_key(pk, token))?;
+            self.database.delete_cf(
+                self.account_balance_sort_cf(),
+                token_balance_sort_key(token, b, pk),
+            )?;
             return Ok(());
         }
 
         // update account balance when some
         let balance = balance.unwrap();
-        if let Some(old) = self.get_account_balance(pk)? {
+        if let Some(old) = self.get_account_balance(pk, token)? {
             // delete stale balance sorting data
-            self.database
-                .delete_cf(self.account_balance_sort_cf(), u64_prefix_key(old, &pk.0))?;
+            self.database.delete_cf(
+                self.account_balance_sort_cf(),
+                token_balance_sort_key(token, old, pk),
+            )?;
         }
         self.database.put_cf(
             self.account_balance_cf(),
-            pk.0.as_bytes(),
+            pk_token_key(pk, token),
             balance.to_be_bytes(),
         )?;
 
-        // add: {balance}{pk} -> _
+        // add: {token}{balance}{pk} -> _
         self.database.put_cf(
             self.account_balance_sort_cf(),
-            u64_prefix_key(balance, &pk.0),
+            token_balance_sort_key(token, balance, pk),
             b"",
         )?;
         Ok(())
@@ -200,12 +186,12 @@ impl AccountStore for IndexerStore {
         Ok(())
     }
 
-    fn get_account_balance(&self, pk: &PublicKey) -> anyhow::Result<Option<u64>> {
-        trace!("Getting account balance {pk}");
+    fn get_account_balance(&self, pk: &PublicKey, token: &TokenId) -> anyhow::Result<Option<u64>> {
+        trace!("Getting account balance {pk} token {token}");
 
         Ok(self
             .database
-            .get_pinned_cf(self.account_balance_cf(), pk.0.as_bytes())?
+            .get_pinned_cf(self.account_balance_cf(), pk_token_key(pk, token))?
             .map(|bytes| {
                 let mut be_bytes = [0; 8];
                 be_bytes.copy_from_slice(&bytes[..8]);
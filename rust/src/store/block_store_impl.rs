@@ -5,15 +5,19 @@ use crate::{
         store::BlockStore,
         BlockComparison, BlockHash,
     },
-    canonicity::{store::CanonicityStore, Canonicity},
-    command::{internal::store::InternalCommandStore, store::UserCommandStore},
+    canonicity::{store::CanonicityStore, Canonicity, Reorg},
+    command::{
+        internal::store::InternalCommandStore, store::UserCommandStore,
+        zkapp::store::ZkappCommandStore,
+    },
     event::{db::*, store::EventStore, IndexerEvent},
     ledger::{diff::LedgerDiff, public_key::PublicKey, store::LedgerStore},
     snark_work::store::SnarkStore,
     store::{
         account::{AccountBalanceUpdate, AccountStore},
-        block_state_hash_from_key, block_u32_prefix_from_key, from_be_bytes, from_u64_be_bytes,
-        to_be_bytes, u32_prefix_key,
+        block_state_hash_from_key, block_u32_prefix_from_key,
+        fork_stats::ForkStatsStore,
+        from_be_bytes, from_u64_be_bytes, to_be_bytes, u32_prefix_key,
         username::UsernameStore,
         DBUpdate, IndexerStore,
     },
@@ -116,9 +120,21 @@ impl BlockStore for IndexerStore {
         // add pcb's version
         self.set_block_version(&state_hash, block.version())?;
 
+        // blocks arriving after their height is tallied are orphaned
+        if let Some(canonical_hash) =
+            self.get_canonical_hash_at_height(block.blockchain_length())?
+        {
+            if self.get_block_tally(&canonical_hash)?.is_some() {
+                self.tally_block(&state_hash, Canonicity::Orphaned)?;
+            }
+        }
+
         // add block user commands
         self.add_user_commands(block)?;
 
+        // add block zkApp commands
+        self.add_zkapp_commands(block)?;
+
         // add block internal commands
         self.add_internal_commands(block)?;
 
@@ -203,6 +219,23 @@ impl BlockStore for IndexerStore {
             // reorg updates
             // canonicity
             let canonicity_updates = self.reorg_canonicity_updates(&old, state_hash)?;
+            let reorg = match canonicity_updates.unapply.last() {
+                // previously canonical blocks are orphaned
+                Some(oldest_orphaned) => Some(Reorg {
+                    old_best_tip: old.clone(),
+                    old_blockchain_length: canonicity_updates.unapply[0].blockchain_length,
+                    new_best_tip: state_hash.clone(),
+                    new_blockchain_length: self
+                        .get_block_height(state_hash)?
+                        .with_context(|| format!("new best tip {state_hash}"))?,
+                    common_ancestor: self
+                        .get_block_parent_hash(&oldest_orphaned.state_hash)?
+                        .with_context(|| format!("parent of {}", oldest_orphaned.state_hash))?,
+                    common_ancestor_length: oldest_orphaned.blockchain_length - 1,
+                    depth: canonicity_updates.unapply.len() as u32,
+                }),
+                None => None,
+            };
             self.update_canonicity(canonicity_updates)?;
 
             // balance-sorted accounts
@@ -212,6 +245,14 @@ impl BlockStore for IndexerStore {
             // usernames
             let username_updates = self.reorg_username_updates(&old, state_hash)?;
             self.update_usernames(username_updates)?;
+
+            // reorg history
+            if let Some(reorg) = reorg {
+                self.add_reorg(&reorg)?;
+                self.add_event(&IndexerEvent::Db(DbEvent::Canonicity(
+                    DbCanonicityEvent::Reorg(reorg),
+                )))?;
+            }
         }
 
         // set new best tip
@@ -913,6 +954,66 @@ impl BlockStore for IndexerStore {
     }
 }
 
+/// `(state hash, blockchain length)` of a branch's blocks, from its tip down
+type ReorgBranch = Vec<(BlockHash, u32)>;
+
+impl IndexerStore {
+    /// Walks the old & new best tips back to their common ancestor, bringing
+    /// the taller branch down to the height of the other first. Walks stop at
+    /// the given genesis blocks
+    ///
+    /// Returns the old, then the new branch's blocks above the common ancestor
+    pub(crate) fn reorg_branches(
+        &self,
+        old_best_tip: &BlockHash,
+        new_best_tip: &BlockHash,
+        genesis_state_hashes: &[BlockHash],
+    ) -> anyhow::Result<(ReorgBranch, ReorgBranch)> {
+        let height = |state_hash: &BlockHash| -> anyhow::Result<u32> {
+            self.get_block_height(state_hash)?
+                .with_context(|| format!("length of {state_hash}"))
+        };
+        let parent = |state_hash: &BlockHash| -> anyhow::Result<BlockHash> {
+            self.get_block_parent_hash(state_hash)?
+                .with_context(|| format!("parent of {state_hash}"))
+        };
+
+        // follows the old best tip back to the common ancestor
+        let mut a = old_best_tip.clone();
+        let mut a_length = height(&a)?;
+        let mut old_branch = vec![];
+
+        // follows the new best tip back to the common ancestor
+        let mut b = new_best_tip.clone();
+        let mut b_length = height(&b)?;
+        let mut new_branch = vec![];
+
+        // bring the taller branch down to the height of the other
+        while a_length > b_length && !genesis_state_hashes.contains(&a) {
+            old_branch.push((a.clone(), a_length));
+            a = parent(&a)?;
+            a_length -= 1;
+        }
+        while b_length > a_length && !genesis_state_hashes.contains(&b) {
+            new_branch.push((b.clone(), b_length));
+            b = parent(&b)?;
+            b_length -= 1;
+        }
+
+        // descend both branches to the common ancestor
+        while a != b && !genesis_state_hashes.contains(&a) {
+            old_branch.push((a.clone(), a_length));
+            new_branch.push((b.clone(), b_length));
+
+            a = parent(&a)?;
+            b = parent(&b)?;
+            a_length -= 1;
+            b_length -= 1;
+        }
+        Ok((old_branch, new_branch))
+    }
+}
+
 /// `{block height BE}{state hash}`
 fn block_height_key(block: &PrecomputedBlock) -> Vec<u8> {
     let mut key = to_be_bytes(block.blockchain_length());
@@ -942,6 +1043,7 @@ fn block_cmp(db: &IndexerStore, a: &BlockHash, b: &BlockHash) -> std::cmp::Order
     let a_cmp = db.get_block_comparison(a).unwrap().unwrap();
     let b_cmp = db.get_block_comparison(b).unwrap().unwrap();
     match (a_canonicity, b_canonicity) {
+        (Some(Canonicity::Canonical), Some(Canonicity::Canonical)) => a_cmp.cmp(&b_cmp),
         (Some(Canonicity::Canonical), _) => Ordering::Less,
         (_, Some(Canonicity::Canonical)) => Ordering::Greater,
         _ => a_cmp.cmp(&b_cmp),
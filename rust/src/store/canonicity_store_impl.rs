@@ -1,14 +1,17 @@
 use super::{column_families::ColumnFamilyHelpers, fixed_keys::FixedKeys};
 use crate::{
     block::{store::BlockStore, BlockHash},
-    canonicity::{store::CanonicityStore, Canonicity, CanonicityDiff, CanonicityUpdate},
+    canonicity::{store::CanonicityStore, Canonicity, CanonicityDiff, CanonicityUpdate, Reorg},
     constants::MAINNET_GENESIS_HASH,
     event::{db::*, store::EventStore, IndexerEvent},
     snark_work::store::SnarkStore,
-    store::{to_be_bytes, DBUpdate, IndexerStore},
+    store::{
+        block_u32_prefix_from_key, fork_stats::ForkStatsStore, to_be_bytes, DBUpdate, IndexerStore,
+    },
 };
 use anyhow::Context;
 use log::trace;
+use speedb::{Direction, IteratorMode};
 
 impl CanonicityStore for IndexerStore {
     fn add_canonical_block(
@@ -70,6 +73,14 @@ impl CanonicityStore for IndexerStore {
             }
         }
 
+        // tally the canonical block & the blocks it orphans
+        self.tally_block(state_hash, Canonicity::Canonical)?;
+        for block in self.get_blocks_at_height(height)? {
+            if block != *state_hash {
+                self.tally_block(&block, Canonicity::Orphaned)?;
+            }
+        }
+
         // record new canonical block event
         self.add_event(&IndexerEvent::Db(DbEvent::Canonicity(
             DbCanonicityEvent::NewCanonicalBlock {
@@ -139,60 +150,67 @@ impl CanonicityStore for IndexerStore {
     ) -> anyhow::Result<CanonicityUpdate> {
         trace!("Getting reorg canonicity updates:\n  old: {old_best_tip}\n  new: {new_best_tip}");
 
-        // follows the old best tip back to the common ancestor
-        let mut a = old_best_tip.clone();
-        let mut unapply = vec![];
-
-        // follows the new best tip back to the common ancestor
-        let mut b = new_best_tip.clone();
-        let mut apply = vec![];
+        let (old_branch, new_branch) =
+            self.reorg_branches(old_best_tip, new_best_tip, &[MAINNET_GENESIS_HASH.into()])?;
+        let diffs = |branch: Vec<(BlockHash, u32)>| -> anyhow::Result<Vec<_>> {
+            branch
+                .into_iter()
+                .map(|(state_hash, blockchain_length)| {
+                    let global_slot = self
+                        .get_block_global_slot(&state_hash)?
+                        .with_context(|| format!("(length {blockchain_length}): {state_hash}"))?;
+                    Ok(CanonicityDiff {
+                        state_hash,
+                        blockchain_length,
+                        global_slot,
+                    })
+                })
+                .collect()
+        };
+
+        let unapply = diffs(old_branch)?;
+        let apply = diffs(new_branch)?;
+        Ok(DBUpdate { apply, unapply })
+    }
 
-        let a_length = self.get_block_height(&a)?.expect("a has a length");
-        let b_length = self.get_block_height(&b)?.expect("b has a length");
+    fn add_reorg(&self, reorg: &Reorg) -> anyhow::Result<()> {
+        trace!("Adding reorg {reorg:?}");
+        let mut key = to_be_bytes(reorg.new_blockchain_length);
+        key.extend_from_slice(reorg.new_best_tip.0.as_bytes());
+        key.extend_from_slice(reorg.old_best_tip.0.as_bytes());
+        Ok(self
+            .database
+            .put_cf(self.reorgs_cf(), key, serde_json::to_vec(reorg)?)?)
+    }
 
-        // bring b back to the same height as a
-        for _ in 0..b_length.saturating_sub(a_length) {
-            // check if there's a previous block
-            if b.0 == MAINNET_GENESIS_HASH {
+    fn get_reorgs(
+        &self,
+        from: Option<u32>,
+        to: Option<u32>,
+        min_depth: u32,
+    ) -> anyhow::Result<Vec<Reorg>> {
+        trace!("Getting reorgs from {from:?} to {to:?}, min depth {min_depth}");
+        let start = to_be_bytes(from.unwrap_or_default());
+        let mut reorgs = vec![];
+        for (key, value) in self
+            .database
+            .iterator_cf(
+                self.reorgs_cf(),
+                IteratorMode::From(&start, Direction::Forward),
+            )
+            .flatten()
+        {
+            let new_blockchain_length = block_u32_prefix_from_key(&key)?;
+            if to.is_some_and(|to| new_blockchain_length > to) {
                 break;
             }
 
-            apply.push(CanonicityDiff {
-                state_hash: b.clone(),
-                blockchain_length: b_length,
-                global_slot: self
-                    .get_block_global_slot(&b)?
-                    .with_context(|| format!("(length {b_length}): {b}"))
-                    .unwrap(),
-            });
-            b = self.get_block_parent_hash(&b)?.expect("b has a parent");
-        }
-
-        // find the common ancestor
-        let mut a_prev = self.get_block_parent_hash(&a)?.expect("a has a parent");
-        let mut b_prev = self.get_block_parent_hash(&b)?.expect("b has a parent");
-
-        while a != b && a.0 != MAINNET_GENESIS_HASH {
-            // collect canonicity diffs
-            unapply.push(CanonicityDiff {
-                state_hash: a.clone(),
-                blockchain_length: self.get_block_height(&a)?.unwrap(),
-                global_slot: self.get_block_global_slot(&a)?.unwrap(),
-            });
-            apply.push(CanonicityDiff {
-                state_hash: b.clone(),
-                blockchain_length: self.get_block_height(&b)?.unwrap(),
-                global_slot: self.get_block_global_slot(&b)?.unwrap(),
-            });
-
-            // descend
-            a = a_prev;
-            b = b_prev;
-
-            a_prev = self.get_block_parent_hash(&a)?.expect("a has a parent");
-            b_prev = self.get_block_parent_hash(&b)?.expect("b has a parent");
+            let reorg: Reorg = serde_json::from_slice(&value)?;
+            if reorg.depth >= min_depth {
+                reorgs.push(reorg);
+            }
         }
-        Ok(DBUpdate { apply, unapply })
+        Ok(reorgs)
     }
 
     fn update_canonicity(&self, updates: CanonicityUpdate) -> anyhow::Result<()> {
@@ -0,0 +1,72 @@
+//! Ingestion checkpoints of `database create`
+
+use crate::block::BlockHash;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+pub trait IngestionCheckpointStore {
+    /// Record the latest ingestion checkpoint
+    fn set_ingestion_checkpoint(&self, checkpoint: &IngestionCheckpoint) -> anyhow::Result<()>;
+
+    /// Get the latest ingestion checkpoint, if ingestion is unfinished
+    fn get_ingestion_checkpoint(&self) -> anyhow::Result<Option<IngestionCheckpoint>>;
+
+    /// Remove the ingestion checkpoint once ingestion finishes
+    fn remove_ingestion_checkpoint(&self) -> anyhow::Result<()>;
+}
+
+/// Progress of an interrupted canonical chain discovery ingestion
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IngestionCheckpoint {
+    /// Blocks dir (or archive) being ingested
+    pub blocks_dir: PathBuf,
+
+    /// Height of the last applied deep canonical block
+    pub canonical_height: u32,
+
+    /// State hash of the last applied deep canonical block
+    pub canonical_state_hash: BlockHash,
+
+    /// Number of block paths consumed by the block parser
+    pub parser_position: u32,
+
+    /// State hash of the last block path consumed by the block parser, the
+    /// path a resumed block parser skips to must match it
+    pub parser_state_hash: Option<BlockHash>,
+
+    /// Number of deep canonical blocks the block parser yields, excluding
+    /// quarantined block files
+    pub num_deep_canonical_blocks: u32,
+
+    /// Number of blocks applied, including genesis
+    pub blocks_processed: u32,
+
+    /// Number of block bytes applied, including genesis
+    pub bytes_processed: u64,
+
+    /// Number of orphaned blocks not yet added to the store, recorded once
+    /// all deep canonical blocks are applied. The pending paths are
+    /// re-derived by skipping `parser_position` paths of a new block parser
+    pub num_pending_orphaned_blocks: Option<u32>,
+}
+
+impl IngestionCheckpoint {
+    /// Whether the deep canonical blocks are still being applied
+    pub fn is_deep_canonical(&self) -> bool {
+        self.num_pending_orphaned_blocks.is_none()
+    }
+}
+
+impl std::fmt::Display for IngestionCheckpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "canonical block (length {}): {}, {} blocks processed",
+            self.canonical_height, self.canonical_state_hash, self.blocks_processed
+        )?;
+        if let Some(num_pending) = self.num_pending_orphaned_blocks {
+            write!(f, ", {num_pending} orphaned blocks pending")?;
+        }
+        Ok(())
+    }
+}
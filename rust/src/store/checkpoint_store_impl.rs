@@ -0,0 +1,30 @@
+use super::{
+    checkpoint::{IngestionCheckpoint, IngestionCheckpointStore},
+    fixed_keys::FixedKeys,
+    IndexerStore,
+};
+use log::trace;
+
+impl IngestionCheckpointStore for IndexerStore {
+    fn set_ingestion_checkpoint(&self, checkpoint: &IngestionCheckpoint) -> anyhow::Result<()> {
+        trace!("Setting ingestion checkpoint {checkpoint}");
+        Ok(self.database.put(
+            Self::INGESTION_CHECKPOINT_KEY,
+            serde_json::to_vec(checkpoint)?,
+        )?)
+    }
+
+    fn get_ingestion_checkpoint(&self) -> anyhow::Result<Option<IngestionCheckpoint>> {
+        trace!("Getting ingestion checkpoint");
+        Ok(self
+            .database
+            .get(Self::INGESTION_CHECKPOINT_KEY)?
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()?)
+    }
+
+    fn remove_ingestion_checkpoint(&self) -> anyhow::Result<()> {
+        trace!("Removing ingestion checkpoint");
+        Ok(self.database.delete(Self::INGESTION_CHECKPOINT_KEY)?)
+    }
+}
@@ -91,6 +91,10 @@ pub trait ColumnFamilyHelpers {
     /// CF for storing canonical state hashes by global slot
     fn canonicity_slot_cf(&self) -> &ColumnFamily;
 
+    /// CF for storing reorgs by
+    /// `{new best tip length}{new best tip}{old best tip}`
+    fn reorgs_cf(&self) -> &ColumnFamily;
+
     ////////////////////////////
     // User command store CFs //
     ////////////////////////////
@@ -150,6 +154,31 @@ pub trait ColumnFamilyHelpers {
     /// CF for sorting internal commands by global slot
     fn internal_commands_slot_cf(&self) -> &ColumnFamily;
 
+    /////////////////////////////
+    // zkApp command store CFs //
+    /////////////////////////////
+
+    /// CF for storing zkApp commands
+    fn zkapp_commands_cf(&self) -> &ColumnFamily;
+
+    /// CF for storing zkApp commands per block
+    fn zkapp_commands_block_cf(&self) -> &ColumnFamily;
+
+    /// CF for sorting zkApp commands by block height
+    fn zkapp_commands_height_sort_cf(&self) -> &ColumnFamily;
+
+    /// CF for sorting zkApp commands by fee payer public key
+    fn zkapp_commands_fee_payer_sort_cf(&self) -> &ColumnFamily;
+
+    /// CF for sorting zkApp commands by touched account public key
+    fn zkapp_commands_pk_sort_cf(&self) -> &ColumnFamily;
+
+    /// CF for storing zkApp events by zkApp public key
+    fn zkapp_events_cf(&self) -> &ColumnFamily;
+
+    /// CF for storing zkApp actions by zkApp public key
+    fn zkapp_actions_cf(&self) -> &ColumnFamily;
+
     //////////////////////
     // Ledger store CFs //
     //////////////////////
@@ -163,6 +192,9 @@ pub trait ColumnFamilyHelpers {
     /// CF for storing block staged ledger hashes
     fn block_staged_ledger_hash_cf(&self) -> &ColumnFamily;
 
+    /// CF for storing per-account balance, nonce & delegate history
+    fn account_history_cf(&self) -> &ColumnFamily;
+
     /// CF for storing staking ledgers
     fn staking_ledgers_cf(&self) -> &ColumnFamily;
 
@@ -271,6 +303,24 @@ pub trait ColumnFamilyHelpers {
     /// CF for per acccount snark totals
     fn snarks_pk_total_cf(&self) -> &ColumnFamily;
 
+    /// CF for storing the fork stats tally of each block
+    fn fork_stats_blocks_cf(&self) -> &ColumnFamily;
+
+    /// CF for per epoch canonical & orphaned block counts
+    fn fork_stats_epoch_cf(&self) -> &ColumnFamily;
+
+    /// CF for per epoch per account canonical & orphaned block counts
+    fn fork_stats_pk_epoch_cf(&self) -> &ColumnFamily;
+
+    /// CF for per account canonical & orphaned block counts
+    fn fork_stats_pk_total_cf(&self) -> &ColumnFamily;
+
+    /// CF for per height orphaned block counts
+    fn fork_stats_height_cf(&self) -> &ColumnFamily;
+
+    /// CF for storing fork lengths by fork root state hash
+    fn fork_stats_fork_length_cf(&self) -> &ColumnFamily;
+
     /////////////////////
     // Chain store CFs //
     /////////////////////
@@ -284,4 +334,11 @@ pub trait ColumnFamilyHelpers {
 
     /// CF for storing indexer store events by sequence number
     fn events_cf(&self) -> &ColumnFamily;
+
+    ////////////////////////////
+    // Witness tree store CFs //
+    ////////////////////////////
+
+    /// CF for storing the persisted witness tree
+    fn witness_tree_cf(&self) -> &ColumnFamily;
 }
@@ -359,6 +359,118 @@ impl ColumnFamilyHelpers for IndexerStore {
             .expect("internal-commands-global-slot column family exists")
     }
 
+    /////////////////////////////
+    // zkApp command store CFs //
+    /////////////////////////////
+
+    /// Key-value pairs
+    /// ```text
+    /// - key: {state_hash}{index}
+    /// - val: [ZkappCommandWithData] serde bytes
+    /// where
+    /// - state_hash: [BlockHash::LEN] bytes
+    /// - index:      4 BE bytes (command index in the block)
+    /// ```
+    fn zkapp_commands_cf(&self) -> &ColumnFamily {
+        self.database
+            .cf_handle("zkapp-commands")
+            .expect("zkapp-commands column family exists")
+    }
+
+    /// Key-value pairs
+    /// ```text
+    /// - key: state_hash
+    /// - val: [Vec<ZkappCommandWithData>] serde bytes
+    /// ```
+    fn zkapp_commands_block_cf(&self) -> &ColumnFamily {
+        self.database
+            .cf_handle("zkapp-commands-block")
+            .expect("zkapp-commands-block column family exists")
+    }
+
+    /// Key-value pairs
+    /// ```text
+    /// - key: {block_height}{state_hash}{index}
+    /// - val: b""
+    /// where
+    /// - block_height: 4 BE bytes
+    /// - state_hash:   [BlockHash::LEN] bytes
+    /// - index:        4 BE bytes (command index in the block)
+    /// ```
+    fn zkapp_commands_height_sort_cf(&self) -> &ColumnFamily {
+        self.database
+            .cf_handle("zkapp-commands-height-sort")
+            .expect("zkapp-commands-height-sort column family exists")
+    }
+
+    /// Key-value pairs
+    /// ```text
+    /// - key: {fee_payer}{block_height}{nonce}{state_hash}{index}
+    /// - val: b""
+    /// where
+    /// - fee_payer:    [PublicKey::LEN] bytes
+    /// - block_height: 4 BE bytes
+    /// - nonce:        4 BE bytes
+    /// - state_hash:   [BlockHash::LEN] bytes
+    /// - index:        4 BE bytes (command index in the block)
+    /// ```
+    fn zkapp_commands_fee_payer_sort_cf(&self) -> &ColumnFamily {
+        self.database
+            .cf_handle("zkapp-commands-fee-payer-sort")
+            .expect("zkapp-commands-fee-payer-sort column family exists")
+    }
+
+    /// Key-value pairs
+    /// ```text
+    /// - key: {pk}{block_height}{nonce}{state_hash}{index}
+    /// - val: b""
+    /// where
+    /// - pk:           [PublicKey::LEN] bytes
+    /// - block_height: 4 BE bytes
+    /// - nonce:        4 BE bytes (fee payer nonce)
+    /// - state_hash:   [BlockHash::LEN] bytes
+    /// - index:        4 BE bytes (command index in the block)
+    /// ```
+    fn zkapp_commands_pk_sort_cf(&self) -> &ColumnFamily {
+        self.database
+            .cf_handle("zkapp-commands-pk-sort")
+            .expect("zkapp-commands-pk-sort column family exists")
+    }
+
+    /// Key-value pairs
+    /// ```text
+    /// - key: {zkapp}{block_height}{state_hash}{command_index}{index}
+    /// - val: [ZkappEventWithData] serde bytes
+    /// where
+    /// - zkapp:        [PublicKey::LEN] bytes
+    /// - block_height: 4 BE bytes
+    /// - state_hash:   [BlockHash::LEN] bytes
+    /// - command_index: 4 BE bytes (command index in the block)
+    /// - index:        4 BE bytes (event index in the command)
+    /// ```
+    fn zkapp_events_cf(&self) -> &ColumnFamily {
+        self.database
+            .cf_handle("zkapp-events")
+            .expect("zkapp-events column family exists")
+    }
+
+    /// Key-value pairs
+    /// ```text
+    /// - key: {zkapp}{block_height}{state_hash}{command_index}{index}
+    /// - val: [ZkappActionWithData] serde bytes
+    /// where
+    /// - zkapp:        [PublicKey::LEN] bytes
+    /// - block_height: 4 BE bytes
+    /// - state_hash:   [BlockHash::LEN] bytes
+    /// - command_index: 4 BE bytes (command index in the block)
+    /// - index:        4 BE bytes (action index in the command)
+    /// ```
+    fn zkapp_actions_cf(&self) -> &ColumnFamily {
+        self.database
+            .cf_handle("zkapp-actions")
+            .expect("zkapp-actions column family exists")
+    }
+
     //////////////////////////
     // Canonicity store CFs //
     //////////////////////////
@@ -375,6 +487,12 @@ impl ColumnFamilyHelpers for IndexerStore {
             .expect("canonicity-slot column family exists")
     }
 
+    fn reorgs_cf(&self) -> &ColumnFamily {
+        self.database
+            .cf_handle("reorgs")
+            .expect("reorgs column family exists")
+    }
+
     //////////////////////
     // Ledger store CFs //
     //////////////////////
@@ -397,6 +515,21 @@ impl ColumnFamilyHelpers for IndexerStore {
             .expect("blocks-staged-ledger-hash column family exists")
     }
 
+    /// Key-value pairs
+    /// ```text
+    /// - key: {pk}{height}{state_hash}
+    /// - val: [Vec<AccountHistory>] serde bytes (one per token)
+    /// where
+    /// - pk:         [PublicKey::LEN] bytes
+    /// - height:     4 BE bytes
+    /// - state_hash: [BlockHash::LEN] bytes
+    /// ```
+    fn account_history_cf(&self) -> &ColumnFamily {
+        self.database
+            .cf_handle("account-history")
+            .expect("account-history column family exists")
+    }
+
     /// CF for storing staking ledgers
     /// ```
     /// - key: {genesis_hash}{epoch}{ledger_hash}
@@ -583,6 +716,16 @@ impl ColumnFamilyHelpers for IndexerStore {
             .expect("events column family exists")
     }
 
+    ////////////////////////////
+    // Witness tree store CFs //
+    ////////////////////////////
+
+    fn witness_tree_cf(&self) -> &ColumnFamily {
+        self.database
+            .cf_handle("witness-tree")
+            .expect("witness-tree column family exists")
+    }
+
     ////////////////////
     // Data count CFs //
     ////////////////////
@@ -730,4 +873,58 @@ impl ColumnFamilyHelpers for IndexerStore {
             .cf_handle("staking-ledger-accounts-epoch")
             .expect("staking-ledger-accounts-epoch column family exists")
     }
+
+    /// CF for the fork stats tally of each block
+    /// - key: `state_hash`
+    /// - value: [crate::store::fork_stats::BlockTally] JSON
+    fn fork_stats_blocks_cf(&self) -> &ColumnFamily {
+        self.database
+            .cf_handle("fork-stats-blocks")
+            .expect("fork-stats-blocks column family exists")
+    }
+
+    /// CF for per epoch canonical & orphaned blocks
+    /// - key: `epoch`
+    /// - value: [crate::store::fork_stats::CanonicityCounts] JSON of `epoch`
+    fn fork_stats_epoch_cf(&self) -> &ColumnFamily {
+        self.database
+            .cf_handle("fork-stats-epoch")
+            .expect("fork-stats-epoch column family exists")
+    }
+
+    /// CF for per epoch per account canonical & orphaned blocks
+    /// - key: `{epoch BE bytes}{pk}`
+    /// - value: [crate::store::fork_stats::CanonicityCounts] JSON of `pk` in `epoch`
+    fn fork_stats_pk_epoch_cf(&self) -> &ColumnFamily {
+        self.database
+            .cf_handle("fork-stats-pk-epoch")
+            .expect("fork-stats-pk-epoch column family exists")
+    }
+
+    /// CF for per account total canonical & orphaned blocks
+    /// - key: `pk`
+    /// - value: [crate::store::fork_stats::CanonicityCounts] JSON of `pk`
+    fn fork_stats_pk_total_cf(&self) -> &ColumnFamily {
+        self.database
+            .cf_handle("fork-stats-pk-total")
+            .expect("fork-stats-pk-total column family exists")
+    }
+
+    /// CF for per height orphaned blocks
+    /// - key: `{height BE bytes}`
+    /// - value: number of orphaned blocks at `height`
+    fn fork_stats_height_cf(&self) -> &ColumnFamily {
+        self.database
+            .cf_handle("fork-stats-height")
+            .expect("fork-stats-height column family exists")
+    }
+
+    /// CF for fork lengths
+    /// - key: `fork root state hash`
+    /// - value: number of blocks from the fork root to its highest descendant
+    fn fork_stats_fork_length_cf(&self) -> &ColumnFamily {
+        self.database
+            .cf_handle("fork-stats-fork-length")
+            .expect("fork-stats-fork-length column family exists")
+    }
 }
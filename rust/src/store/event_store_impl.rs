@@ -9,6 +9,7 @@ use crate::{
     store::{from_be_bytes, to_be_bytes, IndexerStore},
 };
 use log::trace;
+use tokio::sync::broadcast;
 
 impl EventStore for IndexerStore {
     fn add_event(&self, event: &IndexerEvent) -> anyhow::Result<u32> {
@@ -44,6 +45,9 @@ impl EventStore for IndexerStore {
         self.database
             .put(Self::NEXT_EVENT_SEQ_NUM_KEY, next_seq_num.to_be_bytes())?;
 
+        // notify subscribers, if there are any
+        let _ = self.event_sender.send(event.clone());
+
         // return next event sequence number
         Ok(next_seq_num)
     }
@@ -80,4 +84,8 @@ impl EventStore for IndexerStore {
     fn event_log_iterator(&self, mode: speedb::IteratorMode) -> speedb::DBIterator<'_> {
         self.database.iterator_cf(self.events_cf(), mode)
     }
+
+    fn subscribe_events(&self) -> broadcast::Receiver<IndexerEvent> {
+        self.event_sender.subscribe()
+    }
 }
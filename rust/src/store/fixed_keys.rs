@@ -7,6 +7,7 @@ pub trait FixedKeys {
     const KNOWN_GENESIS_PREV_STATE_HASHES_KEY: &'static [u8] =
         "genesis_prev_state_hashes".as_bytes();
     const NUM_BLOCK_BYTES_PROCESSED: &'static [u8] = "num_block_bytes_processed".as_bytes();
+    const INGESTION_CHECKPOINT_KEY: &'static [u8] = "ingestion_checkpoint".as_bytes();
 
     // version info
     const INDEXER_STORE_VERSION_KEY: &'static [u8] = "indexer_store_version".as_bytes();
@@ -17,4 +18,6 @@ pub trait FixedKeys {
     const TOTAL_NUM_SNARKS_KEY: &'static [u8] = "total_num_snarks".as_bytes();
     const TOTAL_NUM_FEE_TRANSFERS_KEY: &'static [u8] = "total_num_fee_transfers".as_bytes();
     const TOTAL_NUM_USER_COMMANDS_KEY: &'static [u8] = "total_num_user_commands".as_bytes();
+    const TOTAL_NUM_FORKS_KEY: &'static [u8] = "total_num_forks".as_bytes();
+    const TOTAL_FORK_LENGTH_KEY: &'static [u8] = "total_fork_length".as_bytes();
 }
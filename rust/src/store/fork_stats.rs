@@ -0,0 +1,141 @@
+//! Orphaned block & fork analytics
+//!
+//! Blocks are tallied once their canonicity is final, i.e. when a block at
+//! their height is added as a canonical block
+
+use crate::{block::BlockHash, canonicity::Canonicity, ledger::public_key::PublicKey};
+use serde::{Deserialize, Serialize};
+
+pub trait ForkStatsStore {
+    /// Tally the block with its final canonicity, no-op if already tallied
+    fn tally_block(&self, state_hash: &BlockHash, canonicity: Canonicity) -> anyhow::Result<()>;
+
+    /// Get the block's tally
+    fn get_block_tally(&self, state_hash: &BlockHash) -> anyhow::Result<Option<BlockTally>>;
+
+    /// Get the epoch's canonical & orphaned block counts
+    fn get_epoch_canonicity_counts(&self, epoch: u32) -> anyhow::Result<CanonicityCounts>;
+
+    /// Get the block producer's canonical & orphaned block counts, in the
+    /// given epoch or in total
+    fn get_pk_canonicity_counts(
+        &self,
+        pk: &PublicKey,
+        epoch: Option<u32>,
+    ) -> anyhow::Result<CanonicityCounts>;
+
+    /// Get the number of orphaned blocks at the height
+    fn get_num_forks_at_height(&self, blockchain_length: u32) -> anyhow::Result<u32>;
+
+    /// Get the number of forks, i.e. orphaned blocks with a canonical parent
+    fn get_num_forks(&self) -> anyhow::Result<u32>;
+
+    /// Get the sum of all fork lengths
+    fn get_total_fork_length(&self) -> anyhow::Result<u32>;
+
+    /// Get the fork stats, optionally constrained to an epoch, a block
+    /// producer and a height range
+    fn get_fork_stats(
+        &self,
+        epoch: Option<u32>,
+        pk: Option<&PublicKey>,
+        from: Option<u32>,
+        to: Option<u32>,
+    ) -> anyhow::Result<ForkStats>;
+}
+
+/// Final canonicity of a tallied block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockTally {
+    pub canonicity: Canonicity,
+
+    /// Lowest orphaned ancestor of an orphaned block, whose parent is
+    /// canonical
+    pub fork_root: Option<BlockHash>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CanonicityCounts {
+    pub canonical: u32,
+    pub orphaned: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EpochOrphanRate {
+    pub epoch: u32,
+    pub canonical: u32,
+    pub orphaned: u32,
+    pub orphan_rate: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProducerOrphanRate {
+    pub public_key: PublicKey,
+    pub canonical: u32,
+    pub orphaned: u32,
+    pub orphan_rate: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeightForks {
+    pub blockchain_length: u32,
+    pub num_forks: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForkStats {
+    /// Orphan rates by epoch
+    pub epochs: Vec<EpochOrphanRate>,
+
+    /// Orphan rates by block producer, most orphaned blocks first
+    pub producers: Vec<ProducerOrphanRate>,
+
+    /// Heights with orphaned blocks
+    pub forks_by_height: Vec<HeightForks>,
+
+    pub num_forks: u32,
+
+    /// Average number of blocks from a fork root to its highest descendant
+    pub average_fork_length: f64,
+}
+
+impl CanonicityCounts {
+    /// Fraction of tallied blocks which are orphaned
+    pub fn orphan_rate(&self) -> f64 {
+        let total = self.canonical + self.orphaned;
+        if total == 0 {
+            return 0.0;
+        }
+        self.orphaned as f64 / total as f64
+    }
+
+    pub fn increment(&mut self, canonicity: &Canonicity) {
+        match canonicity {
+            Canonicity::Canonical => self.canonical += 1,
+            Canonicity::Orphaned => self.orphaned += 1,
+            Canonicity::Pending => (),
+        }
+    }
+}
+
+impl EpochOrphanRate {
+    pub fn new(epoch: u32, counts: CanonicityCounts) -> Self {
+        Self {
+            epoch,
+            canonical: counts.canonical,
+            orphaned: counts.orphaned,
+            orphan_rate: counts.orphan_rate(),
+        }
+    }
+}
+
+impl ProducerOrphanRate {
+    pub fn new(public_key: PublicKey, counts: CanonicityCounts) -> Self {
+        Self {
+            public_key,
+            canonical: counts.canonical,
+            orphaned: counts.orphaned,
+            orphan_rate: counts.orphan_rate(),
+        }
+    }
+}
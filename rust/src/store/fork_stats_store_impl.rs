@@ -0,0 +1,335 @@
+use super::{
+    column_families::ColumnFamilyHelpers,
+    fixed_keys::FixedKeys,
+    fork_stats::{
+        BlockTally, CanonicityCounts, EpochOrphanRate, ForkStats, ForkStatsStore, HeightForks,
+        ProducerOrphanRate,
+    },
+    from_be_bytes, to_be_bytes, u32_prefix_key, IndexerStore,
+};
+use crate::{
+    block::{store::BlockStore, BlockHash},
+    canonicity::Canonicity,
+    ledger::public_key::PublicKey,
+    store::block_u32_prefix_from_key,
+};
+use log::trace;
+use speedb::{Direction, IteratorMode};
+
+impl ForkStatsStore for IndexerStore {
+    fn tally_block(&self, state_hash: &BlockHash, canonicity: Canonicity) -> anyhow::Result<()> {
+        if self.get_block_tally(state_hash)?.is_some() {
+            return Ok(());
+        }
+
+        trace!("Tallying {canonicity:?} block {state_hash}");
+        let (Some(blockchain_length), Some(epoch), Some(creator)) = (
+            self.get_block_height(state_hash)?,
+            self.get_block_epoch(state_hash)?,
+            self.get_block_creator(state_hash)?,
+        ) else {
+            trace!("Block missing from store {state_hash}");
+            return Ok(());
+        };
+
+        // orphaned blocks belong to the fork of their lowest orphaned ancestor
+        let fork_root = match canonicity {
+            Canonicity::Orphaned => {
+                let parent_fork_root = self
+                    .get_block_parent_hash(state_hash)?
+                    .map(|parent_hash| self.get_block_tally(&parent_hash))
+                    .transpose()?
+                    .flatten()
+                    .and_then(|tally| tally.fork_root);
+                Some(match parent_fork_root {
+                    Some(fork_root) => {
+                        self.update_fork_length(&fork_root, blockchain_length)?;
+                        fork_root
+                    }
+                    None => {
+                        self.add_fork(state_hash)?;
+                        state_hash.clone()
+                    }
+                })
+            }
+            _ => None,
+        };
+
+        // epoch counts
+        let mut counts = self.get_epoch_canonicity_counts(epoch)?;
+        counts.increment(&canonicity);
+        self.database.put_cf(
+            self.fork_stats_epoch_cf(),
+            to_be_bytes(epoch),
+            serde_json::to_vec(&counts)?,
+        )?;
+
+        // block producer epoch counts
+        let mut counts = self.get_pk_canonicity_counts(&creator, Some(epoch))?;
+        counts.increment(&canonicity);
+        self.database.put_cf(
+            self.fork_stats_pk_epoch_cf(),
+            u32_prefix_key(epoch, &creator.0),
+            serde_json::to_vec(&counts)?,
+        )?;
+
+        // block producer total counts
+        let mut counts = self.get_pk_canonicity_counts(&creator, None)?;
+        counts.increment(&canonicity);
+        self.database.put_cf(
+            self.fork_stats_pk_total_cf(),
+            creator.0.as_bytes(),
+            serde_json::to_vec(&counts)?,
+        )?;
+
+        // height counts
+        if canonicity == Canonicity::Orphaned {
+            let num_forks = self.get_num_forks_at_height(blockchain_length)?;
+            self.database.put_cf(
+                self.fork_stats_height_cf(),
+                to_be_bytes(blockchain_length),
+                to_be_bytes(num_forks + 1),
+            )?;
+        }
+
+        Ok(self.database.put_cf(
+            self.fork_stats_blocks_cf(),
+            state_hash.0.as_bytes(),
+            serde_json::to_vec(&BlockTally {
+                canonicity,
+                fork_root,
+            })?,
+        )?)
+    }
+
+    fn get_block_tally(&self, state_hash: &BlockHash) -> anyhow::Result<Option<BlockTally>> {
+        trace!("Getting block tally {state_hash}");
+        Ok(self
+            .database
+            .get_cf(self.fork_stats_blocks_cf(), state_hash.0.as_bytes())?
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()?)
+    }
+
+    fn get_epoch_canonicity_counts(&self, epoch: u32) -> anyhow::Result<CanonicityCounts> {
+        trace!("Getting epoch {epoch} canonicity counts");
+        Ok(self
+            .database
+            .get_cf(self.fork_stats_epoch_cf(), to_be_bytes(epoch))?
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()?
+            .unwrap_or_default())
+    }
+
+    fn get_pk_canonicity_counts(
+        &self,
+        pk: &PublicKey,
+        epoch: Option<u32>,
+    ) -> anyhow::Result<CanonicityCounts> {
+        trace!("Getting pk {pk} canonicity counts (epoch {epoch:?})");
+        let bytes = match epoch {
+            Some(epoch) => self
+                .database
+                .get_cf(self.fork_stats_pk_epoch_cf(), u32_prefix_key(epoch, &pk.0))?,
+            None => self
+                .database
+                .get_cf(self.fork_stats_pk_total_cf(), pk.0.as_bytes())?,
+        };
+        Ok(bytes
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()?
+            .unwrap_or_default())
+    }
+
+    fn get_num_forks_at_height(&self, blockchain_length: u32) -> anyhow::Result<u32> {
+        trace!("Getting number of forks at height {blockchain_length}");
+        Ok(self
+            .database
+            .get_cf(self.fork_stats_height_cf(), to_be_bytes(blockchain_length))?
+            .map_or(0, from_be_bytes))
+    }
+
+    fn get_num_forks(&self) -> anyhow::Result<u32> {
+        trace!("Getting number of forks");
+        Ok(self
+            .database
+            .get(Self::TOTAL_NUM_FORKS_KEY)?
+            .map_or(0, from_be_bytes))
+    }
+
+    fn get_total_fork_length(&self) -> anyhow::Result<u32> {
+        trace!("Getting total fork length");
+        Ok(self
+            .database
+            .get(Self::TOTAL_FORK_LENGTH_KEY)?
+            .map_or(0, from_be_bytes))
+    }
+
+    fn get_fork_stats(
+        &self,
+        epoch: Option<u32>,
+        pk: Option<&PublicKey>,
+        from: Option<u32>,
+        to: Option<u32>,
+    ) -> anyhow::Result<ForkStats> {
+        trace!("Getting fork stats (epoch {epoch:?}, pk {pk:?}, from {from:?}, to {to:?})");
+
+        // epoch orphan rates
+        let epochs = match epoch {
+            Some(epoch) => vec![EpochOrphanRate::new(
+                epoch,
+                self.get_epoch_canonicity_counts(epoch)?,
+            )],
+            None => {
+                let mut epochs = vec![];
+                for (key, value) in self
+                    .database
+                    .iterator_cf(self.fork_stats_epoch_cf(), IteratorMode::Start)
+                    .flatten()
+                {
+                    epochs.push(EpochOrphanRate::new(
+                        block_u32_prefix_from_key(&key)?,
+                        serde_json::from_slice(&value)?,
+                    ));
+                }
+                epochs
+            }
+        };
+
+        // block producer orphan rates
+        let mut producers = match (pk, epoch) {
+            (Some(pk), _) => vec![ProducerOrphanRate::new(
+                pk.clone(),
+                self.get_pk_canonicity_counts(pk, epoch)?,
+            )],
+            (None, Some(epoch)) => {
+                let prefix = to_be_bytes(epoch);
+                let mut producers = vec![];
+                for (key, value) in self
+                    .database
+                    .iterator_cf(
+                        self.fork_stats_pk_epoch_cf(),
+                        IteratorMode::From(&prefix, Direction::Forward),
+                    )
+                    .flatten()
+                {
+                    if key[..prefix.len()] != prefix {
+                        break;
+                    }
+                    producers.push(ProducerOrphanRate::new(
+                        PublicKey::from_bytes(&key[prefix.len()..])?,
+                        serde_json::from_slice(&value)?,
+                    ));
+                }
+                producers
+            }
+            (None, None) => {
+                let mut producers = vec![];
+                for (key, value) in self
+                    .database
+                    .iterator_cf(self.fork_stats_pk_total_cf(), IteratorMode::Start)
+                    .flatten()
+                {
+                    producers.push(ProducerOrphanRate::new(
+                        PublicKey::from_bytes(&key)?,
+                        serde_json::from_slice(&value)?,
+                    ));
+                }
+                producers
+            }
+        };
+        producers.sort_by(|a, b| {
+            b.orphaned
+                .cmp(&a.orphaned)
+                .then_with(|| a.public_key.cmp(&b.public_key))
+        });
+
+        // fork counts by height
+        let start = to_be_bytes(from.unwrap_or_default());
+        let mut forks_by_height = vec![];
+        for (key, value) in self
+            .database
+            .iterator_cf(
+                self.fork_stats_height_cf(),
+                IteratorMode::From(&start, Direction::Forward),
+            )
+            .flatten()
+        {
+            let blockchain_length = block_u32_prefix_from_key(&key)?;
+            if to.is_some_and(|to| blockchain_length > to) {
+                break;
+            }
+            forks_by_height.push(HeightForks {
+                blockchain_length,
+                num_forks: from_be_bytes(value.to_vec()),
+            });
+        }
+
+        // average fork length
+        let num_forks = self.get_num_forks()?;
+        let average_fork_length = if num_forks == 0 {
+            0.0
+        } else {
+            self.get_total_fork_length()? as f64 / num_forks as f64
+        };
+
+        Ok(ForkStats {
+            epochs,
+            producers,
+            forks_by_height,
+            num_forks,
+            average_fork_length,
+        })
+    }
+}
+
+impl IndexerStore {
+    /// Record a new fork of length 1, rooted at the orphaned block
+    fn add_fork(&self, fork_root: &BlockHash) -> anyhow::Result<()> {
+        trace!("Adding fork {fork_root}");
+        self.database.put_cf(
+            self.fork_stats_fork_length_cf(),
+            fork_root.0.as_bytes(),
+            to_be_bytes(1),
+        )?;
+        self.database.put(
+            Self::TOTAL_NUM_FORKS_KEY,
+            to_be_bytes(self.get_num_forks()? + 1),
+        )?;
+        Ok(self.database.put(
+            Self::TOTAL_FORK_LENGTH_KEY,
+            to_be_bytes(self.get_total_fork_length()? + 1),
+        )?)
+    }
+
+    /// Extend the fork to the orphaned descendant's height
+    fn update_fork_length(
+        &self,
+        fork_root: &BlockHash,
+        blockchain_length: u32,
+    ) -> anyhow::Result<()> {
+        let (Some(fork_length), Some(root_length)) = (
+            self.database
+                .get_cf(self.fork_stats_fork_length_cf(), fork_root.0.as_bytes())?
+                .map(from_be_bytes),
+            self.get_block_height(fork_root)?,
+        ) else {
+            return Ok(());
+        };
+
+        let length = blockchain_length.saturating_sub(root_length) + 1;
+        if length > fork_length {
+            trace!("Updating fork {fork_root} length {length}");
+            self.database.put_cf(
+                self.fork_stats_fork_length_cf(),
+                fork_root.0.as_bytes(),
+                to_be_bytes(length),
+            )?;
+            self.database.put(
+                Self::TOTAL_FORK_LENGTH_KEY,
+                to_be_bytes(self.get_total_fork_length()? + length - fork_length),
+            )?;
+        }
+        Ok(())
+    }
+}
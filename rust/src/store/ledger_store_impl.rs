@@ -5,16 +5,22 @@ use crate::{
     constants::*,
     event::{db::*, store::EventStore, IndexerEvent},
     ledger::{
+        account::{Account, AccountHistory},
         diff::LedgerDiff,
         public_key::PublicKey,
         staking::{AggregatedEpochStakeDelegations, StakingLedger},
         store::LedgerStore,
+        token::TokenId,
         Ledger, LedgerHash,
     },
-    store::{account::AccountStore, from_be_bytes, to_be_bytes, IndexerStore},
+    store::{
+        account::AccountStore, account_history_key, from_be_bytes, pk_key_prefix,
+        pk_txn_sort_key_prefix, pk_txn_sort_key_sort, to_be_bytes, IndexerStore,
+    },
 };
 use log::{error, trace};
-use std::mem::size_of;
+use speedb::{Direction, IteratorMode};
+use std::{collections::HashMap, mem::size_of};
 
 impl LedgerStore for IndexerStore {
     ////////////////////
@@ -51,7 +57,20 @@ impl LedgerStore for IndexerStore {
         )?;
 
         // index on state hash & add new ledger event
-        if self
+        // a stored block's ledger is indexed on its staged ledger hash, even
+        // when the block is a hardfork's fork block
+        if let Some((block, _)) = self.get_block(state_hash)? {
+            let ledger_hash = block.staged_ledger_hash();
+            if self.add_ledger(&ledger_hash, state_hash).unwrap_or(false) {
+                self.add_event(&IndexerEvent::Db(DbEvent::Ledger(
+                    DbLedgerEvent::NewLedger {
+                        ledger_hash,
+                        state_hash: block.state_hash(),
+                        blockchain_length: block.blockchain_length(),
+                    },
+                )))?;
+            }
+        } else if self
             .get_known_genesis_prev_state_hashes()?
             .contains(state_hash)
         {
@@ -67,26 +86,8 @@ impl LedgerStore for IndexerStore {
                     },
                 )))?;
             }
-        } else {
-            match self.get_block(state_hash)? {
-                Some((block, _)) => {
-                    let ledger_hash = block.staged_ledger_hash();
-                    if self.add_ledger(&ledger_hash, state_hash).unwrap_or(false) {
-                        self.add_event(&IndexerEvent::Db(DbEvent::Ledger(
-                            DbLedgerEvent::NewLedger {
-                                ledger_hash,
-                                state_hash: block.state_hash(),
-                                blockchain_length: block.blockchain_length(),
-                            },
-                        )))?;
-                    }
-                }
-                None => {
-                    if state_hash.0 != MAINNET_GENESIS_PREV_STATE_HASH {
-                        error!("Block missing from store: {state_hash}");
-                    }
-                }
-            }
+        } else if state_hash.0 != MAINNET_GENESIS_PREV_STATE_HASH {
+            error!("Block missing from store: {state_hash}");
         }
         Ok(())
     }
@@ -97,8 +98,8 @@ impl LedgerStore for IndexerStore {
         genesis_ledger: Ledger,
     ) -> anyhow::Result<()> {
         // initialize account balances for sorting
-        for (pk, acct) in &genesis_ledger.accounts {
-            self.update_account_balance(pk, Some(acct.balance.0))?;
+        for ((pk, token), acct) in &genesis_ledger.accounts {
+            self.update_account_balance(pk, token, Some(acct.balance.0))?;
         }
 
         // add the ledger to the db
@@ -112,52 +113,69 @@ impl LedgerStore for IndexerStore {
         memoize: bool,
     ) -> anyhow::Result<Option<Ledger>> {
         trace!("Getting staged ledger state hash {state_hash}");
+        if let Some((ledger_state_hash, diffs)) = self.get_stored_ledger_ancestor(state_hash)? {
+            if let Some(mut ledger) = self
+                .database
+                .get_pinned_cf(self.ledgers_cf(), ledger_state_hash.0.as_bytes())?
+                .and_then(|bytes| Ledger::from_bytes(bytes.to_vec()).ok())
+            {
+                // apply diffs
+                let diff = LedgerDiff::append_vec(diffs);
+                ledger._apply_diff(&diff)?;
 
-        let mut curr_state_hash = state_hash.clone();
-        let mut diffs = vec![];
-
-        // walk chain back to a stored ledger
-        // collect diffs to compute the current ledger
-        while self
-            .database
-            .get_pinned_cf(self.ledgers_cf(), curr_state_hash.0.as_bytes())?
-            .is_none()
-        {
-            trace!("No staged ledger found for state hash {curr_state_hash}");
-            if let Some(diff) = self.get_block_ledger_diff(&curr_state_hash)? {
-                diffs.push(diff);
-                if let Ok(Some(parent_hash)) = self.get_block_parent_hash(&curr_state_hash) {
-                    trace!("Checking for staged ledger state hash {parent_hash}");
-                    curr_state_hash = parent_hash;
-                }
-            } else {
-                if curr_state_hash.0 != MAINNET_GENESIS_PREV_STATE_HASH {
-                    error!("Block missing from store: {curr_state_hash}");
+                if memoize {
+                    trace!("Memoizing ledger for block {state_hash}");
+                    self.add_ledger_state_hash(state_hash, ledger.clone())?;
                 }
-                return Ok(None);
+                return Ok(Some(ledger));
             }
         }
+        Ok(None)
+    }
 
-        trace!("Found staged ledger state hash {curr_state_hash}");
-        if let Some(mut ledger) = self
-            .database
-            .get_pinned_cf(self.ledgers_cf(), curr_state_hash.0.as_bytes())?
-            .and_then(|bytes| Ledger::from_bytes(bytes.to_vec()).ok())
-        {
-            // apply diffs
-            diffs.reverse();
-            let diff = LedgerDiff::append_vec(diffs);
-            ledger._apply_diff(&diff)?;
-
-            if memoize {
-                trace!("Memoizing ledger for block {state_hash}");
-                self.add_ledger_state_hash(state_hash, ledger.clone())?;
+    fn get_account_state_hash(
+        &self,
+        pk: &PublicKey,
+        token: &TokenId,
+        state_hash: &BlockHash,
+    ) -> anyhow::Result<Option<Account>> {
+        trace!("Getting account {pk} ({token}) at state hash {state_hash}");
+        if let Some((ledger_state_hash, diffs)) = self.get_stored_ledger_ancestor(state_hash)? {
+            let mut ledger = Ledger::new();
+            if let Some(bytes) = self
+                .database
+                .get_pinned_cf(self.ledgers_cf(), ledger_state_hash.0.as_bytes())?
+            {
+                if let Some(account) = Ledger::account_from_bytes(&bytes, pk, token)? {
+                    ledger.insert_account(account);
+                }
             }
-            return Ok(Some(ledger));
+
+            // only apply the account's diffs
+            let account_id = (pk.clone(), token.clone());
+            for mut diff in diffs {
+                diff.account_diffs
+                    .retain(|acct_diff| acct_diff.account_id() == account_id);
+                ledger._apply_diff(&diff)?;
+            }
+            return Ok(ledger.accounts.remove(&account_id));
         }
         Ok(None)
     }
 
+    fn get_account_at_height(
+        &self,
+        pk: &PublicKey,
+        token: &TokenId,
+        height: u32,
+    ) -> anyhow::Result<Option<Account>> {
+        trace!("Getting account {pk} ({token}) at height {height}");
+        self.get_canonical_hash_at_height(height)?
+            .map_or(Ok(None), |state_hash| {
+                self.get_account_state_hash(pk, token, &state_hash)
+            })
+    }
+
     fn get_ledger(&self, ledger_hash: &LedgerHash) -> anyhow::Result<Option<Ledger>> {
         trace!("Getting staged ledger hash {ledger_hash}");
         let key = ledger_hash.0.as_bytes();
@@ -185,6 +203,57 @@ impl LedgerStore for IndexerStore {
             })
     }
 
+    fn add_account_history(
+        &self,
+        state_hash: &BlockHash,
+        blockchain_length: u32,
+        accounts: &[&Account],
+    ) -> anyhow::Result<()> {
+        trace!("Adding account history for block {state_hash} (length {blockchain_length})");
+
+        // group token accounts by public key
+        let mut history = <HashMap<&PublicKey, Vec<AccountHistory>>>::new();
+        for account in accounts {
+            history
+                .entry(&account.public_key)
+                .or_default()
+                .push(AccountHistory::new(state_hash, blockchain_length, account));
+        }
+
+        for (pk, history) in history {
+            self.database.put_cf(
+                self.account_history_cf(),
+                account_history_key(pk, blockchain_length, state_hash),
+                serde_json::to_vec(&history)?,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn get_account_history(
+        &self,
+        pk: &PublicKey,
+        token: &TokenId,
+        from: u32,
+        to: u32,
+    ) -> anyhow::Result<Vec<AccountHistory>> {
+        trace!("Getting account history {pk} ({token}) from {from} to {to}");
+        let start = pk_txn_sort_key_prefix(pk.clone(), from);
+        let mut res = vec![];
+        for (key, value) in self
+            .account_history_iterator(IteratorMode::From(&start, Direction::Forward))
+            .flatten()
+        {
+            if pk_key_prefix(&key) != *pk || pk_txn_sort_key_sort(&key) > to {
+                break;
+            }
+
+            let history: Vec<AccountHistory> = serde_json::from_slice(&value)?;
+            res.extend(history.into_iter().filter(|entry| entry.token == *token));
+        }
+        Ok(res)
+    }
+
     fn set_block_ledger_diff(
         &self,
         state_hash: &BlockHash,
@@ -492,6 +561,10 @@ impl LedgerStore for IndexerStore {
         self.database.iterator_cf(self.staking_ledgers_cf(), mode)
     }
 
+    fn account_history_iterator(&self, mode: speedb::IteratorMode) -> speedb::DBIterator<'_> {
+        self.database.iterator_cf(self.account_history_cf(), mode)
+    }
+
     ////////////////////////////
     // Staking ledger counts //
     ///////////////////////////
@@ -526,6 +599,45 @@ impl LedgerStore for IndexerStore {
     }
 }
 
+impl IndexerStore {
+    /// Walks the chain back from `state_hash` to the nearest block with a
+    /// stored ledger, returns its state hash & the ledger diffs since (lowest
+    /// first)
+    fn get_stored_ledger_ancestor(
+        &self,
+        state_hash: &BlockHash,
+    ) -> anyhow::Result<Option<(BlockHash, Vec<LedgerDiff>)>> {
+        let mut curr_state_hash = state_hash.clone();
+        let mut diffs = vec![];
+
+        // walk chain back to a stored ledger
+        // collect diffs to compute the current ledger
+        while self
+            .database
+            .get_pinned_cf(self.ledgers_cf(), curr_state_hash.0.as_bytes())?
+            .is_none()
+        {
+            trace!("No staged ledger found for state hash {curr_state_hash}");
+            if let Some(diff) = self.get_block_ledger_diff(&curr_state_hash)? {
+                diffs.push(diff);
+                if let Ok(Some(parent_hash)) = self.get_block_parent_hash(&curr_state_hash) {
+                    trace!("Checking for staged ledger state hash {parent_hash}");
+                    curr_state_hash = parent_hash;
+                }
+            } else {
+                if curr_state_hash.0 != MAINNET_GENESIS_PREV_STATE_HASH {
+                    error!("Block missing from store: {curr_state_hash}");
+                }
+                return Ok(None);
+            }
+        }
+
+        trace!("Found staged ledger state hash {curr_state_hash}");
+        diffs.reverse();
+        Ok(Some((curr_state_hash, diffs)))
+    }
+}
+
 /// Staking ledger amount sort key
 /// ```
 /// {epoch BE}{amount BE}{suffix}
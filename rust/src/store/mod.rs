@@ -3,8 +3,10 @@
 
 // traits
 pub mod account;
+pub mod checkpoint;
 pub mod column_families;
 pub mod fixed_keys;
+pub mod fork_stats;
 pub mod username;
 pub mod version;
 
@@ -13,20 +15,25 @@ pub mod account_store_impl;
 pub mod block_store_impl;
 pub mod canonicity_store_impl;
 pub mod chain_store_impl;
+pub mod checkpoint_store_impl;
 pub mod column_families_impl;
 pub mod event_store_impl;
+pub mod fork_stats_store_impl;
 pub mod internal_command_store_impl;
 pub mod ledger_store_impl;
 pub mod snark_store_impl;
 pub mod user_command_store_impl;
 pub mod username_store_impl;
 pub mod version_store_impl;
+pub mod witness_tree_store_impl;
+pub mod zkapp_command_store_impl;
 
 use self::fixed_keys::FixedKeys;
 use crate::{
     block::BlockHash,
     command::signed::TXN_HASH_LEN,
-    ledger::{account::Nonce, public_key::PublicKey},
+    event::IndexerEvent,
+    ledger::{account::Nonce, public_key::PublicKey, token::TokenId},
 };
 use anyhow::{anyhow, bail, Context};
 use log::{debug, info};
@@ -38,6 +45,7 @@ use std::{
     mem::size_of,
     path::{Path, PathBuf},
 };
+use tokio::sync::broadcast;
 use version::{IndexerStoreVersion, VersionStore};
 
 #[derive(Debug)]
@@ -45,6 +53,9 @@ pub struct IndexerStore {
     pub db_path: PathBuf,
     pub database: DB,
     pub is_primary: bool,
+
+    /// Sends recorded events to subscribers
+    pub event_sender: broadcast::Sender<IndexerEvent>,
 }
 
 #[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
@@ -54,9 +65,12 @@ pub struct DBUpdate<T> {
 }
 
 impl IndexerStore {
+    /// Number of events buffered for each subscriber
+    const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
     /// Add the corresponding CF helper to [ColumnFamilyHelpers]
     /// & modify [IndexerStoreVersion] as needed!
-    const COLUMN_FAMILIES: [&'static str; 80] = [
+    const COLUMN_FAMILIES: [&'static str; 96] = [
         // accounts
         "account-balance",
         "account-balance-sort",
@@ -85,6 +99,7 @@ impl IndexerStore {
         // canonicity
         "canonicity-length",
         "canonicity-slot",
+        "reorgs",
         // user commands
         "user-commands",
         "user-commands-pk",
@@ -112,12 +127,23 @@ impl IndexerStore {
         // internal commands
         "internal-commands",
         "internal-commands-global-slot",
+        // zkApp commands
+        "zkapp-commands",
+        "zkapp-commands-block",
+        "zkapp-commands-height-sort",
+        "zkapp-commands-fee-payer-sort",
+        "zkapp-commands-pk-sort",
+        "zkapp-events",
+        "zkapp-actions",
         // indexer store events
         "events",
+        // persisted witness tree
+        "witness-tree",
         // staged ledgers
         "ledgers",
         "blocks-ledger-diff",
         "blocks-staged-ledger-hash",
+        "account-history",
         // staking ledgers & delegations
         "staking-ledgers",
         "staking-delegations",
@@ -154,6 +180,13 @@ impl IndexerStore {
         "snarks-epoch",
         "snarks-pk-epoch",
         "snarks-pk-total",
+        // fork stats
+        "fork-stats-blocks",
+        "fork-stats-epoch",
+        "fork-stats-pk-epoch",
+        "fork-stats-pk-total",
+        "fork-stats-height",
+        "fork-stats-fork-length",
     ];
 
     /// Creates a new _primary_ indexer store
@@ -174,6 +207,7 @@ impl IndexerStore {
         let primary = Self {
             is_primary: true,
             db_path: path.into(),
+            event_sender: broadcast::channel(Self::EVENT_CHANNEL_CAPACITY).0,
             database: speedb::DBWithThreadMode::open_cf_descriptors(
                 &database_opts,
                 path,
@@ -231,6 +265,7 @@ impl IndexerStore {
         let read_only = Self {
             is_primary: false,
             db_path: secondary.into(),
+            event_sender: broadcast::channel(Self::EVENT_CHANNEL_CAPACITY).0,
             database: speedb::DBWithThreadMode::open_cf_descriptors_as_secondary(
                 &database_opts,
                 primary,
@@ -361,6 +396,20 @@ fn u64_prefix_key(prefix: u64, suffix: &str) -> Vec<u8> {
     bytes
 }
 
+/// Fixed width key bytes of a transaction hash, post-hardfork hashes are
+/// right-padded with a zero byte to [TXN_HASH_LEN] bytes
+pub fn txn_hash_key_bytes(txn_hash: &str) -> Vec<u8> {
+    let mut bytes = txn_hash.as_bytes().to_vec();
+    bytes.resize(TXN_HASH_LEN, 0);
+    bytes
+}
+
+/// Inverse of [txn_hash_key_bytes]
+pub fn txn_hash_from_key_bytes(bytes: &[u8]) -> anyhow::Result<String> {
+    let len = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    Ok(String::from_utf8(bytes[..len].to_vec())?)
+}
+
 /// Key format for sorting txns by global slot:
 /// `{u32_prefix}{txn_hash}{state_hash}`
 /// ```
@@ -369,7 +418,7 @@ fn u64_prefix_key(prefix: u64, suffix: &str) -> Vec<u8> {
 /// - state_hash: [BlockHash::LEN] bytes
 pub fn txn_sort_key(prefix: u32, txn_hash: &str, state_hash: BlockHash) -> Vec<u8> {
     let mut bytes = to_be_bytes(prefix);
-    bytes.append(&mut txn_hash.as_bytes().to_vec());
+    bytes.append(&mut txn_hash_key_bytes(txn_hash));
     bytes.append(&mut state_hash.to_bytes());
     bytes
 }
@@ -392,7 +441,7 @@ pub fn pk_txn_sort_key(
     let mut bytes = pk.to_bytes();
     bytes.append(&mut to_be_bytes(sort));
     bytes.append(&mut to_be_bytes(nonce.0));
-    bytes.append(&mut txn_hash.as_bytes().to_vec());
+    bytes.append(&mut txn_hash_key_bytes(txn_hash));
     bytes.append(&mut state_hash.to_bytes());
     bytes
 }
@@ -413,6 +462,59 @@ pub fn balance_key_prefix(key: &[u8]) -> u64 {
     from_u64_be_bytes(key[..size_of::<u64>()].to_vec())
 }
 
+/// Key format for the account balance of a token:
+/// `{pk}{token}`
+/// ```text
+/// - pk:    [PublicKey::LEN] bytes
+/// - token: [TokenId] bytes
+/// ```
+pub fn pk_token_key(pk: &PublicKey, token: &TokenId) -> Vec<u8> {
+    let mut bytes = pk.clone().to_bytes();
+    bytes.append(&mut token.clone().to_bytes());
+    bytes
+}
+
+/// Key format for sorting a token's accounts by balance:
+/// `{token}{balance}{pk}`
+/// ```text
+/// - token:   [TokenId] bytes
+/// - balance: 8 BE bytes
+/// - pk:      [PublicKey::LEN] bytes
+/// ```
+pub fn token_balance_sort_key(token: &TokenId, balance: u64, pk: &PublicKey) -> Vec<u8> {
+    let mut bytes = token.clone().to_bytes();
+    bytes.append(&mut balance.to_be_bytes().to_vec());
+    bytes.append(&mut pk.clone().to_bytes());
+    bytes
+}
+
+/// Balance & public key of a `{token}{balance}{pk}` key, if it is one of
+/// the token's keys
+pub fn token_balance_sort_key_balance_pk(key: &[u8], token: &TokenId) -> Option<(u64, PublicKey)> {
+    let token = token.0.as_bytes();
+    if key.len() != token.len() + size_of::<u64>() + PublicKey::LEN || !key.starts_with(token) {
+        return None;
+    }
+
+    let balance = balance_key_prefix(&key[token.len()..]);
+    PublicKey::from_bytes(&key[(token.len() + size_of::<u64>())..])
+        .ok()
+        .map(|pk| (balance, pk))
+}
+
+/// Key format for an account's history:
+/// `{pk}{height}{state_hash}`
+/// ```text
+/// - pk:         [PublicKey::LEN] bytes
+/// - height:     4 BE bytes
+/// - state_hash: [BlockHash::LEN] bytes
+/// ```
+pub fn account_history_key(pk: &PublicKey, height: u32, state_hash: &BlockHash) -> Vec<u8> {
+    let mut bytes = pk_txn_sort_key_prefix(pk.clone(), height);
+    bytes.append(&mut state_hash.clone().to_bytes());
+    bytes
+}
+
 pub fn pk_txn_sort_key_sort(key: &[u8]) -> u32 {
     from_be_bytes(key[PublicKey::LEN..][..size_of::<u32>()].to_vec())
 }
@@ -424,7 +526,7 @@ pub fn pk_txn_sort_key_nonce(key: &[u8]) -> Nonce {
 }
 
 pub fn txn_hash_of_key(key: &[u8]) -> String {
-    String::from_utf8(key[(PublicKey::LEN + 2 * size_of::<u32>())..][..TXN_HASH_LEN].to_vec())
+    txn_hash_from_key_bytes(&key[(PublicKey::LEN + 2 * size_of::<u32>())..][..TXN_HASH_LEN])
         .expect("txn hash")
 }
 
@@ -440,7 +542,7 @@ pub fn block_txn_index_key(state_hash: &BlockHash, index: u32) -> Vec<u8> {
 }
 
 pub fn txn_block_key(txn_hash: &str, state_hash: BlockHash) -> Vec<u8> {
-    let mut bytes = txn_hash.as_bytes().to_vec();
+    let mut bytes = txn_hash_key_bytes(txn_hash);
     bytes.append(&mut state_hash.clone().to_bytes());
     bytes
 }
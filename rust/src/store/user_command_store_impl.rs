@@ -31,7 +31,7 @@ impl UserCommandStore for IndexerStore {
         // per command
         for command in &user_commands {
             let signed = SignedCommand::from(command.clone());
-            let txn_hash = signed.hash_signed_command()?;
+            let txn_hash = signed.txn_hash(&block.version())?;
             trace!("Adding user command {txn_hash} block {}", block.summary());
 
             // add signed command
@@ -44,6 +44,7 @@ impl UserCommandStore for IndexerStore {
                     block.blockchain_length(),
                     block.timestamp(),
                     block.global_slot_since_genesis(),
+                    &block.version(),
                 ))?,
             )?;
 
@@ -144,6 +145,7 @@ impl UserCommandStore for IndexerStore {
                         block.blockchain_length(),
                         block.timestamp(),
                         block.global_slot_since_genesis(),
+                        &block.version(),
                     )
                 })
                 .collect();
@@ -3,11 +3,12 @@ use super::{
     IndexerStore,
 };
 use crate::{
-    block::{store::BlockStore, BlockHash},
+    block::BlockHash,
     canonicity::store::CanonicityStore,
     ledger::{public_key::PublicKey, username::Username},
     store::{column_families::ColumnFamilyHelpers, from_be_bytes, to_be_bytes, DBUpdate},
 };
+use anyhow::Context;
 use log::{error, trace};
 use std::collections::HashMap;
 
@@ -51,52 +52,23 @@ impl UsernameStore for IndexerStore {
     ) -> anyhow::Result<UsernameAccountUpdate> {
         trace!("Getting common username updates:\n  old: {old_best_tip}\n  new: {new_best_tip}");
 
-        // follows the old best tip back to the common ancestor
-        let mut a = old_best_tip.clone();
-        let mut unapply = vec![];
-
-        // follows the new best tip back to the common ancestor
-        let mut b = new_best_tip.clone();
-        let mut apply = vec![];
-
-        let a_length = self.get_block_height(&a)?.expect("a has a length");
-        let b_length = self.get_block_height(&b)?.expect("b has a length");
-
-        // bring b back to the same height as a
-        let genesis_state_hashes: Vec<BlockHash> = self.get_known_genesis_state_hashes()?;
-        for _ in 0..b_length.saturating_sub(a_length) {
-            // check if there's a previous block
-            if genesis_state_hashes.contains(&b) {
-                break;
-            }
-
-            apply.push(UsernameUpdate(
-                self.get_block_username_updates(&b)?.unwrap(),
-            ));
-            b = self.get_block_parent_hash(&b)?.expect("b has a parent");
-        }
-
-        // find the common ancestor
-        let mut a_prev = self.get_block_parent_hash(&a)?.expect("a has a parent");
-        let mut b_prev = self.get_block_parent_hash(&b)?.expect("b has a parent");
-
-        while a != b && !genesis_state_hashes.contains(&a) {
-            // add blocks to appropriate collection
-            unapply.push(UsernameUpdate(
-                self.get_block_username_updates(&a)?.unwrap(),
-            ));
-            apply.push(UsernameUpdate(
-                self.get_block_username_updates(&b)?.unwrap(),
-            ));
-
-            // descend
-            a = a_prev;
-            b = b_prev;
-
-            a_prev = self.get_block_parent_hash(&a)?.expect("a has a parent");
-            b_prev = self.get_block_parent_hash(&b)?.expect("b has a parent");
-        }
-
+        let genesis_state_hashes = self.get_known_genesis_state_hashes()?;
+        let (old_branch, new_branch) =
+            self.reorg_branches(old_best_tip, new_best_tip, &genesis_state_hashes)?;
+        let username_updates = |branch: Vec<(BlockHash, u32)>| -> anyhow::Result<Vec<_>> {
+            branch
+                .into_iter()
+                .map(|(state_hash, _)| {
+                    let updates = self
+                        .get_block_username_updates(&state_hash)?
+                        .with_context(|| format!("username updates of {state_hash}"))?;
+                    Ok(UsernameUpdate(updates))
+                })
+                .collect()
+        };
+
+        let unapply = username_updates(old_branch)?;
+        let mut apply = username_updates(new_branch)?;
         apply.reverse();
         Ok(DBUpdate { apply, unapply })
     }
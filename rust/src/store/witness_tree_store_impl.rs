@@ -0,0 +1,30 @@
+use super::{column_families::ColumnFamilyHelpers, IndexerStore};
+use crate::state::store::{PersistedWitnessTree, WitnessTreeStore};
+use log::trace;
+
+impl IndexerStore {
+    const WITNESS_TREE_KEY: &'static [u8] = "witness_tree".as_bytes();
+}
+
+impl WitnessTreeStore for IndexerStore {
+    fn set_witness_tree(&self, witness_tree: &PersistedWitnessTree) -> anyhow::Result<()> {
+        trace!(
+            "Setting witness tree (next seq num {})",
+            witness_tree.next_seq_num
+        );
+        Ok(self.database.put_cf(
+            self.witness_tree_cf(),
+            Self::WITNESS_TREE_KEY,
+            serde_json::to_vec(witness_tree)?,
+        )?)
+    }
+
+    fn get_witness_tree(&self) -> anyhow::Result<Option<PersistedWitnessTree>> {
+        trace!("Getting witness tree");
+        Ok(self
+            .database
+            .get_cf(self.witness_tree_cf(), Self::WITNESS_TREE_KEY)?
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()?)
+    }
+}
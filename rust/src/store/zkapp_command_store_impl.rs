@@ -0,0 +1,257 @@
+use super::column_families::ColumnFamilyHelpers;
+use crate::{
+    block::{precomputed::PrecomputedBlock, BlockHash},
+    command::zkapp::{
+        store::ZkappCommandStore, ZkappActionWithData, ZkappCommandWithData, ZkappEventWithData,
+    },
+    ledger::public_key::PublicKey,
+    store::{block_txn_index_key, from_be_bytes, pk_key_prefix, to_be_bytes, IndexerStore},
+};
+use log::trace;
+use speedb::{ColumnFamily, DBIterator, Direction, IteratorMode};
+
+impl ZkappCommandStore for IndexerStore {
+    fn add_zkapp_commands(&self, block: &PrecomputedBlock) -> anyhow::Result<()> {
+        trace!("Adding zkapp commands from block {}", block.summary());
+
+        let state_hash = block.state_hash();
+        let blockchain_length = block.blockchain_length();
+        let zkapp_commands = ZkappCommandWithData::from_precomputed(block);
+
+        // per block
+        self.database.put_cf(
+            self.zkapp_commands_block_cf(),
+            state_hash.0.as_bytes(),
+            serde_json::to_vec(&zkapp_commands)?,
+        )?;
+
+        // per command
+        for command in &zkapp_commands {
+            let index = command.index;
+            trace!("Adding zkapp command {index} block {}", block.summary());
+
+            let command_key = block_txn_index_key(&state_hash, index);
+            self.database.put_cf(
+                self.zkapp_commands_cf(),
+                &command_key,
+                serde_json::to_vec(command)?,
+            )?;
+
+            // add index for block height sorting
+            let mut height_key = to_be_bytes(blockchain_length);
+            height_key.extend_from_slice(&command_key);
+            self.database
+                .put_cf(self.zkapp_commands_height_sort_cf(), height_key, b"")?;
+
+            // add fee payer index
+            self.database.put_cf(
+                self.zkapp_commands_fee_payer_sort_cf(),
+                zkapp_pk_sort_key(&command.fee_payer, blockchain_length, command, &command_key),
+                b"",
+            )?;
+
+            // add touched account index
+            for pk in command.public_keys() {
+                self.database.put_cf(
+                    self.zkapp_commands_pk_sort_cf(),
+                    zkapp_pk_sort_key(&pk, blockchain_length, command, &command_key),
+                    b"",
+                )?;
+            }
+
+            // add events & actions by zkApp address
+            for (n, event) in command.events().iter().enumerate() {
+                self.database.put_cf(
+                    self.zkapp_events_cf(),
+                    zkapp_emitted_key(event, n as u32),
+                    serde_json::to_vec(event)?,
+                )?;
+            }
+            for (n, action) in command.actions().iter().enumerate() {
+                self.database.put_cf(
+                    self.zkapp_actions_cf(),
+                    zkapp_emitted_key(action, n as u32),
+                    serde_json::to_vec(action)?,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn get_zkapp_command(
+        &self,
+        state_hash: &BlockHash,
+        index: u32,
+    ) -> anyhow::Result<Option<ZkappCommandWithData>> {
+        trace!("Getting zkapp command {index} in block {state_hash}");
+        Ok(self
+            .database
+            .get_pinned_cf(
+                self.zkapp_commands_cf(),
+                block_txn_index_key(state_hash, index),
+            )?
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok()))
+    }
+
+    fn get_block_zkapp_commands(
+        &self,
+        state_hash: &BlockHash,
+    ) -> anyhow::Result<Option<Vec<ZkappCommandWithData>>> {
+        trace!("Getting block zkapp commands {state_hash}");
+        Ok(self
+            .database
+            .get_pinned_cf(self.zkapp_commands_block_cf(), state_hash.0.as_bytes())?
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok()))
+    }
+
+    fn get_zkapp_commands_fee_payer(
+        &self,
+        pk: &PublicKey,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ZkappCommandWithData>> {
+        trace!("Getting zkapp commands for fee payer {pk}");
+        self.zkapp_commands_pk_sort(self.zkapp_commands_fee_payer_sort_cf(), pk, limit)
+    }
+
+    fn get_zkapp_commands_public_key(
+        &self,
+        pk: &PublicKey,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ZkappCommandWithData>> {
+        trace!("Getting zkapp commands for account {pk}");
+        self.zkapp_commands_pk_sort(self.zkapp_commands_pk_sort_cf(), pk, limit)
+    }
+
+    fn get_zkapp_events(
+        &self,
+        zkapp: &PublicKey,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ZkappEventWithData>> {
+        trace!("Getting zkapp events for {zkapp}");
+        self.zkapp_emitted(self.zkapp_events_cf(), zkapp, limit)
+    }
+
+    fn get_zkapp_actions(
+        &self,
+        zkapp: &PublicKey,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ZkappActionWithData>> {
+        trace!("Getting zkapp actions for {zkapp}");
+        self.zkapp_emitted(self.zkapp_actions_cf(), zkapp, limit)
+    }
+
+    ///////////////
+    // Iterators //
+    ///////////////
+
+    fn zkapp_commands_height_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
+        self.database
+            .iterator_cf(self.zkapp_commands_height_sort_cf(), mode)
+    }
+
+    fn zkapp_commands_fee_payer_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
+        self.database
+            .iterator_cf(self.zkapp_commands_fee_payer_sort_cf(), mode)
+    }
+
+    fn zkapp_commands_pk_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
+        self.database
+            .iterator_cf(self.zkapp_commands_pk_sort_cf(), mode)
+    }
+}
+
+impl IndexerStore {
+    /// Most recent zkApp commands from a `{pk}{height}{nonce}{state}{index}`
+    /// sorted CF, at most `limit`
+    fn zkapp_commands_pk_sort(
+        &self,
+        cf: &ColumnFamily,
+        pk: &PublicKey,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ZkappCommandWithData>> {
+        let mut start = pk.clone().to_bytes();
+        start.append(&mut to_be_bytes(u32::MAX));
+
+        let mut commands = vec![];
+        for (key, _) in self
+            .database
+            .iterator_cf(cf, IteratorMode::From(&start, Direction::Reverse))
+            .flatten()
+        {
+            if commands.len() == limit || pk_key_prefix(&key) != *pk {
+                break;
+            }
+
+            let (state_hash, index) = zkapp_command_of_key(&key)?;
+            if let Some(command) = self.get_zkapp_command(&state_hash, index)? {
+                commands.push(command);
+            }
+        }
+        Ok(commands)
+    }
+
+    /// Most recent events/actions from a `{zkapp}{height}..` sorted CF, at
+    /// most `limit`
+    fn zkapp_emitted(
+        &self,
+        cf: &ColumnFamily,
+        zkapp: &PublicKey,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ZkappEventWithData>> {
+        let mut start = zkapp.clone().to_bytes();
+        start.append(&mut to_be_bytes(u32::MAX));
+
+        let mut emitted = vec![];
+        for (key, value) in self
+            .database
+            .iterator_cf(cf, IteratorMode::From(&start, Direction::Reverse))
+            .flatten()
+        {
+            if emitted.len() == limit || pk_key_prefix(&key) != *zkapp {
+                break;
+            }
+            emitted.push(serde_json::from_slice(&value)?);
+        }
+        Ok(emitted)
+    }
+}
+
+/// Key `{pk}{block_height}{nonce}{state_hash}{index}`
+fn zkapp_pk_sort_key(
+    pk: &PublicKey,
+    blockchain_length: u32,
+    command: &ZkappCommandWithData,
+    command_key: &[u8],
+) -> Vec<u8> {
+    let mut key = pk.clone().to_bytes();
+    key.append(&mut to_be_bytes(blockchain_length));
+    key.append(&mut to_be_bytes(command.nonce));
+    key.extend_from_slice(command_key);
+    key
+}
+
+/// Key `{zkapp}{block_height}{state_hash}{command_index}{index}`
+fn zkapp_emitted_key(emitted: &ZkappEventWithData, index: u32) -> Vec<u8> {
+    let mut key = emitted.zkapp.clone().to_bytes();
+    key.append(&mut to_be_bytes(emitted.blockchain_length));
+    key.append(&mut block_txn_index_key(
+        &emitted.state_hash,
+        emitted.command_index,
+    ));
+    key.append(&mut to_be_bytes(index));
+    key
+}
+
+/// State hash & index of the zkApp command of a sorted CF key, i.e. the
+/// key's `{state_hash}{index}` suffix
+pub fn zkapp_command_of_key(key: &[u8]) -> anyhow::Result<(BlockHash, u32)> {
+    let suffix = key
+        .len()
+        .checked_sub(BlockHash::LEN + 4)
+        .map(|start| &key[start..])
+        .ok_or_else(|| anyhow::anyhow!("zkapp command key too short"))?;
+    Ok((
+        BlockHash::from_bytes(&suffix[..BlockHash::LEN])?,
+        from_be_bytes(suffix[BlockHash::LEN..].to_vec()),
+    ))
+}
@@ -11,11 +11,12 @@ use crate::{
         public_key::{self, PublicKey},
         staking::AggregatedEpochStakeDelegation,
         store::LedgerStore,
+        token::TokenId,
         LedgerHash,
     },
     snark_work::store::SnarkStore,
     state::{summary::SummaryShort, IndexerState},
-    store::version::VersionStore,
+    store::{fork_stats::ForkStatsStore, version::VersionStore},
 };
 use anyhow::{bail, Context};
 use log::{debug, error, info, trace, warn};
@@ -103,7 +104,7 @@ pub async fn handle_connection(
                                 invalid_public_key(&pk)
                             } else {
                                 let pk: PublicKey = pk.into();
-                                let account = ledger.accounts.get(&pk);
+                                let account = ledger.get_mina_account(&pk);
                                 if let Some(account) = account {
                                     info!("Writing account {pk} to client");
                                     Some(format!("{account}"))
@@ -123,6 +124,45 @@ pub async fn handle_connection(
                         best_tip_missing_from_db()
                     }
                 }
+                Accounts::History {
+                    public_key: pk,
+                    from,
+                    to,
+                    path,
+                } => {
+                    info!("Received account history command for {pk}");
+
+                    if !public_key::is_valid_public_key(&pk) {
+                        invalid_public_key(&pk)
+                    } else {
+                        let history = db.get_account_history(
+                            &pk.clone().into(),
+                            &TokenId::default(),
+                            from,
+                            to.unwrap_or(u32::MAX),
+                        )?;
+                        let history_str = serde_json::to_string_pretty(&history)?;
+
+                        if path.is_none() {
+                            debug!("Writing account history for {pk} to stdout");
+                            Some(history_str)
+                        } else {
+                            let path = path.unwrap();
+
+                            if !path.is_dir() {
+                                debug!("Writing account history for {pk} to {}", path.display());
+
+                                std::fs::write(&path, history_str)?;
+                                Some(format!(
+                                    "Account history for {pk} written to {}",
+                                    path.display()
+                                ))
+                            } else {
+                                file_must_not_be_a_directory(&path)
+                            }
+                        }
+                    }
+                }
             },
             ClientCli::Blocks(__) => match __ {
                 Blocks::BestTip { verbose, path } => {
@@ -586,6 +626,67 @@ pub async fn handle_connection(
                         best_tip_missing_from_db()
                     }
                 }
+                Chain::Reorgs {
+                    path,
+                    from,
+                    to,
+                    min_depth,
+                } => {
+                    info!("Received reorgs command");
+                    let reorgs = db.get_reorgs(from, to, min_depth)?;
+                    let reorgs_str = serde_json::to_string_pretty(&reorgs)?;
+
+                    if path.is_none() {
+                        debug!("Writing reorgs to stdout");
+                        Some(reorgs_str)
+                    } else {
+                        let path = path.unwrap();
+
+                        if !path.is_dir() {
+                            debug!("Writing reorgs to {}", path.display());
+
+                            std::fs::write(&path, reorgs_str)?;
+                            Some(format!("Reorgs written to {}", path.display()))
+                        } else {
+                            file_must_not_be_a_directory(&path)
+                        }
+                    }
+                }
+                Chain::Forks {
+                    path,
+                    epoch,
+                    public_key: pk,
+                    from,
+                    to,
+                } => {
+                    info!("Received fork stats command");
+                    match pk {
+                        Some(pk) if !public_key::is_valid_public_key(&pk) => {
+                            invalid_public_key(&pk)
+                        }
+                        pk => {
+                            let pk = pk.map(PublicKey::from);
+                            let fork_stats = db.get_fork_stats(epoch, pk.as_ref(), from, to)?;
+                            let fork_stats_str = serde_json::to_string_pretty(&fork_stats)?;
+
+                            if path.is_none() {
+                                debug!("Writing fork stats to stdout");
+                                Some(fork_stats_str)
+                            } else {
+                                let path = path.unwrap();
+
+                                if !path.is_dir() {
+                                    debug!("Writing fork stats to {}", path.display());
+
+                                    std::fs::write(&path, fork_stats_str)?;
+                                    Some(format!("Fork stats written to {}", path.display()))
+                                } else {
+                                    file_must_not_be_a_directory(&path)
+                                }
+                            }
+                        }
+                    }
+                }
             },
             ClientCli::CreateSnapshot { output_path } => {
                 info!("Received create-snapshot command");
@@ -1019,6 +1120,31 @@ pub async fn handle_connection(
                     }
                 }
             }
+            ClientCli::WitnessTree { path, json } => {
+                info!("Received witness tree command");
+                let witness_tree = state.export_witness_tree();
+                let witness_tree_str = if json {
+                    serde_json::to_string_pretty(&witness_tree)?
+                } else {
+                    witness_tree.to_dot()
+                };
+
+                if path.is_none() {
+                    debug!("Writing witness tree to stdout");
+                    Some(witness_tree_str)
+                } else {
+                    let path = path.unwrap();
+
+                    if !path.is_dir() {
+                        debug!("Writing witness tree to {}", path.display());
+
+                        std::fs::write(&path, witness_tree_str)?;
+                        Some(format!("Witness tree written to {}", path.display()))
+                    } else {
+                        file_must_not_be_a_directory(&path)
+                    }
+                }
+            }
             ClientCli::Transactions(__) => match __ {
                 Transactions::PublicKey {
                     public_key: pk,
@@ -1,25 +1,33 @@
 use super::db;
 use crate::{
-    block::store::BlockStore,
+    block::{is_valid_state_hash, store::BlockStore, BlockHash},
     command::{internal::store::InternalCommandStore, store::UserCommandStore},
-    ledger::{account, public_key::PublicKey, store::LedgerStore},
+    ledger::{
+        account,
+        public_key::{self, PublicKey},
+        store::LedgerStore,
+        token::TokenId,
+    },
+    mina_blocks::v2,
     snark_work::store::SnarkStore,
-    store::{account::AccountStore, username::UsernameStore},
+    store::{account::AccountStore, token_balance_sort_key_balance_pk, username::UsernameStore},
     web::graphql::Timing,
 };
 use async_graphql::{Context, Enum, InputObject, Object, Result, SimpleObject};
 use log::warn;
-use speedb::IteratorMode;
+use speedb::{Direction, IteratorMode};
 
 #[derive(SimpleObject)]
 pub struct Account {
     public_key: String,
+    token: String,
     username: Option<String>,
     delegate: String,
     balance: u64,
     nonce: u32,
     time_locked: bool,
     timing: Option<Timing>,
+    zkapp: Option<ZkappAccount>,
 
     #[graphql(name = "is_genesis_account")]
     is_genesis_account: bool,
@@ -49,10 +57,35 @@ pub struct Account {
     pk_total_num_internal_commands: u32,
 }
 
+#[derive(SimpleObject)]
+pub struct ZkappAccount {
+    app_state: Vec<String>,
+    action_state: Vec<String>,
+    verification_key_hash: Option<String>,
+    proved_state: bool,
+    zkapp_uri: String,
+    zkapp_version: u32,
+    last_action_slot: u32,
+}
+
+/// Account state after a canonical block
+#[derive(SimpleObject)]
+pub struct AccountHistory {
+    state_hash: String,
+    block_height: u32,
+    token: String,
+    balance: u64,
+    nonce: u32,
+    delegate: String,
+}
+
 #[derive(InputObject)]
 pub struct AccountQueryInput {
     public_key: Option<String>,
 
+    /// Token id, defaults to MINA
+    token: Option<String>,
+
     username: Option<String>,
 
     balance: Option<u64>,
@@ -71,6 +104,9 @@ pub struct AccountQueryInput {
 
     #[graphql(name = "balance_ne")]
     balance_ne: Option<u64>,
+
+    /// Only zkApp accounts if true, only non-zkApp accounts if false
+    zkapp: Option<bool>,
 }
 
 #[derive(Enum, Copy, Clone, Eq, PartialEq)]
@@ -90,30 +126,45 @@ impl AccountQueryRoot {
         query: Option<AccountQueryInput>,
         sort_by: Option<AccountSortByInput>,
         #[graphql(default = 100)] limit: usize,
+        block_height: Option<u32>,
+        state_hash: Option<String>,
     ) -> Result<Vec<Account>> {
         let db = db(ctx);
-        let best_ledger = match db.get_best_ledger() {
-            Ok(Some(ledger)) => ledger,
-            Ok(None) | Err(_) => {
-                return Ok(vec![]);
-            }
+        let state_hash: Option<BlockHash> = match state_hash {
+            Some(state_hash) if is_valid_state_hash(&state_hash) => Some(state_hash.into()),
+            Some(_) => return Ok(vec![]),
+            None => None,
         };
+        let token: TokenId = query
+            .as_ref()
+            .and_then(|q| q.token.clone())
+            .map(Into::into)
+            .unwrap_or_default();
 
-        // public key query handler
+        // public key query handler only reconstructs the account
         if let Some(public_key) = query.as_ref().and_then(|q| q.public_key.clone()) {
             let pk: PublicKey = public_key.into();
-            return Ok(best_ledger
-                .accounts
-                .get(&pk)
+            let account = match (state_hash, block_height) {
+                (Some(state_hash), _) => db.get_account_state_hash(&pk, &token, &state_hash),
+                (None, Some(height)) => db.get_account_at_height(&pk, &token, height),
+                (None, None) => match db.get_best_block_hash()? {
+                    Some(best_block_hash) => {
+                        db.get_account_state_hash(&pk, &token, &best_block_hash)
+                    }
+                    None => Ok(None),
+                },
+            };
+            return Ok(account
+                .unwrap_or_default()
                 .into_iter()
                 .filter_map(|acct| {
                     let username = match db.get_username(&pk) {
                         Ok(None) | Err(_) => None,
                         Ok(Some(username)) => Some(username.0),
                     };
-                    if query.as_ref().unwrap().matches(acct, username.as_ref()) {
+                    if query.as_ref().unwrap().matches(&acct, username.as_ref()) {
                         Some(Account::from((
-                            acct.clone(),
+                            acct,
                             db.get_block_production_pk_epoch_count(&pk, None)
                                 .expect("pk epoch block count"),
                             db.get_block_production_pk_total_count(&pk)
@@ -139,16 +190,87 @@ impl AccountQueryRoot {
                 .collect());
         }
 
-        // default query handler use balance-sorted accounts
+        let is_historical = state_hash.is_some() || block_height.is_some();
+        let ledger = match (state_hash, block_height) {
+            (Some(state_hash), _) => db.get_ledger_state_hash(&state_hash, false),
+            (None, Some(height)) => db.get_ledger_at_height(height, false),
+            (None, None) => db.get_best_ledger(),
+        };
+        let best_ledger = match ledger {
+            Ok(Some(ledger)) => ledger,
+            Ok(None) | Err(_) => {
+                return Ok(vec![]);
+            }
+        };
+
+        // historical ledgers aren't balance-sorted in the store
+        if is_historical {
+            let mut ledger_accounts: Vec<&account::Account> =
+                best_ledger.token_accounts(&token).collect();
+            ledger_accounts.sort();
+            if sort_by != Some(AccountSortByInput::BalanceAsc) {
+                ledger_accounts.reverse();
+            }
+
+            let mut accounts = Vec::new();
+            for account in ledger_accounts {
+                let pk = &account.public_key;
+                let username = match db.get_username(pk) {
+                    Ok(None) | Err(_) => None,
+                    Ok(Some(username)) => Some(username.0),
+                };
+                if query
+                    .as_ref()
+                    .map_or(true, |q| q.matches(account, username.as_ref()))
+                {
+                    accounts.push(Account::from((
+                        account.clone(),
+                        db.get_block_production_pk_epoch_count(pk, None)
+                            .expect("pk epoch block count"),
+                        db.get_block_production_pk_total_count(pk)
+                            .expect("pk total block count"),
+                        db.get_snarks_pk_epoch_count(pk, None)
+                            .expect("pk epoch snark count"),
+                        db.get_snarks_pk_total_count(pk)
+                            .expect("pk total snark count"),
+                        db.get_user_commands_pk_epoch_count(pk, None)
+                            .expect("pk epoch user command count"),
+                        db.get_user_commands_pk_total_count(pk)
+                            .expect("pk total user command count"),
+                        db.get_internal_commands_pk_epoch_count(pk, None)
+                            .expect("pk epoch internal command count"),
+                        db.get_internal_commands_pk_total_count(pk)
+                            .expect("pk total internal command count"),
+                        username,
+                    )));
+                    if accounts.len() == limit {
+                        break;
+                    }
+                }
+            }
+            return Ok(accounts);
+        }
+
+        // default query handler use the token's balance-sorted accounts
         let mut accounts = Vec::new();
-        let mode = match sort_by {
-            Some(AccountSortByInput::BalanceAsc) => IteratorMode::Start,
-            Some(AccountSortByInput::BalanceDesc) | None => IteratorMode::End,
+        let mut start = token.clone().to_bytes();
+        let direction = match sort_by {
+            Some(AccountSortByInput::BalanceAsc) => Direction::Forward,
+            Some(AccountSortByInput::BalanceDesc) | None => {
+                start.append(&mut u64::MAX.to_be_bytes().to_vec());
+                Direction::Reverse
+            }
         };
 
-        for (key, _) in db.account_balance_iterator(mode).flatten() {
-            let pk = PublicKey::from_bytes(&key[8..])?;
-            let account = match best_ledger.accounts.get(&pk) {
+        for (key, _) in db
+            .account_balance_iterator(IteratorMode::From(&start, direction))
+            .flatten()
+        {
+            let pk = match token_balance_sort_key_balance_pk(&key, &token) {
+                Some((_, pk)) => pk,
+                None => break,
+            };
+            let account = match best_ledger.get_account(&pk, &token) {
                 Some(account) => account,
                 None => {
                     warn!("Failed to find public key in best ledger: {pk}");
@@ -194,12 +316,57 @@ impl AccountQueryRoot {
 
         Ok(accounts)
     }
+
+    /// Balance, nonce & delegate of the account after each canonical block
+    /// that touched it, between heights `from` & `to` (inclusive)
+    async fn account_history(
+        &self,
+        ctx: &Context<'_>,
+        public_key: String,
+        from: Option<u32>,
+        to: Option<u32>,
+        #[graphql(desc = "Token id, defaults to MINA")] token: Option<String>,
+        #[graphql(default = 100)] limit: usize,
+    ) -> Result<Vec<AccountHistory>> {
+        let db = db(ctx);
+        if !public_key::is_valid_public_key(&public_key) {
+            return Ok(vec![]);
+        }
+
+        let token: TokenId = token.map(Into::into).unwrap_or_default();
+        let mut history: Vec<AccountHistory> = db
+            .get_account_history(
+                &public_key.into(),
+                &token,
+                from.unwrap_or_default(),
+                to.unwrap_or(u32::MAX),
+            )?
+            .into_iter()
+            .map(AccountHistory::from)
+            .collect();
+        history.truncate(limit);
+        Ok(history)
+    }
+}
+
+impl From<account::AccountHistory> for AccountHistory {
+    fn from(history: account::AccountHistory) -> Self {
+        Self {
+            state_hash: history.state_hash.0,
+            block_height: history.blockchain_length,
+            token: history.token.0,
+            balance: history.balance.0,
+            nonce: history.nonce.0,
+            delegate: history.delegate.0,
+        }
+    }
 }
 
 impl AccountQueryInput {
     fn matches(&self, account: &account::Account, username: Option<&String>) -> bool {
         let AccountQueryInput {
             public_key,
+            token,
             username: query_username_prefix,
             balance,
             balance_gt,
@@ -207,12 +374,18 @@ impl AccountQueryInput {
             balance_lt,
             balance_lte,
             balance_ne,
+            zkapp,
         } = self;
         if let Some(public_key) = public_key {
             if *public_key != account.public_key.0 {
                 return false;
             }
         }
+        if let Some(token) = token {
+            if *token != account.token.0 {
+                return false;
+            }
+        }
         if let Some(username_prefix) = query_username_prefix {
             if username.map_or(true, |u| !u.starts_with(username_prefix)) {
                 return false;
@@ -248,6 +421,11 @@ impl AccountQueryInput {
                 return false;
             }
         }
+        if let Some(zkapp) = zkapp {
+            if account.zkapp.is_some() != *zkapp {
+                return false;
+            }
+        }
         true
     }
 }
@@ -282,11 +460,13 @@ impl
     ) -> Self {
         Self {
             public_key: account.0.public_key.0,
+            token: account.0.token.0,
             delegate: account.0.delegate.0,
             nonce: account.0.nonce.0,
             balance: account.0.balance.0,
             time_locked: account.0.timing.is_some(),
             timing: account.0.timing.map(|t| t.into()),
+            zkapp: account.0.zkapp.map(|z| z.into()),
             is_genesis_account: account.0.genesis_account,
             pk_epoch_num_blocks: account.1,
             pk_total_num_blocks: account.2,
@@ -312,3 +492,17 @@ impl From<account::Timing> for Timing {
         }
     }
 }
+
+impl From<v2::ZkappAccount> for ZkappAccount {
+    fn from(zkapp: v2::ZkappAccount) -> Self {
+        Self {
+            app_state: zkapp.app_state.to_vec(),
+            action_state: zkapp.action_state.to_vec(),
+            verification_key_hash: zkapp.verification_key.map(|vk| vk.hash),
+            proved_state: zkapp.proved_state,
+            zkapp_uri: zkapp.zkapp_uri,
+            zkapp_version: zkapp.zkapp_version,
+            last_action_slot: zkapp.last_action_slot,
+        }
+    }
+}
@@ -9,12 +9,7 @@ use crate::{
         signed::SignedCommandWithData,
         store::UserCommandStore,
     },
-    ledger::LedgerHash,
-    proof_systems::signer::pubkey::CompressedPubKey,
-    protocol::serialization_types::{
-        common::Base58EncodableVersionedType, staged_ledger_diff::TransactionStatusFailedType,
-        version_bytes,
-    },
+    protocol::serialization_types::staged_ledger_diff::TransactionStatusFailedType,
     snark_work::{store::SnarkStore, SnarkWorkSummary},
     store::{
         block_state_hash_from_key, block_u32_prefix_from_key, pk_key_prefix, to_be_bytes,
@@ -179,32 +174,7 @@ impl BlocksQueryRoot {
         use BlockSortByInput::*;
 
         let db = db(ctx);
-        let epoch_num_blocks = db.get_block_production_epoch_count(None)?;
-        let total_num_blocks = db.get_block_production_total_count()?;
-        let epoch_num_snarks = db.get_snarks_epoch_count(None).expect("epoch SNARK count");
-        let total_num_snarks = db.get_snarks_total_count().expect("total SNARK count");
-        let epoch_num_user_commands = db
-            .get_user_commands_epoch_count(None)
-            .expect("epoch user command count");
-        let total_num_user_commands = db
-            .get_user_commands_total_count()
-            .expect("total user command count");
-        let epoch_num_internal_commands = db
-            .get_internal_commands_epoch_count(None)
-            .expect("epoch internal command count");
-        let total_num_internal_commands = db
-            .get_internal_commands_total_count()
-            .expect("total internal command count");
-        let counts = [
-            epoch_num_blocks,
-            total_num_blocks,
-            epoch_num_snarks,
-            total_num_snarks,
-            epoch_num_user_commands,
-            total_num_user_commands,
-            epoch_num_internal_commands,
-            total_num_internal_commands,
-        ];
+        let counts = block_counts(db)?;
 
         let mut blocks = Vec::new();
         let sort_by = sort_by.unwrap_or(BlockHeightDesc);
@@ -479,6 +449,21 @@ impl BlocksQueryRoot {
     }
 }
 
+/// Block production, SNARK, user & internal command counts for the current
+/// epoch & in total (the input to [Block::from_precomputed])
+pub(crate) fn block_counts(db: &Arc<IndexerStore>) -> anyhow::Result<[u32; 8]> {
+    Ok([
+        db.get_block_production_epoch_count(None)?,
+        db.get_block_production_total_count()?,
+        db.get_snarks_epoch_count(None)?,
+        db.get_snarks_total_count()?,
+        db.get_user_commands_epoch_count(None)?,
+        db.get_user_commands_total_count()?,
+        db.get_internal_commands_epoch_count(None)?,
+        db.get_internal_commands_total_count()?,
+    ])
+}
+
 fn precomputed_matches_query(
     db: &Arc<IndexerStore>,
     query: &Option<BlockQueryInput>,
@@ -757,8 +742,7 @@ impl BlockWithoutCanonicity {
     ) -> Self {
         let winner_account = block.block_creator().0;
         let date_time = millis_to_iso_date_string(block.timestamp().try_into().unwrap());
-        let pk_creator = block.consensus_state().block_creator;
-        let creator = CompressedPubKey::from(&pk_creator).into_address();
+        let creator = block.block_creator().0;
         let scheduled_time = block.scheduled_time().clone();
         let received_time = millis_to_iso_date_string(scheduled_time.parse::<i64>().unwrap());
         let previous_state_hash = block.previous_state_hash().0;
@@ -767,121 +751,41 @@ impl BlockWithoutCanonicity {
         let utc_date = block.timestamp().to_string();
 
         // blockchain state
-        let blockchain_state = block.blockchain_state();
-        let snarked_ledger_hash =
-            LedgerHash::from_hashv1(blockchain_state.clone().snarked_ledger_hash).0;
-        let staged_ledger_hashv1 = blockchain_state
-            .staged_ledger_hash
-            .t
-            .t
-            .non_snark
-            .t
-            .ledger_hash;
-        let staged_ledger_hash = LedgerHash::from_hashv1(staged_ledger_hashv1).0;
+        let snarked_ledger_hash = block.snarked_ledger_hash().0;
+        let staged_ledger_hash = block.staged_ledger_hash().0;
 
         // consensus state
-        let consensus_state = block.consensus_state();
-
-        let total_currency = consensus_state.total_currency.t.t;
+        let total_currency = block.total_currency();
         let blockchain_length = block.blockchain_length();
         let block_height = blockchain_length;
         let epoch_count = block.epoch_count();
         let epoch = epoch_count;
-        let has_ancestor_in_same_checkpoint_window =
-            consensus_state.has_ancestor_in_same_checkpoint_window;
+        let has_ancestor_in_same_checkpoint_window = block.has_ancestor_in_same_checkpoint_window();
         let last_vrf_output = block.last_vrf_output();
-        let min_window_density = consensus_state.min_window_density.t.t;
-        let slot_since_genesis = consensus_state.global_slot_since_genesis.t.t;
+        let min_window_density = block.min_window_density();
+        let slot_since_genesis = block.global_slot_since_genesis();
         let slot = slot_since_genesis - (epoch_count * 7140);
 
         // NextEpochData
-        let seed_hashv1 = consensus_state.next_epoch_data.t.t.seed;
-        let seed_bs58: Base58EncodableVersionedType<{ version_bytes::EPOCH_SEED }, _> =
-            seed_hashv1.into();
-        let seed = seed_bs58.to_base58_string().expect("bs58 encoded seed");
-        let epoch_length = consensus_state.next_epoch_data.t.t.epoch_length.t.t;
-
-        let start_checkpoint_hashv1 = consensus_state.next_epoch_data.t.t.start_checkpoint;
-        let start_checkpoint_bs58: Base58EncodableVersionedType<{ version_bytes::STATE_HASH }, _> =
-            start_checkpoint_hashv1.into();
-        let start_checkpoint = start_checkpoint_bs58
-            .to_base58_string()
-            .expect("bs58 encoded start checkpoint");
-
-        let lock_checkpoint_hashv1 = consensus_state.next_epoch_data.t.t.lock_checkpoint;
-        let lock_checkpoint_bs58: Base58EncodableVersionedType<{ version_bytes::STATE_HASH }, _> =
-            lock_checkpoint_hashv1.into();
-        let lock_checkpoint = lock_checkpoint_bs58
-            .to_base58_string()
-            .expect("bs58 encoded lock checkpoint");
-
-        let ledger_hashv1 = consensus_state.next_epoch_data.t.t.ledger.t.t.hash;
-        let ledger_hash_bs58: Base58EncodableVersionedType<{ version_bytes::LEDGER_HASH }, _> =
-            ledger_hashv1.into();
-        let ledger_hash = ledger_hash_bs58
-            .to_base58_string()
-            .expect("bs58 encoded ledger hash");
-        let ledger_total_currency = consensus_state
-            .next_epoch_data
-            .t
-            .t
-            .ledger
-            .t
-            .t
-            .total_currency
-            .t
-            .t;
+        let next_epoch_data = block.next_epoch_data();
+        let seed = next_epoch_data.seed;
+        let epoch_length = next_epoch_data.epoch_length;
+        let start_checkpoint = next_epoch_data.start_checkpoint.0;
+        let lock_checkpoint = next_epoch_data.lock_checkpoint.0;
+        let ledger_hash = next_epoch_data.ledger.hash.0;
+        let ledger_total_currency = next_epoch_data.ledger.total_currency;
 
         // StakingEpochData
-        let staking_seed_hashv1 = consensus_state.staking_epoch_data.t.t.seed;
-        let staking_seed_bs58: Base58EncodableVersionedType<{ version_bytes::EPOCH_SEED }, _> =
-            staking_seed_hashv1.into();
-        let staking_seed = staking_seed_bs58
-            .to_base58_string()
-            .expect("bs58 encoded seed");
-
-        let staking_epoch_length = consensus_state.staking_epoch_data.t.t.epoch_length.t.t;
-
-        let staking_start_checkpoint_hashv1 =
-            consensus_state.staking_epoch_data.t.t.start_checkpoint;
-        let staking_start_checkpoint_bs58: Base58EncodableVersionedType<
-            { version_bytes::STATE_HASH },
-            _,
-        > = staking_start_checkpoint_hashv1.into();
-        let staking_start_checkpoint = staking_start_checkpoint_bs58
-            .to_base58_string()
-            .expect("bs58 encoded start checkpoint");
-
-        let staking_lock_checkpoint_hashv1 = consensus_state.staking_epoch_data.t.t.lock_checkpoint;
-        let staking_lock_checkpoint_bs58: Base58EncodableVersionedType<
-            { version_bytes::STATE_HASH },
-            _,
-        > = staking_lock_checkpoint_hashv1.into();
-        let staking_lock_checkpoint = staking_lock_checkpoint_bs58
-            .to_base58_string()
-            .expect("bs58 encoded lock checkpoint");
-
-        let staking_ledger_hashv1 = consensus_state.staking_epoch_data.t.t.ledger.t.t.hash;
-        let staking_ledger_hash_bs58: Base58EncodableVersionedType<
-            { version_bytes::LEDGER_HASH },
-            _,
-        > = staking_ledger_hashv1.into();
-        let staking_ledger_hash = staking_ledger_hash_bs58
-            .to_base58_string()
-            .expect("bs58 encoded ledger hash");
-        let staking_ledger_total_currency = consensus_state
-            .staking_epoch_data
-            .t
-            .t
-            .ledger
-            .t
-            .t
-            .total_currency
-            .t
-            .t;
+        let staking_epoch_data = block.staking_epoch_data();
+        let staking_seed = staking_epoch_data.seed;
+        let staking_epoch_length = staking_epoch_data.epoch_length;
+        let staking_start_checkpoint = staking_epoch_data.start_checkpoint.0;
+        let staking_lock_checkpoint = staking_epoch_data.lock_checkpoint.0;
+        let staking_ledger_hash = staking_epoch_data.ledger.hash.0;
+        let staking_ledger_total_currency = staking_epoch_data.ledger.total_currency;
 
         let coinbase_receiver_account = block.coinbase_receiver().0;
-        let supercharged = consensus_state.supercharge_coinbase;
+        let supercharged = block.supercharge_coinbase();
         let coinbase: u64 = if supercharged {
             2 * MAINNET_COINBASE_REWARD
         } else {
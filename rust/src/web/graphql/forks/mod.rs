@@ -0,0 +1,133 @@
+use super::db;
+use crate::{
+    ledger::public_key::{self, PublicKey},
+    store::fork_stats::{self, ForkStatsStore},
+};
+use async_graphql::{Context, Object, Result, SimpleObject};
+
+#[derive(Default)]
+pub struct ForkQueryRoot;
+
+#[derive(SimpleObject)]
+pub struct ForkStats {
+    /// Value orphan rates by epoch
+    epochs: Vec<EpochOrphanRate>,
+
+    /// Value orphan rates by block producer, most orphaned blocks first
+    producers: Vec<ProducerOrphanRate>,
+
+    /// Value heights with orphaned blocks
+    forks_by_height: Vec<HeightForks>,
+
+    /// Value number of forks, i.e. orphaned blocks with a canonical parent
+    num_forks: u32,
+
+    /// Value average number of blocks from a fork root to its highest
+    /// descendant
+    average_fork_length: f64,
+}
+
+#[derive(SimpleObject)]
+pub struct EpochOrphanRate {
+    /// Value epoch
+    epoch: u32,
+
+    /// Value number of canonical blocks
+    canonical: u32,
+
+    /// Value number of orphaned blocks
+    orphaned: u32,
+
+    /// Value fraction of blocks which are orphaned
+    orphan_rate: f64,
+}
+
+#[derive(SimpleObject)]
+pub struct ProducerOrphanRate {
+    /// Value block producer public key
+    public_key: String,
+
+    /// Value number of canonical blocks
+    canonical: u32,
+
+    /// Value number of orphaned blocks
+    orphaned: u32,
+
+    /// Value fraction of blocks which are orphaned
+    orphan_rate: f64,
+}
+
+#[derive(SimpleObject)]
+pub struct HeightForks {
+    /// Value blockchain length
+    blockchain_length: u32,
+
+    /// Value number of orphaned blocks
+    num_forks: u32,
+}
+
+#[Object]
+impl ForkQueryRoot {
+    /// Orphan rates & fork counts of blocks with final canonicity
+    async fn fork_stats<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        epoch: Option<u32>,
+        public_key: Option<String>,
+        from: Option<u32>,
+        to: Option<u32>,
+        #[graphql(default = 100)] limit: usize,
+    ) -> Result<ForkStats> {
+        let pk = match public_key {
+            Some(pk) if !public_key::is_valid_public_key(&pk) => {
+                return Err(async_graphql::Error::new(format!(
+                    "Invalid public key: {pk}"
+                )));
+            }
+            pk => pk.map(PublicKey::from),
+        };
+
+        let db = db(ctx);
+        let mut fork_stats = db.get_fork_stats(epoch, pk.as_ref(), from, to)?;
+        fork_stats.producers.truncate(limit);
+        fork_stats.forks_by_height.truncate(limit);
+        Ok(fork_stats.into())
+    }
+}
+
+impl From<fork_stats::ForkStats> for ForkStats {
+    fn from(value: fork_stats::ForkStats) -> Self {
+        Self {
+            epochs: value
+                .epochs
+                .into_iter()
+                .map(|epoch| EpochOrphanRate {
+                    epoch: epoch.epoch,
+                    canonical: epoch.canonical,
+                    orphaned: epoch.orphaned,
+                    orphan_rate: epoch.orphan_rate,
+                })
+                .collect(),
+            producers: value
+                .producers
+                .into_iter()
+                .map(|producer| ProducerOrphanRate {
+                    public_key: producer.public_key.0,
+                    canonical: producer.canonical,
+                    orphaned: producer.orphaned,
+                    orphan_rate: producer.orphan_rate,
+                })
+                .collect(),
+            forks_by_height: value
+                .forks_by_height
+                .into_iter()
+                .map(|height| HeightForks {
+                    blockchain_length: height.blockchain_length,
+                    num_forks: height.num_forks,
+                })
+                .collect(),
+            num_forks: value.num_forks,
+            average_fork_length: value.average_fork_length,
+        }
+    }
+}
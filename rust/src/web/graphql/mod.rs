@@ -1,20 +1,28 @@
 pub mod accounts;
 pub mod blocks;
 pub mod feetransfers;
+pub mod forks;
 pub mod gen;
+pub mod reorgs;
 pub mod snarks;
 pub mod staged_ledgers;
 pub mod stakes;
+pub mod subscriptions;
 pub mod transactions;
 pub mod version;
+pub mod zkapps;
 
 use super::ENDPOINT_GRAPHQL;
 use crate::{constants::*, store::IndexerStore};
-use actix_web::HttpResponse;
+use actix_web::{
+    web::{self, Data},
+    HttpRequest, HttpResponse,
+};
 use async_graphql::{
-    http::GraphiQLSource, Context, EmptyMutation, EmptySubscription, InputValueError,
-    InputValueResult, MergedObject, Scalar, ScalarType, Schema, SimpleObject, Value,
+    http::GraphiQLSource, Context, EmptyMutation, InputValueError, InputValueResult, MergedObject,
+    Scalar, ScalarType, Schema, SimpleObject, Value,
 };
+use async_graphql_actix_web::GraphQLSubscription;
 use std::sync::Arc;
 
 #[derive(MergedObject, Default)]
@@ -26,7 +34,10 @@ pub struct Root(
     feetransfers::FeetransferQueryRoot,
     snarks::SnarkQueryRoot,
     staged_ledgers::StagedLedgerQueryRoot,
+    reorgs::ReorgQueryRoot,
+    forks::ForkQueryRoot,
     version::VersionQueryRoot,
+    zkapps::ZkappQueryRoot,
 );
 
 #[derive(SimpleObject)]
@@ -47,17 +58,37 @@ pub struct Timing {
     pub vesting_increment: Option<u64>,
 }
 
+pub type IndexerSchema = Schema<Root, EmptyMutation, subscriptions::SubscriptionRoot>;
+
 /// Build schema for all endpoints
-pub fn build_schema(store: Arc<IndexerStore>) -> Schema<Root, EmptyMutation, EmptySubscription> {
-    Schema::build(Root::default(), EmptyMutation, EmptySubscription)
-        .data(store)
-        .finish()
+pub fn build_schema(store: Arc<IndexerStore>) -> IndexerSchema {
+    Schema::build(
+        Root::default(),
+        EmptyMutation,
+        subscriptions::SubscriptionRoot,
+    )
+    .data(store)
+    .finish()
 }
 
 pub async fn indexer_graphiql() -> actix_web::Result<HttpResponse> {
     Ok(HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
-        .body(GraphiQLSource::build().endpoint(ENDPOINT_GRAPHQL).finish()))
+        .body(
+            GraphiQLSource::build()
+                .endpoint(ENDPOINT_GRAPHQL)
+                .subscription_endpoint(ENDPOINT_GRAPHQL)
+                .finish(),
+        ))
+}
+
+/// Serves subscriptions over the GraphQL websocket protocols
+pub async fn indexer_subscriptions(
+    schema: Data<IndexerSchema>,
+    req: HttpRequest,
+    payload: web::Payload,
+) -> actix_web::Result<HttpResponse> {
+    GraphQLSubscription::new(IndexerSchema::clone(&schema)).start(&req, payload)
 }
 
 pub(crate) fn db<'a>(ctx: &'a Context) -> &'a Arc<IndexerStore> {
@@ -0,0 +1,66 @@
+use super::db;
+use crate::canonicity::{self, store::CanonicityStore};
+use async_graphql::{Context, Object, Result, SimpleObject};
+
+#[derive(Default)]
+pub struct ReorgQueryRoot;
+
+#[derive(SimpleObject)]
+pub struct Reorg {
+    /// Value state hash of the previous best tip
+    old_best_tip: String,
+
+    /// Value blockchain length of the previous best tip
+    old_blockchain_length: u32,
+
+    /// Value state hash of the new best tip
+    new_best_tip: String,
+
+    /// Value blockchain length of the new best tip
+    new_blockchain_length: u32,
+
+    /// Value state hash of the last block common to both branches
+    common_ancestor: String,
+
+    /// Value blockchain length of the common ancestor
+    common_ancestor_length: u32,
+
+    /// Value number of previously canonical blocks orphaned
+    depth: u32,
+}
+
+#[Object]
+impl ReorgQueryRoot {
+    /// Reorgs with new best tip length in `[from, to]`, ordered by new best
+    /// tip length
+    async fn reorgs<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        from: Option<u32>,
+        to: Option<u32>,
+        #[graphql(default = 1)] min_depth: u32,
+        #[graphql(default = 100)] limit: usize,
+    ) -> Result<Vec<Reorg>> {
+        let db = db(ctx);
+        Ok(db
+            .get_reorgs(from, to, min_depth)?
+            .into_iter()
+            .take(limit)
+            .map(Into::into)
+            .collect())
+    }
+}
+
+impl From<canonicity::Reorg> for Reorg {
+    fn from(reorg: canonicity::Reorg) -> Self {
+        Self {
+            old_best_tip: reorg.old_best_tip.0,
+            old_blockchain_length: reorg.old_blockchain_length,
+            new_best_tip: reorg.new_best_tip.0,
+            new_blockchain_length: reorg.new_blockchain_length,
+            common_ancestor: reorg.common_ancestor.0,
+            common_ancestor_length: reorg.common_ancestor_length,
+            depth: reorg.depth,
+        }
+    }
+}
@@ -45,6 +45,7 @@ impl StagedLedgerQueryRoot {
                     ledger
                         .accounts
                         .into_values()
+                        .filter(|acct| acct.token.is_mina())
                         .map(<Account as Into<StagedLedgerAccount>>::into)
                         .collect()
                 });
@@ -62,6 +63,7 @@ impl StagedLedgerQueryRoot {
                     ledger
                         .accounts
                         .into_values()
+                        .filter(|acct| acct.token.is_mina())
                         .map(<Account as Into<StagedLedgerAccount>>::into)
                         .collect()
                 });
@@ -79,6 +81,7 @@ impl StagedLedgerQueryRoot {
                     ledger
                         .accounts
                         .into_values()
+                        .filter(|acct| acct.token.is_mina())
                         .map(<Account as Into<StagedLedgerAccount>>::into)
                         .collect()
                 });
@@ -0,0 +1,192 @@
+//! GraphQL subscriptions
+//!
+//! Subscribers are notified of the events recorded in the indexer store's
+//! event log, see [EventStore::add_event]
+
+use super::{
+    blocks::{block_counts, Block},
+    db,
+};
+use crate::{
+    block::store::BlockStore,
+    event::{db::*, store::EventStore, IndexerEvent},
+};
+use async_graphql::{
+    async_stream, futures_util::Stream, ComplexObject, Context, Result, SimpleObject, Subscription,
+};
+use log::warn;
+use tokio::sync::broadcast::error::RecvError;
+
+#[derive(Default)]
+pub struct SubscriptionRoot;
+
+#[derive(Debug, Clone, PartialEq, Eq, SimpleObject)]
+#[graphql(complex)]
+pub struct BlockEvent {
+    /// Value state hash
+    state_hash: String,
+
+    /// Value blockchain length
+    blockchain_length: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, SimpleObject)]
+pub struct LedgerEvent {
+    /// Value state hash of the block the ledger was applied to
+    state_hash: String,
+
+    /// Value blockchain length
+    blockchain_length: u32,
+
+    /// Value staged ledger hash
+    ledger_hash: String,
+}
+
+#[ComplexObject]
+impl BlockEvent {
+    /// Value block
+    async fn block<'ctx>(&self, ctx: &Context<'ctx>) -> Result<Option<Block>> {
+        let db = db(ctx);
+        let counts = block_counts(db)?;
+        Ok(db
+            .get_block(&self.state_hash.clone().into())?
+            .map(|(block, _)| Block::from_precomputed(db, &block, counts)))
+    }
+}
+
+#[Subscription]
+impl SubscriptionRoot {
+    /// Blocks added to the witness tree
+    async fn new_block<'ctx>(&self, ctx: &Context<'ctx>) -> impl Stream<Item = BlockEvent> {
+        events(ctx, |event| match event {
+            IndexerEvent::Db(DbEvent::Block(DbBlockEvent::NewBlock {
+                state_hash,
+                blockchain_length,
+            })) => Some(BlockEvent::new(state_hash.0, blockchain_length)),
+            _ => None,
+        })
+    }
+
+    /// Best tip updates
+    async fn new_best_tip<'ctx>(&self, ctx: &Context<'ctx>) -> impl Stream<Item = BlockEvent> {
+        events(ctx, |event| match event {
+            IndexerEvent::Db(DbEvent::Block(DbBlockEvent::NewBestTip {
+                state_hash,
+                blockchain_length,
+            })) => Some(BlockEvent::new(state_hash.0, blockchain_length)),
+            _ => None,
+        })
+    }
+
+    /// Blocks becoming canonical
+    async fn new_canonical_block<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> impl Stream<Item = BlockEvent> {
+        events(ctx, |event| match event {
+            IndexerEvent::Db(DbEvent::Canonicity(DbCanonicityEvent::NewCanonicalBlock {
+                state_hash,
+                blockchain_length,
+            })) => Some(BlockEvent::new(state_hash.0, blockchain_length)),
+            _ => None,
+        })
+    }
+
+    /// Staged ledgers added to the store
+    async fn ledger_updated<'ctx>(&self, ctx: &Context<'ctx>) -> impl Stream<Item = LedgerEvent> {
+        events(ctx, |event| match event {
+            IndexerEvent::Db(DbEvent::Ledger(DbLedgerEvent::NewLedger {
+                state_hash,
+                blockchain_length,
+                ledger_hash,
+            })) => Some(LedgerEvent {
+                state_hash: state_hash.0,
+                blockchain_length,
+                ledger_hash: ledger_hash.0,
+            }),
+            _ => None,
+        })
+    }
+}
+
+impl BlockEvent {
+    fn new(state_hash: String, blockchain_length: u32) -> Self {
+        Self {
+            state_hash,
+            blockchain_length,
+        }
+    }
+}
+
+/// Stream of the subscribed events selected by `f`
+fn events<T, F>(ctx: &Context<'_>, f: F) -> impl Stream<Item = T>
+where
+    F: Fn(IndexerEvent) -> Option<T>,
+{
+    let mut receiver = db(ctx).subscribe_events();
+    async_stream::stream! {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    if let Some(item) = f(event) {
+                        yield item;
+                    }
+                }
+                Err(RecvError::Lagged(num)) => {
+                    warn!("GraphQL subscriber lagging, skipped {num} events");
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{store::IndexerStore, web::graphql::build_schema};
+    use async_graphql::futures_util::StreamExt;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn subscribe_new_best_tip() -> anyhow::Result<()> {
+        let store_dir = tempfile::TempDir::with_prefix(env!("CARGO_PKG_NAME"))?;
+        let store = Arc::new(IndexerStore::new(store_dir.path())?);
+        let schema = build_schema(store.clone());
+        let mut stream =
+            schema.execute_stream("subscription { newBestTip { stateHash blockchainLength } }");
+
+        // only best tip events are yielded
+        let producer = store.clone();
+        let state_hash = "3NKeMoncuHab5ScarV5ViyF16cJPT4taWNSaTLS64Dp67wuXigPZ";
+        std::thread::spawn(move || -> anyhow::Result<()> {
+            while producer.event_sender.receiver_count() == 0 {
+                std::thread::yield_now();
+            }
+            producer.add_event(&IndexerEvent::Db(DbEvent::Block(DbBlockEvent::NewBlock {
+                state_hash: "3NKbtEWXjWAU4VA2YP5XXvsSF8HQP5Xt1gXGeKAUtbCJEg7kdETh".into(),
+                blockchain_length: 2,
+            })))?;
+            producer.add_event(&IndexerEvent::Db(DbEvent::Block(
+                DbBlockEvent::NewBestTip {
+                    state_hash: state_hash.into(),
+                    blockchain_length: 1,
+                },
+            )))?;
+            Ok(())
+        });
+
+        let response = stream.next().await.expect("best tip event");
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json()?,
+            serde_json::json!({
+                "newBestTip": {
+                    "stateHash": state_hash,
+                    "blockchainLength": 1,
+                }
+            })
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,351 @@
+use super::{date_time_to_scalar, db, gen::BlockQueryInput, get_block_canonicity, DateTime};
+use crate::{
+    block::is_valid_state_hash,
+    command::zkapp::{self, store::ZkappCommandStore, ZkappCommandWithData, ZkappEventWithData},
+    ledger::public_key::{self, PublicKey},
+    store::{
+        pk_key_prefix, pk_txn_sort_key_prefix, to_be_bytes,
+        zkapp_command_store_impl::zkapp_command_of_key, IndexerStore,
+    },
+};
+use async_graphql::{Context, Enum, InputObject, Object, Result, SimpleObject};
+use speedb::{Direction, IteratorMode};
+use std::sync::Arc;
+
+#[derive(Clone, Debug, SimpleObject)]
+pub struct ZkappCommand {
+    /// Value state hash of the containing block
+    state_hash: String,
+
+    /// Value index among the block's zkApp commands
+    index: u32,
+
+    /// Value block height
+    block_height: u32,
+
+    /// Value global slot since genesis
+    global_slot: u32,
+
+    /// Value block date time
+    date_time: DateTime,
+
+    /// Value canonicity of the containing block
+    canonical: bool,
+
+    /// Value fee payer public key
+    fee_payer: String,
+
+    /// Value fee (nanomina)
+    fee: u64,
+
+    /// Value fee payer nonce
+    nonce: u32,
+
+    /// Value memo
+    memo: String,
+
+    /// Value applied
+    applied: bool,
+
+    /// Value failure reasons, in account update order
+    failure_reasons: Vec<String>,
+
+    /// Value account updates, depth-first in call order
+    account_updates: Vec<AccountUpdate>,
+}
+
+#[derive(Clone, Debug, SimpleObject)]
+pub struct AccountUpdate {
+    /// Value public key
+    public_key: String,
+
+    /// Value token id
+    token_id: String,
+
+    /// Value signed balance change
+    balance_change: i64,
+
+    /// Value increment nonce
+    increment_nonce: bool,
+
+    /// Value depth in the call forest
+    call_depth: u32,
+
+    /// Value authorization kind
+    authorization_kind: String,
+
+    /// Value emitted events
+    events: Vec<Vec<String>>,
+
+    /// Value dispatched actions
+    actions: Vec<Vec<String>>,
+}
+
+/// Event or action of a zkApp
+#[derive(Clone, Debug, SimpleObject)]
+pub struct ZkappEvent {
+    /// Value zkApp public key
+    zkapp: String,
+
+    /// Value state hash of the containing block
+    state_hash: String,
+
+    /// Value index of the emitting zkApp command in the block
+    command_index: u32,
+
+    /// Value block height
+    block_height: u32,
+
+    /// Value block date time
+    date_time: DateTime,
+
+    /// Value canonicity of the containing block
+    canonical: bool,
+
+    /// Value field elements
+    fields: Vec<String>,
+}
+
+#[derive(InputObject)]
+pub struct ZkappCommandQueryInput {
+    block: Option<BlockQueryInput>,
+    fee_payer: Option<String>,
+
+    /// Fee payer or account update public key
+    public_key: Option<String>,
+
+    canonical: Option<bool>,
+    applied: Option<bool>,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum ZkappCommandSortByInput {
+    #[graphql(name = "BLOCKHEIGHT_ASC")]
+    BlockHeightAsc,
+
+    #[graphql(name = "BLOCKHEIGHT_DESC")]
+    BlockHeightDesc,
+}
+
+#[derive(Default)]
+pub struct ZkappQueryRoot;
+
+#[Object]
+impl ZkappQueryRoot {
+    /// zkApp command at the given index of the given block
+    async fn zkapp_command(
+        &self,
+        ctx: &Context<'_>,
+        state_hash: String,
+        index: u32,
+    ) -> Result<Option<ZkappCommand>> {
+        let db = db(ctx);
+        if !is_valid_state_hash(&state_hash) {
+            return Ok(None);
+        }
+        Ok(db
+            .get_zkapp_command(&state_hash.into(), index)?
+            .map(|cmd| ZkappCommand::new(cmd, db)))
+    }
+
+    async fn zkapp_commands(
+        &self,
+        ctx: &Context<'_>,
+        query: Option<ZkappCommandQueryInput>,
+        sort_by: Option<ZkappCommandSortByInput>,
+        #[graphql(default = 100)] limit: usize,
+    ) -> Result<Vec<ZkappCommand>> {
+        let db = db(ctx);
+        let sort_by = sort_by.unwrap_or(ZkappCommandSortByInput::BlockHeightDesc);
+        let matches = |cmd: &ZkappCommand| query.as_ref().map_or(true, |q| q.matches(cmd));
+
+        // state hash
+        if let Some(state_hash) = query
+            .as_ref()
+            .and_then(|q| q.block.as_ref())
+            .and_then(|block| block.state_hash.clone())
+        {
+            let mut commands: Vec<ZkappCommand> = db
+                .get_block_zkapp_commands(&state_hash.into())?
+                .unwrap_or_default()
+                .into_iter()
+                .map(|cmd| ZkappCommand::new(cmd, db))
+                .filter(matches)
+                .collect();
+            commands.truncate(limit);
+            return Ok(commands);
+        }
+
+        let (start_height, direction) = match sort_by {
+            ZkappCommandSortByInput::BlockHeightAsc => (0, Direction::Forward),
+            ZkappCommandSortByInput::BlockHeightDesc => (u32::MAX, Direction::Reverse),
+        };
+
+        // fee payer or touched account
+        let fee_payer = query.as_ref().and_then(|q| q.fee_payer.clone());
+        let public_key = query.as_ref().and_then(|q| q.public_key.clone());
+        if let Some(pk) = fee_payer.as_ref().or(public_key.as_ref()) {
+            if !public_key::is_valid_public_key(pk) {
+                return Ok(vec![]);
+            }
+
+            let pk: PublicKey = pk.as_str().into();
+            let start = pk_txn_sort_key_prefix(pk.clone(), start_height);
+            let mode = IteratorMode::From(&start, direction);
+            let iter = if fee_payer.is_some() {
+                db.zkapp_commands_fee_payer_iterator(mode)
+            } else {
+                db.zkapp_commands_pk_iterator(mode)
+            };
+
+            let mut commands = vec![];
+            for (key, _) in iter.flatten() {
+                if pk_key_prefix(&key) != pk || commands.len() == limit {
+                    break;
+                }
+
+                let (state_hash, index) = zkapp_command_of_key(&key)?;
+                if let Some(cmd) = db.get_zkapp_command(&state_hash, index)? {
+                    let cmd = ZkappCommand::new(cmd, db);
+                    if matches(&cmd) {
+                        commands.push(cmd);
+                    }
+                }
+            }
+            return Ok(commands);
+        }
+
+        // all zkApp commands by block height
+        let start = to_be_bytes(start_height);
+        let mut commands = vec![];
+        for (key, _) in db
+            .zkapp_commands_height_iterator(IteratorMode::From(&start, direction))
+            .flatten()
+        {
+            if commands.len() == limit {
+                break;
+            }
+
+            let (state_hash, index) = zkapp_command_of_key(&key)?;
+            if let Some(cmd) = db.get_zkapp_command(&state_hash, index)? {
+                let cmd = ZkappCommand::new(cmd, db);
+                if matches(&cmd) {
+                    commands.push(cmd);
+                }
+            }
+        }
+        Ok(commands)
+    }
+
+    /// Most recent events emitted by the zkApp
+    async fn zkapp_events(
+        &self,
+        ctx: &Context<'_>,
+        zkapp: String,
+        #[graphql(default = 100)] limit: usize,
+    ) -> Result<Vec<ZkappEvent>> {
+        let db = db(ctx);
+        if !public_key::is_valid_public_key(&zkapp) {
+            return Ok(vec![]);
+        }
+        Ok(db
+            .get_zkapp_events(&zkapp.into(), limit)?
+            .into_iter()
+            .map(|event| ZkappEvent::new(event, db))
+            .collect())
+    }
+
+    /// Most recent actions dispatched by the zkApp
+    async fn zkapp_actions(
+        &self,
+        ctx: &Context<'_>,
+        zkapp: String,
+        #[graphql(default = 100)] limit: usize,
+    ) -> Result<Vec<ZkappEvent>> {
+        let db = db(ctx);
+        if !public_key::is_valid_public_key(&zkapp) {
+            return Ok(vec![]);
+        }
+        Ok(db
+            .get_zkapp_actions(&zkapp.into(), limit)?
+            .into_iter()
+            .map(|action| ZkappEvent::new(action, db))
+            .collect())
+    }
+}
+
+impl ZkappCommand {
+    fn new(cmd: ZkappCommandWithData, db: &Arc<IndexerStore>) -> Self {
+        Self {
+            canonical: get_block_canonicity(db, &cmd.state_hash.0),
+            date_time: date_time_to_scalar(cmd.date_time as i64),
+            state_hash: cmd.state_hash.0,
+            index: cmd.index,
+            block_height: cmd.blockchain_length,
+            global_slot: cmd.global_slot_since_genesis,
+            fee_payer: cmd.fee_payer.0,
+            fee: cmd.fee,
+            nonce: cmd.nonce,
+            memo: cmd.memo,
+            applied: cmd.applied,
+            failure_reasons: cmd.failure_reasons,
+            account_updates: cmd
+                .account_updates
+                .into_iter()
+                .map(AccountUpdate::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<zkapp::ZkappAccountUpdate> for AccountUpdate {
+    fn from(value: zkapp::ZkappAccountUpdate) -> Self {
+        Self {
+            public_key: value.public_key.0,
+            token_id: value.token_id,
+            balance_change: value.balance_change,
+            increment_nonce: value.increment_nonce,
+            call_depth: value.call_depth,
+            authorization_kind: value.authorization_kind,
+            events: value.events,
+            actions: value.actions,
+        }
+    }
+}
+
+impl ZkappEvent {
+    fn new(event: ZkappEventWithData, db: &Arc<IndexerStore>) -> Self {
+        Self {
+            canonical: get_block_canonicity(db, &event.state_hash.0),
+            date_time: date_time_to_scalar(event.date_time as i64),
+            zkapp: event.zkapp.0,
+            state_hash: event.state_hash.0,
+            command_index: event.command_index,
+            block_height: event.blockchain_length,
+            fields: event.fields,
+        }
+    }
+}
+
+impl ZkappCommandQueryInput {
+    fn matches(&self, cmd: &ZkappCommand) -> bool {
+        let Self {
+            block,
+            fee_payer,
+            public_key,
+            canonical,
+            applied,
+        } = self;
+
+        block
+            .as_ref()
+            .and_then(|block| block.state_hash.as_ref())
+            .map_or(true, |state_hash| *state_hash == cmd.state_hash)
+            && fee_payer.as_ref().map_or(true, |pk| *pk == cmd.fee_payer)
+            && public_key.as_ref().map_or(true, |pk| {
+                *pk == cmd.fee_payer || cmd.account_updates.iter().any(|u| u.public_key == *pk)
+            })
+            && canonical.map_or(true, |canonical| canonical == cmd.canonical)
+            && applied.map_or(true, |applied| applied == cmd.applied)
+    }
+}
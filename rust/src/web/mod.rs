@@ -1,18 +1,20 @@
 pub mod graphql;
 pub mod rest;
+pub mod rosetta;
 
 pub const ENDPOINT_GRAPHQL: &str = "/graphql";
 
 use self::{
-    graphql::{build_schema, indexer_graphiql},
-    rest::{accounts, blockchain, blocks, locked_balances::LockedBalances},
+    graphql::{build_schema, indexer_graphiql, indexer_subscriptions},
+    rest::{accounts, blockchain, blocks, locked_balances::LockedBalances, witness_tree},
 };
-use crate::store::IndexerStore;
+use crate::{state::IndexerState, store::IndexerStore};
 use actix_cors::Cors;
 use actix_web::{guard, middleware, web, web::Data, App, HttpServer};
 use async_graphql_actix_web::GraphQL;
 use log::warn;
 use std::{net, sync::Arc};
+use tokio::sync::RwLock;
 use tokio_graceful_shutdown::{FutureExt, SubsystemHandle};
 
 fn load_locked_balances() -> LockedBalances {
@@ -31,19 +33,39 @@ pub async fn start_web_server<A: net::ToSocketAddrs>(
     addrs: A,
 ) -> anyhow::Result<()> {
     let locked = Arc::new(load_locked_balances());
+    let schema = build_schema(state.clone());
 
     let _ = HttpServer::new(move || {
         App::new()
             .app_data(Data::new(state.clone()))
             .app_data(Data::new(locked.clone()))
+            .app_data(Data::new(schema.clone()))
             .service(blocks::get_blocks)
             .service(blocks::get_block)
             .service(accounts::get_account)
             .service(blockchain::get_blockchain_summary)
+            .service(rosetta::network::network_list)
+            .service(rosetta::network::network_status)
+            .service(rosetta::block::get_block)
+            .service(rosetta::block::block_transaction)
+            .service(rosetta::account::account_balance)
+            .service(rosetta::search::search_transactions)
+            .service(rosetta::construction::construction_derive)
+            .service(rosetta::construction::construction_preprocess)
+            .service(rosetta::construction::construction_payloads)
+            .service(rosetta::construction::construction_parse)
+            .service(rosetta::construction::construction_combine)
+            .service(rosetta::construction::construction_hash)
             .service(
                 web::resource(ENDPOINT_GRAPHQL)
                     .guard(guard::Post())
-                    .to(GraphQL::new(build_schema(state.clone()))),
+                    .to(GraphQL::new(schema.clone())),
+            )
+            .service(
+                web::resource(ENDPOINT_GRAPHQL)
+                    .guard(guard::Get())
+                    .guard(guard::Header("upgrade", "websocket"))
+                    .to(indexer_subscriptions),
             )
             .service(
                 web::resource(ENDPOINT_GRAPHQL)
@@ -61,3 +83,24 @@ pub async fn start_web_server<A: net::ToSocketAddrs>(
 
     Ok(())
 }
+
+/// Starts the admin web server with read-only access to the live indexer state
+pub async fn start_admin_web_server<A: net::ToSocketAddrs>(
+    subsys: SubsystemHandle,
+    state: Arc<RwLock<IndexerState>>,
+    addrs: A,
+) -> anyhow::Result<()> {
+    let _ = HttpServer::new(move || {
+        App::new()
+            .app_data(Data::new(state.clone()))
+            .service(witness_tree::get_witness_tree)
+            .wrap(middleware::Logger::default())
+    })
+    .bind(addrs)
+    .unwrap()
+    .run()
+    .cancel_on_shutdown(&subsys)
+    .await;
+
+    Ok(())
+}
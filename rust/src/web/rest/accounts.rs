@@ -1,7 +1,13 @@
 use crate::{
-    block::store::BlockStore,
+    block::{is_valid_state_hash, store::BlockStore},
+    canonicity::store::CanonicityStore,
     command::{internal::store::InternalCommandStore, store::UserCommandStore},
-    ledger::{account, public_key::PublicKey, store::LedgerStore},
+    ledger::{
+        account,
+        public_key::PublicKey,
+        store::LedgerStore,
+        token::{is_valid_token_id, TokenId},
+    },
     snark_work::store::SnarkStore,
     store::IndexerStore,
 };
@@ -29,20 +35,57 @@ struct Account {
     total_num_internal_commands: u32,
 }
 
+#[derive(Deserialize)]
+struct Params {
+    /// Canonical block height of the ledger, defaults to the best ledger
+    height: Option<String>,
+
+    /// State hash of the ledger's block, takes precedence over `height`
+    state_hash: Option<String>,
+
+    /// Token id of the account, defaults to MINA
+    token: Option<String>,
+}
+
 #[get("/accounts/{public_key}")]
 pub async fn get_account(
     store: Data<Arc<IndexerStore>>,
     public_key: web::Path<String>,
+    params: web::Query<Params>,
 ) -> HttpResponse {
     let db = store.as_ref();
-    if let Ok(Some(ledger)) = db.get_best_ledger() {
-        debug!("Found best ledger");
-        let pk: &PublicKey = &public_key.clone().into();
-        let account = ledger.accounts.get(pk);
-        if let Some(account) = account {
-            debug!("Found account in ledger: {:?}", account);
+    let pk: &PublicKey = &public_key.clone().into();
+
+    // malformed params are rejected rather than reported as a missing account
+    let height = match params.height.as_ref() {
+        Some(height) => match height.parse::<u32>() {
+            Ok(height) => Some(height),
+            Err(_) => return HttpResponse::BadRequest().body(format!("Invalid height: {height}")),
+        },
+        None => None,
+    };
+    if let Some(state_hash) = params.state_hash.as_ref() {
+        if !is_valid_state_hash(state_hash) {
+            return HttpResponse::BadRequest().body(format!("Invalid state hash: {state_hash}"));
+        }
+    }
+    let token: TokenId = match params.token.as_ref() {
+        Some(token) if is_valid_token_id(token) => token.clone().into(),
+        Some(token) => return HttpResponse::BadRequest().body(format!("Invalid token: {token}")),
+        None => TokenId::default(),
+    };
+
+    // only the account is reconstructed, not the whole ledger
+    let state_hash = match (params.state_hash.clone(), height) {
+        (Some(state_hash), _) => Some(state_hash.into()),
+        (None, Some(height)) => db.get_canonical_hash_at_height(height).unwrap_or_default(),
+        (None, None) => db.get_best_block_hash().unwrap_or_default(),
+    };
+    if let Some(state_hash) = state_hash {
+        if let Ok(Some(account)) = db.get_account_state_hash(pk, &token, &state_hash) {
+            debug!("Found account at {state_hash}: {account:?}");
             let account = Account {
-                account: account.clone(),
+                account,
                 epoch_num_blocks: db
                     .get_block_production_pk_epoch_count(pk, None)
                     .unwrap_or_default(),
@@ -3,10 +3,7 @@ use crate::{
     chain::store::ChainStore,
     command::{internal::store::InternalCommandStore, store::UserCommandStore},
     constants::VERSION,
-    ledger::{
-        account::{nanomina_to_mina, Amount},
-        LedgerHash,
-    },
+    ledger::account::{nanomina_to_mina, Amount},
     snark_work::store::SnarkStore,
     store::{
         account::AccountStore,
@@ -98,49 +95,15 @@ fn calculate_summary(input: SummaryInput) -> Option<BlockchainSummary> {
     let date_time = millis_to_date_string(best_tip.timestamp().try_into().unwrap());
     let epoch = best_tip.epoch_count();
     let global_slot = best_tip.global_slot_since_genesis();
-    let min_window_density = best_tip.consensus_state().min_window_density.t.t;
-    let next_epoch_ledger_hash = LedgerHash::from_hashv1(
-        best_tip
-            .consensus_state()
-            .next_epoch_data
-            .inner()
-            .inner()
-            .ledger
-            .inner()
-            .inner()
-            .hash,
-    )
-    .0;
-
+    let min_window_density = best_tip.min_window_density();
+    let next_epoch_ledger_hash = best_tip.next_epoch_data().ledger.hash.0;
     let previous_state_hash = best_tip.previous_state_hash().0;
     let slot = global_slot - (epoch * 7140);
-    let snarked_ledger_hash =
-        LedgerHash::from_hashv1(best_tip.blockchain_state().snarked_ledger_hash).0;
-    let staged_ledger_hash = LedgerHash::from_hashv1(
-        best_tip
-            .blockchain_state()
-            .staged_ledger_hash
-            .inner()
-            .inner()
-            .non_snark
-            .inner()
-            .ledger_hash,
-    )
-    .0;
-    let staking_epoch_ledger_hash = LedgerHash::from_hashv1(
-        best_tip
-            .consensus_state()
-            .staking_epoch_data
-            .inner()
-            .inner()
-            .ledger
-            .inner()
-            .inner()
-            .hash,
-    )
-    .0;
+    let snarked_ledger_hash = best_tip.snarked_ledger_hash().0;
+    let staged_ledger_hash = best_tip.staged_ledger_hash().0;
+    let staking_epoch_ledger_hash = best_tip.staking_epoch_data().ledger.hash.0;
     let state_hash = best_tip.state_hash().0;
-    let total_currency_u64 = best_tip.consensus_state().total_currency.inner().inner();
+    let total_currency_u64 = best_tip.total_currency();
     let locked_currency_u64 = locked_balance.map(|a| a.0).unwrap_or(0);
     let total_currency = nanomina_to_mina(total_currency_u64);
     let circulating_supply = nanomina_to_mina(total_currency_u64 - locked_currency_u64);
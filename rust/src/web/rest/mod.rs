@@ -2,3 +2,4 @@ pub mod accounts;
 pub mod blockchain;
 pub mod blocks;
 pub mod locked_balances;
+pub mod witness_tree;
@@ -0,0 +1,42 @@
+use crate::state::IndexerState;
+use actix_web::{
+    get,
+    http::header::ContentType,
+    web::{self, Data},
+    HttpResponse,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+#[derive(Deserialize)]
+struct Params {
+    format: Option<String>,
+}
+
+/// Returns the running indexer's witness tree as JSON, or as Graphviz DOT
+/// with `format=dot`
+///
+/// Only served by the admin web server
+#[get("/witness_tree")]
+pub async fn get_witness_tree(
+    state: Data<Arc<RwLock<IndexerState>>>,
+    params: web::Query<Params>,
+) -> HttpResponse {
+    let witness_tree = state.read().await.export_witness_tree();
+
+    match params.format.as_deref() {
+        None | Some("json") => match serde_json::to_string_pretty(&witness_tree) {
+            Ok(body) => HttpResponse::Ok()
+                .content_type(ContentType::json())
+                .body(body),
+            Err(_) => HttpResponse::InternalServerError().finish(),
+        },
+        Some("dot") => HttpResponse::Ok()
+            .content_type(ContentType::plaintext())
+            .body(witness_tree.to_dot()),
+        Some(format) => {
+            HttpResponse::BadRequest().body(format!("Unknown witness tree format: {format}"))
+        }
+    }
+}
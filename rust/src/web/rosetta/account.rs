@@ -0,0 +1,73 @@
+use super::{
+    amount, block_identifier, check_network, check_public_key, check_token, lookup_block,
+    to_response, AccountIdentifier, Amount, BlockIdentifier, NetworkIdentifier,
+    PartialBlockIdentifier, RosettaError, RosettaResult,
+};
+use crate::{ledger::store::LedgerStore, store::IndexerStore};
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpResponse,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountBalanceRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub account_identifier: AccountIdentifier,
+
+    /// Defaults to the best block
+    #[serde(default)]
+    pub block_identifier: PartialBlockIdentifier,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountBalanceResponse {
+    pub block_identifier: BlockIdentifier,
+    pub balances: Vec<Amount>,
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[post("/account/balance")]
+pub async fn account_balance(
+    store: Data<Arc<IndexerStore>>,
+    req: web::Json<AccountBalanceRequest>,
+) -> HttpResponse {
+    to_response(get_account_balance(store.as_ref(), &req))
+}
+
+fn get_account_balance(
+    db: &IndexerStore,
+    req: &AccountBalanceRequest,
+) -> RosettaResult<AccountBalanceResponse> {
+    check_network(db, &req.network_identifier)?;
+
+    let pk = check_public_key(&req.account_identifier.address)?;
+    let token = check_token(&req.account_identifier)?;
+    let block = lookup_block(db, &req.block_identifier)?;
+    let state_hash = block.state_hash();
+
+    // canonical heights use the (memoized) canonical ledgers,
+    // any other block has its ledger computed from the nearest ancestor
+    let ledger = match (&req.block_identifier.hash, req.block_identifier.index) {
+        (None, Some(index)) => db.get_ledger_at_height(index, false)?,
+        _ => db.get_ledger_state_hash(&state_hash, false)?,
+    }
+    .ok_or(RosettaError::BlockMissing(format!(
+        "ledger for block {state_hash}"
+    )))?;
+    let account = ledger
+        .get_account(&pk, &token)
+        .ok_or(RosettaError::AccountNotFound(format!("{pk} {token}")))?;
+
+    Ok(AccountBalanceResponse {
+        block_identifier: block_identifier(&block),
+        balances: vec![amount(account.balance.0 as i128)],
+        metadata: Some(serde_json::json!({
+            "nonce": account.nonce.0.to_string(),
+            "delegate": account.delegate.0,
+            "token_id": account.token.0,
+        })),
+    })
+}
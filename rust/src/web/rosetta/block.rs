@@ -0,0 +1,139 @@
+use super::{
+    block_identifier, block_status, check_network, internal_command_transaction, lookup_block,
+    parent_block_identifier, parse_internal_command_id, to_response, user_command_transaction,
+    Block, BlockIdentifier, NetworkIdentifier, PartialBlockIdentifier, RosettaError, RosettaResult,
+    Transaction, TransactionIdentifier,
+};
+use crate::{
+    block::{precomputed::PrecomputedBlock, BlockHash},
+    command::{
+        internal::store::InternalCommandStore, signed::SignedCommandWithData,
+        store::UserCommandStore,
+    },
+    store::IndexerStore,
+};
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpResponse,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlockRequest {
+    pub network_identifier: NetworkIdentifier,
+
+    #[serde(default)]
+    pub block_identifier: PartialBlockIdentifier,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlockResponse {
+    pub block: Block,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlockTransactionRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub block_identifier: BlockIdentifier,
+    pub transaction_identifier: TransactionIdentifier,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlockTransactionResponse {
+    pub transaction: Transaction,
+}
+
+#[post("/block")]
+pub async fn get_block(
+    store: Data<Arc<IndexerStore>>,
+    req: web::Json<BlockRequest>,
+) -> HttpResponse {
+    let db = store.as_ref();
+    to_response(check_network(db, &req.network_identifier).and_then(|_| {
+        let block = lookup_block(db, &req.block_identifier)?;
+        Ok(BlockResponse {
+            block: rosetta_block(db, &block)?,
+        })
+    }))
+}
+
+#[post("/block/transaction")]
+pub async fn block_transaction(
+    store: Data<Arc<IndexerStore>>,
+    req: web::Json<BlockTransactionRequest>,
+) -> HttpResponse {
+    to_response(get_block_transaction(store.as_ref(), &req))
+}
+
+/// All user & internal command transactions of the block
+pub fn block_transactions(
+    db: &IndexerStore,
+    block: &PrecomputedBlock,
+) -> RosettaResult<Vec<Transaction>> {
+    let state_hash = block.state_hash();
+    let mut transactions: Vec<Transaction> = SignedCommandWithData::from_precomputed(block)
+        .iter()
+        .map(user_command_transaction)
+        .collect();
+
+    transactions.extend(
+        db.get_internal_commands(&state_hash)?
+            .iter()
+            .enumerate()
+            .map(|(index, cmd)| internal_command_transaction(cmd, &state_hash, index)),
+    );
+    Ok(transactions)
+}
+
+fn rosetta_block(db: &IndexerStore, block: &PrecomputedBlock) -> RosettaResult<Block> {
+    let state_hash = block.state_hash();
+    Ok(Block {
+        block_identifier: block_identifier(block),
+        parent_block_identifier: parent_block_identifier(db, block),
+        timestamp: block.timestamp(),
+        transactions: block_transactions(db, block)?,
+        metadata: Some(serde_json::json!({
+            "creator": block.block_creator().0,
+            "winner": block.block_stake_winner().0,
+            "coinbase_receiver": block.coinbase_receiver().0,
+            "global_slot_since_genesis": block.global_slot_since_genesis(),
+            "epoch": block.epoch_count(),
+            "block_status": block_status(db, &state_hash),
+        })),
+    })
+}
+
+fn get_block_transaction(
+    db: &IndexerStore,
+    req: &BlockTransactionRequest,
+) -> RosettaResult<BlockTransactionResponse> {
+    check_network(db, &req.network_identifier)?;
+
+    let state_hash: BlockHash = req.block_identifier.hash.clone().into();
+    let txn_hash = &req.transaction_identifier.hash;
+    let not_found = || RosettaError::TransactionNotFound(txn_hash.to_string());
+
+    // internal command
+    if let Some((internal_state_hash, index)) = parse_internal_command_id(txn_hash) {
+        if internal_state_hash != state_hash {
+            return Err(not_found());
+        }
+        return db
+            .get_internal_commands(&state_hash)?
+            .get(index)
+            .map(|cmd| BlockTransactionResponse {
+                transaction: internal_command_transaction(cmd, &state_hash, index),
+            })
+            .ok_or_else(not_found);
+    }
+
+    // user command
+    db.get_user_command_state_hash(txn_hash, &state_hash)?
+        .filter(|cmd| cmd.blockchain_length == req.block_identifier.index)
+        .map(|cmd| BlockTransactionResponse {
+            transaction: user_command_transaction(&cmd),
+        })
+        .ok_or_else(not_found)
+}
@@ -0,0 +1,796 @@
+//! Rosetta Construction API (offline mode)
+//!
+//! Payments & stake delegations are constructed from operations & signed by
+//! the client. Combining is rejected since signatures can't yet be verified
+//! against the payload; `/construction/hash` & `/construction/parse` accept
+//! the daemon's signed command JSON. The nonce
+//! must be supplied via the request metadata since there is no online
+//! `/construction/metadata` endpoint.
+
+use super::{
+    account_identifier, check_network, check_public_key, command_operations, to_response,
+    AccountIdentifier, NetworkIdentifier, Operation, RosettaError, RosettaResult,
+    TransactionIdentifier, OP_DELEGATE_CHANGE, OP_FEE_PAYMENT, OP_PAYMENT_RECEIVER_INC,
+    OP_PAYMENT_SOURCE_DEC,
+};
+use crate::{
+    block::{precomputed::PcbVersion, store::BlockStore},
+    command::{
+        encode_memo,
+        signed::SignedCommand,
+        unsigned::{UnsignedCommand, UnsignedCommandBody, MINA_TOKEN_ID, VALID_UNTIL_DEFAULT},
+    },
+    ledger::{
+        account::{Amount, Nonce},
+        public_key::PublicKey,
+    },
+    proof_systems::{
+        signer::{
+            pubkey::CompressedPubKey,
+            signature::{BaseField, Signature},
+        },
+        FieldHelpers,
+    },
+    store::IndexerStore,
+};
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpResponse,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const CURVE_TYPE: &str = "pallas";
+pub const SIGNATURE_TYPE: &str = "schnorr_poseidon";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RosettaPublicKey {
+    pub hex_bytes: String,
+    pub curve_type: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningPayload {
+    pub account_identifier: AccountIdentifier,
+    pub hex_bytes: String,
+    pub signature_type: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RosettaSignature {
+    pub signing_payload: SigningPayload,
+    pub public_key: RosettaPublicKey,
+    pub signature_type: String,
+    pub hex_bytes: String,
+}
+
+/// The `unsigned_transaction` passed between the construction endpoints
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnsignedTransaction {
+    /// Hex encoded message to sign
+    #[serde(rename = "randomOracleInput")]
+    pub random_oracle_input: String,
+    pub command: UnsignedCommand,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConstructionDeriveRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub public_key: RosettaPublicKey,
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConstructionDeriveResponse {
+    pub account_identifier: AccountIdentifier,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConstructionPreprocessRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub operations: Vec<Operation>,
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConstructionPreprocessResponse {
+    pub options: serde_json::Value,
+    pub required_public_keys: Vec<AccountIdentifier>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConstructionPayloadsRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub operations: Vec<Operation>,
+
+    /// Must contain the sender's `nonce`, may contain `valid_until` & `memo`
+    pub metadata: Option<serde_json::Value>,
+    pub public_keys: Option<Vec<RosettaPublicKey>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConstructionPayloadsResponse {
+    pub unsigned_transaction: String,
+    pub payloads: Vec<SigningPayload>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConstructionParseRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub signed: bool,
+    pub transaction: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConstructionParseResponse {
+    pub operations: Vec<Operation>,
+    pub account_identifier_signers: Vec<AccountIdentifier>,
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConstructionCombineRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub unsigned_transaction: String,
+    pub signatures: Vec<RosettaSignature>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConstructionCombineResponse {
+    /// Daemon JSON of the signed command
+    pub signed_transaction: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConstructionHashRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub signed_transaction: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransactionIdentifierResponse {
+    pub transaction_identifier: TransactionIdentifier,
+}
+
+///////////////
+// endpoints //
+///////////////
+
+#[post("/construction/derive")]
+pub async fn construction_derive(
+    store: Data<Arc<IndexerStore>>,
+    req: web::Json<ConstructionDeriveRequest>,
+) -> HttpResponse {
+    to_response(check_network(store.as_ref(), &req.network_identifier).and_then(|_| derive(&req)))
+}
+
+#[post("/construction/preprocess")]
+pub async fn construction_preprocess(
+    store: Data<Arc<IndexerStore>>,
+    req: web::Json<ConstructionPreprocessRequest>,
+) -> HttpResponse {
+    to_response(
+        check_network(store.as_ref(), &req.network_identifier).and_then(|_| preprocess(&req)),
+    )
+}
+
+#[post("/construction/payloads")]
+pub async fn construction_payloads(
+    store: Data<Arc<IndexerStore>>,
+    req: web::Json<ConstructionPayloadsRequest>,
+) -> HttpResponse {
+    to_response(check_network(store.as_ref(), &req.network_identifier).and_then(|_| payloads(&req)))
+}
+
+#[post("/construction/parse")]
+pub async fn construction_parse(
+    store: Data<Arc<IndexerStore>>,
+    req: web::Json<ConstructionParseRequest>,
+) -> HttpResponse {
+    to_response(check_network(store.as_ref(), &req.network_identifier).and_then(|_| parse(&req)))
+}
+
+#[post("/construction/combine")]
+pub async fn construction_combine(
+    store: Data<Arc<IndexerStore>>,
+    req: web::Json<ConstructionCombineRequest>,
+) -> HttpResponse {
+    to_response(check_network(store.as_ref(), &req.network_identifier).and_then(|_| combine(&req)))
+}
+
+#[post("/construction/hash")]
+pub async fn construction_hash(
+    store: Data<Arc<IndexerStore>>,
+    req: web::Json<ConstructionHashRequest>,
+) -> HttpResponse {
+    let db = store.as_ref();
+    to_response(check_network(db, &req.network_identifier).and_then(|_| {
+        // hash with the version of the indexed chain's best tip
+        let version = db
+            .get_best_block_hash()
+            .ok()
+            .flatten()
+            .and_then(|state_hash| db.get_block_version(&state_hash).ok().flatten())
+            .unwrap_or_default();
+        hash(&req, &version)
+    }))
+}
+
+////////////////////
+// implementation //
+////////////////////
+
+fn derive(req: &ConstructionDeriveRequest) -> RosettaResult<ConstructionDeriveResponse> {
+    let pk = pubkey_from_rosetta(&req.public_key)?;
+    Ok(ConstructionDeriveResponse {
+        account_identifier: account_identifier(&pk.into_address().into()),
+    })
+}
+
+fn preprocess(
+    req: &ConstructionPreprocessRequest,
+) -> RosettaResult<ConstructionPreprocessResponse> {
+    // the nonce is only known at the payloads step
+    let cmd = command_from_operations(&req.operations, Nonce::default(), &req.metadata)?;
+    let mut options = serde_json::json!({
+        "sender": cmd.sender.0,
+        "receiver": cmd.receiver().0,
+        "token_id": MINA_TOKEN_ID.to_string(),
+    });
+
+    if let Some(valid_until) = metadata_u32(&req.metadata, "valid_until")? {
+        options["valid_until"] = valid_until.to_string().into();
+    }
+    if let Some(memo) = metadata_str(&req.metadata, "memo")? {
+        options["memo"] = memo.into();
+    }
+
+    Ok(ConstructionPreprocessResponse {
+        options,
+        required_public_keys: vec![account_identifier(&cmd.sender)],
+    })
+}
+
+fn payloads(req: &ConstructionPayloadsRequest) -> RosettaResult<ConstructionPayloadsResponse> {
+    let nonce = metadata_u32(&req.metadata, "nonce")?.ok_or(RosettaError::InvalidRequest(
+        "metadata must contain the sender's nonce".into(),
+    ))?;
+    let cmd = command_from_operations(&req.operations, Nonce(nonce), &req.metadata)?;
+    let random_oracle_input = cmd
+        .to_roinput()
+        .map_err(|e| RosettaError::OperationsNotValid(e.to_string()))?
+        .to_hex();
+    let payload = SigningPayload {
+        account_identifier: account_identifier(&cmd.sender),
+        hex_bytes: random_oracle_input.clone(),
+        signature_type: SIGNATURE_TYPE.to_string(),
+    };
+    let unsigned_transaction = serde_json::to_string(&UnsignedTransaction {
+        random_oracle_input,
+        command: cmd,
+    })
+    .expect("serialize unsigned transaction");
+
+    Ok(ConstructionPayloadsResponse {
+        unsigned_transaction,
+        payloads: vec![payload],
+    })
+}
+
+fn parse(req: &ConstructionParseRequest) -> RosettaResult<ConstructionParseResponse> {
+    let (cmd, signers) = if req.signed {
+        let signed = parse_signed_transaction(&req.transaction)?;
+        (
+            UnsignedCommand::from(&signed),
+            vec![account_identifier(&signed.signer())],
+        )
+    } else {
+        (
+            parse_unsigned_transaction(&req.transaction)?.command,
+            vec![],
+        )
+    };
+
+    Ok(ConstructionParseResponse {
+        operations: command_operations(&cmd, None),
+        account_identifier_signers: signers,
+        metadata: Some(serde_json::json!({
+            "nonce": cmd.nonce.0.to_string(),
+            "valid_until": cmd.valid_until.to_string(),
+            "memo": cmd.memo,
+        })),
+    })
+}
+
+fn combine(req: &ConstructionCombineRequest) -> RosettaResult<ConstructionCombineResponse> {
+    let unsigned = parse_unsigned_transaction(&req.unsigned_transaction)?;
+    let signature = match req.signatures.as_slice() {
+        [signature] => signature,
+        [] => return Err(RosettaError::SignatureMissing),
+        _ => {
+            return Err(RosettaError::SignatureInvalid(
+                "expected a single signature".into(),
+            ))
+        }
+    };
+
+    // the signature must belong to the sender
+    let signer = pubkey_from_rosetta(&signature.public_key)?;
+    if signer.into_address() != unsigned.command.sender.0 {
+        return Err(RosettaError::SignatureInvalid(format!(
+            "signer {} is not the sender {}",
+            signer.into_address(),
+            unsigned.command.sender
+        )));
+    }
+
+    // the signature must be over the unsigned transaction's payload
+    let random_oracle_input = unsigned
+        .command
+        .to_roinput()
+        .map_err(|e| RosettaError::InvalidRequest(e.to_string()))?
+        .to_hex();
+    if signature.signing_payload.hex_bytes != random_oracle_input
+        || unsigned.random_oracle_input != random_oracle_input
+    {
+        return Err(RosettaError::SignatureInvalid(
+            "signing payload doesn't match the unsigned transaction".into(),
+        ));
+    }
+
+    Signature::from_hex(&signature.hex_bytes)
+        .map_err(|e| RosettaError::SignatureInvalid(e.to_string()))?;
+
+    // without a Poseidon hasher the signature can't be checked against the
+    // payload, so refuse rather than return a possibly invalid transaction
+    Err(RosettaError::UnsupportedOperation(
+        "combine: signatures can't be verified against the payload".into(),
+    ))
+}
+
+fn hash(
+    req: &ConstructionHashRequest,
+    version: &PcbVersion,
+) -> RosettaResult<TransactionIdentifierResponse> {
+    let signed = parse_signed_transaction(&req.signed_transaction)?;
+    let hash = signed
+        .txn_hash(version)
+        .map_err(|e| RosettaError::InvalidRequest(e.to_string()))?;
+
+    Ok(TransactionIdentifierResponse {
+        transaction_identifier: TransactionIdentifier { hash },
+    })
+}
+
+/////////////
+// helpers //
+/////////////
+
+fn parse_unsigned_transaction(txn: &str) -> RosettaResult<UnsignedTransaction> {
+    serde_json::from_str(txn).map_err(|e| RosettaError::InvalidRequest(e.to_string()))
+}
+
+fn parse_signed_transaction(txn: &str) -> RosettaResult<SignedCommand> {
+    SignedCommand::from_daemon_json(txn).map_err(|e| RosettaError::InvalidRequest(e.to_string()))
+}
+
+/// Rosetta public keys are the hex encoded big-endian x-coordinate, with the
+/// y-coordinate's parity in the most significant bit
+pub fn pubkey_from_rosetta(pk: &RosettaPublicKey) -> RosettaResult<CompressedPubKey> {
+    let invalid = |msg: &str| RosettaError::PublicKeyFormatNotValid(format!("{msg}: {pk:?}"));
+    if pk.curve_type != CURVE_TYPE {
+        return Err(invalid("unsupported curve type"));
+    }
+
+    let mut bytes = hex::decode(&pk.hex_bytes).map_err(|_| invalid("invalid hex"))?;
+    if bytes.len() != BaseField::size_in_bytes() {
+        return Err(invalid("invalid length"));
+    }
+
+    let is_odd = bytes[0] & 0x80 != 0;
+    bytes[0] &= 0x7f;
+    bytes.reverse();
+    bytes.push(is_odd as u8);
+    CompressedPubKey::from_bytes(&bytes).map_err(|e| invalid(&e.to_string()))
+}
+
+/// Inverse of [pubkey_from_rosetta]
+pub fn pubkey_to_rosetta(pk: &CompressedPubKey) -> RosettaPublicKey {
+    let mut bytes = pk.x.to_bytes();
+    bytes.reverse();
+    if pk.is_odd {
+        bytes[0] |= 0x80;
+    }
+
+    RosettaPublicKey {
+        hex_bytes: hex::encode(bytes),
+        curve_type: CURVE_TYPE.to_string(),
+    }
+}
+
+fn metadata_value<'a>(
+    metadata: &'a Option<serde_json::Value>,
+    key: &str,
+) -> Option<&'a serde_json::Value> {
+    metadata.as_ref().and_then(|metadata| metadata.get(key))
+}
+
+/// Metadata numbers may be given as JSON numbers or strings
+fn metadata_u32(metadata: &Option<serde_json::Value>, key: &str) -> RosettaResult<Option<u32>> {
+    use serde_json::Value;
+
+    match metadata_value(metadata, key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Number(n)) => n
+            .as_u64()
+            .and_then(|n| u32::try_from(n).ok())
+            .map(Some)
+            .ok_or(RosettaError::InvalidRequest(format!("invalid {key}: {n}"))),
+        Some(Value::String(s)) => s
+            .parse()
+            .map(Some)
+            .map_err(|_| RosettaError::InvalidRequest(format!("invalid {key}: {s}"))),
+        Some(value) => Err(RosettaError::InvalidRequest(format!(
+            "invalid {key}: {value}"
+        ))),
+    }
+}
+
+fn metadata_str(metadata: &Option<serde_json::Value>, key: &str) -> RosettaResult<Option<String>> {
+    use serde_json::Value;
+
+    match metadata_value(metadata, key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s.clone())),
+        Some(value) => Err(RosettaError::InvalidRequest(format!(
+            "invalid {key}: {value}"
+        ))),
+    }
+}
+
+fn op_value(op: &Operation) -> RosettaResult<i128> {
+    op.amount
+        .as_ref()
+        .and_then(|amount| amount.value.parse().ok())
+        .ok_or(RosettaError::OperationsNotValid(format!(
+            "{} requires an amount",
+            op.kind
+        )))
+}
+
+fn op_pk(op: &Operation) -> RosettaResult<PublicKey> {
+    op.account
+        .as_ref()
+        .ok_or(RosettaError::OperationsNotValid(format!(
+            "{} requires an account",
+            op.kind
+        )))
+        .and_then(|account| check_public_key(&account.address))
+}
+
+/// Finds the unique operation of the given kind
+fn find_op<'a>(ops: &'a [Operation], kind: &str) -> RosettaResult<Option<&'a Operation>> {
+    let mut found = ops.iter().filter(|op| op.kind == kind);
+    match (found.next(), found.next()) {
+        (op, None) => Ok(op),
+        _ => Err(RosettaError::OperationsNotValid(format!(
+            "multiple {kind} operations"
+        ))),
+    }
+}
+
+/// Converts operations (as produced by [command_operations]) to a payment or
+/// delegation
+pub fn command_from_operations(
+    ops: &[Operation],
+    nonce: Nonce,
+    metadata: &Option<serde_json::Value>,
+) -> RosettaResult<UnsignedCommand> {
+    const SUPPORTED: [&str; 4] = [
+        OP_FEE_PAYMENT,
+        OP_PAYMENT_SOURCE_DEC,
+        OP_PAYMENT_RECEIVER_INC,
+        OP_DELEGATE_CHANGE,
+    ];
+    if let Some(op) = ops.iter().find(|op| !SUPPORTED.contains(&op.kind.as_str())) {
+        return Err(RosettaError::UnsupportedOperation(op.kind.clone()));
+    }
+
+    // fee
+    let fee_op = find_op(ops, OP_FEE_PAYMENT)?.ok_or(RosettaError::OperationsNotValid(
+        "missing fee_payment".into(),
+    ))?;
+    let sender = op_pk(fee_op)?;
+    let fee = op_value(fee_op)?;
+    if fee > 0 {
+        return Err(RosettaError::OperationsNotValid(
+            "fee_payment must be negative".into(),
+        ));
+    }
+
+    // body
+    let source_op = find_op(ops, OP_PAYMENT_SOURCE_DEC)?;
+    let receiver_op = find_op(ops, OP_PAYMENT_RECEIVER_INC)?;
+    let delegate_op = find_op(ops, OP_DELEGATE_CHANGE)?;
+    let body = match (source_op, receiver_op, delegate_op) {
+        (Some(source_op), Some(receiver_op), None) => {
+            let amount = op_value(receiver_op)?;
+            if op_pk(source_op)? != sender {
+                return Err(RosettaError::OperationsNotValid(
+                    "payment source must pay the fee".into(),
+                ));
+            }
+            if amount < 0 || op_value(source_op)? != -amount {
+                return Err(RosettaError::OperationsNotValid(
+                    "payment_source_dec & payment_receiver_inc amounts must cancel".into(),
+                ));
+            }
+
+            UnsignedCommandBody::Payment {
+                receiver: op_pk(receiver_op)?,
+                amount: Amount(u64::try_from(amount).map_err(|_| {
+                    RosettaError::OperationsNotValid(format!("amount out of range: {amount}"))
+                })?),
+            }
+        }
+        (None, None, Some(delegate_op)) => {
+            if op_pk(delegate_op)? != sender {
+                return Err(RosettaError::OperationsNotValid(
+                    "delegator must pay the fee".into(),
+                ));
+            }
+            let new_delegate = delegate_op
+                .metadata
+                .as_ref()
+                .and_then(|metadata| metadata.get("delegate_change_target"))
+                .and_then(|target| target.as_str())
+                .ok_or(RosettaError::OperationsNotValid(
+                    "delegate_change requires a delegate_change_target".into(),
+                ))?;
+
+            UnsignedCommandBody::Delegation {
+                new_delegate: check_public_key(new_delegate)?,
+            }
+        }
+        _ => {
+            return Err(RosettaError::OperationsNotValid(
+                "expected a payment or a delegation".into(),
+            ))
+        }
+    };
+
+    let memo = metadata_str(metadata, "memo")?.unwrap_or_default();
+    encode_memo(&memo).map_err(|e| RosettaError::MemoInvalid(e.to_string()))?;
+
+    let fee = fee
+        .checked_neg()
+        .and_then(|fee| u64::try_from(fee).ok())
+        .ok_or(RosettaError::OperationsNotValid(format!(
+            "fee out of range: {fee}"
+        )))?;
+
+    Ok(UnsignedCommand {
+        sender,
+        fee: Amount(fee),
+        nonce,
+        valid_until: metadata_u32(metadata, "valid_until")?.unwrap_or(VALID_UNTIL_DEFAULT),
+        memo,
+        body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::precomputed::PrecomputedBlock;
+    use std::path::PathBuf;
+
+    fn block_commands() -> anyhow::Result<Vec<SignedCommand>> {
+        let path = PathBuf::from("./tests/data/non_sequential_blocks/mainnet-220897-3NL4HLb7MQrxmAqVw8D4vEXCj2tdT8zgP9DFWGRoDxP72b4wxyUw.json");
+        let block = PrecomputedBlock::parse_file(&path, PcbVersion::V1)?;
+        Ok(SignedCommand::from_precomputed(&block)
+            .into_iter()
+            .map(|cmd| cmd.signed_command)
+            .collect())
+    }
+
+    #[test]
+    fn rosetta_public_keys() -> anyhow::Result<()> {
+        let address = "B62qre3erTHfzQckNuibViWQGyyKwZseztqrjPZBv6SQF384Rg6ESAy";
+        let pk = CompressedPubKey::from_address(address)?;
+        let rosetta_pk = pubkey_to_rosetta(&pk);
+
+        assert_eq!(rosetta_pk.hex_bytes.len(), 64);
+        assert_eq!(pubkey_from_rosetta(&rosetta_pk)?.into_address(), address);
+
+        let wrong_curve = RosettaPublicKey {
+            curve_type: "secp256k1".into(),
+            ..rosetta_pk
+        };
+        assert!(pubkey_from_rosetta(&wrong_curve).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn operations_roundtrip() -> anyhow::Result<()> {
+        for signed in block_commands()? {
+            let cmd = UnsignedCommand::from(&signed);
+            let metadata = Some(serde_json::json!({
+                "memo": cmd.memo,
+                "valid_until": cmd.valid_until,
+            }));
+            let ops = command_operations(&cmd, None);
+
+            assert_eq!(command_from_operations(&ops, cmd.nonce, &metadata), Ok(cmd));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_operations() {
+        let cmd = UnsignedCommand {
+            sender: "B62qre3erTHfzQckNuibViWQGyyKwZseztqrjPZBv6SQF384Rg6ESAy".into(),
+            fee: Amount(10_000_000),
+            nonce: Nonce(0),
+            valid_until: VALID_UNTIL_DEFAULT,
+            memo: String::new(),
+            body: UnsignedCommandBody::Payment {
+                receiver: "B62qs2YyNuo1LbNo5sbhPByDDAB7NZiejFM6H1ctND5ui7wH4PWa7qm".into(),
+                amount: Amount(1_000_000_000),
+            },
+        };
+        let mut ops = command_operations(&cmd, None);
+
+        // amounts don't cancel
+        ops[2].amount = Some(super::super::amount(1));
+        assert!(matches!(
+            command_from_operations(&ops, Nonce(0), &None),
+            Err(RosettaError::OperationsNotValid(_))
+        ));
+
+        // unsupported
+        ops[2].kind = "coinbase_inc".into();
+        assert_eq!(
+            command_from_operations(&ops, Nonce(0), &None),
+            Err(RosettaError::UnsupportedOperation("coinbase_inc".into()))
+        );
+
+        // amount overflows u64
+        let mut ops = command_operations(&cmd, None);
+        let too_large = i128::from(u64::MAX) + 1;
+        ops[1].amount = Some(super::super::amount(-too_large));
+        ops[2].amount = Some(super::super::amount(too_large));
+        assert!(matches!(
+            command_from_operations(&ops, Nonce(0), &None),
+            Err(RosettaError::OperationsNotValid(_))
+        ));
+
+        // fee overflows u64
+        let mut ops = command_operations(&cmd, None);
+        ops[0].amount = Some(super::super::amount(-too_large));
+        assert!(matches!(
+            command_from_operations(&ops, Nonce(0), &None),
+            Err(RosettaError::OperationsNotValid(_))
+        ));
+
+        // memo too long
+        let ops = command_operations(&cmd, None);
+        let metadata = Some(serde_json::json!({ "memo": "x".repeat(33) }));
+        assert!(matches!(
+            command_from_operations(&ops, Nonce(0), &metadata),
+            Err(RosettaError::MemoInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn combine_rejected_hash_and_parse() -> anyhow::Result<()> {
+        for signed in block_commands()? {
+            let unsigned = UnsignedCommand::from(&signed);
+            let sender = CompressedPubKey::from_address(&unsigned.sender.0)?;
+            let signature: Signature = signed.0.t.t.signature.clone().into();
+            let unsigned_transaction = serde_json::to_string(&UnsignedTransaction {
+                random_oracle_input: unsigned.to_roinput()?.to_hex(),
+                command: unsigned.clone(),
+            })?;
+            let signing_payload = SigningPayload {
+                account_identifier: account_identifier(&unsigned.sender),
+                hex_bytes: unsigned.to_roinput()?.to_hex(),
+                signature_type: SIGNATURE_TYPE.into(),
+            };
+
+            // the signature can't be verified, so combining is rejected
+            let combined = combine(&ConstructionCombineRequest {
+                network_identifier: NetworkIdentifier {
+                    blockchain: "mina".into(),
+                    network: "mainnet".into(),
+                },
+                unsigned_transaction,
+                signatures: vec![RosettaSignature {
+                    signing_payload,
+                    public_key: pubkey_to_rosetta(&sender),
+                    signature_type: SIGNATURE_TYPE.into(),
+                    hex_bytes: signature.to_string(),
+                }],
+            });
+            assert!(matches!(
+                combined,
+                Err(RosettaError::UnsupportedOperation(_))
+            ));
+            let signed_transaction = unsigned
+                .clone()
+                .into_signed_command(&signature)?
+                .to_daemon_json()?
+                .to_string();
+
+            // the signed transaction hashes to the original command hash
+            let hashed = hash(
+                &ConstructionHashRequest {
+                    network_identifier: NetworkIdentifier {
+                        blockchain: "mina".into(),
+                        network: "mainnet".into(),
+                    },
+                    signed_transaction: signed_transaction.clone(),
+                },
+                &PcbVersion::V1,
+            )?;
+            assert_eq!(
+                hashed.transaction_identifier.hash,
+                signed.txn_hash(&PcbVersion::V1)?
+            );
+
+            // parsing the signed transaction recovers the signer
+            let parsed = parse(&ConstructionParseRequest {
+                network_identifier: NetworkIdentifier {
+                    blockchain: "mina".into(),
+                    network: "mainnet".into(),
+                },
+                signed: true,
+                transaction: signed_transaction,
+            })?;
+            assert_eq!(
+                parsed.account_identifier_signers,
+                vec![account_identifier(&unsigned.sender)]
+            );
+            assert_eq!(parsed.operations, command_operations(&unsigned, None));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn combine_mismatched_payload() -> anyhow::Result<()> {
+        let signed = block_commands()?.remove(0);
+        let unsigned = UnsignedCommand::from(&signed);
+        let sender = CompressedPubKey::from_address(&unsigned.sender.0)?;
+        let signature: Signature = signed.0.t.t.signature.clone().into();
+
+        // the signature is over a different fee
+        let other = UnsignedCommand {
+            fee: Amount(unsigned.fee.0 + 1),
+            ..unsigned.clone()
+        };
+        let err = combine(&ConstructionCombineRequest {
+            network_identifier: NetworkIdentifier {
+                blockchain: "mina".into(),
+                network: "mainnet".into(),
+            },
+            unsigned_transaction: serde_json::to_string(&UnsignedTransaction {
+                random_oracle_input: unsigned.to_roinput()?.to_hex(),
+                command: unsigned.clone(),
+            })?,
+            signatures: vec![RosettaSignature {
+                signing_payload: SigningPayload {
+                    account_identifier: account_identifier(&unsigned.sender),
+                    hex_bytes: other.to_roinput()?.to_hex(),
+                    signature_type: SIGNATURE_TYPE.into(),
+                },
+                public_key: pubkey_to_rosetta(&sender),
+                signature_type: SIGNATURE_TYPE.into(),
+                hex_bytes: signature.to_string(),
+            }],
+        });
+
+        assert!(matches!(err, Err(RosettaError::SignatureInvalid(_))));
+        Ok(())
+    }
+}
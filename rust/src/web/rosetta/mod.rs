@@ -0,0 +1,711 @@
+//! Rosetta API
+//!
+//! Implementation of the [Rosetta](https://docs.cdp.coinbase.com/mesh/docs/api-reference)
+//! Data API on top of the indexer store & the offline Construction API. All
+//! endpoints are `POST` requests with JSON bodies. See
+//! `docs/rosetta/README.org` for the data requirements.
+
+pub mod account;
+pub mod block;
+pub mod construction;
+pub mod network;
+pub mod search;
+
+use crate::{
+    block::{precomputed::PrecomputedBlock, store::BlockStore, BlockHash},
+    canonicity::{store::CanonicityStore, Canonicity},
+    chain::store::ChainStore,
+    command::{
+        internal::InternalCommandWithData,
+        signed::SignedCommandWithData,
+        unsigned::{UnsignedCommand, UnsignedCommandBody},
+    },
+    constants::MAINNET_ACCOUNT_CREATION_FEE,
+    ledger::{
+        public_key::{is_valid_public_key, PublicKey},
+        token::{is_valid_token_id, TokenId},
+    },
+    store::IndexerStore,
+};
+use actix_web::{http::header::ContentType, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+pub const BLOCKCHAIN: &str = "mina";
+pub const CURRENCY_SYMBOL: &str = "MINA";
+pub const CURRENCY_DECIMALS: u32 = 9;
+
+/// Default & max number of transactions returned by `/search/transactions`
+pub const SEARCH_LIMIT_DEFAULT: u64 = 100;
+pub const SEARCH_LIMIT_MAX: u64 = 1000;
+
+/// Prefix of the identifiers we assign to internal commands, which have no
+/// hash of their own: `internal:{state_hash}:{index}`
+pub const INTERNAL_COMMAND_ID_PREFIX: &str = "internal";
+
+/////////////////
+// identifiers //
+/////////////////
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkIdentifier {
+    pub blockchain: String,
+    pub network: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockIdentifier {
+    pub index: u32,
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartialBlockIdentifier {
+    pub index: Option<u32>,
+    pub hash: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionIdentifier {
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountIdentifier {
+    pub address: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationIdentifier {
+    pub index: u64,
+}
+
+/////////////
+// objects //
+/////////////
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Currency {
+    pub symbol: String,
+    pub decimals: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Amount {
+    /// Signed value in nanomina
+    pub value: String,
+    pub currency: Currency,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Operation {
+    pub operation_identifier: OperationIdentifier,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related_operations: Vec<OperationIdentifier>,
+
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+
+    pub account: Option<AccountIdentifier>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<Amount>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transaction {
+    pub transaction_identifier: TransactionIdentifier,
+    pub operations: Vec<Operation>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Block {
+    pub block_identifier: BlockIdentifier,
+    pub parent_block_identifier: BlockIdentifier,
+
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub transactions: Vec<Transaction>,
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockTransaction {
+    pub block_identifier: BlockIdentifier,
+    pub transaction: Transaction,
+}
+
+/////////////////////
+// operation kinds //
+/////////////////////
+
+pub const OP_FEE_PAYMENT: &str = "fee_payment";
+pub const OP_PAYMENT_SOURCE_DEC: &str = "payment_source_dec";
+pub const OP_PAYMENT_RECEIVER_INC: &str = "payment_receiver_inc";
+pub const OP_ACCOUNT_CREATION_FEE_VIA_PAYMENT: &str = "account_creation_fee_via_payment";
+pub const OP_DELEGATE_CHANGE: &str = "delegate_change";
+pub const OP_COINBASE_INC: &str = "coinbase_inc";
+pub const OP_FEE_RECEIVER_INC: &str = "fee_receiver_inc";
+pub const OP_FEE_PAYER_DEC: &str = "fee_payer_dec";
+
+pub const STATUS_SUCCESS: &str = "Success";
+pub const STATUS_FAILED: &str = "Failed";
+
+////////////
+// errors //
+////////////
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Error {
+    pub code: u32,
+    pub message: String,
+    pub retriable: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RosettaError {
+    Database(String),
+    NetworkDoesntExist(NetworkIdentifier),
+    ChainInfoMissing,
+    AccountNotFound(String),
+    TransactionNotFound(String),
+    BlockMissing(String),
+    MalformedPublicKey(String),
+    InvalidRequest(String),
+    OperationsNotValid(String),
+    UnsupportedOperation(String),
+    SignatureMissing,
+    PublicKeyFormatNotValid(String),
+    SignatureInvalid(String),
+    MemoInvalid(String),
+}
+
+impl RosettaError {
+    pub fn code(&self) -> u32 {
+        match self {
+            Self::Database(_) => 1,
+            Self::InvalidRequest(_) => 2,
+            Self::NetworkDoesntExist(_) => 4,
+            Self::ChainInfoMissing => 5,
+            Self::AccountNotFound(_) => 6,
+            Self::TransactionNotFound(_) => 8,
+            Self::BlockMissing(_) => 9,
+            Self::MalformedPublicKey(_) => 10,
+            Self::OperationsNotValid(_) => 11,
+            Self::UnsupportedOperation(_) => 12,
+            Self::SignatureMissing => 13,
+            Self::PublicKeyFormatNotValid(_) => 14,
+            Self::SignatureInvalid(_) => 17,
+            Self::MemoInvalid(_) => 18,
+        }
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::Database(_) => "Database error",
+            Self::InvalidRequest(_) => "Invalid request",
+            Self::NetworkDoesntExist(_) => "Network doesn't exist",
+            Self::ChainInfoMissing => "Chain info missing",
+            Self::AccountNotFound(_) => "Account not found",
+            Self::TransactionNotFound(_) => "Transaction not found",
+            Self::BlockMissing(_) => "Block not found",
+            Self::MalformedPublicKey(_) => "Malformed public key",
+            Self::OperationsNotValid(_) => "Cannot convert operations to valid transaction",
+            Self::UnsupportedOperation(_) => "Unsupported operation for construction",
+            Self::SignatureMissing => "Signature missing",
+            Self::PublicKeyFormatNotValid(_) => "Invalid public key format",
+            Self::SignatureInvalid(_) => "Invalid signature",
+            Self::MemoInvalid(_) => "Invalid memo",
+        }
+    }
+
+    /// Only database errors may resolve themselves on retry, missing
+    /// blocks/transactions may still be ingested
+    pub fn retriable(&self) -> bool {
+        matches!(
+            self,
+            Self::Database(_) | Self::BlockMissing(_) | Self::TransactionNotFound(_)
+        )
+    }
+
+    fn details(&self) -> Option<serde_json::Value> {
+        use serde_json::json;
+
+        match self {
+            Self::ChainInfoMissing | Self::SignatureMissing => None,
+            Self::NetworkDoesntExist(network_identifier) => {
+                Some(json!({ "network_identifier": network_identifier }))
+            }
+            Self::Database(msg)
+            | Self::AccountNotFound(msg)
+            | Self::TransactionNotFound(msg)
+            | Self::BlockMissing(msg)
+            | Self::MalformedPublicKey(msg)
+            | Self::InvalidRequest(msg)
+            | Self::OperationsNotValid(msg)
+            | Self::UnsupportedOperation(msg)
+            | Self::PublicKeyFormatNotValid(msg)
+            | Self::SignatureInvalid(msg)
+            | Self::MemoInvalid(msg) => {
+                if msg.is_empty() {
+                    None
+                } else {
+                    Some(json!({ "error": msg }))
+                }
+            }
+        }
+    }
+
+    pub fn to_error(&self) -> Error {
+        Error {
+            code: self.code(),
+            message: self.message().to_string(),
+            retriable: self.retriable(),
+            details: self.details(),
+        }
+    }
+}
+
+impl std::fmt::Display for RosettaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.details() {
+            Some(details) => write!(f, "{}: {details}", self.message()),
+            None => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+impl std::error::Error for RosettaError {}
+
+impl From<anyhow::Error> for RosettaError {
+    fn from(value: anyhow::Error) -> Self {
+        Self::Database(value.to_string())
+    }
+}
+
+pub type RosettaResult<T> = Result<T, RosettaError>;
+
+/// Rosetta responds with 200 on success & 500 with an [Error] otherwise
+pub fn to_response<T: Serialize>(res: RosettaResult<T>) -> HttpResponse {
+    match res {
+        Ok(body) => HttpResponse::Ok()
+            .content_type(ContentType::json())
+            .body(serde_json::to_string(&body).expect("serialize rosetta response")),
+        Err(e) => HttpResponse::InternalServerError()
+            .content_type(ContentType::json())
+            .body(serde_json::to_string(&e.to_error()).expect("serialize rosetta error")),
+    }
+}
+
+/////////////
+// helpers //
+/////////////
+
+/// The network identifier of the indexed chain
+pub fn network_identifier(db: &IndexerStore) -> RosettaResult<NetworkIdentifier> {
+    let network = db
+        .get_current_network()
+        .map_err(|_| RosettaError::ChainInfoMissing)?;
+    Ok(NetworkIdentifier {
+        blockchain: BLOCKCHAIN.to_string(),
+        network: network.to_string(),
+    })
+}
+
+/// Checks that the request's network identifier matches the indexed chain
+pub fn check_network(db: &IndexerStore, requested: &NetworkIdentifier) -> RosettaResult<()> {
+    if network_identifier(db)? == *requested {
+        Ok(())
+    } else {
+        Err(RosettaError::NetworkDoesntExist(requested.clone()))
+    }
+}
+
+pub fn check_public_key(address: &str) -> RosettaResult<PublicKey> {
+    if is_valid_public_key(address) {
+        Ok(address.into())
+    } else {
+        Err(RosettaError::MalformedPublicKey(address.to_string()))
+    }
+}
+
+pub fn currency() -> Currency {
+    Currency {
+        symbol: CURRENCY_SYMBOL.to_string(),
+        decimals: CURRENCY_DECIMALS,
+    }
+}
+
+pub fn amount(value: i128) -> Amount {
+    Amount {
+        value: value.to_string(),
+        currency: currency(),
+    }
+}
+
+/// Identifier of the MINA account of `pk`. Payments, delegations & internal
+/// commands only move MINA
+pub fn account_identifier(pk: &PublicKey) -> AccountIdentifier {
+    token_account_identifier(pk, &TokenId::default())
+}
+
+pub fn token_account_identifier(pk: &PublicKey, token: &TokenId) -> AccountIdentifier {
+    AccountIdentifier {
+        address: pk.to_address(),
+        metadata: Some(serde_json::json!({ "token_id": token.0 })),
+    }
+}
+
+/// Token of the account identifier's `token_id` metadata, MINA by default
+pub fn check_token(account_identifier: &AccountIdentifier) -> RosettaResult<TokenId> {
+    match account_identifier
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.get("token_id"))
+    {
+        None => Ok(TokenId::default()),
+        Some(serde_json::Value::String(token)) if is_valid_token_id(token) => {
+            Ok(TokenId::new(token.as_str()))
+        }
+        Some(token) => Err(RosettaError::InvalidRequest(format!(
+            "invalid token_id {token}"
+        ))),
+    }
+}
+
+pub fn block_identifier(block: &PrecomputedBlock) -> BlockIdentifier {
+    BlockIdentifier {
+        index: block.blockchain_length(),
+        hash: block.state_hash().0,
+    }
+}
+
+/// The genesis block is its own parent
+pub fn parent_block_identifier(db: &IndexerStore, block: &PrecomputedBlock) -> BlockIdentifier {
+    let parent_hash = block.previous_state_hash();
+    match db.get_block_height(&parent_hash) {
+        Ok(Some(index)) => BlockIdentifier {
+            index,
+            hash: parent_hash.0,
+        },
+        _ => block_identifier(block),
+    }
+}
+
+/// Looks up a block via (partial) identifier. If neither the hash nor the
+/// index is given, the best block is returned. Heights are resolved via the
+/// canonical chain.
+pub fn lookup_block(
+    db: &IndexerStore,
+    block_identifier: &PartialBlockIdentifier,
+) -> RosettaResult<PrecomputedBlock> {
+    let state_hash = match (&block_identifier.hash, block_identifier.index) {
+        (Some(hash), _) => BlockHash(hash.clone()),
+        (None, Some(index)) => db
+            .get_canonical_hash_at_height(index)?
+            .ok_or(RosettaError::BlockMissing(format!("index {index}")))?,
+        (None, None) => db
+            .get_best_block_hash()?
+            .ok_or(RosettaError::BlockMissing("best block".into()))?,
+    };
+    let (block, _) = db
+        .get_block(&state_hash)?
+        .ok_or(RosettaError::BlockMissing(state_hash.0.clone()))?;
+
+    if let Some(index) = block_identifier.index {
+        if block.blockchain_length() != index {
+            return Err(RosettaError::BlockMissing(format!(
+                "index {index} with hash {state_hash}"
+            )));
+        }
+    }
+    Ok(block)
+}
+
+/// Rosetta block status: Pending, Canonical, or Orphaned
+pub fn block_status(db: &IndexerStore, state_hash: &BlockHash) -> Option<String> {
+    db.get_block_canonicity(state_hash)
+        .ok()
+        .flatten()
+        .map(|canonicity| {
+            match canonicity {
+                Canonicity::Canonical => "Canonical",
+                Canonicity::Orphaned => "Orphaned",
+                Canonicity::Pending => "Pending",
+            }
+            .to_string()
+        })
+}
+
+pub fn internal_command_id(state_hash: &BlockHash, index: usize) -> String {
+    format!("{INTERNAL_COMMAND_ID_PREFIX}:{state_hash}:{index}")
+}
+
+/// Parses `internal:{state_hash}:{index}` into its parts
+pub fn parse_internal_command_id(id: &str) -> Option<(BlockHash, usize)> {
+    let mut parts = id.split(':');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(INTERNAL_COMMAND_ID_PREFIX), Some(state_hash), Some(index), None) => {
+            Some((state_hash.into(), index.parse().ok()?))
+        }
+        _ => None,
+    }
+}
+
+fn operation(
+    index: u64,
+    kind: &str,
+    status: Option<&str>,
+    pk: &PublicKey,
+    value: Option<i128>,
+) -> Operation {
+    Operation {
+        operation_identifier: OperationIdentifier { index },
+        related_operations: vec![],
+        kind: kind.to_string(),
+        status: status.map(str::to_string),
+        account: Some(account_identifier(pk)),
+        amount: value.map(amount),
+        metadata: None,
+    }
+}
+
+/// Rosetta operations for a payment or delegation. The `status` is `None`
+/// for commands which have not been included in a block.
+pub fn command_operations(cmd: &UnsignedCommand, status: Option<&str>) -> Vec<Operation> {
+    // the fee is charged whether or not the command is applied
+    let fee_status = status.map(|_| STATUS_SUCCESS);
+    let mut ops = vec![operation(
+        0,
+        OP_FEE_PAYMENT,
+        fee_status,
+        &cmd.sender,
+        Some(-(cmd.fee.0 as i128)),
+    )];
+
+    match &cmd.body {
+        UnsignedCommandBody::Payment { receiver, amount } => {
+            let amt = amount.0 as i128;
+            ops.push(operation(
+                1,
+                OP_PAYMENT_SOURCE_DEC,
+                status,
+                &cmd.sender,
+                Some(-amt),
+            ));
+
+            let mut receiver_inc =
+                operation(2, OP_PAYMENT_RECEIVER_INC, status, receiver, Some(amt));
+            receiver_inc.related_operations = vec![OperationIdentifier { index: 1 }];
+            ops.push(receiver_inc);
+        }
+        UnsignedCommandBody::Delegation { new_delegate } => {
+            let mut delegate_change = operation(1, OP_DELEGATE_CHANGE, status, &cmd.sender, None);
+            delegate_change.metadata = Some(serde_json::json!({
+                "delegate_change_target": new_delegate.0
+            }));
+            ops.push(delegate_change);
+        }
+    }
+    ops
+}
+
+/// Rosetta operations for a user command (payment or delegation)
+pub fn user_command_operations(cmd: &SignedCommandWithData) -> Vec<Operation> {
+    let status = if cmd.status.is_applied() {
+        STATUS_SUCCESS
+    } else {
+        STATUS_FAILED
+    };
+    let unsigned = UnsignedCommand::from(&cmd.command);
+    let mut ops = command_operations(&unsigned, Some(status));
+
+    if let UnsignedCommandBody::Payment { receiver, .. } = &unsigned.body {
+        if cmd.status.receiver_account_creation_fee_paid().is_some() {
+            ops.push(operation(
+                3,
+                OP_ACCOUNT_CREATION_FEE_VIA_PAYMENT,
+                Some(status),
+                receiver,
+                Some(-(MAINNET_ACCOUNT_CREATION_FEE.0 as i128)),
+            ));
+        }
+    }
+    ops
+}
+
+pub fn user_command_transaction(cmd: &SignedCommandWithData) -> Transaction {
+    Transaction {
+        transaction_identifier: TransactionIdentifier {
+            hash: cmd.tx_hash.clone(),
+        },
+        operations: user_command_operations(cmd),
+        metadata: Some(serde_json::json!({
+            "nonce": cmd.nonce.0,
+            "memo": cmd.command.memo(),
+        })),
+    }
+}
+
+/// Rosetta operations for an internal command (coinbase or fee transfer)
+pub fn internal_command_operations(cmd: &InternalCommandWithData) -> Vec<Operation> {
+    use crate::command::internal::InternalCommandKind;
+
+    match cmd {
+        InternalCommandWithData::Coinbase {
+            receiver, amount, ..
+        } => vec![operation(
+            0,
+            OP_COINBASE_INC,
+            Some(STATUS_SUCCESS),
+            receiver,
+            Some(*amount as i128),
+        )],
+        InternalCommandWithData::FeeTransfer {
+            sender,
+            receiver,
+            amount,
+            kind,
+            ..
+        } => {
+            let mut ops = vec![operation(
+                0,
+                OP_FEE_RECEIVER_INC,
+                Some(STATUS_SUCCESS),
+                receiver,
+                Some(*amount as i128),
+            )];
+
+            // fees transferred via coinbase are paid by the coinbase receiver
+            if *kind == InternalCommandKind::FeeTransferViaCoinbase {
+                let mut payer_dec = operation(
+                    1,
+                    OP_FEE_PAYER_DEC,
+                    Some(STATUS_SUCCESS),
+                    sender,
+                    Some(-(*amount as i128)),
+                );
+                payer_dec.related_operations = vec![OperationIdentifier { index: 0 }];
+                ops.push(payer_dec);
+            }
+            ops
+        }
+    }
+}
+
+pub fn internal_command_transaction(
+    cmd: &InternalCommandWithData,
+    state_hash: &BlockHash,
+    index: usize,
+) -> Transaction {
+    let kind = match cmd {
+        InternalCommandWithData::Coinbase { kind, .. }
+        | InternalCommandWithData::FeeTransfer { kind, .. } => kind,
+    };
+    Transaction {
+        transaction_identifier: TransactionIdentifier {
+            hash: internal_command_id(state_hash, index),
+        },
+        operations: internal_command_operations(cmd),
+        metadata: Some(serde_json::json!({ "kind": kind.to_string() })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        block::precomputed::PcbVersion, command::internal::InternalCommandKind,
+        constants::MAINNET_COINBASE_REWARD,
+    };
+    use std::path::PathBuf;
+
+    #[test]
+    fn internal_command_ids() {
+        let state_hash: BlockHash = "3NLMeYAFXxsmhSFtLHFxdtjGcfHTVFmBmBF8uTJvP4Ve5yEmxYeA".into();
+        let id = internal_command_id(&state_hash, 3);
+
+        assert_eq!(
+            id,
+            "internal:3NLMeYAFXxsmhSFtLHFxdtjGcfHTVFmBmBF8uTJvP4Ve5yEmxYeA:3"
+        );
+        assert_eq!(parse_internal_command_id(&id), Some((state_hash, 3)));
+        assert_eq!(parse_internal_command_id("internal:3NL:x"), None);
+        assert_eq!(
+            parse_internal_command_id("CkpZ5KkJi2NiDJzr2QZFxyoMbETPoVh2HjRnPkzXbBuPcDu6BGPWH"),
+            None
+        );
+    }
+
+    #[test]
+    fn internal_operations() -> anyhow::Result<()> {
+        let path = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous/mainnet-11-3NLMeYAFXxsmhSFtLHFxdtjGcfHTVFmBmBF8uTJvP4Ve5yEmxYeA.json");
+        let block = PrecomputedBlock::parse_file(&path, PcbVersion::V1)?;
+        let cmds = InternalCommandWithData::from_precomputed(&block);
+        let coinbase = cmds
+            .iter()
+            .find(|cmd| matches!(cmd, InternalCommandWithData::Coinbase { .. }))
+            .unwrap();
+        let ops = internal_command_operations(coinbase);
+
+        assert_eq!(ops.len(), 1);
+        assert_eq!(ops[0].kind, OP_COINBASE_INC);
+        assert_eq!(ops[0].amount, Some(amount(MAINNET_COINBASE_REWARD as i128)));
+
+        let via_coinbase = InternalCommandWithData::FeeTransfer {
+            sender: "B62qs2YyNuo1LbNo5sbhPByDDAB7NZiejFM6H1ctND5ui7wH4PWa7qm".into(),
+            receiver: "B62qre3erTHfzQckNuibViWQGyyKwZseztqrjPZBv6SQF384Rg6ESAy".into(),
+            amount: 10,
+            state_hash: block.state_hash(),
+            kind: InternalCommandKind::FeeTransferViaCoinbase,
+            date_time: 0,
+            block_height: 11,
+        };
+        let ops = internal_command_operations(&via_coinbase);
+
+        assert_eq!(ops.len(), 2);
+        assert_eq!(ops[0].kind, OP_FEE_RECEIVER_INC);
+        assert_eq!(ops[0].amount, Some(amount(10)));
+        assert_eq!(ops[1].kind, OP_FEE_PAYER_DEC);
+        assert_eq!(ops[1].amount, Some(amount(-10)));
+        Ok(())
+    }
+
+    #[test]
+    fn user_command_operations_balance() -> anyhow::Result<()> {
+        let path = PathBuf::from("./tests/data/non_sequential_blocks/mainnet-220897-3NL4HLb7MQrxmAqVw8D4vEXCj2tdT8zgP9DFWGRoDxP72b4wxyUw.json");
+        let block = PrecomputedBlock::parse_file(&path, PcbVersion::V1)?;
+        let cmds = SignedCommandWithData::from_precomputed(&block);
+        assert!(!cmds.is_empty());
+
+        for cmd in cmds {
+            let ops = user_command_operations(&cmd);
+            assert_eq!(ops[0].kind, OP_FEE_PAYMENT);
+
+            // payment decrements & increments cancel out
+            if !cmd.command.is_delegation() {
+                let sum: i128 = ops[1..3]
+                    .iter()
+                    .map(|op| op.amount.as_ref().unwrap().value.parse::<i128>().unwrap())
+                    .sum();
+                assert_eq!(sum, 0);
+            }
+        }
+        Ok(())
+    }
+}
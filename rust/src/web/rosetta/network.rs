@@ -0,0 +1,101 @@
+use super::{
+    block_identifier, check_network, network_identifier, to_response, BlockIdentifier,
+    NetworkIdentifier, RosettaError, RosettaResult,
+};
+use crate::{
+    block::store::BlockStore,
+    store::{block_state_hash_from_key, IndexerStore},
+};
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpResponse,
+};
+use serde::{Deserialize, Serialize};
+use speedb::IteratorMode;
+use std::sync::Arc;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MetadataRequest {
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NetworkListResponse {
+    pub network_identifiers: Vec<NetworkIdentifier>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NetworkRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NetworkStatusResponse {
+    pub current_block_identifier: BlockIdentifier,
+
+    /// Milliseconds since the Unix epoch
+    pub current_block_timestamp: u64,
+    pub genesis_block_identifier: BlockIdentifier,
+    pub oldest_block_identifier: Option<BlockIdentifier>,
+    pub peers: Vec<serde_json::Value>,
+}
+
+#[post("/network/list")]
+pub async fn network_list(
+    store: Data<Arc<IndexerStore>>,
+    _req: web::Json<MetadataRequest>,
+) -> HttpResponse {
+    let db = store.as_ref();
+    to_response(
+        network_identifier(db).map(|network_identifier| NetworkListResponse {
+            network_identifiers: vec![network_identifier],
+        }),
+    )
+}
+
+#[post("/network/status")]
+pub async fn network_status(
+    store: Data<Arc<IndexerStore>>,
+    req: web::Json<NetworkRequest>,
+) -> HttpResponse {
+    to_response(get_network_status(store.as_ref(), &req))
+}
+
+fn get_network_status(
+    db: &IndexerStore,
+    req: &NetworkRequest,
+) -> RosettaResult<NetworkStatusResponse> {
+    check_network(db, &req.network_identifier)?;
+
+    let best_block = db
+        .get_best_block()?
+        .ok_or(RosettaError::BlockMissing("best block".into()))?;
+    let genesis_state_hash = best_block.genesis_state_hash();
+    let genesis_block_identifier = BlockIdentifier {
+        index: db.get_block_height(&genesis_state_hash)?.unwrap_or(1),
+        hash: genesis_state_hash.0,
+    };
+
+    // lowest block in the store
+    let oldest_block_identifier = match db.blocks_height_iterator(IteratorMode::Start).next() {
+        Some(Ok((key, _))) => {
+            let state_hash = block_state_hash_from_key(&key)?;
+            db.get_block_height(&state_hash)?
+                .map(|index| BlockIdentifier {
+                    index,
+                    hash: state_hash.0,
+                })
+        }
+        _ => None,
+    };
+
+    Ok(NetworkStatusResponse {
+        current_block_identifier: block_identifier(&best_block),
+        current_block_timestamp: best_block.timestamp(),
+        genesis_block_identifier,
+        oldest_block_identifier,
+        peers: vec![],
+    })
+}
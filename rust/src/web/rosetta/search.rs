@@ -0,0 +1,235 @@
+use super::{
+    check_network, check_public_key, internal_command_transaction, parse_internal_command_id,
+    to_response, user_command_transaction, AccountIdentifier, BlockIdentifier, BlockTransaction,
+    NetworkIdentifier, RosettaError, RosettaResult, TransactionIdentifier, SEARCH_LIMIT_DEFAULT,
+    SEARCH_LIMIT_MAX, STATUS_FAILED, STATUS_SUCCESS,
+};
+use crate::{
+    block::BlockHash,
+    canonicity::{store::CanonicityStore, Canonicity},
+    command::{
+        internal::{store::InternalCommandStore, InternalCommandWithData},
+        store::UserCommandStore,
+    },
+    store::IndexerStore,
+};
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpResponse,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchTransactionsRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub transaction_identifier: Option<TransactionIdentifier>,
+    pub account_identifier: Option<AccountIdentifier>,
+
+    /// Only consider blocks at or below this height
+    pub max_block: Option<u32>,
+    pub offset: Option<u64>,
+    pub limit: Option<u64>,
+
+    /// Operation status, i.e. `Success` or `Failed`
+    pub status: Option<String>,
+
+    /// Operation type
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    pub success: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchTransactionsResponse {
+    pub transactions: Vec<BlockTransaction>,
+    pub total_count: u64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<u64>,
+}
+
+#[post("/search/transactions")]
+pub async fn search_transactions(
+    store: Data<Arc<IndexerStore>>,
+    req: web::Json<SearchTransactionsRequest>,
+) -> HttpResponse {
+    to_response(get_search_transactions(store.as_ref(), &req))
+}
+
+fn get_search_transactions(
+    db: &IndexerStore,
+    req: &SearchTransactionsRequest,
+) -> RosettaResult<SearchTransactionsResponse> {
+    check_network(db, &req.network_identifier)?;
+
+    let mut transactions = match (&req.transaction_identifier, &req.account_identifier) {
+        (Some(txn_id), _) => transactions_by_hash(db, &txn_id.hash)?,
+        (None, Some(account_id)) => transactions_by_account(db, &account_id.address)?,
+        (None, None) => {
+            return Err(RosettaError::InvalidRequest(
+                "search requires a transaction_identifier or account_identifier".into(),
+            ))
+        }
+    };
+
+    // orphaned blocks are never part of the history
+    transactions.retain(|txn| {
+        !matches!(
+            db.get_block_canonicity(&txn.block_identifier.hash.clone().into()),
+            Ok(Some(Canonicity::Orphaned))
+        ) && matches(req, txn)
+    });
+
+    // most recent first
+    transactions.sort_by(|a, b| {
+        b.block_identifier
+            .index
+            .cmp(&a.block_identifier.index)
+            .then_with(|| {
+                a.transaction
+                    .transaction_identifier
+                    .hash
+                    .cmp(&b.transaction.transaction_identifier.hash)
+            })
+    });
+
+    let total_count = transactions.len() as u64;
+    let offset = req.offset.unwrap_or_default();
+    let limit = req
+        .limit
+        .unwrap_or(SEARCH_LIMIT_DEFAULT)
+        .min(SEARCH_LIMIT_MAX);
+    let transactions: Vec<BlockTransaction> = transactions
+        .into_iter()
+        .skip(offset as usize)
+        .take(limit as usize)
+        .collect();
+    let next_offset = Some(offset + transactions.len() as u64).filter(|n| *n < total_count);
+
+    Ok(SearchTransactionsResponse {
+        transactions,
+        total_count,
+        next_offset,
+    })
+}
+
+/// Checks the request's height, status, and operation type filters
+fn matches(req: &SearchTransactionsRequest, txn: &BlockTransaction) -> bool {
+    let ops = &txn.transaction.operations;
+    let status = req.status.as_deref().or(match req.success {
+        Some(true) => Some(STATUS_SUCCESS),
+        Some(false) => Some(STATUS_FAILED),
+        None => None,
+    });
+
+    req.max_block
+        .map_or(true, |max| txn.block_identifier.index <= max)
+        && status.map_or(true, |status| {
+            ops.iter().any(|op| op.status.as_deref() == Some(status))
+        })
+        && req
+            .kind
+            .as_ref()
+            .map_or(true, |kind| ops.iter().any(|op| op.kind == *kind))
+}
+
+fn transactions_by_hash(db: &IndexerStore, txn_hash: &str) -> RosettaResult<Vec<BlockTransaction>> {
+    if let Some((state_hash, index)) = parse_internal_command_id(txn_hash) {
+        return Ok(db
+            .get_internal_commands(&state_hash)?
+            .get(index)
+            .map(|cmd| {
+                vec![BlockTransaction {
+                    block_identifier: internal_block_identifier(cmd, &state_hash),
+                    transaction: internal_command_transaction(cmd, &state_hash, index),
+                }]
+            })
+            .unwrap_or_default());
+    }
+
+    let mut transactions = vec![];
+    for state_hash in db
+        .get_user_command_state_hashes(txn_hash)?
+        .unwrap_or_default()
+    {
+        if let Some(cmd) = db.get_user_command_state_hash(txn_hash, &state_hash)? {
+            transactions.push(BlockTransaction {
+                block_identifier: BlockIdentifier {
+                    index: cmd.blockchain_length,
+                    hash: state_hash.0,
+                },
+                transaction: user_command_transaction(&cmd),
+            });
+        }
+    }
+    Ok(transactions)
+}
+
+fn transactions_by_account(
+    db: &IndexerStore,
+    address: &str,
+) -> RosettaResult<Vec<BlockTransaction>> {
+    let pk = check_public_key(address)?;
+    let mut transactions: Vec<BlockTransaction> = db
+        .get_user_commands_for_public_key(&pk)?
+        .unwrap_or_default()
+        .iter()
+        .map(|cmd| BlockTransaction {
+            block_identifier: BlockIdentifier {
+                index: cmd.blockchain_length,
+                hash: cmd.state_hash.0.clone(),
+            },
+            transaction: user_command_transaction(cmd),
+        })
+        .collect();
+
+    // internal commands are identified by their position in the block
+    for cmd in db.get_internal_commands_public_key(&pk)? {
+        let state_hash = internal_state_hash(&cmd);
+        if let Some(index) = db
+            .get_internal_commands(state_hash)?
+            .iter()
+            .position(|block_cmd| *block_cmd == cmd)
+        {
+            transactions.push(BlockTransaction {
+                block_identifier: internal_block_identifier(&cmd, state_hash),
+                transaction: internal_command_transaction(&cmd, state_hash, index),
+            });
+        }
+    }
+
+    // e.g. the fee payers of a fee transfer's fees have no operation in it
+    let address = pk.to_address();
+    transactions.retain(|txn| {
+        txn.transaction.operations.iter().any(|op| {
+            op.account
+                .as_ref()
+                .is_some_and(|account| account.address == address)
+        })
+    });
+    Ok(transactions)
+}
+
+fn internal_state_hash(cmd: &InternalCommandWithData) -> &BlockHash {
+    use InternalCommandWithData::*;
+
+    match cmd {
+        Coinbase { state_hash, .. } | FeeTransfer { state_hash, .. } => state_hash,
+    }
+}
+
+fn internal_block_identifier(
+    cmd: &InternalCommandWithData,
+    state_hash: &BlockHash,
+) -> BlockIdentifier {
+    use InternalCommandWithData::*;
+
+    match cmd {
+        Coinbase { block_height, .. } | FeeTransfer { block_height, .. } => BlockIdentifier {
+            index: *block_height,
+            hash: state_hash.0.clone(),
+        },
+    }
+}
@@ -0,0 +1,150 @@
+use crate::helpers::setup_new_db_dir;
+use mina_indexer::{
+    block::{
+        archive::BlockArchive,
+        parser::BlockParser,
+        precomputed::{PcbVersion, PrecomputedBlock},
+    },
+    constants::*,
+};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+const BLOCKS_DIR: &str = "./tests/data/canonical_chain_discovery/contiguous";
+
+fn file_names(paths: &[PathBuf]) -> Vec<String> {
+    paths
+        .iter()
+        .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+        .collect()
+}
+
+/// State hashes & sizes of the parsed blocks, in order
+async fn parse_all(blocks_dir: &Path) -> anyhow::Result<(BlockParser, Vec<(String, u64)>)> {
+    let mut block_parser = BlockParser::new_with_canonical_chain_discovery(
+        blocks_dir,
+        PcbVersion::V1,
+        MAINNET_CANONICAL_THRESHOLD,
+        BLOCK_REPORTING_FREQ_NUM,
+    )
+    .await?;
+
+    let mut blocks = vec![];
+    while let Some((block, block_bytes)) = block_parser.next_block().await? {
+        let block: PrecomputedBlock = block.into();
+        blocks.push((block.state_hash().0, block_bytes));
+    }
+    Ok((block_parser, blocks))
+}
+
+#[tokio::test]
+async fn canonical_chain_discovery_over_archives() -> anyhow::Result<()> {
+    let (dir_parser, expected_blocks) = parse_all(Path::new(BLOCKS_DIR)).await?;
+    let expected_paths = dir_parser.paths();
+    assert_eq!(dir_parser.num_deep_canonical_blocks, 10);
+
+    // uncompressed tar with the blocks nested in a directory
+    let archive_dir = setup_new_db_dir("block-archive")?;
+    fs::create_dir_all(archive_dir.path())?;
+    let tar_path = archive_dir.path().join("contiguous.tar");
+    let mut builder = tar::Builder::new(fs::File::create(&tar_path)?);
+    builder.append_dir_all("contiguous", BLOCKS_DIR)?;
+    builder.finish()?;
+
+    for archive in [
+        tar_path,
+        PathBuf::from("./tests/data/archives/contiguous.tar.gz"),
+        PathBuf::from("./tests/data/archives/contiguous.tar.zst"),
+    ] {
+        let (parser, blocks) = parse_all(&archive).await?;
+        let paths = parser.paths();
+
+        assert_eq!(blocks, expected_blocks, "{}", archive.display());
+        assert_eq!(
+            file_names(&paths.canonical_paths),
+            file_names(&expected_paths.canonical_paths)
+        );
+        assert_eq!(
+            file_names(&paths.recent_paths),
+            file_names(&expected_paths.recent_paths)
+        );
+        assert_eq!(
+            file_names(&paths.orphaned_paths),
+            file_names(&expected_paths.orphaned_paths)
+        );
+        assert_eq!(parser.total_num_blocks, dir_parser.total_num_blocks);
+        assert_eq!(parser.total_num_bytes, dir_parser.total_num_bytes);
+        assert_eq!(
+            parser.num_deep_canonical_blocks,
+            dir_parser.num_deep_canonical_blocks
+        );
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn corrupt_archive() -> anyhow::Result<()> {
+    let archive_dir = setup_new_db_dir("block-archive-corrupt")?;
+    fs::create_dir_all(archive_dir.path())?;
+
+    // truncated zstd stream
+    let bytes = fs::read("./tests/data/archives/contiguous.tar.zst")?;
+    let truncated = archive_dir.path().join("truncated.tar.zst");
+    fs::write(&truncated, &bytes[..bytes.len() / 2])?;
+    assert!(BlockParser::new_testing(&truncated).is_err());
+
+    // corrupt gzip stream
+    let mut bytes = fs::read("./tests/data/archives/contiguous.tar.gz")?;
+    let mid = bytes.len() / 2;
+    bytes[mid] ^= 0xff;
+    let corrupt = archive_dir.path().join("corrupt.tar.gz");
+    fs::write(&corrupt, bytes)?;
+    assert!(BlockParser::new_testing(&corrupt).is_err());
+    Ok(())
+}
+
+#[test]
+fn multi_member_gzip_archive() -> anyhow::Result<()> {
+    let archive_dir = setup_new_db_dir("block-archive-multi-member")?;
+    fs::create_dir_all(archive_dir.path())?;
+
+    let mut builder = tar::Builder::new(vec![]);
+    builder.append_dir_all("contiguous", BLOCKS_DIR)?;
+    let tar = builder.into_inner()?;
+
+    // the tar stream split across two concatenated gzip members
+    let (first, second) = tar.split_at(tar.len() / 2);
+    let mut bytes = vec![];
+    for member in [first, second] {
+        let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::fast());
+        encoder.write_all(member)?;
+        bytes.extend(encoder.finish()?);
+    }
+    let path = archive_dir.path().join("multi-member.tar.gz");
+    fs::write(&path, bytes)?;
+
+    let archive = BlockArchive::open(&path)?;
+    assert_eq!(archive.len(), fs::read_dir(BLOCKS_DIR)?.count());
+
+    // sorted by height, then file name
+    let mut expected: Vec<_> = fs::read_dir(BLOCKS_DIR)?
+        .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().to_string()))
+        .collect::<Result<_, _>>()?;
+    expected.sort_by_cached_key(|name| {
+        (
+            name.split('-').nth(1).unwrap().parse::<u32>().unwrap(),
+            name.clone(),
+        )
+    });
+    assert_eq!(file_names(&archive.paths()), expected);
+    for entry in fs::read_dir(BLOCKS_DIR)? {
+        let name = entry?.file_name();
+        let contents = fs::read(Path::new(BLOCKS_DIR).join(&name))?;
+        assert_eq!(archive.size(&path.join(&name)), Some(contents.len() as u64));
+        assert_eq!(archive.read(&path.join(&name))?, contents);
+    }
+    Ok(())
+}
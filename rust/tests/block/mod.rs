@@ -1,2 +1,5 @@
+mod archive;
 mod parser;
+mod source;
 mod store;
+mod verify;
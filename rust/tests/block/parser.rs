@@ -123,3 +123,55 @@ async fn orphaned_blocks() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Parse-ahead yields the same blocks, in the same order, & quarantines the
+/// same block files as serial parsing
+#[tokio::test]
+async fn parallel_parse_preserves_order() -> anyhow::Result<()> {
+    use mina_indexer::constants::*;
+    use std::fs;
+
+    // relabel the height of a block so verification quarantines it
+    let source_dir = PathBuf::from("./tests/data/sequential_blocks");
+    let blocks_dir = tempfile::TempDir::new()?;
+    for entry in fs::read_dir(&source_dir)? {
+        let name = entry?.file_name();
+        let dest = match name.to_string_lossy().as_ref() {
+            "mainnet-105496-3NK7yacg7pjHgV52sUmbNv9p7xxrKUV4sevy4Su5j6CrdTjyzaPL.json" => {
+                "mainnet-105497-3NK7yacg7pjHgV52sUmbNv9p7xxrKUV4sevy4Su5j6CrdTjyzaPL.json".into()
+            }
+            _ => name.clone(),
+        };
+        fs::copy(source_dir.join(&name), blocks_dir.path().join(dest))?;
+    }
+
+    let mut parsed = vec![];
+    for parse_threads in [1, 4] {
+        let mut block_parser = BlockParser::new_with_canonical_chain_discovery(
+            blocks_dir.path(),
+            PcbVersion::V1,
+            MAINNET_CANONICAL_THRESHOLD,
+            MAINNET_TRANSITION_FRONTIER_K,
+        )
+        .await?;
+        block_parser.parse_threads = parse_threads;
+        block_parser.verify = true;
+
+        let mut blocks = vec![];
+        while let Some((block, block_bytes)) = block_parser.next_block().await? {
+            blocks.push((block, block_bytes));
+        }
+
+        assert_eq!(blocks.len() as u32, block_parser.blocks_processed);
+        assert_eq!(block_parser.quarantined.len(), 1);
+        parsed.push((
+            blocks,
+            block_parser.quarantined,
+            block_parser.num_deep_canonical_blocks,
+            block_parser.bytes_processed,
+        ));
+    }
+
+    assert_eq!(parsed[0], parsed[1]);
+    Ok(())
+}
@@ -0,0 +1,222 @@
+use crate::helpers::setup_new_db_dir;
+use mina_indexer::{
+    block::source::{
+        fetch_with_retry, BlockSource, BlockSourceError, HttpObjectStoreSource, LocalDirSource,
+        RetryPolicy, TarArchiveSource,
+    },
+    chain::Network,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+const BLOCKS_DIR: &str = "./tests/data/canonical_chain_discovery/contiguous";
+const BLOCK_2: &str = "mainnet-2-3NLyWnjZqUECniE1q719CoLmes6WDQAod4vrTeLfN7XXJbHv6EHH.json";
+
+/// Object store stand-in serving [BLOCKS_DIR] under `/bucket`, failing the
+/// first `num_failures` requests with a 503
+///
+/// Listings are paginated one key per page, with entity-escaped keys and a
+/// non-block key sorted before each height's blocks
+async fn object_store_stand_in(num_failures: u32) -> anyhow::Result<(String, Arc<AtomicU32>)> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}/bucket", listener.local_addr()?);
+    let num_requests = Arc::new(AtomicU32::new(0));
+
+    let counter = num_requests.clone();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut request = vec![0; 4096];
+            let n = stream.read(&mut request).await.unwrap();
+            let request = String::from_utf8_lossy(&request[..n]).to_string();
+            let path = request.split_whitespace().nth(1).unwrap().to_string();
+
+            let response = if counter.fetch_add(1, Ordering::SeqCst) < num_failures {
+                response("503 Service Unavailable", vec![])
+            } else if let Some(query) = path.strip_prefix("/bucket?") {
+                let param = |name: &str| {
+                    query
+                        .split('&')
+                        .find_map(|pair| pair.strip_prefix(&format!("{name}=")))
+                        .map(str::to_string)
+                };
+                let prefix = param("prefix").unwrap();
+                let marker = param("marker").unwrap_or_default();
+
+                let mut keys: Vec<_> = fs::read_dir(BLOCKS_DIR)
+                    .unwrap()
+                    .flatten()
+                    .map(|e| e.file_name().to_string_lossy().to_string())
+                    .chain([format!("{prefix}0-notes.txt")])
+                    .filter(|key| key.starts_with(&prefix) && *key > marker)
+                    .collect();
+                keys.sort();
+
+                let xml = match keys.first() {
+                    Some(key) => format!(
+                        "<ListBucketResult><IsTruncated>{}</IsTruncated><Contents><Key>{}</Key></Contents></ListBucketResult>",
+                        keys.len() > 1,
+                        key.replace('-', "&#45;")
+                    ),
+                    None => "<ListBucketResult><IsTruncated>false</IsTruncated></ListBucketResult>"
+                        .to_string(),
+                };
+                response("200 OK", xml.into_bytes())
+            } else if let Some(key) = path.strip_prefix("/bucket/") {
+                response("200 OK", fs::read(Path::new(BLOCKS_DIR).join(key)).unwrap())
+            } else {
+                response("404 Not Found", vec![])
+            };
+            stream.write_all(&response).await.unwrap();
+        }
+    });
+    Ok((url, num_requests))
+}
+
+fn response(status: &str, body: Vec<u8>) -> Vec<u8> {
+    let mut response = format!(
+        "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )
+    .into_bytes();
+    response.extend(body);
+    response
+}
+
+fn file_names(paths: &[PathBuf]) -> Vec<String> {
+    paths
+        .iter()
+        .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn http_object_store() -> anyhow::Result<()> {
+    let blocks_dir = setup_new_db_dir("block-source-http")?;
+    fs::create_dir_all(blocks_dir.path())?;
+
+    let (url, num_requests) = object_store_stand_in(2).await?;
+    let source = HttpObjectStoreSource::new(&url)?;
+    let policy = RetryPolicy {
+        max_attempts: 3,
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(50),
+    };
+
+    // retried past the 503s, the block is on the second listing page
+    let paths = fetch_with_retry(&source, &policy, &Network::Mainnet, 2, blocks_dir.path()).await?;
+    assert_eq!(file_names(&paths), vec![BLOCK_2.to_string()]);
+    assert_eq!(
+        fs::read(&paths[0])?,
+        fs::read(Path::new(BLOCKS_DIR).join(BLOCK_2))?
+    );
+    assert_eq!(num_requests.load(Ordering::SeqCst), 5);
+
+    // already present
+    assert!(source
+        .fetch(&Network::Mainnet, 2, blocks_dir.path())
+        .await?
+        .is_empty());
+
+    // nothing at this height
+    assert!(source
+        .fetch(&Network::Mainnet, 1_000_000, blocks_dir.path())
+        .await?
+        .is_empty());
+
+    // permanent failure
+    let missing = HttpObjectStoreSource::new(&url.replace("/bucket", "/missing"))?;
+    match missing.fetch(&Network::Mainnet, 2, blocks_dir.path()).await {
+        Err(e @ BlockSourceError::Status { status: 404, .. }) => assert!(!e.is_transient()),
+        res => panic!("unexpected result {res:?}"),
+    }
+
+    // retries exhausted
+    let (url, _) = object_store_stand_in(u32::MAX).await?;
+    let source = HttpObjectStoreSource::new(&url)?;
+    match fetch_with_retry(&source, &policy, &Network::Mainnet, 3, blocks_dir.path()).await {
+        Err(BlockSourceError::Status { status: 503, .. }) => (),
+        res => panic!("unexpected result {res:?}"),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn local_dir_and_tar_archive() -> anyhow::Result<()> {
+    let blocks_dir = setup_new_db_dir("block-source-dir")?;
+    fs::create_dir_all(blocks_dir.path())?;
+
+    let source = LocalDirSource::new(BLOCKS_DIR)?;
+    let paths = source
+        .fetch(&Network::Mainnet, 2, blocks_dir.path())
+        .await?;
+    assert_eq!(file_names(&paths), vec![BLOCK_2.to_string()]);
+
+    // other networks' blocks are not fetched
+    assert!(source
+        .fetch(&Network::Devnet, 3, blocks_dir.path())
+        .await?
+        .is_empty());
+
+    // already present
+    assert!(source
+        .fetch(&Network::Mainnet, 2, blocks_dir.path())
+        .await?
+        .is_empty());
+
+    // the directory is indexed once, when the source is opened
+    let source_dir = setup_new_db_dir("block-source-indexed")?;
+    fs::create_dir_all(source_dir.path())?;
+    let source = LocalDirSource::new(source_dir.path())?;
+    fs::copy(
+        Path::new(BLOCKS_DIR).join(BLOCK_2),
+        source_dir.path().join(BLOCK_2),
+    )?;
+    let other_dir = setup_new_db_dir("block-source-other")?;
+    fs::create_dir_all(other_dir.path())?;
+    assert!(source
+        .fetch(&Network::Mainnet, 2, other_dir.path())
+        .await?
+        .is_empty());
+    assert_eq!(
+        file_names(
+            &LocalDirSource::new(source_dir.path())?
+                .fetch(&Network::Mainnet, 2, other_dir.path())
+                .await?
+        ),
+        vec![BLOCK_2.to_string()]
+    );
+
+    // tar archive of the same blocks
+    let archive_dir = setup_new_db_dir("block-source-tar")?;
+    fs::create_dir_all(archive_dir.path())?;
+    let archive_path = archive_dir.path().join("blocks.tar");
+    let mut builder = tar::Builder::new(fs::File::create(&archive_path)?);
+    builder.append_dir_all("blocks", BLOCKS_DIR)?;
+    builder.finish()?;
+
+    let source = TarArchiveSource::new(&archive_path)?;
+    let paths = source
+        .fetch(&Network::Mainnet, 3, blocks_dir.path())
+        .await?;
+    assert!(!paths.is_empty());
+    for path in paths {
+        let name = path.file_name().unwrap();
+        assert!(name.to_string_lossy().starts_with("mainnet-3-"));
+        assert_eq!(
+            fs::read(&path)?,
+            fs::read(Path::new(BLOCKS_DIR).join(name))?
+        );
+    }
+    Ok(())
+}
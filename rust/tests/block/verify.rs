@@ -0,0 +1,67 @@
+use crate::helpers::setup_new_db_dir;
+use mina_indexer::{
+    block::{parser::BlockParser, verify::verify_store},
+    constants::*,
+    ledger::genesis::{GenesisLedger, GenesisRoot},
+    server::IndexerVersion,
+    state::IndexerState,
+    store::IndexerStore,
+};
+use std::{fs, path::PathBuf, sync::Arc};
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn quarantine_mislabeled_block_files() -> anyhow::Result<()> {
+    let blocks_dir = TempDir::new()?;
+    let source_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+    for entry in fs::read_dir(&source_dir)?.flatten() {
+        fs::copy(entry.path(), blocks_dir.path().join(entry.file_name()))?;
+    }
+
+    // relabel the height of a block
+    let mislabeled = blocks_dir
+        .path()
+        .join("mainnet-7-3NLyWnjZqUECniE1q719CoLmes6WDQAod4vrTeLfN7XXJbHv6EHH.json");
+    fs::copy(
+        source_dir.join("mainnet-2-3NLyWnjZqUECniE1q719CoLmes6WDQAod4vrTeLfN7XXJbHv6EHH.json"),
+        &mislabeled,
+    )?;
+
+    let mut block_parser = BlockParser::new_testing(blocks_dir.path())?;
+    block_parser.verify = true;
+
+    let mut num_blocks = 0;
+    while block_parser.next_block().await?.is_some() {
+        num_blocks += 1;
+    }
+    assert_eq!(block_parser.quarantined, vec![mislabeled]);
+    assert_eq!(num_blocks + 1, block_parser.total_num_blocks);
+    Ok(())
+}
+
+#[tokio::test]
+async fn verify_store_linkage() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("block-verify")?;
+    let blocks_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+    let indexer_store = Arc::new(IndexerStore::new(store_dir.path())?);
+    let genesis_root =
+        serde_json::from_str::<GenesisRoot>(GenesisLedger::MAINNET_V1_GENESIS_LEDGER_CONTENTS)?;
+    let mut state = IndexerState::new(
+        genesis_root.into(),
+        IndexerVersion::new_testing(),
+        indexer_store.clone(),
+        MAINNET_CANONICAL_THRESHOLD,
+        10,
+    )?;
+
+    let mut block_parser = BlockParser::new_testing(&blocks_dir)?;
+    block_parser.verify = true;
+    state.add_blocks(&mut block_parser).await?;
+    assert!(block_parser.quarantined.is_empty());
+
+    let verification = verify_store(&indexer_store)?;
+    assert!(verification.failures.is_empty(), "{verification:?}");
+    assert_eq!(verification.num_missing_parents, 0);
+    assert!(verification.num_blocks as usize > block_parser.total_num_blocks as usize);
+    Ok(())
+}
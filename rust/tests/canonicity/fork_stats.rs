@@ -0,0 +1,165 @@
+use crate::helpers::setup_new_db_dir;
+use mina_indexer::{
+    block::{parser::BlockParser, precomputed::PrecomputedBlock, store::BlockStore, BlockHash},
+    canonicity::{store::CanonicityStore, Canonicity},
+    constants::MAINNET_GENESIS_HASH,
+    store::{
+        fork_stats::{ForkStatsStore, HeightForks},
+        IndexerStore,
+    },
+};
+use std::path::PathBuf;
+
+/// Tallies the dangling branch fixture blocks
+///
+/// 13 canonical blocks (105489 - 105501) & 20 orphaned blocks in 19 forks,
+/// the only fork of length 2 is rooted at 105494
+#[tokio::test]
+async fn tally_forks() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("canonicity-fork-stats")?;
+    let blocks_dir = PathBuf::from("./tests/data/sequential_blocks");
+    let mut block_parser = BlockParser::new_testing(&blocks_dir)?;
+    let indexer_store = IndexerStore::new(store_dir.path())?;
+
+    // late orphaned block, added after its height is canonical
+    let late_block: BlockHash = "3NKBHgd9qR31HcnBRmyx5LDgXxhbmdVrfSbxtT8VJXBpQtdTsMev".into();
+    let mut late_block_bytes = None;
+
+    while let Some((block, block_bytes)) = block_parser.next_block().await? {
+        let block: PrecomputedBlock = block.into();
+        if block.state_hash() == late_block {
+            late_block_bytes = Some((block, block_bytes));
+            continue;
+        }
+        indexer_store.add_block(&block, block_bytes)?;
+    }
+
+    // canonical chain, from the root to the best tip
+    let best_tip: BlockHash = "3NLJheWWdpapwu4HpYvwyhAFgyBzDWRPLLEZPi6veZineGyvDbwt".into();
+    let mut canonical_chain = vec![];
+    let mut state_hash = Some(best_tip.clone());
+
+    while let Some(hash) = state_hash {
+        if indexer_store.get_block_height(&hash)?.is_none() {
+            break;
+        }
+        state_hash = indexer_store.get_block_parent_hash(&hash)?;
+        canonical_chain.push(hash);
+    }
+    canonical_chain.reverse();
+    assert_eq!(canonical_chain.len(), 13);
+
+    for _ in 0..2 {
+        // re-adding canonical blocks doesn't double count
+        for state_hash in canonical_chain.iter() {
+            indexer_store.add_canonical_block(
+                indexer_store.get_block_height(state_hash)?.unwrap(),
+                indexer_store.get_block_global_slot(state_hash)?.unwrap(),
+                state_hash,
+                &MAINNET_GENESIS_HASH.into(),
+                None,
+            )?;
+        }
+    }
+
+    let (block, block_bytes) = late_block_bytes.unwrap();
+    indexer_store.add_block(&block, block_bytes)?;
+    assert_eq!(
+        indexer_store
+            .get_block_tally(&late_block)?
+            .unwrap()
+            .canonicity,
+        Canonicity::Orphaned
+    );
+
+    // the fork of length 2 is rooted at its lower block
+    let fork_root: BlockHash = "3NKXsaznJ6WdyA4PHfXxn25RzVanzQsNMZrxjidbhoBug8R4LZDy".into();
+    assert_eq!(
+        indexer_store
+            .get_block_tally(&fork_root)?
+            .unwrap()
+            .fork_root,
+        Some(fork_root)
+    );
+    assert_eq!(
+        indexer_store
+            .get_block_tally(&best_tip)?
+            .unwrap()
+            .canonicity,
+        Canonicity::Canonical
+    );
+
+    // all blocks are in the same epoch
+    let fork_stats = indexer_store.get_fork_stats(None, None, None, None)?;
+    assert_eq!(fork_stats.epochs.len(), 1);
+    assert_eq!(fork_stats.epochs[0].canonical, 13);
+    assert_eq!(fork_stats.epochs[0].orphaned, 20);
+    assert_eq!(fork_stats.epochs[0].orphan_rate, 20.0 / 33.0);
+
+    assert_eq!(
+        fork_stats
+            .producers
+            .iter()
+            .map(|producer| (producer.canonical, producer.orphaned))
+            .fold((0, 0), |acc, counts| (acc.0 + counts.0, acc.1 + counts.1)),
+        (13, 20)
+    );
+    assert!(fork_stats
+        .producers
+        .windows(2)
+        .all(|pair| pair[0].orphaned >= pair[1].orphaned));
+
+    assert_eq!(fork_stats.num_forks, 19);
+    assert_eq!(fork_stats.average_fork_length, 20.0 / 19.0);
+
+    // orphaned blocks by height
+    let forks_by_height: Vec<(u32, u32)> = fork_stats
+        .forks_by_height
+        .iter()
+        .map(|height| (height.blockchain_length, height.num_forks))
+        .collect();
+    assert_eq!(
+        forks_by_height,
+        vec![
+            (105489, 2),
+            (105494, 2),
+            (105495, 1),
+            (105496, 6),
+            (105499, 3),
+            (105500, 5),
+            (105501, 1)
+        ]
+    );
+
+    // epoch, block producer & height filters
+    let epoch = fork_stats.epochs[0].epoch;
+    let producer = fork_stats.producers[0].clone();
+    let fork_stats = indexer_store.get_fork_stats(
+        Some(epoch),
+        Some(&producer.public_key),
+        Some(105496),
+        Some(105499),
+    )?;
+    assert_eq!(fork_stats.producers, vec![producer]);
+    assert_eq!(
+        fork_stats.forks_by_height,
+        vec![
+            HeightForks {
+                blockchain_length: 105496,
+                num_forks: 6
+            },
+            HeightForks {
+                blockchain_length: 105499,
+                num_forks: 3
+            }
+        ]
+    );
+
+    let fork_stats = indexer_store.get_fork_stats(Some(epoch + 1), None, None, None)?;
+    assert_eq!(
+        fork_stats.epochs[0].canonical + fork_stats.epochs[0].orphaned,
+        0
+    );
+    assert!(fork_stats.producers.is_empty());
+    Ok(())
+}
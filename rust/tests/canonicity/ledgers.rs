@@ -49,8 +49,9 @@ async fn test() -> anyhow::Result<()> {
             .unwrap();
 
         if ledger != ledger_diff {
-            let mut keys: Vec<&PublicKey> = ledger.accounts.keys().collect();
-            let mut keys_diff: Vec<&PublicKey> = ledger_diff.accounts.keys().collect();
+            let mut keys: Vec<&PublicKey> = ledger.accounts.keys().map(|(pk, _)| pk).collect();
+            let mut keys_diff: Vec<&PublicKey> =
+                ledger_diff.accounts.keys().map(|(pk, _)| pk).collect();
 
             keys.sort();
             keys_diff.sort();
@@ -68,14 +69,12 @@ async fn test() -> anyhow::Result<()> {
                 let pk_diff = keys_diff[n];
                 let ledger_balance = |pk: &PublicKey| {
                     ledger
-                        .accounts
-                        .get(pk)
+                        .get_mina_account(pk)
                         .map(|acct| (acct.balance.0, acct.nonce.0))
                 };
                 let ledger_diff_balance = |pk: &PublicKey| {
                     ledger_diff
-                        .accounts
-                        .get(pk)
+                        .get_mina_account(pk)
                         .map(|acct| (acct.balance.0, acct.nonce.0))
                 };
 
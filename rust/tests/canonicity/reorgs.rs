@@ -0,0 +1,102 @@
+use crate::helpers::setup_new_db_dir;
+use mina_indexer::{
+    block::{parser::BlockParser, precomputed::PrecomputedBlock, store::BlockStore},
+    canonicity::{store::CanonicityStore, Canonicity, Reorg},
+    event::{
+        db::{DbCanonicityEvent, DbEvent},
+        store::EventStore,
+        IndexerEvent,
+    },
+    store::IndexerStore,
+};
+use std::path::PathBuf;
+
+#[tokio::test]
+async fn record_reorgs() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("canonicity-reorgs")?;
+    let blocks_dir = PathBuf::from("./tests/data/sequential_blocks");
+    let mut block_parser = BlockParser::new_testing(&blocks_dir)?;
+    let indexer_store = IndexerStore::new(store_dir.path())?;
+
+    while let Some((block, block_bytes)) = block_parser.next_block().await? {
+        let block: PrecomputedBlock = block.into();
+        indexer_store.add_block(&block, block_bytes)?;
+    }
+
+    // extending the best tip isn't a reorg
+    let common_ancestor = "3NKakum3B2Tigw9TSsxwvXvV3x8L2LvrJ3yXFLEAJDMZu2vkn7db";
+    let fork_tip = "3NL4zEKGtSokPMy29pGv7tm8uJt8GitM9JqrRg6Lkf3tRdnwrjpF";
+    indexer_store.set_best_block(&common_ancestor.into())?;
+    indexer_store.set_best_block(&fork_tip.into())?;
+    assert_eq!(indexer_store.get_reorgs(None, None, 0)?, vec![]);
+
+    // switching branches orphans the fork blocks above the common ancestor
+    let best_tip = "3NLJheWWdpapwu4HpYvwyhAFgyBzDWRPLLEZPi6veZineGyvDbwt";
+    indexer_store.set_best_block(&best_tip.into())?;
+
+    let reorg = Reorg {
+        old_best_tip: fork_tip.into(),
+        old_blockchain_length: 105495,
+        new_best_tip: best_tip.into(),
+        new_blockchain_length: 105501,
+        common_ancestor: common_ancestor.into(),
+        common_ancestor_length: 105493,
+        depth: 2,
+    };
+    assert_eq!(
+        indexer_store.get_reorgs(None, None, 1)?,
+        vec![reorg.clone()]
+    );
+
+    // reorg event is recorded
+    assert!(indexer_store
+        .get_event_log()?
+        .contains(&IndexerEvent::Db(DbEvent::Canonicity(
+            DbCanonicityEvent::Reorg(reorg.clone())
+        ))));
+
+    // length & depth filters
+    assert_eq!(
+        indexer_store.get_reorgs(Some(105501), Some(105501), 2)?,
+        vec![reorg.clone()]
+    );
+    assert_eq!(indexer_store.get_reorgs(None, Some(105500), 1)?, vec![]);
+    assert_eq!(indexer_store.get_reorgs(Some(105502), None, 1)?, vec![]);
+    assert_eq!(indexer_store.get_reorgs(None, None, 3)?, vec![]);
+
+    // switching back to the shorter fork walks the longer old branch down to
+    // the fork's height first
+    indexer_store.set_best_block(&fork_tip.into())?;
+
+    let shorter_reorg = Reorg {
+        old_best_tip: best_tip.into(),
+        old_blockchain_length: 105501,
+        new_best_tip: fork_tip.into(),
+        new_blockchain_length: 105495,
+        common_ancestor: common_ancestor.into(),
+        common_ancestor_length: 105493,
+        depth: 8,
+    };
+    assert_eq!(
+        indexer_store.get_reorgs(None, None, 3)?,
+        vec![shorter_reorg.clone()]
+    );
+    assert_eq!(
+        indexer_store.get_reorgs(None, None, 1)?,
+        vec![shorter_reorg, reorg]
+    );
+
+    // the fork is canonical, the old branch above it is unapplied
+    assert_eq!(
+        indexer_store.get_block_canonicity(&fork_tip.into())?,
+        Some(Canonicity::Canonical)
+    );
+    assert_eq!(
+        indexer_store.get_canonical_hash_at_height(105494)?,
+        indexer_store.get_block_parent_hash(&fork_tip.into())?
+    );
+    for height in 105496..=105501 {
+        assert_eq!(indexer_store.get_canonical_hash_at_height(height)?, None);
+    }
+    Ok(())
+}
@@ -1 +1,2 @@
 mod store;
+mod zkapp_store;
@@ -86,7 +86,7 @@ async fn add_and_get() -> anyhow::Result<()> {
     for cmd in SignedCommand::from_precomputed(&block) {
         let cmd = cmd.signed_command;
         let result_cmd: Option<SignedCommand> = indexer_store
-            .get_user_command(&cmd.hash_signed_command()?, 0)?
+            .get_user_command(&cmd.txn_hash(&block.version())?, 0)?
             .map(|c| c.into());
         assert_eq!(result_cmd, Some(cmd));
     }
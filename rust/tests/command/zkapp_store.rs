@@ -0,0 +1,65 @@
+use crate::helpers::setup_new_db_dir;
+use mina_indexer::{
+    block::{
+        precomputed::{PcbVersion, PrecomputedBlock},
+        store::BlockStore,
+    },
+    command::zkapp::{store::ZkappCommandStore, ZkappCommandWithData},
+    ledger::public_key::PublicKey,
+    store::IndexerStore,
+};
+use std::path::PathBuf;
+
+#[test]
+fn add_and_get() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("zkapp-command-store")?;
+    let indexer_store = IndexerStore::new(store_dir.path())?;
+    let path: PathBuf = "./tests/data/berkeley/non_sequential_blocks/berkeley-4969-3NL8QoLQMtsBH8vUnccQw3vt8PgYuZRMApq1yZT1jwhZjbBLMRJU.json".into();
+    let block = PrecomputedBlock::parse_file(&path, PcbVersion::V2)?;
+    let state_hash = block.state_hash();
+    indexer_store.add_block(&block, path.metadata()?.len())?;
+
+    let zkapp_commands = ZkappCommandWithData::from_precomputed(&block);
+    assert_eq!(zkapp_commands.len(), 1);
+
+    let cmd = zkapp_commands[0].clone();
+    let zkapp: PublicKey = "B62qqpAFkz374qJpuFKYZPjT1KxSmnLoY4zEc878FaW4DSxgYNXZiny".into();
+
+    // by block
+    assert_eq!(
+        indexer_store.get_block_zkapp_commands(&state_hash)?,
+        Some(zkapp_commands.clone())
+    );
+
+    // by state hash & index
+    assert_eq!(
+        indexer_store.get_zkapp_command(&state_hash, 0)?,
+        Some(cmd.clone())
+    );
+    assert_eq!(indexer_store.get_zkapp_command(&state_hash, 1)?, None);
+
+    // by fee payer
+    assert_eq!(
+        indexer_store.get_zkapp_commands_fee_payer(&cmd.fee_payer, 10)?,
+        vec![cmd.clone()]
+    );
+    assert!(indexer_store
+        .get_zkapp_commands_fee_payer(&zkapp, 10)?
+        .is_empty());
+
+    // by touched account
+    assert_eq!(
+        indexer_store.get_zkapp_commands_public_key(&zkapp, 10)?,
+        vec![cmd.clone()]
+    );
+    assert_eq!(
+        indexer_store.get_zkapp_commands_public_key(&cmd.fee_payer, 10)?,
+        vec![cmd.clone()]
+    );
+
+    // events & actions by zkApp address
+    assert_eq!(indexer_store.get_zkapp_events(&zkapp, 10)?, cmd.events());
+    assert_eq!(indexer_store.get_zkapp_events(&zkapp, 0)?, vec![]);
+    assert!(indexer_store.get_zkapp_actions(&zkapp, 10)?.is_empty());
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use crate::helpers::setup_new_db_dir;
 use mina_indexer::{
-    block::{vrf_output::VrfOutput, Block, BlockHash},
+    block::{chain_selection::ConsensusInfo, vrf_output::VrfOutput, Block, BlockHash},
     event::{db::*, store::*, witness_tree::*, *},
     ledger::LedgerHash,
     store::IndexerStore,
@@ -31,6 +31,7 @@ fn add_and_get_events() -> anyhow::Result<()> {
         blockchain_length: 1,
         global_slot_since_genesis: 0,
         hash_last_vrf_output: VrfOutput::new("last_vrf_output".as_bytes().to_vec()),
+        consensus_info: ConsensusInfo::default(),
     };
     let event3 = IndexerEvent::WitnessTree(WitnessTreeEvent::UpdateBestTip {
         best_tip: block.clone(),
@@ -0,0 +1,67 @@
+use crate::helpers::setup_new_db_dir;
+use mina_indexer::{
+    block::parser::BlockParser,
+    canonicity::store::CanonicityStore,
+    constants::*,
+    ledger::{
+        genesis::{GenesisLedger, GenesisRoot},
+        store::LedgerStore,
+    },
+    server::IndexerVersion,
+    state::IndexerState,
+    store::IndexerStore,
+};
+use std::{path::PathBuf, sync::Arc};
+
+#[tokio::test]
+async fn history_matches_canonical_ledgers() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("account-history")?;
+    let blocks_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+    let indexer_store = Arc::new(IndexerStore::new(store_dir.path())?);
+    let genesis_root =
+        serde_json::from_str::<GenesisRoot>(GenesisLedger::MAINNET_V1_GENESIS_LEDGER_CONTENTS)?;
+    let mut state = IndexerState::new(
+        genesis_root.into(),
+        IndexerVersion::new_testing(),
+        indexer_store.clone(),
+        MAINNET_CANONICAL_THRESHOLD,
+        10,
+    )?;
+    state
+        .add_blocks(&mut BlockParser::new_testing(&blocks_dir)?)
+        .await?;
+
+    // the latest history entry of every account is its state in the
+    // canonical ledger at each height, up to the canonical root
+    let canonical_root_height = state.canonical_root_block().blockchain_length;
+    let mut max_num_entries = 0;
+    for height in 1..=canonical_root_height {
+        let state_hash = indexer_store.get_canonical_hash_at_height(height)?.unwrap();
+        let ledger = indexer_store
+            .get_ledger_state_hash(&state_hash, false)?
+            .unwrap();
+        for ((pk, token), account) in ledger.accounts.iter() {
+            let history = indexer_store.get_account_history(pk, token, 0, height)?;
+            assert!(history
+                .windows(2)
+                .all(|w| w[0].blockchain_length < w[1].blockchain_length));
+
+            let latest = history.last().unwrap();
+            assert_eq!(latest.balance, account.balance, "{pk} at {height}");
+            assert_eq!(latest.nonce, account.nonce, "{pk} at {height}");
+            assert_eq!(latest.delegate, account.delegate, "{pk} at {height}");
+            max_num_entries = max_num_entries.max(history.len());
+        }
+    }
+    assert!(canonical_root_height > 2);
+    assert!(max_num_entries > 1);
+
+    // bounded by height
+    let ledger = indexer_store.get_ledger_at_height(1, false)?.unwrap();
+    let ((pk, token), _) = ledger.accounts.iter().next().unwrap();
+    let history = indexer_store.get_account_history(pk, token, 2, 5)?;
+    assert!(history
+        .iter()
+        .all(|entry| entry.blockchain_length >= 2 && entry.blockchain_length <= 5));
+    Ok(())
+}
@@ -6,10 +6,11 @@ use mina_indexer::{
     ledger::{
         genesis::{GenesisLedger, GenesisRoot},
         store::LedgerStore,
+        token::TokenId,
     },
     server::IndexerVersion,
     state::IndexerState,
-    store::{account::AccountStore, balance_key_prefix, pk_key_prefix, IndexerStore},
+    store::{account::AccountStore, token_balance_sort_key_balance_pk, IndexerStore},
 };
 use std::{path::PathBuf, sync::Arc};
 
@@ -42,17 +43,16 @@ async fn check_balance() -> anyhow::Result<()> {
     // check sorted store balances equal best ledger balances
     let mut curr_ledger_balance = None;
     let best_ledger = indexer_store.get_best_ledger()?.unwrap();
+    let mina = TokenId::default();
     for (n, (key, _)) in indexer_store
         .account_balance_iterator(speedb::IteratorMode::End)
         .flatten()
         .enumerate()
     {
-        let pk = pk_key_prefix(&key[8..]);
-        let pk_key_balance = balance_key_prefix(&key);
-        let pk_store_balance = indexer_store.get_account_balance(&pk)?.unwrap();
+        let (pk_key_balance, pk) = token_balance_sort_key_balance_pk(&key, &mina).unwrap();
+        let pk_store_balance = indexer_store.get_account_balance(&pk, &mina)?.unwrap();
         let pk_ledger_balance = best_ledger
-            .accounts
-            .get(&pk)
+            .get_mina_account(&pk)
             .with_context(|| format!("pk: {pk}"))
             .unwrap()
             .balance
@@ -76,10 +76,10 @@ async fn check_balance() -> anyhow::Result<()> {
     }
 
     // check best ledger balances equal sorted store balances
-    for (pk, acct) in best_ledger.accounts {
+    for ((pk, token), acct) in best_ledger.accounts {
         assert_eq!(
             acct.balance.0,
-            indexer_store.get_account_balance(&pk)?.unwrap()
+            indexer_store.get_account_balance(&pk, &token)?.unwrap()
         );
     }
 
@@ -0,0 +1,77 @@
+use crate::helpers::setup_new_db_dir;
+use mina_indexer::{
+    block::{parser::BlockParser, store::BlockStore},
+    canonicity::store::CanonicityStore,
+    constants::*,
+    ledger::{
+        genesis::{GenesisLedger, GenesisRoot},
+        store::LedgerStore,
+        token::TokenId,
+    },
+    server::IndexerVersion,
+    state::IndexerState,
+    store::IndexerStore,
+};
+use std::{path::PathBuf, sync::Arc};
+
+#[tokio::test]
+async fn account_matches_ledger() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("historical-accounts")?;
+    let blocks_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+    let indexer_store = Arc::new(IndexerStore::new(store_dir.path())?);
+    let genesis_root =
+        serde_json::from_str::<GenesisRoot>(GenesisLedger::MAINNET_V1_GENESIS_LEDGER_CONTENTS)?;
+    let mut state = IndexerState::new(
+        genesis_root.into(),
+        IndexerVersion::new_testing(),
+        indexer_store.clone(),
+        MAINNET_CANONICAL_THRESHOLD,
+        10,
+    )?;
+    state
+        .add_blocks(&mut BlockParser::new_testing(&blocks_dir)?)
+        .await?;
+
+    // every account of canonical & best block ledgers
+    let best_block_hash = indexer_store.get_best_block_hash()?.unwrap();
+    let mut state_hashes = vec![best_block_hash];
+    for height in [1, 2, 7, 11] {
+        state_hashes.push(indexer_store.get_canonical_hash_at_height(height)?.unwrap());
+    }
+    for state_hash in state_hashes {
+        let ledger = indexer_store
+            .get_ledger_state_hash(&state_hash, false)?
+            .unwrap();
+        for ((pk, token), account) in ledger.accounts.iter() {
+            assert_eq!(
+                indexer_store
+                    .get_account_state_hash(pk, token, &state_hash)?
+                    .as_ref(),
+                Some(account),
+                "{pk} at {state_hash}",
+            );
+        }
+
+        // missing token account
+        let (pk, _) = ledger.accounts.keys().next().unwrap();
+        let token = TokenId::new("wSMg68vWG5sUbsHsnctN8sDBSshoLDseY6E8HPSWzuqdhfpDFL");
+        assert!(ledger.get_account(pk, &token).is_none());
+        assert_eq!(
+            indexer_store.get_account_state_hash(pk, &token, &state_hash)?,
+            None
+        );
+    }
+
+    // canonical height lookup
+    let height = 7;
+    let ledger = indexer_store.get_ledger_at_height(height, false)?.unwrap();
+    for ((pk, token), account) in ledger.accounts.iter().take(100) {
+        assert_eq!(
+            indexer_store
+                .get_account_at_height(pk, token, height)?
+                .as_ref(),
+            Some(account)
+        );
+    }
+    Ok(())
+}
@@ -1 +1,4 @@
+mod account_history;
 mod balance_sorted_accounts;
+mod historical_accounts;
+mod token_balances;
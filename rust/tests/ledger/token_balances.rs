@@ -0,0 +1,78 @@
+use crate::helpers::setup_new_db_dir;
+use mina_indexer::{
+    ledger::{
+        diff::account::{PaymentDiff, UpdateType},
+        public_key::PublicKey,
+        token::TokenId,
+    },
+    store::{
+        account::{AccountBalanceUpdate, AccountStore, DBAccountBalanceUpdate},
+        token_balance_sort_key_balance_pk, IndexerStore,
+    },
+};
+use speedb::{Direction, IteratorMode};
+
+#[test]
+fn token_balances() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("token-balances")?;
+    let indexer_store = IndexerStore::new(store_dir.path())?;
+
+    let mina = TokenId::default();
+    let token = TokenId::new("wSMg68vWG5sUbsHsnctN8sDBSshoLDseY6E8HPSWzuqdhfpDFL");
+    let pk0 = PublicKey::new("B62qre3erTHfzQckNuibViWQGyyKwZseztqrjPZBv6SQF384Rg6ESAy");
+    let pk1 = PublicKey::new("B62qmMypEDCchUgPD6RU99gVKXJcY46urKdjbFmG5cYtaVpfKysXTz6");
+
+    // same public keys, different tokens
+    let payment = |pk: &PublicKey, token: &TokenId, amount: u64| {
+        AccountBalanceUpdate::Payment(PaymentDiff {
+            update_type: UpdateType::Credit,
+            public_key: pk.clone(),
+            amount: amount.into(),
+            token: token.clone(),
+        })
+    };
+    let updates = DBAccountBalanceUpdate::new(
+        vec![
+            AccountBalanceUpdate::CreateAccount(pk0.clone(), mina.clone()),
+            AccountBalanceUpdate::CreateAccount(pk0.clone(), token.clone()),
+            payment(&pk0, &mina, 100),
+            payment(&pk1, &mina, 200),
+            payment(&pk0, &token, 5),
+            payment(&pk1, &token, 3),
+        ],
+        vec![],
+    );
+    indexer_store.update_account_balances(&Default::default(), &updates)?;
+
+    assert_eq!(indexer_store.get_account_balance(&pk0, &mina)?, Some(100));
+    assert_eq!(indexer_store.get_account_balance(&pk1, &mina)?, Some(200));
+    assert_eq!(indexer_store.get_account_balance(&pk0, &token)?, Some(5));
+    assert_eq!(indexer_store.get_account_balance(&pk1, &token)?, Some(3));
+    assert_eq!(indexer_store.get_num_accounts()?, Some(2));
+
+    // balance-sorted per token
+    let token_balances = |token: &TokenId| -> Vec<(u64, PublicKey)> {
+        let mut start = token.clone().to_bytes();
+        start.append(&mut u64::MAX.to_be_bytes().to_vec());
+        indexer_store
+            .account_balance_iterator(IteratorMode::From(&start, Direction::Reverse))
+            .flatten()
+            .map_while(|(key, _)| token_balance_sort_key_balance_pk(&key, token))
+            .collect()
+    };
+    assert_eq!(
+        token_balances(&mina),
+        vec![(200, pk1.clone()), (100, pk0.clone())]
+    );
+    assert_eq!(
+        token_balances(&token),
+        vec![(5, pk0.clone()), (3, pk1.clone())]
+    );
+
+    // removing the token account leaves the MINA account
+    indexer_store.update_account_balance(&pk0, &token, None)?;
+    assert_eq!(indexer_store.get_account_balance(&pk0, &token)?, None);
+    assert_eq!(indexer_store.get_account_balance(&pk0, &mina)?, Some(100));
+    assert_eq!(token_balances(&token), vec![(3, pk1)]);
+    Ok(())
+}
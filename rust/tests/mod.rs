@@ -8,6 +8,7 @@ mod protocol;
 mod snark_work;
 mod state;
 mod usernames;
+mod web;
 
 pub mod helpers {
     /// Sets up a new temp dir, deleted when it goes out of scope
@@ -0,0 +1,148 @@
+use crate::helpers::setup_new_db_dir;
+use mina_indexer::{
+    block::{
+        chain_selection::{select_best, ChainSelection},
+        extract_block_height,
+        parser::BlockParser,
+        precomputed::PrecomputedBlock,
+        Block,
+    },
+    state::IndexerState,
+};
+use serde_json::Value;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const FORK_TIP: &str = "3NL4zEKGtSokPMy29pGv7tm8uJt8GitM9JqrRg6Lkf3tRdnwrjpF";
+
+/// Selects between the dangling branch fixture forks
+///
+/// ```text
+///   105493 (common ancestor)
+///    /            \
+/// 105494        105494
+///    |             |
+/// 105495 (fork)   ...
+///                  |
+///               105501 (best tip)
+/// ```
+#[tokio::test]
+async fn forks() -> anyhow::Result<()> {
+    let blocks_dir = PathBuf::from("./tests/data/sequential_blocks");
+    let state = witness_tree(&blocks_dir).await?;
+
+    let mut block_parser = BlockParser::new_testing(&blocks_dir)?;
+    let (fork_tip, _) = block_parser.get_precomputed_block(FORK_TIP).await?;
+    let (best_tip, _) = block_parser
+        .get_precomputed_block("3NLJheWWdpapwu4HpYvwyhAFgyBzDWRPLLEZPi6veZineGyvDbwt")
+        .await?;
+    let fork_tip = Block::from(fork_tip);
+    let mut best_tip = Block::from(best_tip);
+
+    // short-range fork, the longer chain is selected
+    assert!(fork_tip
+        .consensus_info
+        .is_short_range(&best_tip.consensus_info));
+    assert!(best_tip.is_better_chain(&fork_tip));
+    assert!(!fork_tip.is_better_chain(&best_tip));
+
+    // the state's best tip is selected among the leaves
+    let leaves = state.root_branch.leaves();
+    assert_eq!(state.best_tip_block().blockchain_length, 105501);
+    assert_eq!(select_best(leaves.iter()), Some(state.best_tip_block()));
+    assert!(leaves
+        .iter()
+        .all(|leaf| !leaf.is_better_chain(state.best_tip_block())));
+
+    // long-range fork, the longer chain has an unrelated lock checkpoint and
+    // the shorter chain has the denser window
+    best_tip.consensus_info.epoch_count += 2;
+    best_tip.consensus_info.staking_lock_checkpoint = fork_tip.state_hash.clone();
+    assert!(!fork_tip
+        .consensus_info
+        .is_short_range(&best_tip.consensus_info));
+
+    best_tip.consensus_info.min_window_density = fork_tip.consensus_info.min_window_density - 1;
+    assert!(fork_tip.is_better_chain(&best_tip));
+    assert!(!best_tip.is_better_chain(&fork_tip));
+
+    // equally dense long-range forks fall back to the longer chain
+    best_tip.consensus_info.curr_global_slot = fork_tip.consensus_info.curr_global_slot;
+    best_tip.consensus_info.sub_window_densities =
+        fork_tip.consensus_info.sub_window_densities.clone();
+    best_tip.consensus_info.min_window_density = fork_tip.consensus_info.min_window_density;
+    assert!(best_tip.is_better_chain(&fork_tip));
+    Ok(())
+}
+
+/// The blocks of the [forks] fixture above the common ancestor, except the
+/// fork's, are moved two epochs ahead onto an unrelated lock checkpoint with a
+/// sparse window, so the shorter, denser fork wins the long-range fork rule
+#[tokio::test]
+async fn denser_shorter_fork() -> anyhow::Result<()> {
+    let src_dir = PathBuf::from("./tests/data/sequential_blocks");
+    let blocks_dir = setup_new_db_dir("chain-selection-blocks")?;
+    fs::create_dir_all(blocks_dir.path())?;
+
+    let fork_tip_path = fs::read_dir(&src_dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .find(|path| path.to_string_lossy().contains(FORK_TIP))
+        .unwrap();
+    let fork_tip: Value = serde_json::from_slice(&fs::read(fork_tip_path)?)?;
+    let fork_parent = fork_tip["protocol_state"]["previous_state_hash"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    for entry in fs::read_dir(&src_dir)? {
+        let path = entry?.path();
+        let dest = blocks_dir.path().join(path.file_name().unwrap());
+        let name = path.file_name().unwrap().to_string_lossy();
+        if extract_block_height(&path).unwrap() <= 105493
+            || name.contains(FORK_TIP)
+            || name.contains(&fork_parent)
+        {
+            fs::copy(&path, &dest)?;
+            continue;
+        }
+
+        let mut block: Value = serde_json::from_slice(&fs::read(&path)?)?;
+        let consensus_state = &mut block["protocol_state"]["body"]["consensus_state"];
+        consensus_state["epoch_count"] = "23".into();
+        consensus_state["min_window_density"] = "1".into();
+        consensus_state["staking_epoch_data"]["lock_checkpoint"] = FORK_TIP.into();
+        fs::write(&dest, serde_json::to_vec(&block)?)?;
+    }
+
+    let state = witness_tree(blocks_dir.path()).await?;
+    let top_leaves = state.root_branch.top_leaves();
+    assert!(top_leaves
+        .iter()
+        .all(|leaf| leaf.blockchain_length == 105501));
+
+    // the shorter fork is selected over the tallest tips
+    let best_tip = state.best_tip_block();
+    assert_eq!(best_tip.state_hash.0, FORK_TIP);
+    assert_eq!(best_tip.blockchain_length, 105495);
+    assert_eq!(state.root_branch.best_tip().as_ref(), Some(best_tip));
+    assert!(top_leaves.iter().all(|leaf| best_tip.is_better_chain(leaf)));
+    Ok(())
+}
+
+/// Ingests the blocks of `blocks_dir` into a witness tree
+async fn witness_tree(blocks_dir: &Path) -> anyhow::Result<IndexerState> {
+    let mut block_parser = BlockParser::new_testing(blocks_dir)?;
+    let (block, block_bytes) = block_parser.next_block().await?.unwrap();
+    let block: PrecomputedBlock = block.into();
+    let mut state = IndexerState::new_testing(&block, block_bytes, None, None, None, None, None)?;
+
+    while let Some((block, _)) = block_parser.next_block().await? {
+        let block: PrecomputedBlock = block.into();
+        state.add_block_to_witness_tree(&block, true)?;
+    }
+    Ok(state)
+}
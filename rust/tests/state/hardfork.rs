@@ -0,0 +1,159 @@
+use crate::helpers::setup_new_db_dir;
+use mina_indexer::{
+    block::{
+        parser::BlockParser,
+        precomputed::{PcbVersion, PrecomputedBlock},
+        store::BlockStore,
+        BlockHash,
+    },
+    canonicity::store::CanonicityStore,
+    constants::*,
+    ledger::{
+        account::Nonce,
+        genesis::{parse_hardfork_file, GenesisLedger, GenesisRoot},
+        public_key::PublicKey,
+        store::LedgerStore,
+        token::TokenId,
+    },
+    server::IndexerVersion,
+    state::IndexerState,
+    store::{account::AccountStore, IndexerStore},
+};
+use std::{path::PathBuf, sync::Arc};
+
+/// Stitches a V2 block onto `parent` as its child
+fn post_fork_block(
+    path: &str,
+    parent: &BlockHash,
+    genesis_state_hash: Option<&BlockHash>,
+    blockchain_length: u32,
+    global_slot: u32,
+) -> anyhow::Result<PrecomputedBlock> {
+    let mut block = PrecomputedBlock::parse_file(&PathBuf::from(path), PcbVersion::V2)?;
+    if let PrecomputedBlock::V2(v2) = &mut block {
+        let body = &mut v2.protocol_state.body;
+        v2.blockchain_length = blockchain_length;
+        v2.protocol_state.previous_state_hash = parent.clone();
+        body.genesis_state_hash = genesis_state_hash.unwrap_or(&v2.state_hash).clone();
+        body.consensus_state.blockchain_length = blockchain_length;
+        body.consensus_state.global_slot_since_genesis = global_slot;
+    }
+    Ok(block)
+}
+
+#[tokio::test]
+async fn stitch_fork_genesis() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("hardfork")?;
+    let blocks_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+    let indexer_store = Arc::new(IndexerStore::new(store_dir.path())?);
+    let genesis_root =
+        serde_json::from_str::<GenesisRoot>(GenesisLedger::MAINNET_V1_GENESIS_LEDGER_CONTENTS)?;
+    let mut state = IndexerState::new(
+        genesis_root.into(),
+        IndexerVersion::new_testing(),
+        indexer_store.clone(),
+        MAINNET_CANONICAL_THRESHOLD,
+        10,
+    )?;
+
+    // pre-fork blocks up to the fork block
+    let hardfork = parse_hardfork_file("./tests/data/hardfork/config.json")?;
+    let fork_block = hardfork.fork.state_hash.clone();
+    state.hardfork = Some(hardfork);
+    state
+        .add_blocks(&mut BlockParser::new_testing(&blocks_dir)?)
+        .await?;
+    assert_eq!(state.best_tip.state_hash, fork_block);
+
+    // hardfork genesis block
+    let fork_genesis = post_fork_block(
+        "./tests/data/berkeley/sequential_blocks/berkeley-2-3NLBi19dn8P4Fm5UZgd2gdmi1WbuxyM1uuk2ci1zEwP4iEijHEwJ.json",
+        &fork_block,
+        None,
+        22,
+        30,
+    )?;
+    let fork_genesis_hash = fork_genesis.state_hash();
+    assert!(state.block_pipeline(&fork_genesis, 0)?);
+
+    // post-fork blocks are V2 & rooted at the hardfork genesis
+    assert!(state.hardfork.is_none());
+    assert_eq!(state.version.version, PcbVersion::V2);
+    assert_eq!(state.version.genesis_state_hash, fork_genesis_hash);
+    assert_eq!(state.root_branch.root_block().state_hash, fork_genesis_hash);
+    assert_eq!(state.canonical_root.state_hash, fork_genesis_hash);
+
+    // the pre-fork chain is canonical up to the fork block
+    assert_eq!(
+        indexer_store.get_canonical_hash_at_height(21)?,
+        Some(fork_block.clone())
+    );
+    assert_eq!(
+        indexer_store.get_canonical_hash_at_height(22)?,
+        Some(fork_genesis_hash.clone())
+    );
+    for height in 1..=21 {
+        assert!(indexer_store
+            .get_canonical_hash_at_height(height)?
+            .is_some());
+    }
+    assert!(indexer_store
+        .get_known_genesis_state_hashes()?
+        .contains(&fork_genesis_hash));
+    assert!(indexer_store
+        .get_known_genesis_prev_state_hashes()?
+        .contains(&fork_block));
+
+    // pre-fork ledger at the fork block, hardfork ledger at its genesis
+    let pk = PublicKey::from("B62qmqMrgPshhHKLJ7DqWn1KeizEgga5MuGmWb2bXajUnyivfeMW6JE");
+    let pre_fork_ledger = indexer_store
+        .get_ledger_state_hash(&fork_block, false)?
+        .unwrap();
+    let post_fork_ledger = indexer_store
+        .get_ledger_state_hash(&fork_genesis_hash, false)?
+        .unwrap();
+    assert_eq!(
+        pre_fork_ledger.get_mina_account(&pk).unwrap().balance.0,
+        372093 * 1_000_000_000
+    );
+    let account = post_fork_ledger.get_mina_account(&pk).unwrap();
+    assert_eq!(account.balance.0, 372000 * 1_000_000_000);
+    assert_eq!(account.nonce, Nonce(3));
+    assert_eq!(post_fork_ledger, state.ledger);
+
+    // account balances continue from the hardfork ledger
+    let mina = TokenId::default();
+    assert_eq!(
+        indexer_store.get_account_balance(&pk, &mina)?,
+        Some(372000 * 1_000_000_000)
+    );
+
+    // the next post-fork block extends the hardfork genesis
+    let block = post_fork_block(
+        "./tests/data/berkeley/sequential_blocks/berkeley-3-3NLNtuR4UFZwZzVzZrPb7hVJmoMYKNutWLW2i2VRTEDCocNic1PB.json",
+        &fork_genesis_hash,
+        Some(&fork_genesis_hash),
+        23,
+        31,
+    )?;
+    assert!(state.block_pipeline(&block, 0)?);
+    assert_eq!(state.best_tip.state_hash, block.state_hash());
+    assert_eq!(
+        indexer_store.get_best_block_hash()?,
+        Some(block.state_hash())
+    );
+
+    let receiver = block.coinbase_receiver();
+    let best_ledger = indexer_store
+        .get_ledger_state_hash(&block.state_hash(), false)?
+        .unwrap();
+    assert_eq!(
+        indexer_store.get_account_balance(&receiver, &mina)?,
+        Some(best_ledger.get_mina_account(&receiver).unwrap().balance.0)
+    );
+    assert_eq!(
+        best_ledger.get_mina_account(&pk).unwrap().balance.0,
+        372000 * 1_000_000_000
+    );
+    Ok(())
+}
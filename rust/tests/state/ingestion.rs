@@ -0,0 +1,261 @@
+use crate::helpers::setup_new_db_dir;
+use mina_indexer::{
+    block::{parser::BlockParser, precomputed::PcbVersion, store::BlockStore},
+    constants::*,
+    ledger::{
+        genesis::{GenesisLedger, GenesisRoot},
+        store::LedgerStore,
+        Ledger,
+    },
+    server::IndexerVersion,
+    state::{IndexerState, IndexerStateConfig},
+    store::{checkpoint::IngestionCheckpointStore, IndexerStore},
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tempfile::TempDir;
+use tokio::time::Instant;
+
+/// Ingestion benchmark, serial vs parallel parsing
+#[tokio::test]
+#[ignore = "benchmark, run with --ignored"]
+async fn ingestion_benches() -> anyhow::Result<()> {
+    let blocks_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+    let mut results = vec![];
+
+    for parse_threads in [1, 4] {
+        let store_dir = setup_new_db_dir(&format!("ingestion-{parse_threads}"))?;
+        let indexer_store = Arc::new(IndexerStore::new(store_dir.path())?);
+        let genesis_root =
+            serde_json::from_str::<GenesisRoot>(GenesisLedger::MAINNET_V1_GENESIS_LEDGER_CONTENTS)?;
+        let mut state = IndexerState::new(
+            genesis_root.into(),
+            IndexerVersion::new_testing(),
+            indexer_store.clone(),
+            MAINNET_CANONICAL_THRESHOLD,
+            10,
+        )?;
+
+        let start = Instant::now();
+        let mut block_parser = BlockParser::new_with_canonical_chain_discovery(
+            &blocks_dir,
+            PcbVersion::V1,
+            MAINNET_CANONICAL_THRESHOLD,
+            BLOCK_REPORTING_FREQ_NUM,
+        )
+        .await?;
+        block_parser.parse_threads = parse_threads;
+        state
+            .initialize_with_canonical_chain_discovery(&mut block_parser)
+            .await?;
+
+        println!(
+            "Ingest {} blocks with {parse_threads} parse threads: {:?}",
+            block_parser.blocks_processed,
+            start.elapsed()
+        );
+        results.push((
+            state.blocks_processed,
+            state.best_tip_block().state_hash.clone(),
+            state.canonical_root_block().state_hash.clone(),
+            indexer_store.get_best_block_hash()?,
+            state.ledger.clone(),
+        ));
+    }
+
+    assert_eq!(results[0], results[1]);
+    Ok(())
+}
+
+fn state_config(indexer_store: &Arc<IndexerStore>) -> anyhow::Result<IndexerStateConfig> {
+    let genesis_root =
+        serde_json::from_str::<GenesisRoot>(GenesisLedger::MAINNET_V1_GENESIS_LEDGER_CONTENTS)?;
+    let mut config = IndexerStateConfig::new(
+        genesis_root.into(),
+        IndexerVersion::new_testing(),
+        indexer_store.clone(),
+        MAINNET_CANONICAL_THRESHOLD,
+        MAINNET_TRANSITION_FRONTIER_K,
+    );
+    config.ledger_cadence = 5;
+    Ok(config)
+}
+
+/// Runs `database create`'s ingestion
+async fn ingest(indexer_store: &Arc<IndexerStore>, blocks_dir: &Path) -> anyhow::Result<()> {
+    let mut state = IndexerState::new_from_config(state_config(indexer_store)?)?;
+    let mut block_parser = BlockParser::new_with_canonical_chain_discovery(
+        blocks_dir,
+        PcbVersion::V1,
+        MAINNET_CANONICAL_THRESHOLD,
+        BLOCK_REPORTING_FREQ_NUM,
+    )
+    .await?;
+    state
+        .initialize_with_canonical_chain_discovery(&mut block_parser)
+        .await
+}
+
+/// Resumes ingestion like `database create` in a non-empty database dir
+async fn resume(indexer_store: &Arc<IndexerStore>, blocks_dir: &Path) -> anyhow::Result<()> {
+    let checkpoint = indexer_store.get_ingestion_checkpoint()?.unwrap();
+    let mut state = IndexerState::new_without_genesis_events(state_config(indexer_store)?)?;
+    let mut block_parser = BlockParser::new_with_canonical_chain_discovery(
+        blocks_dir,
+        PcbVersion::V1,
+        MAINNET_CANONICAL_THRESHOLD,
+        BLOCK_REPORTING_FREQ_NUM,
+    )
+    .await?;
+    state
+        .resume_from_checkpoint(&mut block_parser, &checkpoint)
+        .await
+}
+
+fn db_summary(
+    indexer_store: &IndexerStore,
+) -> anyhow::Result<(Option<String>, u32, Option<Ledger>)> {
+    Ok((
+        indexer_store.get_best_block_hash()?.map(|hash| hash.0),
+        indexer_store.get_block_production_total_count()?,
+        indexer_store.get_best_ledger()?,
+    ))
+}
+
+#[tokio::test]
+async fn resume_interrupted_ingestion() -> anyhow::Result<()> {
+    let src_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    // uninterrupted ingestion
+    let store_dir = setup_new_db_dir("ingestion-complete")?;
+    let indexer_store = Arc::new(IndexerStore::new(store_dir.path())?);
+    ingest(&indexer_store, &src_dir).await?;
+    assert_eq!(indexer_store.get_ingestion_checkpoint()?, None);
+    let expected = db_summary(&indexer_store)?;
+
+    // interrupt ingestion at an unparsable deep canonical (8) & recent (15) block
+    for (height, deep_canonical, checkpoint_height) in [(8, true, 5), (15, false, 11)] {
+        let blocks_dir = setup_new_db_dir("ingestion-blocks")?;
+        fs::create_dir_all(blocks_dir.path())?;
+        let mut broken = None;
+        for entry in fs::read_dir(&src_dir)? {
+            let path = entry?.path();
+            let dest = blocks_dir.path().join(path.file_name().unwrap());
+            fs::copy(&path, &dest)?;
+            if path
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with(&format!("mainnet-{height}-"))
+            {
+                broken = Some((path, dest));
+            }
+        }
+        let (path, dest) = broken.unwrap();
+        let contents = fs::read_to_string(&path)?;
+        fs::write(
+            &dest,
+            contents.replace("\"staged_ledger_diff\"", "\"staged_ledger\""),
+        )?;
+
+        let store_dir = setup_new_db_dir("ingestion-interrupted")?;
+        let indexer_store = Arc::new(IndexerStore::new(store_dir.path())?);
+        assert_eq!(
+            ingest(&indexer_store, blocks_dir.path()).await.is_err(),
+            deep_canonical
+        );
+
+        let checkpoint = indexer_store.get_ingestion_checkpoint()?.unwrap();
+        assert_eq!(checkpoint.is_deep_canonical(), deep_canonical);
+        assert_eq!(checkpoint.canonical_height, checkpoint_height);
+        assert_eq!(checkpoint.blocks_dir, blocks_dir.path());
+
+        // fix the block file & resume
+        fs::copy(&path, &dest)?;
+        resume(&indexer_store, blocks_dir.path()).await?;
+
+        assert_eq!(indexer_store.get_ingestion_checkpoint()?, None);
+        assert_eq!(
+            db_summary(&indexer_store)?,
+            expected,
+            "interrupted at {height}"
+        );
+    }
+    Ok(())
+}
+
+/// Writes a gzip-compressed tar archive of the block files in `dir`
+fn write_archive(dir: &Path, archive: &Path) -> anyhow::Result<()> {
+    let encoder = flate2::write::GzEncoder::new(fs::File::create(archive)?, Default::default());
+    let mut builder = tar::Builder::new(encoder);
+    builder.append_dir_all("blocks", dir)?;
+    builder.into_inner()?.finish()?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn resume_interrupted_archive_ingestion() -> anyhow::Result<()> {
+    let src_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+    let store_dir = setup_new_db_dir("ingestion-archive-complete")?;
+    let indexer_store = Arc::new(IndexerStore::new(store_dir.path())?);
+    ingest(&indexer_store, &src_dir).await?;
+    let expected = db_summary(&indexer_store)?;
+
+    // archive with an unparsable deep canonical block
+    let blocks_dir = TempDir::new()?;
+    let mut broken = None;
+    for entry in fs::read_dir(&src_dir)? {
+        let path = entry?.path();
+        let dest = blocks_dir.path().join(path.file_name().unwrap());
+        if path
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("mainnet-8-")
+        {
+            let contents = fs::read_to_string(&path)?;
+            fs::write(
+                &dest,
+                contents.replace("\"staged_ledger_diff\"", "\"staged_ledger\""),
+            )?;
+            broken = Some((path, dest));
+        } else {
+            fs::copy(&path, &dest)?;
+        }
+    }
+    let archive_dir = TempDir::new()?;
+    let archive = archive_dir.path().join("blocks.tar.gz");
+    write_archive(blocks_dir.path(), &archive)?;
+
+    let store_dir = setup_new_db_dir("ingestion-archive-interrupted")?;
+    let indexer_store = Arc::new(IndexerStore::new(store_dir.path())?);
+    assert!(ingest(&indexer_store, &archive).await.is_err());
+
+    let checkpoint = indexer_store.get_ingestion_checkpoint()?.unwrap();
+    assert!(checkpoint.is_deep_canonical());
+    assert_eq!(checkpoint.blocks_dir, archive);
+    assert_eq!(
+        checkpoint.parser_state_hash,
+        Some(checkpoint.canonical_state_hash.clone())
+    );
+
+    // a checkpoint of another path order is rejected
+    let mut moved = checkpoint.clone();
+    moved.parser_state_hash = Some("3NLyWnjZqUECniE1q719CoLmes6WDQAod4vrTeLfN7XXJbHv6EHH".into());
+    indexer_store.set_ingestion_checkpoint(&moved)?;
+    assert!(resume(&indexer_store, &archive).await.is_err());
+    indexer_store.set_ingestion_checkpoint(&checkpoint)?;
+
+    // fix the block & resume from the rebuilt archive
+    let (path, dest) = broken.unwrap();
+    fs::copy(&path, &dest)?;
+    write_archive(blocks_dir.path(), &archive)?;
+    resume(&indexer_store, &archive).await?;
+
+    assert_eq!(indexer_store.get_ingestion_checkpoint()?, None);
+    assert_eq!(db_summary(&indexer_store)?, expected);
+    Ok(())
+}
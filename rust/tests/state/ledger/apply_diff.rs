@@ -130,12 +130,12 @@ async fn account_diffs() {
     println!("{ledger:?}");
 
     println!("Nonce ledger:");
-    for (pk, account) in &ledger.accounts {
+    for ((pk, _), account) in &ledger.accounts {
         println!("{pk}: {}", account.nonce.0);
     }
 
     println!("Nonce expected:");
-    for (pk, account) in &expected.accounts {
+    for ((pk, _), account) in &expected.accounts {
         println!("{pk}: {}", account.nonce.0);
     }
     assert_eq!(ledger, expected);
@@ -64,16 +64,19 @@ async fn account_diffs() {
                 public_key,
                 amount,
                 update_type,
+                ..
             })
             | AccountDiff::FeeTransfer(PaymentDiff {
                 public_key,
                 amount,
                 update_type,
+                ..
             })
             | AccountDiff::FeeTransferViaCoinbase(PaymentDiff {
                 public_key,
                 amount,
                 update_type,
+                ..
             }) => {
                 println!("\n* Payment");
                 println!("public_key:  {public_key}");
@@ -141,6 +144,9 @@ async fn account_diffs() {
                     ledger.insert(public_key, (0, 0));
                 }
             }
+            AccountDiff::ZkappFeePayerNonce(_) | AccountDiff::ZkappState(_) => {
+                unreachable!("pre-hardfork block")
+            }
         }
     }
 
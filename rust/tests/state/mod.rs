@@ -1,4 +1,8 @@
+mod chain_selection;
 mod dangling_branches;
+mod hardfork;
+mod ingestion;
 mod ledger;
 mod orphaned_blocks;
 mod root_branch;
+mod witness_tree;
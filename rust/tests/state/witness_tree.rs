@@ -0,0 +1,158 @@
+use crate::helpers::setup_new_db_dir;
+use mina_indexer::{
+    block::{
+        parser::BlockParser,
+        precomputed::{PcbVersion, PrecomputedBlock},
+    },
+    constants::*,
+    ledger::genesis::{GenesisLedger, GenesisRoot},
+    server::IndexerVersion,
+    state::{export::WitnessTreeExport, store::WitnessTreeStore, IndexerState, IndexerStateConfig},
+    store::IndexerStore,
+};
+use std::{fs, path::PathBuf, sync::Arc};
+use tempfile::TempDir;
+
+fn state_config(indexer_store: &Arc<IndexerStore>) -> anyhow::Result<IndexerStateConfig> {
+    let genesis_root =
+        serde_json::from_str::<GenesisRoot>(GenesisLedger::MAINNET_V1_GENESIS_LEDGER_CONTENTS)?;
+    Ok(IndexerStateConfig::new(
+        genesis_root.into(),
+        IndexerVersion::new_testing(),
+        indexer_store.clone(),
+        MAINNET_CANONICAL_THRESHOLD,
+        MAINNET_TRANSITION_FRONTIER_K,
+    ))
+}
+
+/// Copies the contiguous blocks without block 14, so blocks 15-21 form a
+/// dangling branch. Returns the missing block's path
+fn blocks_without_block_14(blocks_dir: &TempDir) -> anyhow::Result<Option<PathBuf>> {
+    let src_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+    fs::create_dir_all(blocks_dir.path())?;
+
+    let mut missing_block = None;
+    for entry in fs::read_dir(src_dir)? {
+        let path = entry?.path();
+        if path
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("mainnet-14-")
+        {
+            missing_block = Some(path);
+        } else {
+            fs::copy(&path, blocks_dir.path().join(path.file_name().unwrap()))?;
+        }
+    }
+    Ok(missing_block)
+}
+
+#[tokio::test]
+async fn persist_and_restore() -> anyhow::Result<()> {
+    let blocks_dir = setup_new_db_dir("witness-tree-blocks")?;
+    let missing_block = blocks_without_block_14(&blocks_dir)?;
+
+    let store_dir = setup_new_db_dir("witness-tree")?;
+    let indexer_store = Arc::new(IndexerStore::new(store_dir.path())?);
+    let mut state = IndexerState::new_from_config(state_config(&indexer_store)?)?;
+    let mut block_parser = BlockParser::new_testing(blocks_dir.path())?;
+    state.add_blocks(&mut block_parser).await?;
+
+    assert_eq!(state.dangling_branches.len(), 1);
+    assert_eq!(state.dangling_branches[0].len(), 7);
+    state.persist_witness_tree()?;
+
+    // restored as is
+    let mut restored = IndexerState::new_without_genesis_events(state_config(&indexer_store)?)?;
+    let min_length_filter = restored.sync_from_db()?;
+
+    assert_eq!(
+        min_length_filter,
+        Some(state.root_branch.root_block().blockchain_length)
+    );
+    assert_eq!(restored.root_branch.blocks(), state.root_branch.blocks());
+    assert_eq!(
+        restored.dangling_branches[0].blocks(),
+        state.dangling_branches[0].blocks()
+    );
+    assert_eq!(restored.best_tip.state_hash, state.best_tip.state_hash);
+    assert_eq!(
+        restored.canonical_root.state_hash,
+        state.canonical_root.state_hash
+    );
+    assert_eq!(restored.diffs_map, state.diffs_map);
+    assert_eq!(restored.ledger, state.ledger);
+    assert_eq!(restored.blocks_processed, state.blocks_processed);
+
+    // the missing block connects the restored dangling branch
+    let missing_block = missing_block.unwrap();
+    let block = PrecomputedBlock::parse_file(&missing_block, PcbVersion::V1)?;
+    restored.block_pipeline(&block, missing_block.metadata()?.len())?;
+    assert!(restored.dangling_branches.is_empty());
+    assert_eq!(restored.best_tip_block().blockchain_length, 21);
+
+    // later events make the persisted witness tree stale, it's rebuilt from the db
+    let mut synced = IndexerState::new_without_genesis_events(state_config(&indexer_store)?)?;
+    synced.sync_from_db()?;
+    assert_eq!(synced.root_branch.root_block().blockchain_length, 11);
+    assert_eq!(synced.best_tip_block().blockchain_length, 21);
+    Ok(())
+}
+
+#[tokio::test]
+async fn export() -> anyhow::Result<()> {
+    let blocks_dir = setup_new_db_dir("witness-tree-export-blocks")?;
+    blocks_without_block_14(&blocks_dir)?;
+
+    let store_dir = setup_new_db_dir("witness-tree-export")?;
+    let indexer_store = Arc::new(IndexerStore::new(store_dir.path())?);
+    let mut state = IndexerState::new_from_config(state_config(&indexer_store)?)?;
+    let mut block_parser = BlockParser::new_testing(blocks_dir.path())?;
+    state.add_blocks(&mut block_parser).await?;
+
+    let export = state.export_witness_tree();
+    assert_eq!(export.best_tip, state.best_tip.state_hash);
+    assert_eq!(export.canonical_root, state.canonical_root.state_hash);
+    assert_eq!(export.root_branch.len() as u32, state.root_branch.len());
+
+    // exactly one best tip & canonical root
+    let root_blocks = export.root_branch.iter();
+    assert_eq!(root_blocks.clone().filter(|b| b.is_best_tip).count(), 1);
+    assert_eq!(root_blocks.filter(|b| b.is_canonical_root).count(), 1);
+
+    // the dangling branch is rooted at block 15
+    assert_eq!(export.dangling_branches.len(), 1);
+    let dangling_root = &export.dangling_branches[0][0];
+    assert_eq!(dangling_root.blockchain_length, 15);
+    assert_eq!(
+        export.dangling_branches[0]
+            .iter()
+            .map(|block| block.blockchain_length)
+            .collect::<Vec<_>>(),
+        (15..=21).collect::<Vec<_>>()
+    );
+
+    // the missing parent is dashed & best tip is highlighted
+    let dot = export.to_dot();
+    assert!(dot.contains("subgraph cluster_dangling_0"));
+    assert!(dot.contains(&format!(
+        "\"{}\" [label=\"missing\\n{}\", style=dashed];",
+        dangling_root.parent_hash, dangling_root.parent_hash
+    )));
+    assert!(dot.contains(&format!(
+        "\"{}\" -> \"{}\";",
+        dangling_root.parent_hash, dangling_root.state_hash
+    )));
+    assert!(dot.contains(&format!("\"{}\" [label=", export.best_tip)));
+
+    // JSON roundtrip
+    let json = serde_json::to_string(&export)?;
+    assert_eq!(serde_json::from_str::<WitnessTreeExport>(&json)?, export);
+
+    // the persisted witness tree exports the same
+    state.persist_witness_tree()?;
+    let persisted = indexer_store.get_witness_tree()?.unwrap();
+    assert_eq!(WitnessTreeExport::from(&persisted), export);
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use super::indexer_store;
+use actix_web::{test, web::Data, App};
+use mina_indexer::{
+    block::store::BlockStore, command::signed::SignedCommandWithData, web::rest::accounts,
+};
+
+const BLOCK_11: &str = "3NLMeYAFXxsmhSFtLHFxdtjGcfHTVFmBmBF8uTJvP4Ve5yEmxYeA";
+
+#[tokio::test]
+async fn get_account_params() -> anyhow::Result<()> {
+    let (_store_dir, store) = indexer_store("rest-accounts").await?;
+    let (pcb, _) = store.get_block(&BLOCK_11.into())?.unwrap();
+    let receiver = SignedCommandWithData::from_precomputed(&pcb)[0]
+        .command
+        .receiver_pk();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(store.clone()))
+            .service(accounts::get_account),
+    )
+    .await;
+    let status = |query: String| {
+        let req = test::TestRequest::get()
+            .uri(&format!("/accounts/{}{query}", receiver.0))
+            .to_request();
+        let app = &app;
+        async move { test::call_service(app, req).await.status().as_u16() }
+    };
+
+    assert_eq!(status(String::new()).await, 200);
+    assert_eq!(status("?height=11".into()).await, 200);
+    assert_eq!(status(format!("?state_hash={BLOCK_11}")).await, 200);
+
+    // well-formed, but unknown block
+    assert_eq!(
+        status("?state_hash=3NL4HLb7MQrxmAqVw8D4vEXCj2tdT8zgP9DFWGRoDxP72b4wxyUw".into()).await,
+        404
+    );
+
+    // malformed params
+    assert_eq!(status("?height=eleven".into()).await, 400);
+    assert_eq!(status("?height=-1".into()).await, 400);
+    assert_eq!(status("?state_hash=3NLMeYAF".into()).await, 400);
+    assert_eq!(status("?token=1".into()).await, 400);
+    Ok(())
+}
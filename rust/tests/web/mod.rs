@@ -0,0 +1,33 @@
+mod accounts;
+mod rosetta;
+
+use crate::helpers::setup_new_db_dir;
+use mina_indexer::{
+    block::parser::BlockParser,
+    constants::*,
+    ledger::genesis::{GenesisLedger, GenesisRoot},
+    server::IndexerVersion,
+    state::IndexerState,
+    store::IndexerStore,
+};
+use std::{path::PathBuf, sync::Arc};
+
+/// Indexes the contiguous mainnet blocks 2..=21
+async fn indexer_store(prefix: &str) -> anyhow::Result<(tempfile::TempDir, Arc<IndexerStore>)> {
+    let store_dir = setup_new_db_dir(prefix)?;
+    let blocks_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+    let mut block_parser = BlockParser::new_testing(&blocks_dir)?;
+    let indexer_store = Arc::new(IndexerStore::new(store_dir.path())?);
+    let genesis_ledger =
+        serde_json::from_str::<GenesisRoot>(GenesisLedger::MAINNET_V1_GENESIS_LEDGER_CONTENTS)?;
+    let mut state = IndexerState::new(
+        genesis_ledger.into(),
+        IndexerVersion::new_testing(),
+        indexer_store.clone(),
+        MAINNET_CANONICAL_THRESHOLD,
+        MAINNET_TRANSITION_FRONTIER_K,
+    )?;
+
+    state.add_blocks(&mut block_parser).await?;
+    Ok((store_dir, indexer_store))
+}
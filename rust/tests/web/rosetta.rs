@@ -0,0 +1,302 @@
+use super::indexer_store;
+use actix_web::{test, web::Data, App};
+use mina_indexer::{
+    block::{precomputed::PcbVersion, store::BlockStore},
+    chain::store::ChainStore,
+    command::signed::SignedCommandWithData,
+    constants::*,
+    ledger::{store::LedgerStore, token::TokenId},
+    store::IndexerStore,
+    web::rosetta::{self, account::AccountBalanceResponse, block::BlockResponse, *},
+};
+use serde_json::{json, Value};
+
+const BLOCK_11: &str = "3NLMeYAFXxsmhSFtLHFxdtjGcfHTVFmBmBF8uTJvP4Ve5yEmxYeA";
+
+/// POSTs the JSON body to the Rosetta endpoint, returning the status code &
+/// response body
+macro_rules! post {
+    ($store:expr, $service:expr, $uri:expr, $body:expr) => {{
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::new($store.clone()))
+                .service($service),
+        )
+        .await;
+        let req = test::TestRequest::post()
+            .uri($uri)
+            .set_json($body)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        let status = resp.status().as_u16();
+        let body: Value = serde_json::from_slice(&test::read_body(resp).await)?;
+        (status, body)
+    }};
+}
+
+fn network(db: &IndexerStore) -> anyhow::Result<Value> {
+    Ok(json!({
+        "blockchain": BLOCKCHAIN,
+        "network": db.get_current_network()?.to_string(),
+    }))
+}
+
+#[tokio::test]
+async fn network_status() -> anyhow::Result<()> {
+    let (_store_dir, store) = indexer_store("rosetta-network-status").await?;
+    let best_block = store.get_best_block()?.unwrap();
+
+    let (status, body) = post!(
+        store,
+        rosetta::network::network_status,
+        "/network/status",
+        json!({ "network_identifier": network(&store)? })
+    );
+    assert_eq!(status, 200);
+    assert_eq!(
+        body["current_block_identifier"],
+        json!({ "index": 21, "hash": best_block.state_hash().0 })
+    );
+    assert_eq!(body["current_block_timestamp"], best_block.timestamp());
+    assert_eq!(
+        body["genesis_block_identifier"]["hash"],
+        MAINNET_GENESIS_HASH
+    );
+    assert_eq!(body["oldest_block_identifier"]["index"], 1);
+
+    // unknown network
+    let (status, body) = post!(
+        store,
+        rosetta::network::network_status,
+        "/network/status",
+        json!({ "network_identifier": { "blockchain": BLOCKCHAIN, "network": "devnet" } })
+    );
+    assert_eq!(status, 500);
+    assert_eq!(body["code"], 4);
+    Ok(())
+}
+
+#[tokio::test]
+async fn block() -> anyhow::Result<()> {
+    let (_store_dir, store) = indexer_store("rosetta-block").await?;
+    let (pcb, _) = store.get_block(&BLOCK_11.into())?.unwrap();
+    let user_cmds = SignedCommandWithData::from_precomputed(&pcb);
+
+    let (status, body) = post!(
+        store,
+        rosetta::block::get_block,
+        "/block",
+        json!({
+            "network_identifier": network(&store)?,
+            "block_identifier": { "index": 11 },
+        })
+    );
+    assert_eq!(status, 200);
+
+    let BlockResponse { block } = serde_json::from_value(body)?;
+    assert_eq!(block.block_identifier.hash, BLOCK_11);
+    assert_eq!(
+        block.parent_block_identifier,
+        BlockIdentifier {
+            index: 10,
+            hash: "3NKGgTk7en3347KH81yDra876GPAUSoSePrfVKPmwR1KHfMpvJC5".into()
+        }
+    );
+    assert_eq!(block.metadata.unwrap()["block_status"], "Canonical");
+
+    // both payments, then the internal commands
+    assert_eq!(user_cmds.len(), 2);
+    for (txn, cmd) in block.transactions.iter().zip(user_cmds.iter()) {
+        assert_eq!(txn.transaction_identifier.hash, cmd.tx_hash);
+        assert_eq!(
+            txn.operations[..3]
+                .iter()
+                .map(|op| op.kind.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                OP_FEE_PAYMENT,
+                OP_PAYMENT_SOURCE_DEC,
+                OP_PAYMENT_RECEIVER_INC
+            ]
+        );
+    }
+    assert!(block.transactions[2..].iter().all(|txn| txn
+        .transaction_identifier
+        .hash
+        .starts_with(&format!("{INTERNAL_COMMAND_ID_PREFIX}:{BLOCK_11}:"))));
+
+    // every operation is on a MINA account
+    let mina = json!({ "token_id": TokenId::default().0 });
+    assert!(block
+        .transactions
+        .iter()
+        .flat_map(|txn| txn.operations.iter())
+        .all(|op| op.account.as_ref().unwrap().metadata.as_ref() == Some(&mina)));
+
+    // mismatched index & hash
+    let (status, body) = post!(
+        store,
+        rosetta::block::get_block,
+        "/block",
+        json!({
+            "network_identifier": network(&store)?,
+            "block_identifier": { "index": 12, "hash": BLOCK_11 },
+        })
+    );
+    assert_eq!(status, 500);
+    assert_eq!(body["code"], 9);
+    Ok(())
+}
+
+#[tokio::test]
+async fn account_balance() -> anyhow::Result<()> {
+    let (_store_dir, store) = indexer_store("rosetta-account-balance").await?;
+    let (pcb, _) = store.get_block(&BLOCK_11.into())?.unwrap();
+    let payment = &SignedCommandWithData::from_precomputed(&pcb)[0];
+    let receiver = payment.command.receiver_pk();
+
+    let balance = |state_hash: &str| -> anyhow::Result<u64> {
+        let ledger = store
+            .get_ledger_state_hash(&state_hash.into(), false)?
+            .unwrap();
+        Ok(ledger.get_mina_account(&receiver).unwrap().balance.0)
+    };
+
+    let (status, body) = post!(
+        store,
+        rosetta::account::account_balance,
+        "/account/balance",
+        json!({
+            "network_identifier": network(&store)?,
+            "account_identifier": { "address": receiver.0 },
+            "block_identifier": { "hash": BLOCK_11 },
+        })
+    );
+    assert_eq!(status, 200);
+
+    let resp: AccountBalanceResponse = serde_json::from_value(body)?;
+    assert_eq!(resp.block_identifier.index, 11);
+    assert_eq!(resp.balances, vec![amount(balance(BLOCK_11)? as i128)]);
+    assert_eq!(resp.metadata.unwrap()["token_id"], TokenId::default().0);
+
+    // the canonical block at the height
+    let (status, body) = post!(
+        store,
+        rosetta::account::account_balance,
+        "/account/balance",
+        json!({
+            "network_identifier": network(&store)?,
+            "account_identifier": { "address": receiver.0 },
+            "block_identifier": { "index": 11 },
+        })
+    );
+    assert_eq!(status, 200);
+    assert_eq!(body["block_identifier"]["hash"], BLOCK_11);
+
+    // the receiver has no account of another token
+    let (status, body) = post!(
+        store,
+        rosetta::account::account_balance,
+        "/account/balance",
+        json!({
+            "network_identifier": network(&store)?,
+            "account_identifier": {
+                "address": receiver.0,
+                "metadata": { "token_id": "wSMg68vWG5sUbsHsnctN8sDBSshoLDseY6E8HPSWzuqdhfpDFL" },
+            },
+        })
+    );
+    assert_eq!(status, 500);
+    assert_eq!(body["code"], 6);
+
+    // malformed token
+    let (status, body) = post!(
+        store,
+        rosetta::account::account_balance,
+        "/account/balance",
+        json!({
+            "network_identifier": network(&store)?,
+            "account_identifier": { "address": receiver.0, "metadata": { "token_id": "1" } },
+        })
+    );
+    assert_eq!(status, 500);
+    assert_eq!(body["code"], 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn search_transactions() -> anyhow::Result<()> {
+    let (_store_dir, store) = indexer_store("rosetta-search-transactions").await?;
+    let (pcb, _) = store.get_block(&BLOCK_11.into())?.unwrap();
+    let payment = &SignedCommandWithData::from_precomputed(&pcb)[0];
+    assert_eq!(payment.tx_hash, payment.command.txn_hash(&PcbVersion::V1)?);
+
+    // by transaction hash
+    let (status, body) = post!(
+        store,
+        rosetta::search::search_transactions,
+        "/search/transactions",
+        json!({
+            "network_identifier": network(&store)?,
+            "transaction_identifier": { "hash": payment.tx_hash },
+        })
+    );
+    assert_eq!(status, 200);
+    assert_eq!(body["total_count"], 1);
+    assert_eq!(
+        body["transactions"][0]["block_identifier"],
+        json!({ "index": 11, "hash": BLOCK_11 })
+    );
+
+    // by account, most recent first, paginated
+    let sender = payment.command.source_pk();
+    let search = |offset: u64| {
+        json!({
+            "network_identifier": network(&store).unwrap(),
+            "account_identifier": { "address": sender.0 },
+            "offset": offset,
+            "limit": 1,
+        })
+    };
+    let (status, first) = post!(
+        store,
+        rosetta::search::search_transactions,
+        "/search/transactions",
+        search(0)
+    );
+    assert_eq!(status, 200);
+
+    let total_count = first["total_count"].as_u64().unwrap();
+    assert!(total_count > 1);
+    assert_eq!(first["next_offset"], 1);
+
+    let (_, second) = post!(
+        store,
+        rosetta::search::search_transactions,
+        "/search/transactions",
+        search(1)
+    );
+    let height = |body: &Value| body["transactions"][0]["block_identifier"]["index"].as_u64();
+    assert!(height(&first) >= height(&second));
+
+    // every transaction has an operation on the account
+    for body in [&first, &second] {
+        let ops = body["transactions"][0]["transaction"]["operations"]
+            .as_array()
+            .unwrap();
+        assert!(ops
+            .iter()
+            .any(|op| op["account"]["address"] == sender.0.as_str()));
+    }
+
+    // missing search criteria
+    let (status, body) = post!(
+        store,
+        rosetta::search::search_transactions,
+        "/search/transactions",
+        json!({ "network_identifier": network(&store)? })
+    );
+    assert_eq!(status, 500);
+    assert_eq!(body["code"], 2);
+    Ok(())
+}